@@ -0,0 +1,43 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, StatusCode},
+    Json,
+};
+use convexfx_types::AccountId;
+
+use crate::state::AppState;
+
+/// Trader identity resolved from an `Authorization: Bearer <token>` header
+///
+/// Rejects the request with `401 Unauthorized` when the header is missing,
+/// malformed, or carries a token not registered with `AppState::register_api_token`.
+pub struct AuthenticatedTrader(pub AccountId);
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthenticatedTrader {
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+            .ok_or_else(|| unauthorized("Missing bearer token"))?;
+
+        state
+            .api_tokens
+            .lock()
+            .unwrap()
+            .get(token)
+            .cloned()
+            .map(AuthenticatedTrader)
+            .ok_or_else(|| unauthorized("Unknown API token"))
+    }
+}
+
+fn unauthorized(message: &str) -> (StatusCode, Json<serde_json::Value>) {
+    (StatusCode::UNAUTHORIZED, Json(serde_json::json!({"error": message})))
+}
@@ -1,21 +1,28 @@
 use axum::{
-    extract::{Path, State},
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, State,
+    },
     http::StatusCode,
     Json,
     response::IntoResponse,
 };
+use tokio::sync::broadcast;
 use serde::{Deserialize, Serialize};
 use convexfx_types::{AssetId, AccountId, PairOrder, Amount};
 use convexfx_ledger::Ledger;
-use sha2::{Sha256, Digest};
-use hex;
 
-use crate::state::AppState;
+use crate::auth::AuthenticatedTrader;
+use crate::state::{AppState, POOL_ACCOUNT};
 
 #[derive(Serialize)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
+    pub solver_backend: String,
+    /// Why the solver self-check failed, when `status` is `degraded`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -24,12 +31,36 @@ pub struct InfoResponse {
     pub description: String,
 }
 
+/// Time allowed for the solver self-check before health reports `degraded`
+const HEALTH_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
 /// Health check endpoint
-pub async fn health() -> impl IntoResponse {
-    Json(HealthResponse {
-        status: "ok".to_string(),
+///
+/// Runs a tiny canned QP through the active solver backend. A backend that
+/// errors, panics or exceeds `HEALTH_CHECK_TIMEOUT` is reported as
+/// `degraded` with `503 Service Unavailable`.
+pub async fn health(State(state): State<AppState>) -> impl IntoResponse {
+    let engine = state.clearing_engine.clone();
+    let solver_backend = engine.backend_name().to_string();
+    let check = tokio::task::spawn_blocking(move || engine.check_backend());
+
+    let error = match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, check).await {
+        Ok(Ok(Ok(()))) => None,
+        Ok(Ok(Err(e))) => Some(e.to_string()),
+        Ok(Err(e)) => Some(format!("solver check panicked: {}", e)),
+        Err(_) => Some(format!("solver check timed out after {:?}", HEALTH_CHECK_TIMEOUT)),
+    };
+    let (code, status) = match error {
+        None => (StatusCode::OK, "ok"),
+        Some(_) => (StatusCode::SERVICE_UNAVAILABLE, "degraded"),
+    };
+
+    (code, Json(HealthResponse {
+        status: status.to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
-    })
+        solver_backend,
+        error,
+    }))
 }
 
 /// Info endpoint
@@ -52,19 +83,19 @@ pub struct CommitResponse {
 }
 
 
+/// Commitment to an order that stays hidden until it is revealed
 #[derive(Deserialize)]
 pub struct OrderSubmissionRequest {
-    pub pay_asset: String,
-    pub receive_asset: String,
-    pub budget: String, // Amount as string for JSON
-    pub limit_ratio: Option<f64>,
-    pub min_fill_fraction: Option<f64>,
+    /// Hex SHA-256 from `convexfx_orders::compute_commitment` over the order
+    /// exactly as it will be revealed (attributed to the submitting trader)
+    /// and a secret salt of at least `MIN_SALT_BYTES` bytes
+    pub commitment_hash: String,
 }
 
 #[derive(Serialize)]
 pub struct OrderSubmissionResponse {
-    pub order_id: String,
     pub commitment_hash: String,
+    pub epoch_id: u64,
     pub accepted: bool,
 }
 
@@ -73,10 +104,14 @@ pub struct OrderRevealRequest {
     pub order_id: String,
     pub pay_asset: String,
     pub receive_asset: String,
-    pub budget: String,
-    pub trader: String,
+    pub budget: String, // Amount as string for JSON
     pub limit_ratio: Option<f64>,
     pub min_fill_fraction: Option<f64>,
+    /// Cap (bps) on adverse slippage versus the oracle mid at reveal
+    #[serde(default)]
+    pub max_slippage_bps: Option<f64>,
+    /// Hex-encoded salt the commitment was computed with
+    pub salt: String,
 }
 
 #[derive(Serialize)]
@@ -85,14 +120,14 @@ pub struct OrderRevealResponse {
     pub epoch_id: u64,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceResponse {
     pub asset: String,
     pub price: f64,
     pub log_price: f64,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PricesResponse {
     pub prices: Vec<PriceResponse>,
     pub epoch_id: u64,
@@ -122,79 +157,294 @@ pub struct SystemStatusResponse {
     pub uptime_seconds: u64,
 }
 
-/// Submit a new order (creates commitment)
-pub async fn submit_order(
-    State(state): State<AppState>,
-    Json(req): Json<OrderSubmissionRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    // Parse assets
-    let pay_asset = match AssetId::from_str(&req.pay_asset) {
-        Some(asset) => asset,
-        None => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "Invalid pay asset"}))),
-    };
-
-    let receive_asset = match AssetId::from_str(&req.receive_asset) {
-        Some(asset) => asset,
-        None => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "Invalid receive asset"}))),
-    };
+/// Shortest salt accepted at reveal. The order fields are guessable from
+/// public market data, so the salt is what keeps a commitment hiding.
+pub const MIN_SALT_BYTES: usize = 16;
 
-    // Parse budget
-    let budget = match Amount::from_string(&req.budget) {
-        Ok(amount) => amount,
-        Err(_) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "Invalid budget format"}))),
-    };
+/// Parse and validate a revealed order on behalf of `trader`
+fn order_from_request(req: &OrderRevealRequest, trader: AccountId) -> Result<PairOrder, String> {
+    let pay_asset = AssetId::from_str(&req.pay_asset).ok_or("Invalid pay asset")?;
+    let receive_asset = AssetId::from_str(&req.receive_asset).ok_or("Invalid receive asset")?;
+    let budget = Amount::from_string(&req.budget).map_err(|_| "Invalid budget format")?;
 
-    // Create order
     let order = PairOrder {
-        id: format!("order_{}", chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()),
-        trader: AccountId::new("api_user"), // TODO: Get from auth
+        id: req.order_id.clone(),
+        trader,
         pay: pay_asset,
         receive: receive_asset,
         budget,
         limit_ratio: req.limit_ratio,
         min_fill_fraction: req.min_fill_fraction,
+        max_slippage_bps: req.max_slippage_bps,
+        submission_mid: None,
         metadata: serde_json::json!({}),
     };
+    convexfx_orders::validate_order(&order).map_err(|e| e.to_string())?;
+    Ok(order)
+}
+
+/// Parse a submitted commitment hash
+fn commitment_from_request(req: &OrderSubmissionRequest) -> Result<convexfx_orders::CommitmentHash, String> {
+    convexfx_orders::CommitmentHash::from_hex(&req.commitment_hash).map_err(|e| e.to_string())
+}
+
+/// Submit an order commitment
+///
+/// Only the commitment hash is sent, so the order stays hidden until
+/// `reveal_order`, which is where it is validated and checked against the
+/// trader's balance. The commitment counts against the authenticated
+/// trader's per-epoch order limit.
+pub async fn submit_order(
+    State(state): State<AppState>,
+    AuthenticatedTrader(trader): AuthenticatedTrader,
+    Json(req): Json<OrderSubmissionRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    use convexfx_orders::Commitment;
 
-    // Create commitment hash
-    let mut hasher = Sha256::new();
-    let commitment_data = format!("{:?}", order);
-    hasher.update(commitment_data.as_bytes());
-    let hash = hasher.finalize();
-    let commitment_hash = hex::encode(hash);
+    let commitment_hash = match commitment_from_request(&req) {
+        Ok(hash) => hash,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e}))),
+    };
 
     // Store commitment in orderbook
     let mut orderbook = state.orderbook.lock().unwrap();
-    use convexfx_orders::{Commitment, CommitmentHash};
-
-    // Create a proper commitment hash
-    let commitment_hash_obj = CommitmentHash::from_hex(&commitment_hash).unwrap();
+    let epoch_id = orderbook.epoch_id;
 
-    match orderbook.commit(Commitment {
-        hash: commitment_hash_obj,
-        epoch_id: 1, // TODO: Get current epoch
+    match orderbook.commit_as(&trader, Commitment {
+        hash: commitment_hash.clone(),
+        epoch_id,
         timestamp_ms: chrono::Utc::now().timestamp_millis() as u64,
     }) {
-        Ok(_) => (StatusCode::OK, Json(serde_json::json!({
-            "order_id": order.id,
-            "commitment_hash": commitment_hash,
-            "accepted": true
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!(OrderSubmissionResponse {
+            commitment_hash: commitment_hash.to_string(),
+            epoch_id,
+            accepted: true,
         }))),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": "Failed to commit order"}))),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("Commitment rejected: {}", e)}))),
+    }
+}
+
+#[derive(Serialize)]
+pub struct BatchOrderStatus {
+    pub index: usize,
+    pub commitment_hash: Option<String>,
+    pub accepted: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BatchSubmissionResponse {
+    pub accepted: bool,
+    pub epoch_id: u64,
+    pub orders: Vec<BatchOrderStatus>,
+}
+
+/// Submit several order commitments into the current epoch atomically
+///
+/// Each commitment's `accepted` says whether it parsed and could be stored;
+/// the batch is committed only if all of them could, otherwise nothing is
+/// committed and the response is `400 Bad Request`. The orders are then
+/// revealed with `reveal_order`, or together with `reveal_order_batch`.
+pub async fn submit_order_batch(
+    State(state): State<AppState>,
+    AuthenticatedTrader(trader): AuthenticatedTrader,
+    Json(reqs): Json<Vec<OrderSubmissionRequest>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    use convexfx_orders::Commitment;
+
+    if reqs.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "Batch contains no orders"})));
     }
+
+    let mut orderbook = state.orderbook.lock().unwrap();
+    let epoch_id = orderbook.epoch_id;
+
+    // Commit into a copy so a failure part-way leaves the book untouched
+    let mut staged = orderbook.clone();
+    let timestamp_ms = chrono::Utc::now().timestamp_millis() as u64;
+    let statuses: Vec<BatchOrderStatus> = reqs
+        .iter()
+        .enumerate()
+        .map(|(index, req)| {
+            let committed = commitment_from_request(req).and_then(|hash| {
+                staged
+                    .commit_as(&trader, Commitment { hash: hash.clone(), epoch_id, timestamp_ms })
+                    .map(|_| hash)
+                    .map_err(|e| e.to_string())
+            });
+            BatchOrderStatus {
+                index,
+                commitment_hash: committed.as_ref().ok().map(|hash| hash.to_string()),
+                accepted: committed.is_ok(),
+                error: committed.err(),
+            }
+        })
+        .collect();
+
+    let accepted = statuses.iter().all(|status| status.accepted);
+    let code = if accepted {
+        *orderbook = staged;
+        StatusCode::OK
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+    (code, Json(serde_json::json!(BatchSubmissionResponse {
+        accepted,
+        epoch_id,
+        orders: statuses,
+    })))
+}
+
+/// Parse a revealed order and its salt for `trader`, recording the mid of
+/// `ref_prices` as the order's `submission_mid`
+fn revealed_order(
+    req: &OrderRevealRequest,
+    trader: AccountId,
+    ref_prices: &convexfx_oracle::RefPrices,
+) -> Result<(PairOrder, Vec<u8>), String> {
+    let salt = match hex::decode(&req.salt) {
+        Ok(salt) if salt.len() >= MIN_SALT_BYTES => salt,
+        Ok(_) => return Err(format!("Salt must be at least {} bytes", MIN_SALT_BYTES)),
+        Err(_) => return Err("Salt must be hex encoded".to_string()),
+    };
+
+    let mut order = order_from_request(req, trader)?;
+    // Record the mid the slippage cap is measured against
+    order.submission_mid = Some(ref_prices.mid_rate(order.pay, order.receive));
+
+    Ok((order, salt))
 }
 
 /// Reveal an order (submit actual order details)
+///
+/// Rebuilds the order from the revealed fields for the authenticated
+/// trader and recomputes its commitment with the revealed salt. The reveal
+/// is rejected with `400 Bad Request` if the salt is shorter than
+/// `MIN_SALT_BYTES`, the order is invalid, the trader cannot cover the
+/// budget, or the commitment does not match one stored by `submit_order`
+/// for the current epoch; on success the order joins the epoch's pending
+/// orders with the current oracle mid recorded as its `submission_mid`.
 pub async fn reveal_order(
-    State(_state): State<AppState>,
-    Json(_req): Json<OrderRevealRequest>,
-) -> impl IntoResponse {
-    // This would validate the commitment and add the order to the current epoch
-    // For now, return success
-    (StatusCode::OK, Json(OrderRevealResponse {
-        accepted: true,
-        epoch_id: 1,
-    }))
+    State(state): State<AppState>,
+    AuthenticatedTrader(trader): AuthenticatedTrader,
+    Json(req): Json<OrderRevealRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    use convexfx_oracle::Oracle;
+
+    let ref_prices = match state.oracle.lock().unwrap().current_prices() {
+        Ok(prices) => prices,
+        Err(e) => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({"error": format!("Oracle unavailable: {}", e)}))),
+    };
+
+    let (order, salt) = match revealed_order(&req, trader, &ref_prices) {
+        Ok(revealed) => revealed,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e}))),
+    };
+
+    if !state.ledger.lock().unwrap().has_sufficient(&order.trader, order.pay, order.budget) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("Insufficient {} balance for {}", order.pay, order.trader)})));
+    }
+
+    let mut orderbook = state.orderbook.lock().unwrap();
+    let epoch_id = orderbook.epoch_id;
+
+    match orderbook.reveal(order, &salt) {
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!(OrderRevealResponse {
+            accepted: true,
+            epoch_id,
+        }))),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("Reveal rejected: {}", e)}))),
+    }
+}
+
+#[derive(Serialize)]
+pub struct BatchRevealStatus {
+    pub index: usize,
+    pub order_id: String,
+    pub accepted: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BatchRevealResponse {
+    pub accepted: bool,
+    pub epoch_id: u64,
+    pub orders: Vec<BatchRevealStatus>,
+}
+
+/// Reveal several committed orders into the current epoch atomically
+///
+/// Every order goes through the same checks as `reveal_order`, each
+/// against the book as staged by the orders before it. The batch lands
+/// only if all of them pass; otherwise nothing is revealed and the
+/// response is `400 Bad Request`, with each order's `accepted` and `error`
+/// saying which ones failed.
+pub async fn reveal_order_batch(
+    State(state): State<AppState>,
+    AuthenticatedTrader(trader): AuthenticatedTrader,
+    Json(reqs): Json<Vec<OrderRevealRequest>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    use convexfx_oracle::Oracle;
+
+    if reqs.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "Batch contains no orders"})));
+    }
+
+    let ref_prices = match state.oracle.lock().unwrap().current_prices() {
+        Ok(prices) => prices,
+        Err(e) => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({"error": format!("Oracle unavailable: {}", e)}))),
+    };
+
+    let revealed: Vec<Result<(PairOrder, Vec<u8>), String>> = {
+        let ledger = state.ledger.lock().unwrap();
+        reqs.iter()
+            .map(|req| {
+                let (order, salt) = revealed_order(req, trader.clone(), &ref_prices)?;
+                if !ledger.has_sufficient(&order.trader, order.pay, order.budget) {
+                    return Err(format!("Insufficient {} balance for {}", order.pay, order.trader));
+                }
+                Ok((order, salt))
+            })
+            .collect()
+    };
+
+    let mut orderbook = state.orderbook.lock().unwrap();
+    let epoch_id = orderbook.epoch_id;
+
+    // Reveal into a copy so a failure part-way leaves the book untouched
+    let mut staged = orderbook.clone();
+    let statuses: Vec<BatchRevealStatus> = reqs
+        .iter()
+        .zip(revealed)
+        .enumerate()
+        .map(|(index, (req, revealed))| {
+            let outcome = revealed.and_then(|(order, salt)| {
+                staged
+                    .reveal(order, &salt)
+                    .map(|_| ())
+                    .map_err(|e| format!("Reveal rejected: {}", e))
+            });
+            BatchRevealStatus {
+                index,
+                order_id: req.order_id.clone(),
+                accepted: outcome.is_ok(),
+                error: outcome.err(),
+            }
+        })
+        .collect();
+
+    let accepted = statuses.iter().all(|status| status.accepted);
+    let code = if accepted {
+        *orderbook = staged;
+        StatusCode::OK
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+    (code, Json(serde_json::json!(BatchRevealResponse {
+        accepted,
+        epoch_id,
+        orders: statuses,
+    })))
 }
 
 /// Get current prices from oracle
@@ -222,6 +472,245 @@ pub async fn get_prices(
     })
 }
 
+#[derive(Serialize)]
+pub struct BatchResponse {
+    pub epoch_id: u64,
+    pub fill_count: usize,
+    pub prices: PricesResponse,
+}
+
+/// Outcome of one order in a cleared batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderFillStatus {
+    Filled,
+    PartiallyFilled,
+    Unfilled,
+}
+
+/// Order status pushed to `/ws/orders/{account}` subscribers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderStatus {
+    pub order_id: String,
+    pub epoch_id: u64,
+    pub status: OrderFillStatus,
+    pub fill_frac: f64,
+    pub pay_units: f64,
+    pub recv_units: f64,
+}
+
+/// Push each order's outcome to its trader's order channel, if subscribed
+fn publish_order_statuses(
+    state: &AppState,
+    instance: &convexfx_clearing::EpochInstance,
+    solution: &convexfx_clearing::EpochSolution,
+) {
+    let mut channels = state.order_updates.lock().unwrap();
+    // Forget accounts whose subscribers have all disconnected
+    channels.retain(|_, sender| sender.receiver_count() > 0);
+    if channels.is_empty() {
+        return;
+    }
+
+    for fill in &solution.fills {
+        let Some(order) = instance.orders.iter().find(|order| order.id == fill.order_id) else {
+            continue;
+        };
+        let Some(sender) = channels.get(&order.trader) else {
+            continue;
+        };
+        let status = if fill.is_complete() {
+            OrderFillStatus::Filled
+        } else if fill.is_partial() {
+            OrderFillStatus::PartiallyFilled
+        } else {
+            OrderFillStatus::Unfilled
+        };
+        let _ = sender.send(OrderStatus {
+            order_id: fill.order_id.clone(),
+            epoch_id: solution.epoch_id,
+            status,
+            fill_frac: fill.fill_frac,
+            pay_units: fill.pay_units,
+            recv_units: fill.recv_units,
+        });
+    }
+}
+
+/// Move every fill's funds between its trader and the pool
+///
+/// Each trader pays `pay_units` into `POOL_ACCOUNT` and receives
+/// `recv_units` from it. Settlement runs in one ledger transaction: if any
+/// leg cannot be covered it is rolled back and no fill is applied.
+fn settle_fills(
+    ledger: &mut convexfx_ledger::MemoryLedger,
+    instance: &convexfx_clearing::EpochInstance,
+    solution: &convexfx_clearing::EpochSolution,
+) -> Result<(), String> {
+    let pool = AccountId::new(POOL_ACCOUNT);
+    ledger.with_transaction(|tx| {
+        for fill in solution.fills.iter().filter(|f| f.fill_frac > 0.0) {
+            let order = instance
+                .orders
+                .iter()
+                .find(|order| order.id == fill.order_id)
+                .ok_or_else(|| format!("Order {} not found", fill.order_id))?;
+            let pay = Amount::from_f64(fill.pay_units)
+                .map_err(|e| format!("Invalid pay units for {}: {}", fill.order_id, e))?;
+            let recv = Amount::from_f64(fill.recv_units)
+                .map_err(|e| format!("Invalid receive units for {}: {}", fill.order_id, e))?;
+            tx.transfer(&order.trader, &pool, fill.pay_asset, pay)
+                .and_then(|_| tx.transfer(&pool, &order.trader, fill.recv_asset, recv))
+                .map_err(|e| format!("Cannot settle {}: {}", fill.order_id, e))?;
+        }
+        Ok(())
+    })
+}
+
+/// Clear the current epoch's revealed orders
+///
+/// Freezes the order book, clears it against the pool's holdings and oracle
+/// prices with `AppState::risk_params`, and settles the fills to the ledger
+/// (see `settle_fills`). It then opens the next epoch, publishes the
+/// clearing prices to `/ws/prices` subscribers and each order's outcome to
+/// its trader's `/ws/orders/{account}` subscribers. A batch that fails to
+/// clear or settle is marked `FAILED` and moves no funds.
+pub async fn execute_batch(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    use convexfx_clearing::EpochInstance;
+    use convexfx_oracle::Oracle;
+
+    let ref_prices = match state.oracle.lock().unwrap().current_prices() {
+        Ok(prices) => prices,
+        Err(e) => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({"error": format!("Oracle unavailable: {}", e)}))),
+    };
+
+    let epoch_id = {
+        let mut current_epoch = state.current_epoch.lock().unwrap();
+        let epoch_id = *current_epoch;
+        *current_epoch += 1;
+        epoch_id
+    };
+    let orders = {
+        let mut orderbook = state.orderbook.lock().unwrap();
+        let mut next = orderbook.for_epoch(epoch_id + 1);
+        next.expire_unrevealed(epoch_id);
+        std::mem::replace(&mut *orderbook, next).freeze()
+    };
+    let inventory = state
+        .ledger
+        .lock()
+        .unwrap()
+        .account_balances(&AccountId::new(POOL_ACCOUNT))
+        .to_solver_map();
+
+    let instance = EpochInstance::new(epoch_id, inventory, orders, ref_prices, state.risk_params.clone());
+    let solution = match state.clearing_engine.clear_epoch(&instance) {
+        Ok(solution) => solution,
+        Err(e) => {
+            state.epoch_states.lock().unwrap().insert(epoch_id, "FAILED".to_string());
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("Clearing failed: {}", e)})));
+        }
+    };
+    {
+        let mut ledger = state.ledger.lock().unwrap();
+        ledger.set_epoch(epoch_id);
+        if let Err(e) = settle_fills(&mut ledger, &instance, &solution) {
+            state.epoch_states.lock().unwrap().insert(epoch_id, "FAILED".to_string());
+            return (StatusCode::CONFLICT, Json(serde_json::json!({"error": format!("Settlement failed: {}", e)})));
+        }
+    }
+    state.epoch_states.lock().unwrap().insert(epoch_id, "COMPLETED".to_string());
+
+    {
+        let slippage: Vec<f64> = solution
+            .fills
+            .iter()
+            .filter(|f| f.fill_frac > 0.0)
+            .map(|f| solution.fill_slippage_bps(&instance, f))
+            .collect();
+
+        let mut metrics = state.batch_metrics.lock().unwrap();
+        metrics.batches_total += 1;
+        metrics.last_batch_iterations = solution.diagnostics.iterations;
+        metrics.last_batch_slippage_p90_bps = convexfx_clearing::percentile(&slippage, 0.90);
+        metrics.last_batch_solve_ms = solution.diagnostics.solve_time_ms;
+    }
+
+    let prices = PricesResponse {
+        prices: solution
+            .y_star
+            .iter()
+            .map(|(asset, y)| PriceResponse {
+                asset: asset.to_string(),
+                price: y.exp(),
+                log_price: *y,
+            })
+            .collect(),
+        epoch_id,
+    };
+
+    // No subscribers is not an error
+    let _ = state.price_updates.send(prices.clone());
+    publish_order_statuses(&state, &instance, &solution);
+
+    let fill_count = solution.fills.iter().filter(|f| f.fill_frac > 0.0).count();
+    state.epoch_history.lock().unwrap().insert(epoch_id, solution);
+
+    (StatusCode::OK, Json(serde_json::json!(BatchResponse {
+        epoch_id,
+        fill_count,
+        prices,
+    })))
+}
+
+/// Stream clearing prices over a WebSocket, one JSON frame per batch
+pub async fn stream_prices(
+    State(state): State<AppState>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let updates = state.price_updates.subscribe();
+    ws.on_upgrade(move |socket| forward_updates(socket, updates))
+}
+
+/// Stream status updates for `account`'s orders, one JSON frame per order
+/// per batch
+///
+/// Only the authenticated owner of `account` may subscribe; anyone else
+/// gets `403 Forbidden`.
+pub async fn stream_order_updates(
+    State(state): State<AppState>,
+    Path(account): Path<String>,
+    AuthenticatedTrader(trader): AuthenticatedTrader,
+    ws: WebSocketUpgrade,
+) -> axum::response::Response {
+    let account = AccountId::new(account);
+    if trader != account {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({"error": format!("Not authorized for account {}", account)})))
+            .into_response();
+    }
+    let updates = state.subscribe_orders(&account);
+    ws.on_upgrade(move |socket| forward_updates(socket, updates))
+}
+
+/// Send every message from `updates` as a JSON text frame until either side closes
+async fn forward_updates<T: Clone + Serialize>(mut socket: WebSocket, mut updates: broadcast::Receiver<T>) {
+    loop {
+        let update = match updates.recv().await {
+            Ok(update) => update,
+            // A slow client just misses the frames it could not keep up with
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let Ok(frame) = serde_json::to_string(&update) else {
+            continue;
+        };
+        if socket.send(Message::Text(frame)).await.is_err() {
+            break;
+        }
+    }
+}
+
 /// List epochs
 pub async fn list_epochs(
     State(_state): State<AppState>,
@@ -240,19 +729,52 @@ pub async fn list_epochs(
     Json(EpochListResponse { epochs })
 }
 
-/// Get epoch details by ID
+#[derive(Serialize)]
+pub struct EpochDetailResponse {
+    pub epoch_id: u64,
+    pub state: String,
+    pub fill_count: usize,
+    pub fills: Vec<convexfx_types::Fill>,
+    pub prices: Vec<PriceResponse>,
+    pub diagnostics: convexfx_clearing::Diagnostics,
+}
+
+/// Get the clearing result of a completed epoch
+///
+/// Returns `404 Not Found` for epochs that have not been cleared.
 pub async fn get_epoch_by_id(
-    State(_state): State<AppState>,
-    Path(_epoch_id): Path<u64>,
-) -> impl IntoResponse {
-    // Placeholder: return epoch details
-    Json(EpochInfo {
-        epoch_id: 1,
-        state: "COMPLETED".to_string(),
-        order_count: 0,
-        start_time: None,
-        end_time: None,
-    })
+    State(state): State<AppState>,
+    Path(epoch_id): Path<u64>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let history = state.epoch_history.lock().unwrap();
+    let Some(solution) = history.get(&epoch_id) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": format!("Epoch {} not found", epoch_id)})));
+    };
+
+    let epoch_state = state
+        .epoch_states
+        .lock()
+        .unwrap()
+        .get(&epoch_id)
+        .cloned()
+        .unwrap_or_else(|| "COMPLETED".to_string());
+
+    (StatusCode::OK, Json(serde_json::json!(EpochDetailResponse {
+        epoch_id,
+        state: epoch_state,
+        fill_count: solution.fills.iter().filter(|f| f.fill_frac > 0.0).count(),
+        fills: solution.fills.clone(),
+        prices: solution
+            .y_star
+            .iter()
+            .map(|(asset, y)| PriceResponse {
+                asset: asset.to_string(),
+                price: y.exp(),
+                log_price: *y,
+            })
+            .collect(),
+        diagnostics: solution.diagnostics.clone(),
+    })))
 }
 
 /// Get system status and metrics
@@ -269,11 +791,42 @@ pub async fn get_system_status(
         current_epoch: *state.current_epoch.lock().unwrap(),
         total_accounts: ledger.list_accounts().len(),
         total_orders_pending: orderbook.commitment_count(),
-        solver_backend: "clarabel".to_string(),
+        solver_backend: state.clearing_engine.backend_name().to_string(),
         uptime_seconds: 3600, // TODO: Track actual uptime
     })
 }
 
+/// Export system and last-batch metrics in Prometheus text format
+pub async fn metrics(
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    use std::fmt::Write as _;
+
+    let current_epoch = *state.current_epoch.lock().unwrap();
+    let total_accounts = state.ledger.lock().unwrap().list_accounts().len();
+    let pending_orders = state.orderbook.lock().unwrap().commitment_count();
+    let batch = state.batch_metrics.lock().unwrap().clone();
+
+    let metrics: [(&str, &str, &str, String); 7] = [
+        ("convexfx_current_epoch", "gauge", "Epoch currently collecting orders", current_epoch.to_string()),
+        ("convexfx_total_accounts", "gauge", "Accounts known to the ledger", total_accounts.to_string()),
+        ("convexfx_pending_orders", "gauge", "Orders committed in the current epoch", pending_orders.to_string()),
+        ("convexfx_batches_total", "counter", "Batches cleared successfully", batch.batches_total.to_string()),
+        ("convexfx_last_batch_iterations", "gauge", "SCP iterations used by the last batch", batch.last_batch_iterations.to_string()),
+        ("convexfx_last_batch_slippage_p90_bps", "gauge", "90th percentile fill slippage of the last batch (bps)", batch.last_batch_slippage_p90_bps.to_string()),
+        ("convexfx_last_batch_solve_ms", "gauge", "Solver wall-clock time of the last batch (ms)", batch.last_batch_solve_ms.to_string()),
+    ];
+
+    let mut body = String::new();
+    for (name, kind, help, value) in metrics {
+        let _ = writeln!(body, "# HELP {} {}", name, help);
+        let _ = writeln!(body, "# TYPE {} {}", name, kind);
+        let _ = writeln!(body, "{} {}", name, value);
+    }
+
+    ([(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
 /// Submit commitment endpoint (legacy)
 pub async fn submit_commitment(
     State(_state): State<AppState>,
@@ -404,7 +957,7 @@ pub async fn provide_liquidity(
             (StatusCode::OK, Json(serde_json::json!({
                 "success": true,
                 "message": "Liquidity provided successfully",
-                "new_balance": new_balance.to_string()
+                "new_balance": new_balance.to_display_string(asset_id)
             })))
         }
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("Failed to provide liquidity: {}", e)}))),
@@ -448,11 +1001,11 @@ pub async fn get_liquidity(
     let mut liquidity_data = serde_json::Map::new();
     for account in accounts {
         let balances = ledger.account_balances(&account);
-        let f64_map = balances.to_f64_map();
 
         let mut account_balances = serde_json::Map::new();
-        for (asset, amount) in f64_map {
-            account_balances.insert(asset.to_string(), serde_json::Value::String(format!("{:.6}", amount)));
+        for asset in balances.to_f64_map().keys() {
+            let amount = balances.get(*asset).to_display_string(*asset);
+            account_balances.insert(asset.to_string(), serde_json::Value::String(amount));
         }
 
         if !account_balances.is_empty() {
@@ -1,9 +1,11 @@
 mod server;
 mod handlers;
 mod state;
+mod auth;
 
 pub use server::create_app;
-pub use state::AppState;
+pub use state::{AppState, BatchMetrics, POOL_ACCOUNT};
+pub use auth::AuthenticatedTrader;
 
 #[cfg(test)]
 mod tests;
@@ -1,4 +1,5 @@
 use convexfx_api::{create_app, AppState};
+use convexfx_types::AccountId;
 use tracing_subscriber;
 
 #[tokio::main]
@@ -7,7 +8,25 @@ async fn main() {
     tracing_subscriber::fmt::init();
 
     // Create app state
-    let state = AppState::new();
+    let mut state = AppState::new();
+
+    // Optional cap on orders per trader per epoch
+    if let Ok(max) = std::env::var("CONVEXFX_MAX_ORDERS_PER_TRADER") {
+        match max.parse() {
+            Ok(max) => state = state.with_max_orders_per_trader(max),
+            Err(_) => eprintln!("Ignoring CONVEXFX_MAX_ORDERS_PER_TRADER={} (expected a count)", max),
+        }
+    }
+
+    // API tokens as comma-separated `token:account` pairs
+    if let Ok(tokens) = std::env::var("CONVEXFX_API_TOKENS") {
+        for entry in tokens.split(',').filter(|e| !e.is_empty()) {
+            match entry.split_once(':') {
+                Some((token, account)) => state.register_api_token(token, AccountId::new(account)),
+                None => eprintln!("Ignoring malformed API token entry (expected token:account)"),
+            }
+        }
+    }
 
     // Create the app
     let app = create_app(state);
@@ -16,7 +16,9 @@ pub fn create_app(state: AppState) -> Router {
 
         // Orders
         .route("/v1/orders/submit", post(handlers::submit_order))
+        .route("/v1/orders/batch", post(handlers::submit_order_batch))
         .route("/v1/orders/reveal", post(handlers::reveal_order))
+        .route("/v1/orders/batch/reveal", post(handlers::reveal_order_batch))
         .route("/v1/orders/commit", post(handlers::submit_commitment))
 
         // Prices and market data
@@ -26,9 +28,15 @@ pub fn create_app(state: AppState) -> Router {
         .route("/v1/epochs", get(handlers::list_epochs))
         .route("/v1/epochs/current", get(handlers::get_epoch))
         .route("/v1/epochs/:epoch_id", get(handlers::get_epoch_by_id))
+        .route("/v1/epochs/execute", post(handlers::execute_batch))
+
+        // Streaming
+        .route("/ws/prices", get(handlers::stream_prices))
+        .route("/ws/orders/:account", get(handlers::stream_order_updates))
 
         // System status
         .route("/v1/status", get(handlers::get_system_status))
+        .route("/metrics", get(handlers::metrics))
 
         // Asset management
         .route("/v1/assets", get(handlers::list_assets))
@@ -1,11 +1,34 @@
 use convexfx_ledger::MemoryLedger;
 use convexfx_orders::OrderBook;
 use convexfx_oracle::MockOracle;
-use convexfx_clearing::ScpClearing;
+use convexfx_clearing::{EpochSolution, ScpClearing};
 use convexfx_report::MemoryReporter;
+use convexfx_risk::RiskParams;
+use convexfx_types::AccountId;
 // SolverBackend is defined in convexfx-solver but accessed through clearing
 use std::sync::{Arc, Mutex};
 use std::collections::BTreeMap;
+use tokio::sync::broadcast;
+
+use crate::handlers::{OrderStatus, PricesResponse};
+
+/// Account holding the liquidity batches clear against and settle with
+pub const POOL_ACCOUNT: &str = "pool";
+
+/// Number of price frames buffered for slow WebSocket subscribers
+const PRICE_CHANNEL_CAPACITY: usize = 64;
+
+/// Number of order status frames buffered per subscribed account
+const ORDER_CHANNEL_CAPACITY: usize = 256;
+
+/// Figures from the most recent cleared batch, exported on `/metrics`
+#[derive(Debug, Clone, Default)]
+pub struct BatchMetrics {
+    pub batches_total: u64,
+    pub last_batch_iterations: usize,
+    pub last_batch_slippage_p90_bps: f64,
+    pub last_batch_solve_ms: u64,
+}
 
 /// Shared application state
 #[derive(Clone)]
@@ -16,7 +39,19 @@ pub struct AppState {
     pub clearing_engine: Arc<ScpClearing>,
     pub reporter: Arc<Mutex<MemoryReporter>>,
     pub current_epoch: Arc<Mutex<u64>>,
+    /// Risk parameters every batch is cleared with
+    pub risk_params: RiskParams,
     pub epoch_states: Arc<Mutex<BTreeMap<u64, String>>>,
+    /// Clearing solution of every completed epoch
+    pub epoch_history: Arc<Mutex<BTreeMap<u64, EpochSolution>>>,
+    /// Clearing prices published after every batch
+    pub price_updates: broadcast::Sender<PricesResponse>,
+    /// Order status updates for each account with a live subscription
+    pub order_updates: Arc<Mutex<BTreeMap<AccountId, broadcast::Sender<OrderStatus>>>>,
+    /// Updated after every successfully cleared batch
+    pub batch_metrics: Arc<Mutex<BatchMetrics>>,
+    /// Bearer tokens and the trader account each one authenticates
+    pub api_tokens: Arc<Mutex<BTreeMap<String, AccountId>>>,
 }
 
 impl AppState {
@@ -28,9 +63,40 @@ impl AppState {
             clearing_engine: Arc::new(ScpClearing::new()),
             reporter: Arc::new(Mutex::new(MemoryReporter::new())),
             current_epoch: Arc::new(Mutex::new(1)),
+            risk_params: RiskParams::default_demo(),
             epoch_states: Arc::new(Mutex::new(BTreeMap::new())),
+            epoch_history: Arc::new(Mutex::new(BTreeMap::new())),
+            price_updates: broadcast::channel(PRICE_CHANNEL_CAPACITY).0,
+            order_updates: Arc::new(Mutex::new(BTreeMap::new())),
+            batch_metrics: Arc::new(Mutex::new(BatchMetrics::default())),
+            api_tokens: Arc::new(Mutex::new(BTreeMap::new())),
         }
     }
+
+    /// Accept at most `max` commitments and `max` reveals per trader in each
+    /// epoch
+    pub fn with_max_orders_per_trader(self, max: usize) -> Self {
+        {
+            let mut orderbook = self.orderbook.lock().unwrap();
+            *orderbook = orderbook.clone().with_max_orders_per_trader(max);
+        }
+        self
+    }
+
+    /// Receive status updates for `account`'s orders in every later batch
+    pub fn subscribe_orders(&self, account: &AccountId) -> broadcast::Receiver<OrderStatus> {
+        self.order_updates
+            .lock()
+            .unwrap()
+            .entry(account.clone())
+            .or_insert_with(|| broadcast::channel(ORDER_CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Authenticate requests bearing `token` as `account`
+    pub fn register_api_token(&self, token: impl Into<String>, account: AccountId) {
+        self.api_tokens.lock().unwrap().insert(token.into(), account);
+    }
 }
 
 impl Default for AppState {
@@ -3,6 +3,9 @@
 #[cfg(test)]
 mod tests {
     use crate::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
 
     #[test]
     fn test_app_state() {
@@ -10,6 +13,613 @@ mod tests {
         // Test we can clone it
         let _state2 = state.clone();
     }
-}
 
+    async fn post_json(state: &AppState, uri: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        post_json_as(state, uri, None, body).await
+    }
+
+    async fn post_json_as(
+        state: &AppState,
+        uri: &str,
+        token: Option<&str>,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::post(uri).header("content-type", "application/json");
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+        let response = create_app(state.clone())
+            .oneshot(request.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    /// Register `token` for `trader` and fund the trader with 1000 USD
+    async fn funded_trader(state: &AppState, token: &str, trader: &str) {
+        state.register_api_token(token, convexfx_types::AccountId::new(trader));
+        let (status, _) = post_json(state, "/v1/liquidity", serde_json::json!({
+            "account_id": trader,
+            "asset_symbol": "USD",
+            "amount": "1000"
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    /// Fund the pool with 1000 units of every asset
+    async fn funded_pool(state: &AppState) {
+        for asset in convexfx_types::AssetId::all() {
+            let (status, _) = post_json(state, "/v1/liquidity", serde_json::json!({
+                "account_id": POOL_ACCOUNT,
+                "asset_symbol": asset.to_string(),
+                "amount": "1000"
+            })).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+    }
+
+    /// Salt every test order is committed with
+    const SALT: &str = "00112233445566778899aabbccddeeff";
+
+    /// Reveal body for an order paying USD
+    fn reveal_body(order_id: &str, receive: &str, budget: &str, limit_ratio: Option<f64>) -> serde_json::Value {
+        serde_json::json!({
+            "order_id": order_id,
+            "pay_asset": "USD",
+            "receive_asset": receive,
+            "budget": budget,
+            "limit_ratio": limit_ratio,
+            "min_fill_fraction": null,
+            "salt": SALT
+        })
+    }
+
+    /// Commitment hash a client computes for `reveal` on behalf of `trader`
+    fn commitment_for(trader: &str, reveal: &serde_json::Value) -> String {
+        use convexfx_types::{AccountId, Amount, AssetId, PairOrder};
+        let field = |name: &str| reveal[name].as_str().unwrap();
+        let order = PairOrder {
+            id: field("order_id").to_string(),
+            trader: AccountId::new(trader),
+            pay: AssetId::from_str(field("pay_asset")).unwrap(),
+            receive: AssetId::from_str(field("receive_asset")).unwrap(),
+            budget: Amount::from_string(field("budget")).unwrap(),
+            limit_ratio: reveal["limit_ratio"].as_f64(),
+            min_fill_fraction: reveal["min_fill_fraction"].as_f64(),
+            max_slippage_bps: reveal["max_slippage_bps"].as_f64(),
+            submission_mid: None,
+            metadata: serde_json::json!({}),
+        };
+        let salt = hex::decode(field("salt")).unwrap();
+        convexfx_orders::compute_commitment(&order, &salt).unwrap().to_string()
+    }
+
+    /// Commit to `reveal` as `trader`, then reveal it
+    async fn commit_and_reveal(state: &AppState, token: &str, trader: &str, reveal: serde_json::Value) -> StatusCode {
+        let hash = commitment_for(trader, &reveal);
+        let (status, _) = post_json_as(state, "/v1/orders/submit", Some(token), serde_json::json!({
+            "commitment_hash": hash
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = post_json_as(state, "/v1/orders/reveal", Some(token), reveal).await;
+        status
+    }
+
+    #[tokio::test]
+    async fn test_commit_reveal_flow() {
+        let state = AppState::new();
+        funded_trader(&state, "alice-token", "alice").await;
+
+        // Only the hash reaches the server before the reveal
+        let reveal = |budget: &str| reveal_body("alice_order_1", "EUR", budget, Some(1.2));
+        let hash = commitment_for("alice", &reveal("1000"));
+        let (status, submitted) = post_json_as(&state, "/v1/orders/submit", Some("alice-token"), serde_json::json!({
+            "commitment_hash": hash
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(submitted["commitment_hash"], hash);
+        assert_eq!(submitted["epoch_id"], 1);
+
+        // Reveals are authenticated
+        let (status, _) = post_json(&state, "/v1/orders/reveal", reveal("1000")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // Tampered budget does not match the commitment
+        let (status, _) = post_json_as(&state, "/v1/orders/reveal", Some("alice-token"), reveal("5000")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(state.orderbook.lock().unwrap().revealed_count(), 0);
+
+        // Faithful reveal joins the pending orders
+        let (status, revealed) = post_json_as(&state, "/v1/orders/reveal", Some("alice-token"), reveal("1000")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(revealed["accepted"], true);
+        assert_eq!(revealed["epoch_id"], 1);
+        assert_eq!(state.orderbook.lock().unwrap().revealed_count(), 1);
+
+        // A commitment can only be revealed once
+        let (status, _) = post_json_as(&state, "/v1/orders/reveal", Some("alice-token"), reveal("1000")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_reveal_records_slippage_cap_and_mid() {
+        use convexfx_oracle::Oracle;
+        let state = AppState::new();
+        funded_trader(&state, "alice-token", "alice").await;
+
+        let mut reveal = reveal_body("alice_order_1", "EUR", "10", None);
+        reveal["max_slippage_bps"] = serde_json::json!(20.0);
+        let status = commit_and_reveal(&state, "alice-token", "alice", reveal).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let orders = state.orderbook.lock().unwrap().clone().freeze();
+        let mid = state.oracle.lock().unwrap().current_prices().unwrap().mid_rate(convexfx_types::AssetId::USD, convexfx_types::AssetId::EUR);
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].max_slippage_bps, Some(20.0));
+        assert_eq!(orders[0].submission_mid, Some(mid));
+    }
+
+    #[tokio::test]
+    async fn test_reveal_requires_salt() {
+        let state = AppState::new();
+        funded_trader(&state, "alice-token", "alice").await;
+
+        // Unsalted and short-salted commitments are stored, but can never
+        // be revealed: their orders could be brute-forced from the hash
+        for (index, salt) in ["", "00ff", "not hex"].into_iter().enumerate() {
+            let mut reveal = reveal_body(&format!("alice_order_{}", index), "EUR", "100", None);
+            reveal["salt"] = serde_json::json!(salt);
+            if hex::decode(salt).is_ok() {
+                let (status, _) = post_json_as(&state, "/v1/orders/submit", Some("alice-token"), serde_json::json!({
+                    "commitment_hash": commitment_for("alice", &reveal)
+                })).await;
+                assert_eq!(status, StatusCode::OK);
+            }
+            let (status, body) = post_json_as(&state, "/v1/orders/reveal", Some("alice-token"), reveal).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert!(body["error"].as_str().unwrap().contains("Salt"), "{}", body);
+        }
+        assert_eq!(state.orderbook.lock().unwrap().revealed_count(), 0);
+
+        // Malformed hashes are rejected at submission
+        let (status, _) = post_json_as(&state, "/v1/orders/submit", Some("alice-token"), serde_json::json!({
+            "commitment_hash": "abc"
+        })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_price_stream_receives_batch() {
+        use futures_util::StreamExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let state = AppState::new();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = create_app(state.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws/prices", addr))
+            .await
+            .unwrap();
+
+        // The subscription is registered once the upgrade completes
+        while state.price_updates.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let (status, batch) = post_json(&state, "/v1/epochs/execute", serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(batch["epoch_id"], 1);
+
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), ws.next())
+            .await
+            .expect("no price frame within 5s")
+            .unwrap()
+            .unwrap();
+        let Message::Text(text) = frame else {
+            panic!("expected text frame, got {:?}", frame);
+        };
+        let update: crate::handlers::PricesResponse = serde_json::from_str(&text).unwrap();
+
+        assert_eq!(update.epoch_id, 1);
+        let usd = update.prices.iter().find(|p| p.asset == "USD").unwrap();
+        assert!((usd.price - 1.0).abs() < 1e-12);
+        assert!(update.prices.iter().all(|p| (p.price - p.log_price.exp()).abs() < 1e-12));
+    }
+
+    #[tokio::test]
+    async fn test_order_stream_receives_own_fill() {
+        use futures_util::StreamExt;
+        use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
+
+        let state = AppState::new();
+        funded_pool(&state).await;
+
+        // Alice and Bob each get an order into the batch
+        let mut order_ids = Vec::new();
+        for (token, trader) in [("alice-token", "alice"), ("bob-token", "bob")] {
+            funded_trader(&state, token, trader).await;
+            let order_id = format!("{}_order_1", trader);
+            let status = commit_and_reveal(&state, token, trader, reveal_body(&order_id, "EUR", "1", None)).await;
+            assert_eq!(status, StatusCode::OK);
+            order_ids.push(order_id);
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = create_app(state.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let connect = |account: &str, token: &str| {
+            let mut request = format!("ws://{}/ws/orders/{}", addr, account).into_client_request().unwrap();
+            request.headers_mut().insert("authorization", format!("Bearer {}", token).parse().unwrap());
+            tokio_tungstenite::connect_async(request)
+        };
+
+        // Alice cannot listen to Bob's orders
+        assert!(connect("bob", "alice-token").await.is_err());
 
+        let (mut ws, _) = connect("alice", "alice-token").await.unwrap();
+        while state.order_updates.lock().unwrap().values().all(|sender| sender.receiver_count() == 0) {
+            tokio::task::yield_now().await;
+        }
+
+        let (status, _) = post_json(&state, "/v1/epochs/execute", serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), ws.next())
+            .await
+            .expect("no order frame within 5s")
+            .unwrap()
+            .unwrap();
+        let Message::Text(text) = frame else {
+            panic!("expected text frame, got {:?}", frame);
+        };
+        let update: crate::handlers::OrderStatus = serde_json::from_str(&text).unwrap();
+        assert_eq!(update.order_id, order_ids[0]);
+        assert_eq!(update.epoch_id, 1);
+        assert_eq!(update.status, crate::handlers::OrderFillStatus::Filled);
+        assert!(update.recv_units > 0.0);
+
+        // Bob's fill is not pushed to Alice
+        let next = tokio::time::timeout(std::time::Duration::from_millis(200), ws.next()).await;
+        assert!(next.is_err(), "unexpected frame: {:?}", next);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let state = AppState::new();
+        let (status, _) = post_json(&state, "/v1/epochs/execute", serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let response = create_app(state.clone())
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/plain"));
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(bytes.to_vec()).unwrap();
+
+        for name in [
+            "convexfx_current_epoch",
+            "convexfx_total_accounts",
+            "convexfx_pending_orders",
+            "convexfx_last_batch_iterations",
+            "convexfx_last_batch_slippage_p90_bps",
+            "convexfx_last_batch_solve_ms",
+        ] {
+            assert!(body.contains(&format!("# TYPE {} gauge", name)), "missing {}", name);
+        }
+        assert!(body.contains("convexfx_current_epoch 2\n"));
+        assert!(body.contains("convexfx_batches_total 1\n"));
+    }
+
+    #[tokio::test]
+    async fn test_get_epoch_by_id_returns_cleared_batch() {
+        let state = AppState::new();
+        funded_pool(&state).await;
+
+        funded_trader(&state, "alice-token", "alice").await;
+        let status = commit_and_reveal(&state, "alice-token", "alice", reveal_body("alice_order_1", "EUR", "10", None)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, batch) = post_json(&state, "/v1/epochs/execute", serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(batch["fill_count"], 1);
+
+        let get = |uri: &'static str| {
+            create_app(state.clone()).oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        };
+
+        let response = get("/v1/epochs/1").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let epoch: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(epoch["epoch_id"], 1);
+        assert_eq!(epoch["state"], "COMPLETED");
+        assert_eq!(epoch["fill_count"], batch["fill_count"]);
+        assert_eq!(epoch["fills"][0]["order_id"], "alice_order_1");
+        assert_eq!(epoch["prices"].as_array().unwrap().len(), 6);
+        assert!(epoch["diagnostics"]["iterations"].as_u64().unwrap() >= 1);
+
+        // Epochs that have not been cleared are not found
+        let response = get("/v1/epochs/2").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_settles_fills() {
+        use convexfx_ledger::Ledger;
+        use convexfx_types::{AccountId, AssetId};
+
+        let state = AppState::new();
+        funded_pool(&state).await;
+        funded_trader(&state, "alice-token", "alice").await;
+        let status = commit_and_reveal(&state, "alice-token", "alice", reveal_body("alice_order_1", "EUR", "10", None)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, batch) = post_json(&state, "/v1/epochs/execute", serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(batch["fill_count"], 1);
+
+        // Balances move by exactly the published fill
+        let fill = state.epoch_history.lock().unwrap()[&1].fills[0].clone();
+        assert!(fill.pay_units > 0.0 && fill.recv_units > 0.0);
+        let ledger = state.ledger.lock().unwrap();
+        let balance = |account: &str, asset: AssetId| ledger.balance(&AccountId::new(account), asset).to_f64();
+        assert!((balance("alice", AssetId::USD) - (1000.0 - fill.pay_units)).abs() < 1e-6);
+        assert!((balance("alice", AssetId::EUR) - fill.recv_units).abs() < 1e-6);
+        assert!((balance(POOL_ACCOUNT, AssetId::USD) - (1000.0 + fill.pay_units)).abs() < 1e-6);
+        assert!((balance(POOL_ACCOUNT, AssetId::EUR) - (1000.0 - fill.recv_units)).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_without_pool_settles_nothing() {
+        use convexfx_ledger::Ledger;
+        use convexfx_types::{AccountId, AssetId};
+
+        // Liquidity outside the pool is not traded against
+        let state = AppState::new();
+        let (status, _) = post_json(&state, "/v1/liquidity", serde_json::json!({
+            "account_id": "lp_1",
+            "asset_symbol": "EUR",
+            "amount": "1000"
+        })).await;
+        assert_eq!(status, StatusCode::OK);
+        funded_trader(&state, "alice-token", "alice").await;
+        let status = commit_and_reveal(&state, "alice-token", "alice", reveal_body("alice_order_1", "EUR", "10", None)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        post_json(&state, "/v1/epochs/execute", serde_json::json!({})).await;
+        let ledger = state.ledger.lock().unwrap();
+        assert_eq!(ledger.balance(&AccountId::new("alice"), AssetId::EUR).to_f64(), 0.0);
+        assert_eq!(ledger.balance(&AccountId::new("lp_1"), AssetId::EUR).to_f64(), 1000.0);
+    }
+
+    #[tokio::test]
+    async fn test_liquidity_balances_use_asset_decimals() {
+        let state = AppState::new();
+        let (_, jpy) = post_json(&state, "/v1/liquidity", serde_json::json!({
+            "account_id": "lp_1",
+            "asset_symbol": "JPY",
+            "amount": "1500.4"
+        })).await;
+        assert_eq!(jpy["new_balance"], "1500");
+        let (_, eur) = post_json(&state, "/v1/liquidity", serde_json::json!({
+            "account_id": "lp_1",
+            "asset_symbol": "EUR",
+            "amount": "12.346"
+        })).await;
+        assert_eq!(eur["new_balance"], "12.35");
+
+        let response = create_app(state.clone())
+            .oneshot(Request::get("/v1/liquidity").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let liquidity: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(liquidity["lp_1"]["JPY"], "1500");
+        assert_eq!(liquidity["lp_1"]["EUR"], "12.35");
+    }
+
+    #[tokio::test]
+    async fn test_submit_order_requires_authentication() {
+        let state = AppState::new();
+        funded_trader(&state, "alice-token", "alice").await;
+        state.register_api_token("bob-token", convexfx_types::AccountId::new("bob"));
+
+        let reveal = reveal_body("order_1", "EUR", "100", None);
+        let commitment = serde_json::json!({"commitment_hash": commitment_for("alice", &reveal)});
+
+        // Missing and unknown tokens are rejected before anything is committed
+        let (status, _) = post_json(&state, "/v1/orders/submit", commitment.clone()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = post_json_as(&state, "/v1/orders/submit", Some("forged"), commitment.clone()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(state.orderbook.lock().unwrap().commitment_count(), 0);
+
+        // The revealed order is attributed to the authenticated trader, so
+        // only alice can reveal the order she committed to
+        let (status, _) = post_json_as(&state, "/v1/orders/submit", Some("alice-token"), commitment).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = post_json_as(&state, "/v1/orders/reveal", Some("bob-token"), reveal.clone()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = post_json_as(&state, "/v1/orders/reveal", Some("alice-token"), reveal).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Balance checks apply to the authenticated account: bob holds no USD
+        let reveal = reveal_body("order_2", "EUR", "100", None);
+        let status = commit_and_reveal(&state, "bob-token", "bob", reveal).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_reveal_rejects_invalid_orders() {
+        let state = AppState::new();
+        funded_trader(&state, "alice-token", "alice").await;
+
+        let order = |id: &str, pay: &str, budget: &str, limit_ratio: Option<f64>, min_fill: Option<f64>| {
+            let mut reveal = reveal_body(id, "EUR", budget, limit_ratio);
+            reveal["pay_asset"] = serde_json::json!(pay);
+            reveal["min_fill_fraction"] = serde_json::json!(min_fill);
+            reveal
+        };
+
+        for (reveal, reason) in [
+            (order("o1", "EUR", "10", None, None), "different"),
+            (order("o2", "USD", "0", None, None), "budget"),
+            (order("o3", "USD", "10", Some(-1.0), None), "limit ratio"),
+            (order("o4", "USD", "10", None, Some(1.5)), "min fill"),
+        ] {
+            let hash = commitment_for("alice", &reveal);
+            let (status, _) = post_json_as(&state, "/v1/orders/submit", Some("alice-token"), serde_json::json!({
+                "commitment_hash": hash
+            })).await;
+            assert_eq!(status, StatusCode::OK);
+            let (status, body) = post_json_as(&state, "/v1/orders/reveal", Some("alice-token"), reveal).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert!(body["error"].as_str().unwrap().contains(reason), "{}", body);
+        }
+        assert_eq!(state.orderbook.lock().unwrap().revealed_count(), 0);
+
+        let status = commit_and_reveal(&state, "alice-token", "alice", order("o5", "USD", "10", Some(0.9), Some(0.5))).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_order_batch_is_atomic() {
+        let state = AppState::new();
+        funded_trader(&state, "alice-token", "alice").await;
+
+        let commitment = |reveal: &serde_json::Value| serde_json::json!({"commitment_hash": commitment_for("alice", reveal)});
+        let valid = |id: &str| reveal_body(id, "EUR", "100", None);
+        // Only the hash is sent, so an order paying and receiving EUR commits fine
+        let mut invalid = valid("o_bad");
+        invalid["pay_asset"] = serde_json::json!("EUR");
+
+        // A commitment repeated within the batch sinks the whole batch
+        let (status, body) = post_json_as(&state, "/v1/orders/batch", Some("alice-token"), serde_json::json!([
+            commitment(&valid("o1")),
+            commitment(&valid("o1")),
+        ])).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["orders"][1]["error"].as_str().unwrap().contains("already exists"));
+        assert_eq!(state.orderbook.lock().unwrap().commitment_count(), 0);
+
+        let (status, body) = post_json_as(&state, "/v1/orders/batch", Some("alice-token"), serde_json::json!([
+            commitment(&valid("o1")),
+            commitment(&invalid),
+            commitment(&valid("o2")),
+        ])).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["accepted"], true);
+        assert_eq!(body["epoch_id"], 1);
+        assert_eq!(state.orderbook.lock().unwrap().commitment_count(), 3);
+
+        // The invalid middle order sinks the whole reveal
+        let (status, body) = post_json_as(&state, "/v1/orders/batch/reveal", Some("alice-token"), serde_json::json!([
+            valid("o1"),
+            invalid,
+            valid("o2"),
+        ])).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["accepted"], false);
+        let statuses: Vec<bool> = body["orders"].as_array().unwrap().iter().map(|o| o["accepted"].as_bool().unwrap()).collect();
+        assert_eq!(statuses, vec![true, false, true]);
+        assert_eq!(body["orders"][1]["order_id"], "o_bad");
+        assert!(body["orders"][1]["error"].as_str().unwrap().contains("different"), "{}", body);
+        assert_eq!(state.orderbook.lock().unwrap().revealed_count(), 0);
+
+        // Without it, the rest land together
+        let (status, body) = post_json_as(&state, "/v1/orders/batch/reveal", Some("alice-token"), serde_json::json!([
+            valid("o1"),
+            valid("o2"),
+        ])).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["accepted"], true);
+        assert_eq!(state.orderbook.lock().unwrap().revealed_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_commitments_limited_per_trader() {
+        let state = AppState::new().with_max_orders_per_trader(2);
+        funded_trader(&state, "alice-token", "alice").await;
+        funded_trader(&state, "bob-token", "bob").await;
+
+        let commitment = |trader: &str, id: &str| {
+            serde_json::json!({"commitment_hash": commitment_for(trader, &reveal_body(id, "EUR", "100", None))})
+        };
+        for id in ["o1", "o2"] {
+            let (status, _) = post_json_as(&state, "/v1/orders/submit", Some("alice-token"), commitment("alice", id)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        // Alice's third commitment is refused, alone or in a batch
+        let (status, body) = post_json_as(&state, "/v1/orders/submit", Some("alice-token"), commitment("alice", "o3")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("already has 2 commitments"), "{}", body);
+        let (status, _) = post_json_as(&state, "/v1/orders/batch", Some("alice-token"), serde_json::json!([
+            commitment("alice", "o3"),
+        ])).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Bob is unaffected
+        let (status, _) = post_json_as(&state, "/v1/orders/batch", Some("bob-token"), serde_json::json!([
+            commitment("bob", "o1"),
+            commitment("bob", "o2"),
+        ])).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(state.orderbook.lock().unwrap().commitment_count(), 4);
+    }
+
+    async fn get_health(state: &AppState) -> (StatusCode, serde_json::Value) {
+        let response = create_app(state.clone())
+            .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    /// Backend that fails every solve
+    struct BrokenBackend;
+
+    impl convexfx_solver::SolverBackend for BrokenBackend {
+        fn solve_qp(&self, _model: &convexfx_solver::QpModel) -> convexfx_types::Result<convexfx_solver::QpSolution> {
+            Err(convexfx_types::ConvexFxError::SolverError("backend offline".to_string()))
+        }
+
+        fn name(&self) -> &'static str {
+            "broken"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_ok_with_clarabel() {
+        let state = AppState::new();
+        let (status, body) = get_health(&state).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["solver_backend"], "clarabel");
+        assert!(body.get("error").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_degraded_with_broken_backend() {
+        let mut state = AppState::new();
+        state.clearing_engine = std::sync::Arc::new(convexfx_clearing::ScpClearing::with_backend(
+            std::sync::Arc::new(BrokenBackend),
+            convexfx_clearing::ScpParams::default(),
+        ));
+        let (status, body) = get_health(&state).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "degraded");
+        assert_eq!(body["solver_backend"], "broken");
+        assert!(body["error"].as_str().unwrap().contains("backend offline"));
+    }
+}
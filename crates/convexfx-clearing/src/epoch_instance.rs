@@ -1,8 +1,22 @@
 use convexfx_oracle::RefPrices;
+use convexfx_report::{compute_hash, HashRef};
 use convexfx_risk::RiskParams;
-use convexfx_types::{AssetId, EpochId, PairOrder};
+use convexfx_types::{AccountId, AssetId, BasketOrder, ConvexFxError, EpochId, Order, PairOrder, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use crate::epoch_solution::EpochSolution;
+use crate::scp_clearing::ScpClearing;
+
+/// Grid that solution values are snapped to before fingerprinting
+const FINGERPRINT_QUANTUM: f64 = 1e-9;
+
+/// Provider name recorded on reference prices pinned by `with_fixed_prices`
+pub const FIXED_PRICE_PROVIDER: &str = "fixed";
+
+/// Multiplier on the fill incentive of pool orders over customer orders
+const POOL_ORDER_PRIORITY: f64 = 10.0;
 
 /// Input instance for epoch clearing
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,10 +26,59 @@ pub struct EpochInstance {
     pub inventory_q: BTreeMap<AssetId, f64>,
     /// Orders to be cleared
     pub orders: Vec<PairOrder>,
+    /// Basket orders cleared in the same solve as `orders`
+    #[serde(default)]
+    pub baskets: Vec<BasketOrder>,
+    /// Reduce-only orders the pool emits to unwind its own positions
+    ///
+    /// Written like any order: the pool takes in `pay` and gives up
+    /// `receive`, so unwinding a long EUR position is an order paying USD
+    /// for EUR. A pool order only fills while the pool is above target in
+    /// `receive`, at most down to the target, and is preferred over
+    /// customer orders in the objective.
+    #[serde(default)]
+    pub pool_orders: Vec<PairOrder>,
     /// Reference prices from oracle
     pub ref_prices: RefPrices,
     /// Risk parameters
     pub risk: RiskParams,
+    /// Asset whose log-price is pinned at 0; every other price is quoted in it
+    #[serde(default = "default_numeraire")]
+    pub numeraire: AssetId,
+    /// Scalars applied to each objective term
+    #[serde(default)]
+    pub objective_weights: ObjectiveWeights,
+    /// Weight of the pro-rata fairness regularizer. Above 0, pair orders
+    /// receiving the same asset are pulled towards a common fill fraction,
+    /// so scarce inventory is shared in proportion to budget instead of
+    /// going to whichever order the gradients favor.
+    #[serde(default)]
+    pub pro_rata_weight: f64,
+}
+
+/// Multipliers on the three terms of the clearing objective
+///
+/// `tracking` scales W in the QP, `incentive` scales η and `risk` scales the
+/// inventory penalty, which enters the solve through
+/// `risk.fill_incentive_cap`. All default to 1, leaving the objective as
+/// configured in `RiskParams`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObjectiveWeights {
+    pub risk: f64,
+    pub tracking: f64,
+    pub incentive: f64,
+}
+
+impl Default for ObjectiveWeights {
+    fn default() -> Self {
+        ObjectiveWeights { risk: 1.0, tracking: 1.0, incentive: 1.0 }
+    }
+}
+
+/// Numeraire of instances and solutions that do not specify one
+pub(crate) fn default_numeraire() -> AssetId {
+    AssetId::USD
 }
 
 impl EpochInstance {
@@ -30,20 +93,388 @@ impl EpochInstance {
             epoch_id,
             inventory_q,
             orders,
+            baskets: Vec::new(),
+            pool_orders: Vec::new(),
             ref_prices,
             risk,
+            numeraire: default_numeraire(),
+            objective_weights: ObjectiveWeights::default(),
+            pro_rata_weight: 0.0,
+        }
+    }
+
+    /// Like `new`, but rejects reference prices with non-finite values
+    /// instead of building an instance the solver cannot clear
+    pub fn try_new(
+        epoch_id: EpochId,
+        inventory_q: BTreeMap<AssetId, f64>,
+        orders: Vec<PairOrder>,
+        ref_prices: RefPrices,
+        risk: RiskParams,
+    ) -> Result<Self> {
+        ref_prices.validate()?;
+        Ok(Self::new(epoch_id, inventory_q, orders, ref_prices, risk))
+    }
+
+    /// Create an instance from a mixed list of pair and basket orders
+    ///
+    /// Pair orders keep their relative order in `orders`, baskets in `baskets`.
+    pub fn from_orders(
+        epoch_id: EpochId,
+        inventory_q: BTreeMap<AssetId, f64>,
+        orders: Vec<Order>,
+        ref_prices: RefPrices,
+        risk: RiskParams,
+    ) -> Self {
+        let mut inst = Self::new(epoch_id, inventory_q, Vec::new(), ref_prices, risk);
+        for order in orders {
+            match order {
+                Order::Pair(pair) => inst.orders.push(pair),
+                Order::Basket(basket) => inst.baskets.push(basket),
+            }
+        }
+        inst
+    }
+
+    /// Replace the oracle reference prices with a pinned set of linear prices
+    ///
+    /// Meant for reproducing a specific price regime: clearing, slippage and
+    /// fingerprinting all read `ref_prices`, so they see exactly these prices.
+    /// The numeraire stays at 1 whatever the input says. Every other asset
+    /// must be listed, with a positive and finite price.
+    pub fn with_fixed_prices(mut self, prices: BTreeMap<AssetId, f64>) -> Result<Self> {
+        if let Some(asset) = AssetId::all()
+            .iter()
+            .find(|asset| **asset != self.numeraire && !prices.contains_key(asset))
+        {
+            return Err(ConvexFxError::InvalidPrice(format!("no fixed price for {}", asset)));
+        }
+        if let Some((asset, price)) = prices
+            .iter()
+            .find(|(asset, price)| **asset != self.numeraire && !(price.is_finite() && **price > 0.0))
+        {
+            return Err(ConvexFxError::InvalidPrice(format!(
+                "fixed price for {} must be positive and finite, got {}",
+                asset, price
+            )));
+        }
+
+        let mut y_ref: BTreeMap<AssetId, f64> = prices
+            .iter()
+            .map(|(asset, price)| (*asset, price.ln()))
+            .collect();
+        y_ref.insert(self.numeraire, 0.0);
+
+        self.ref_prices = RefPrices::new(
+            y_ref,
+            self.risk.price_band_bps,
+            self.ref_prices.timestamp_ms,
+            vec![FIXED_PRICE_PROVIDER.to_string()],
+        );
+        Ok(self)
+    }
+
+    /// Quote every price in `numeraire` instead of USD
+    ///
+    /// Reference prices and their bands are rebased so y_ref[numeraire] = 0;
+    /// cross rates are unchanged. Clearing then pins y*[numeraire] = 0 in place of y*[USD].
+    pub fn with_numeraire(mut self, numeraire: AssetId) -> Self {
+        let shift = self.ref_prices.get_ref(numeraire);
+        let prices = &mut self.ref_prices;
+        for y in prices
+            .y_ref
+            .values_mut()
+            .chain(prices.band_low.values_mut())
+            .chain(prices.band_high.values_mut())
+        {
+            *y -= shift;
         }
+        self.numeraire = numeraire;
+        self
     }
 
-    /// Get number of orders
+    /// Scale the objective terms, e.g. to sweep fill rate against slippage
+    pub fn with_objective_weights(mut self, weights: ObjectiveWeights) -> Self {
+        self.objective_weights = weights;
+        self
+    }
+
+    /// Share scarce inventory pro rata (see `pro_rata_weight`)
+    pub fn with_pro_rata_fairness(mut self, weight: f64) -> Self {
+        self.pro_rata_weight = weight;
+        self
+    }
+
+    /// Add reduce-only pool orders (see `pool_orders`)
+    pub fn with_pool_orders(mut self, pool_orders: Vec<PairOrder>) -> Self {
+        self.pool_orders = pool_orders;
+        self
+    }
+
+    /// Cross-check the instance before clearing
+    ///
+    /// Rejects non-finite reference prices, orders touching an asset without
+    /// a reference price, slippage caps without a submission mid, malformed
+    /// baskets or baskets over `RiskParams::max_basket_legs`, inventory that does not cover exactly
+    /// `AssetId::all()`, risk bounds violating `q_min <= q_target <= q_max`
+    /// and a Γ that is not positive semidefinite. Bounds with `q_min > q_max`
+    /// admit no inventory at all and are reported as `Infeasible`.
+    pub fn validate(&self) -> Result<()> {
+        self.ref_prices.validate()?;
+
+        // Slippage caps are measured against the mid at submission
+        if let Some(order) = self.orders.iter().find(|o| o.max_slippage_bps.is_some() && o.submission_mid.is_none()) {
+            return Err(ConvexFxError::InvalidOrder(format!(
+                "order {} caps slippage but has no submission mid",
+                order.id
+            )));
+        }
+
+        for basket in &self.baskets {
+            convexfx_orders::validate_basket_order(basket, self.risk.max_basket_legs)?;
+        }
+
+        for order in self.order_legs() {
+            let assets = std::iter::once(order.pay).chain(order.legs.iter().map(|(asset, _)| *asset));
+            for asset in assets {
+                if !self.ref_prices.y_ref.contains_key(&asset) {
+                    return Err(ConvexFxError::InvalidPrice(format!(
+                        "order {} trades {} but there is no reference price for it",
+                        order.id, asset
+                    )));
+                }
+            }
+        }
+
+        let assets = AssetId::all();
+        let missing: Vec<String> = assets
+            .iter()
+            .filter(|asset| !self.inventory_q.contains_key(asset))
+            .map(|asset| asset.to_string())
+            .collect();
+        if !missing.is_empty() {
+            return Err(ConvexFxError::ConfigError(format!(
+                "inventory must list every asset, missing {}",
+                missing.join(", ")
+            )));
+        }
+
+        for asset in assets {
+            let (min, target, max) = (self.risk.min_bound(*asset), self.risk.target(*asset), self.risk.max_bound(*asset));
+            if min > max {
+                return Err(ConvexFxError::Infeasible(format!(
+                    "inventory bounds for {} cannot be satisfied: q_min {} > q_max {}",
+                    asset, min, max
+                )));
+            }
+            if !(min..=max).contains(&target) {
+                return Err(ConvexFxError::ConfigError(format!(
+                    "risk bounds for {} must satisfy q_min <= q_target <= q_max, got {} <= {} <= {}",
+                    asset, min, target, max
+                )));
+            }
+        }
+
+        if !self.risk.gamma_is_psd() {
+            return Err(ConvexFxError::ConfigError(
+                "inventory risk matrix gamma is not positive semidefinite".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Get number of orders (pair, basket and pool)
     pub fn num_orders(&self) -> usize {
-        self.orders.len()
+        self.orders.len() + self.baskets.len() + self.pool_orders.len()
+    }
+
+    /// Every order as a fill variable, in QP column order: pair orders
+    /// first, then baskets, then pool orders
+    pub(crate) fn order_legs(&self) -> Vec<OrderLegs<'_>> {
+        let pairs = self.orders.iter().map(|order| OrderLegs::pair(order, 1.0));
+        let baskets = self.baskets.iter().map(|basket| OrderLegs {
+            id: &basket.id,
+            trader: &basket.trader,
+            pay: basket.pay,
+            budget: basket.budget.to_f64(),
+            min_fill: basket.min_fill(),
+            legs: basket.normalized_weights(),
+            priority: 1.0,
+        });
+        let pool = self.pool_orders.iter().map(|order| OrderLegs::pair(order, POOL_ORDER_PRIORITY));
+        pairs.chain(baskets).chain(pool).collect()
+    }
+
+    /// Pair and pool orders with their QP column, the orders that can carry
+    /// a limit
+    pub(crate) fn pair_columns(&self) -> impl Iterator<Item = (usize, &PairOrder)> {
+        let pool_start = self.orders.len() + self.baskets.len();
+        self.orders
+            .iter()
+            .enumerate()
+            .chain(self.pool_orders.iter().enumerate().map(move |(p, order)| (pool_start + p, order)))
+    }
+
+    /// Largest fill fraction of a pool order that still reduces the pool's
+    /// distance to target in its `receive` asset
+    ///
+    /// Evaluated at reference prices against the starting inventory: zero
+    /// when the pool is at or below target, otherwise enough to bring it
+    /// back to target, capped at 1.
+    pub fn reduce_only_cap(&self, order: &PairOrder) -> f64 {
+        let excess = self.inventory_q.get(&order.receive).copied().unwrap_or(0.0)
+            - self.risk.target(order.receive);
+        let budget = order.budget.to_f64();
+        if excess <= 0.0 || budget <= 0.0 {
+            return 0.0;
+        }
+        let beta = (self.ref_prices.get_ref(order.pay) - self.ref_prices.get_ref(order.receive)).exp();
+        (excess / (budget * beta)).min(1.0)
+    }
+
+    /// Whether the pool holds `asset` at or below its minimum inventory, so
+    /// it cannot pay any out without crossing the bound
+    pub fn is_depleted(&self, asset: AssetId) -> bool {
+        self.inventory_q.get(&asset).copied().unwrap_or(0.0) <= self.risk.min_bound(asset)
+    }
+
+    /// Cap on an order's fill from the inventory hysteresis band: the
+    /// tightest damping over the asset the pool takes in and those it pays out
+    pub(crate) fn hysteresis_cap(&self, order: &OrderLegs) -> f64 {
+        let q = |asset: AssetId| self.inventory_q.get(&asset).copied().unwrap_or(0.0);
+        order
+            .legs
+            .iter()
+            .filter(|(_, share)| *share > 0.0)
+            .map(|(receive, _)| self.risk.hysteresis_damping(*receive, q(*receive), false))
+            .fold(self.risk.hysteresis_damping(order.pay, q(order.pay), true), f64::min)
+    }
+
+    /// Indices into `order_legs()` in canonical fill order: by order id,
+    /// then submission index (pairs before baskets)
+    ///
+    /// Fills are always reported in this order, so the same set of orders
+    /// yields the same fill sequence however the instance was assembled.
+    pub(crate) fn fill_order(&self) -> Vec<usize> {
+        let legs = self.order_legs();
+        let mut sequence: Vec<usize> = (0..legs.len()).collect();
+        sequence.sort_by(|a, b| legs[*a].id.cmp(legs[*b].id).then(a.cmp(b)));
+        sequence
     }
 
     /// Get number of assets
     pub fn num_assets(&self) -> usize {
         AssetId::all().len()
     }
+
+    /// Fill incentive weight η, scaled by `objective_weights.incentive`,
+    /// after applying `risk.fill_incentive_cap`
+    ///
+    /// Evaluated at reference prices with every order filled in full: the
+    /// total incentive η·Σ B_k·β_k may not exceed the cap times the weighted
+    /// inventory risk those fills add. If filling everything does not add
+    /// risk, there is no overshoot to guard against and η is returned
+    /// unchanged.
+    pub fn effective_eta(&self) -> f64 {
+        let eta = self.objective_weights.incentive * self.risk.eta;
+        let Some(cap) = self.risk.fill_incentive_cap else {
+            return eta;
+        };
+
+        let mut q_full = self.inventory_q.clone();
+        let mut full_incentive = 0.0;
+        for order in self.order_legs() {
+            *q_full.entry(order.pay).or_insert(0.0) += order.budget;
+            for (receive, share) in &order.legs {
+                let beta = (self.ref_prices.get_ref(order.pay) - self.ref_prices.get_ref(*receive)).exp();
+                full_incentive += order.budget * share * beta;
+                *q_full.entry(*receive).or_insert(0.0) -= order.budget * share * beta;
+            }
+        }
+
+        let added_risk = self.objective_weights.risk
+            * (self.risk.inventory_penalty(&q_full) - self.risk.inventory_penalty(&self.inventory_q));
+        if added_risk <= 0.0 || full_incentive <= 0.0 {
+            return eta;
+        }
+
+        eta.min(cap.max(0.0) * added_risk / full_incentive)
+    }
+
+    /// Clear this instance and hash the solution, for detecting divergence
+    /// across builds or platforms
+    ///
+    /// The solution is canonicalized before hashing: every float in `y_star`,
+    /// `q_post` and the fills (`fill_frac`, `pay_units`, `recv_units`) is
+    /// snapped to a 1e-9 grid, so last-bit floating point noise does not change
+    /// the fingerprint. Values lying within noise of a grid midpoint can still
+    /// round differently. Diagnostics and objective terms are excluded since
+    /// they contain wall-clock timings or are derived from the hashed values.
+    pub fn clearing_fingerprint(&self, solver: &ScpClearing) -> Result<HashRef> {
+        let solution = solver.clear_epoch(self)?;
+        Ok(compute_hash(canonical_solution(&solution).as_bytes()))
+    }
+}
+
+/// Flows of one order's fill variable
+///
+/// A pair order has a single leg with share 1; a basket splits its budget
+/// across legs by normalized weight. Filling a fraction alpha pays
+/// alpha * budget of `pay` and receives alpha * budget * share * exp(y_pay - y_leg)
+/// of each leg.
+pub(crate) struct OrderLegs<'a> {
+    pub id: &'a str,
+    pub trader: &'a AccountId,
+    pub pay: AssetId,
+    pub budget: f64,
+    pub min_fill: f64,
+    pub legs: Vec<(AssetId, f64)>,
+    /// Multiplier on the order's fill incentive
+    pub priority: f64,
+}
+
+impl<'a> OrderLegs<'a> {
+    fn pair(order: &'a PairOrder, priority: f64) -> Self {
+        OrderLegs {
+            id: &order.id,
+            trader: &order.trader,
+            pay: order.pay,
+            budget: order.budget.to_f64(),
+            min_fill: order.min_fill(),
+            legs: vec![(order.receive, 1.0)],
+            priority,
+        }
+    }
+}
+
+/// Snap a float to the fingerprint grid (normalizing -0 to 0)
+fn quantize(value: f64) -> i64 {
+    let q = (value / FINGERPRINT_QUANTUM).round() as i64;
+    if q == 0 { 0 } else { q }
+}
+
+/// Deterministic text form of the hashed parts of a solution
+fn canonical_solution(solution: &EpochSolution) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "epoch:{}", solution.epoch_id);
+    for (asset, y) in &solution.y_star {
+        let _ = writeln!(out, "y:{}:{}", asset, quantize(*y));
+    }
+    for (asset, q) in &solution.q_post {
+        let _ = writeln!(out, "q:{}:{}", asset, quantize(*q));
+    }
+    for fill in &solution.fills {
+        let _ = writeln!(
+            out,
+            "fill:{}:{}:{}:{}",
+            fill.order_id,
+            quantize(fill.fill_frac),
+            quantize(fill.pay_units),
+            quantize(fill.recv_units),
+        );
+    }
+    out
 }
 
 
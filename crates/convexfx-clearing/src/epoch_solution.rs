@@ -1,7 +1,11 @@
-use convexfx_types::{AssetId, EpochId, Fill};
+use convexfx_types::{AssetId, EpochId, Fill, OrderId};
+use convexfx_solver::{BindingConstraint, QpStatus};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+use crate::epoch_instance::{default_numeraire, EpochInstance, ObjectiveWeights};
+use crate::scp_clearing::MIN_FILL_TOLERANCE;
+
 /// Solution from epoch clearing
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EpochSolution {
@@ -12,21 +16,234 @@ pub struct EpochSolution {
     pub prices: BTreeMap<AssetId, f64>,
     /// Post-trade inventory
     pub q_post: BTreeMap<AssetId, f64>,
-    /// Order fills
+    /// Order fills, one per pair order and per basket leg, sorted by order
+    /// id and then submission index
     pub fills: Vec<Fill>,
     /// Objective function breakdown
     pub objective_terms: ObjectiveTerms,
     /// Diagnostic information
     pub diagnostics: Diagnostics,
+    /// Asset pinned at y* = 0 (see `EpochInstance::numeraire`)
+    #[serde(default = "default_numeraire")]
+    pub numeraire: AssetId,
+}
+
+impl EpochSolution {
+    /// Round linear prices to `sig_figs` significant figures for settlement
+    ///
+    /// The numeraire stays exactly 1.0. Returns the coherence error of the
+    /// published prices: the worst deviation (bps) of a cross rate they
+    /// imply from the cleared rate in `y_star`. `y_star` is then recomputed
+    /// from the rounded prices and every fill is repriced at the published
+    /// rate, with post-trade inventory rebuilt as in `reject_slippage_above`.
+    pub fn round_prices(&mut self, inst: &EpochInstance, sig_figs: u32) -> f64 {
+        for (asset, price) in self.prices.iter_mut() {
+            if *asset == self.numeraire {
+                *price = 1.0;
+            } else {
+                *price = round_sig_figs(*price, sig_figs);
+            }
+        }
+        let error_bps = self.coherence_error_bps();
+
+        for (asset, price) in &self.prices {
+            self.y_star.insert(*asset, price.ln());
+        }
+        let prices = &self.prices;
+        let price = |asset: AssetId| prices.get(&asset).copied().unwrap_or(1.0);
+        for fill in self.fills.iter_mut() {
+            fill.recv_units = fill.pay_units * price(fill.pay_asset) / price(fill.recv_asset);
+        }
+        self.recompute_q_post(inst);
+
+        error_bps
+    }
+
+    /// Cleared exchange rate: units of `to` received per unit of `from`
+    ///
+    /// Computed as exp(y*[from] - y*[to]), so it holds for any pair, not
+    /// just against the numeraire.
+    pub fn rate(&self, from: AssetId, to: AssetId) -> f64 {
+        let y = |asset: AssetId| self.y_star.get(&asset).copied().unwrap_or(0.0);
+        (y(from) - y(to)).exp()
+    }
+
+    /// Adverse slippage of a fill versus the oracle mid, in bps
+    ///
+    /// The mid is the order's `submission_mid` when the venue recorded one,
+    /// and the epoch's reference mid otherwise (baskets, and pair orders
+    /// without a slippage cap built outside a venue). Positive values mean
+    /// the trader received less than at the mid; favorable executions are
+    /// negative.
+    pub fn fill_slippage_bps(&self, inst: &EpochInstance, fill: &Fill) -> f64 {
+        let y = |asset: AssetId| self.y_star.get(&asset).copied().unwrap_or(0.0);
+        let delta_exec = y(fill.pay_asset) - y(fill.recv_asset);
+        let delta_mid = inst
+            .orders
+            .iter()
+            .find(|order| order.id == fill.order_id)
+            .and_then(|order| order.submission_mid)
+            .map(f64::ln)
+            .unwrap_or_else(|| inst.ref_prices.mid_rate(fill.pay_asset, fill.recv_asset).ln());
+        (delta_mid - delta_exec) * 10_000.0
+    }
+
+    /// Zero every fill whose adverse slippage exceeds `max_slippage_bps`
+    ///
+    /// A rejected fill also loses its `fees_paid`: no trade, no fee credited
+    /// to the pool. Post-trade inventory is recomputed from the instance's
+    /// starting inventory and the remaining fills, fees included, so
+    /// conservation still holds. Returns the ids of the rejected orders.
+    pub fn reject_slippage_above(
+        &mut self,
+        inst: &EpochInstance,
+        max_slippage_bps: f64,
+    ) -> Vec<OrderId> {
+        let rejected: Vec<OrderId> = self
+            .fills
+            .iter()
+            .filter(|f| f.fill_frac > 0.0 && self.fill_slippage_bps(inst, f) > max_slippage_bps)
+            .map(|f| f.order_id.clone())
+            .collect();
+
+        if rejected.is_empty() {
+            return rejected;
+        }
+
+        for fill in self.fills.iter_mut().filter(|f| rejected.contains(&f.order_id)) {
+            fill.fill_frac = 0.0;
+            fill.pay_units = 0.0;
+            fill.recv_units = 0.0;
+            fill.fees_paid.clear();
+        }
+        self.recompute_q_post(inst);
+
+        rejected
+    }
+
+    /// Scale fills down so no asset's gross flow exceeds its cap
+    ///
+    /// Gross flow is the numeraire value, at the cleared prices, of the
+    /// units of an asset paid into the pool plus the units paid out of it.
+    /// Every order touching an over-cap asset is scaled by that asset's cap
+    /// over its flow (the smallest such ratio across the order's legs), at
+    /// unchanged prices. An order scaled below its `min_fill_fraction`,
+    /// including an all-or-nothing order scaled at all, is zeroed instead.
+    /// Fees scale with the fill. Post-trade inventory is recomputed as in
+    /// `reject_slippage_above`.
+    /// Returns the throttled assets.
+    pub fn throttle_flows(
+        &mut self,
+        inst: &EpochInstance,
+        caps: &BTreeMap<AssetId, f64>,
+    ) -> Vec<AssetId> {
+        let price = |asset: AssetId| self.prices.get(&asset).copied().unwrap_or(0.0);
+        let mut flows: BTreeMap<AssetId, f64> = BTreeMap::new();
+        for fill in &self.fills {
+            *flows.entry(fill.pay_asset).or_insert(0.0) += fill.pay_units * price(fill.pay_asset);
+            *flows.entry(fill.recv_asset).or_insert(0.0) += fill.recv_units * price(fill.recv_asset);
+        }
+
+        let factors: BTreeMap<AssetId, f64> = caps
+            .iter()
+            .filter_map(|(asset, cap)| {
+                let flow = flows.get(asset).copied().unwrap_or(0.0);
+                (flow > *cap).then(|| (*asset, (cap / flow).max(0.0)))
+            })
+            .collect();
+        if factors.is_empty() {
+            return Vec::new();
+        }
+
+        let factor = |asset: AssetId| factors.get(&asset).copied().unwrap_or(1.0);
+        let mut order_factors: BTreeMap<OrderId, f64> = BTreeMap::new();
+        for fill in &self.fills {
+            let f = order_factors.entry(fill.order_id.clone()).or_insert(1.0);
+            *f = f.min(factor(fill.pay_asset)).min(factor(fill.recv_asset));
+        }
+
+        // Scaling must not leave an order below its minimum fill
+        for leg in inst.order_legs() {
+            let Some(f) = order_factors.get_mut(leg.id) else { continue };
+            let scaled_below_min = self.fills.iter().any(|fill| {
+                fill.order_id == leg.id
+                    && fill.fill_frac > 0.0
+                    && fill.fill_frac * *f + MIN_FILL_TOLERANCE < leg.min_fill
+            });
+            if scaled_below_min && *f < 1.0 {
+                tracing::warn!(order_id = leg.id, "throttled below min fill; order rejected");
+                *f = 0.0;
+            }
+        }
+
+        for fill in self.fills.iter_mut() {
+            let f = order_factors[&fill.order_id];
+            fill.fill_frac *= f;
+            fill.pay_units *= f;
+            fill.recv_units *= f;
+            for fee in fill.fees_paid.values_mut() {
+                *fee *= f;
+            }
+        }
+        self.recompute_q_post(inst);
+
+        factors.into_keys().collect()
+    }
+
+    /// Rebuild `q_post` from the instance's starting inventory and the fills,
+    /// counting each fill's `fees_paid` as credited to the pool
+    fn recompute_q_post(&mut self, inst: &EpochInstance) {
+        let mut q_post = inst.inventory_q.clone();
+        for (asset, delta) in self.fills.iter().flat_map(Fill::inventory_deltas) {
+            *q_post.entry(asset).or_insert(0.0) += delta;
+        }
+        self.q_post = q_post;
+    }
+
+    /// Worst triangular coherence error across all asset triples (bps)
+    ///
+    /// Compares the cross rate implied by chaining linear prices through a
+    /// third asset with the direct rate from `y_star`.
+    pub fn coherence_error_bps(&self) -> f64 {
+        let mut max_error: f64 = 0.0;
+        for (a, p_a) in &self.prices {
+            for (b, p_b) in &self.prices {
+                for (c, p_c) in &self.prices {
+                    if a == b || b == c || a == c {
+                        continue;
+                    }
+                    let implied = (p_a / p_b) * (p_b / p_c);
+                    let y_a = self.y_star.get(a).copied().unwrap_or(0.0);
+                    let y_c = self.y_star.get(c).copied().unwrap_or(0.0);
+                    let direct = (y_a - y_c).exp();
+                    max_error = max_error.max((implied / direct - 1.0).abs() * 10_000.0);
+                }
+            }
+        }
+        max_error
+    }
+}
+
+/// Round a positive value to the given number of significant figures
+fn round_sig_figs(value: f64, sig_figs: u32) -> f64 {
+    if value == 0.0 || !value.is_finite() || sig_figs == 0 {
+        return value;
+    }
+    let magnitude = value.abs().log10().floor() as i32;
+    let factor = 10f64.powi(sig_figs as i32 - 1 - magnitude);
+    (value * factor).round() / factor
 }
 
-/// Objective function breakdown
+/// Objective function breakdown, each term already scaled by `weights`
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObjectiveTerms {
     pub inventory_risk: f64,
     pub price_tracking: f64,
     pub fill_incentive: f64,
     pub total: f64,
+    /// Weights the terms were scaled by
+    #[serde(default)]
+    pub weights: ObjectiveWeights,
 }
 
 /// Diagnostic information from clearing
@@ -36,7 +253,26 @@ pub struct Diagnostics {
     pub convergence_achieved: bool,
     pub final_step_norm_y: f64,
     pub final_step_norm_alpha: f64,
-    pub qp_status: String,
+    /// Status of the final QP subproblem
+    pub qp_status: QpStatus,
+    /// Total wall-clock time spent in clear_epoch (milliseconds)
+    pub solve_time_ms: u64,
+    /// Wall-clock time of each SCP iteration (microseconds)
+    pub iteration_times_us: Vec<u64>,
+    /// Constraints active in the final QP subproblem
+    #[serde(default)]
+    pub binding_constraints: Vec<BindingConstraint>,
+    /// Objective total (see `ObjectiveTerms`) at the iterate after each SCP
+    /// iteration
+    #[serde(default)]
+    pub objective_trajectory: Vec<f64>,
+    /// Worst triangular coherence error of the returned prices (bps)
+    #[serde(default)]
+    pub max_coherence_error_bps: f64,
+    /// Backend that solved the final QP subproblem, e.g. "simple" when the
+    /// fallback replaced a non-converged Clarabel solve
+    #[serde(default)]
+    pub qp_backend: String,
 }
 
 
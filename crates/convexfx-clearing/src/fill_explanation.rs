@@ -0,0 +1,114 @@
+use convexfx_types::{AssetId, OrderId};
+use serde::{Deserialize, Serialize};
+
+use crate::epoch_instance::EpochInstance;
+use crate::epoch_solution::EpochSolution;
+
+/// Relative slack used when deciding whether a constraint is binding
+const BINDING_TOLERANCE: f64 = 1e-4;
+
+/// Reason an order was (or was not) filled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FillReason {
+    /// Order was completely filled
+    FullyFilled,
+    /// Clearing ratio p_receive/p_pay reached or exceeded the order's limit
+    LimitNotMet { limit_ratio: f64, clearing_ratio: f64 },
+    /// Post-trade inventory for an asset sits at its min/max bound
+    InventoryBound { asset: AssetId, q_post: f64, bound: f64 },
+    /// Requested minimum fill could not be reached
+    MinFillNotAchievable { min_fill: f64, fill_frac: f64 },
+    /// Price of an asset sits at the edge of its trust region
+    PriceBand { asset: AssetId, deviation_bps: f64 },
+    /// No constraint is binding: the optimizer traded the fill off against
+    /// inventory risk and price tracking
+    RiskTradeoff,
+}
+
+/// Explanation of a single order's fill outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillExplanation {
+    pub order_id: OrderId,
+    pub fill_frac: f64,
+    pub reasons: Vec<FillReason>,
+}
+
+impl FillExplanation {
+    /// Check whether any reason matches the predicate
+    pub fn cites(&self, pred: impl Fn(&FillReason) -> bool) -> bool {
+        self.reasons.iter().any(pred)
+    }
+}
+
+impl EpochSolution {
+    /// Explain why an order was unfilled or only partially filled
+    ///
+    /// Inspects the solution against the instance it was cleared from and
+    /// reports every constraint that is binding for the order. Returns `None`
+    /// if the order is not part of the instance or solution.
+    pub fn explain(&self, inst: &EpochInstance, order_id: &str) -> Option<FillExplanation> {
+        let order = inst.pair_columns().map(|(_, o)| o).find(|o| o.id == order_id)?;
+        let fill = self.fills.iter().find(|f| f.order_id == order_id)?;
+
+        if fill.is_complete() {
+            return Some(FillExplanation {
+                order_id: order.id.clone(),
+                fill_frac: fill.fill_frac,
+                reasons: vec![FillReason::FullyFilled],
+            });
+        }
+
+        let mut reasons = Vec::new();
+
+        let y_pay = self.y_star.get(&order.pay).copied().unwrap_or(0.0);
+        let y_recv = self.y_star.get(&order.receive).copied().unwrap_or(0.0);
+
+        // Limit: p_receive / p_pay must stay below limit_ratio
+        if let Some(limit_ratio) = order.limit_ratio {
+            let clearing_ratio = (y_recv - y_pay).exp();
+            if clearing_ratio >= limit_ratio * (1.0 - BINDING_TOLERANCE) {
+                reasons.push(FillReason::LimitNotMet { limit_ratio, clearing_ratio });
+            }
+        }
+
+        // Inventory: pool pays out the receive asset and takes in the pay asset
+        let q_recv = self.q_post.get(&order.receive).copied().unwrap_or(0.0);
+        let q_min = inst.risk.soft_min_bound(order.receive);
+        if q_min.is_finite() && q_recv <= q_min + BINDING_TOLERANCE * q_min.abs().max(1.0) {
+            reasons.push(FillReason::InventoryBound { asset: order.receive, q_post: q_recv, bound: q_min });
+        }
+        let q_pay = self.q_post.get(&order.pay).copied().unwrap_or(0.0);
+        let q_max = inst.risk.soft_max_bound(order.pay);
+        if q_max.is_finite() && q_pay >= q_max - BINDING_TOLERANCE * q_max.abs().max(1.0) {
+            reasons.push(FillReason::InventoryBound { asset: order.pay, q_post: q_pay, bound: q_max });
+        }
+
+        // Trust region: price pinned at the edge of its band
+        let band = inst.risk.price_band_bps / 10000.0;
+        for (asset, y) in [(order.pay, y_pay), (order.receive, y_recv)] {
+            if asset == inst.numeraire {
+                continue;
+            }
+            let deviation = (y - inst.ref_prices.get_ref(asset)).abs();
+            let band = band * inst.ref_prices.band_widening(asset);
+            if deviation >= band * (1.0 - BINDING_TOLERANCE) {
+                reasons.push(FillReason::PriceBand { asset, deviation_bps: deviation * 10000.0 });
+            }
+        }
+
+        let min_fill = order.min_fill();
+        if min_fill > 0.0 && fill.fill_frac < min_fill {
+            reasons.push(FillReason::MinFillNotAchievable { min_fill, fill_frac: fill.fill_frac });
+        }
+
+        if reasons.is_empty() {
+            reasons.push(FillReason::RiskTradeoff);
+        }
+
+        Some(FillExplanation {
+            order_id: order.id.clone(),
+            fill_frac: fill.fill_frac,
+            reasons,
+        })
+    }
+}
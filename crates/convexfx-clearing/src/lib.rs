@@ -2,10 +2,20 @@ mod epoch_instance;
 mod epoch_solution;
 mod scp_clearing;
 mod qp_builder;
+mod fill_explanation;
+mod validation;
+mod liquidity;
+mod stats;
 
-pub use epoch_instance::EpochInstance;
+pub use epoch_instance::{EpochInstance, ObjectiveWeights, FIXED_PRICE_PROVIDER};
 pub use epoch_solution::{EpochSolution, Diagnostics, ObjectiveTerms};
-pub use scp_clearing::{ScpClearing, ScpParams};
+pub use scp_clearing::{BoundRepair, ScpClearing, ScpParams};
+pub use fill_explanation::{FillExplanation, FillReason};
+/// Tolerances used by `EpochSolution::validate`, shared with the SP1 program
+pub use convexfx_local_laws::Tolerances as ValidationTolerances;
+pub use liquidity::estimate_required_liquidity;
+pub use stats::percentile;
+pub use convexfx_solver::QpStatus;
 
 #[cfg(test)]
 mod tests;
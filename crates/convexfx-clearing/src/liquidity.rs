@@ -0,0 +1,118 @@
+use convexfx_oracle::RefPrices;
+use convexfx_risk::RiskParams;
+use convexfx_types::{AssetId, ConvexFxError, PairOrder, Result};
+use std::collections::BTreeMap;
+
+use crate::epoch_instance::EpochInstance;
+use crate::epoch_solution::EpochSolution;
+use crate::scp_clearing::{ScpClearing, ScpParams};
+
+/// Halvings or doublings of the pool tried before giving up on a bracket
+const MAX_BRACKET_STEPS: usize = 30;
+
+/// Bisection steps once the minimum pool size is bracketed
+const BISECTION_STEPS: usize = 20;
+
+/// Smallest pool, as a multiple of `risk`'s targets, worth reporting
+const MIN_POOL_SCALE: f64 = 1e-6;
+
+/// Inventory needed to clear `expected_flow` at a fill rate of at least
+/// `target_fill`
+///
+/// Scales every target in `risk`, and its bounds with it, by a common factor
+/// and clears the flow in one epoch from the scaled targets with inventory
+/// bounds enforced. Doubles or halves the factor to bracket the target fill
+/// rate, then bisects to the smallest factor that reaches it. Fill rate is
+/// filled notional over submitted notional. The returned levels are the
+/// scaled targets; clearing against `risk.with_inventory_levels(&levels)`
+/// reproduces the estimate.
+pub fn estimate_required_liquidity(
+    expected_flow: &[PairOrder],
+    target_fill: f64,
+    risk: &RiskParams,
+    ref_prices: &RefPrices,
+) -> Result<BTreeMap<AssetId, f64>> {
+    if !(target_fill > 0.0 && target_fill <= 1.0) {
+        return Err(ConvexFxError::ConfigError(format!(
+            "target fill rate must be in (0, 1], got {}",
+            target_fill
+        )));
+    }
+
+    let clearing = ScpClearing::new().with_params(ScpParams {
+        enforce_inventory_bounds: true,
+        ..ScpParams::default()
+    });
+    let levels = |scale: f64| -> BTreeMap<AssetId, f64> {
+        risk.q_target.iter().map(|(asset, q)| (*asset, q * scale)).collect()
+    };
+    let meets_target = |scale: f64| -> Result<bool> {
+        let levels = levels(scale);
+        let inst = EpochInstance::new(
+            0,
+            levels.clone(),
+            expected_flow.to_vec(),
+            ref_prices.clone(),
+            risk.clone().with_inventory_levels(&levels),
+        );
+        Ok(fill_rate(expected_flow, &clearing.clear_epoch(&inst)?) >= target_fill)
+    };
+
+    // Bracket the smallest passing scale between `lo` (fails) and `hi` (passes)
+    let (mut lo, mut hi) = (1.0, 1.0);
+    if meets_target(1.0)? {
+        while lo > MIN_POOL_SCALE && meets_target(lo / 2.0)? {
+            lo /= 2.0;
+        }
+        hi = lo;
+        lo /= 2.0;
+        if hi <= MIN_POOL_SCALE {
+            return Ok(levels(hi));
+        }
+    } else {
+        let mut steps = 0;
+        loop {
+            hi *= 2.0;
+            if meets_target(hi)? {
+                break;
+            }
+            lo = hi;
+            steps += 1;
+            if steps >= MAX_BRACKET_STEPS {
+                return Err(ConvexFxError::Infeasible(format!(
+                    "fill rate {} not reached at {}x the target inventory",
+                    target_fill, hi
+                )));
+            }
+        }
+    }
+
+    for _ in 0..BISECTION_STEPS {
+        let mid = 0.5 * (lo + hi);
+        if meets_target(mid)? {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    Ok(levels(hi))
+}
+
+/// Filled notional over submitted notional
+pub(crate) fn fill_rate(orders: &[PairOrder], solution: &EpochSolution) -> f64 {
+    let submitted: f64 = orders.iter().map(|o| o.budget.to_f64()).sum();
+    if submitted <= 0.0 {
+        return 0.0;
+    }
+    let filled: f64 = solution
+        .fills
+        .iter()
+        .filter_map(|fill| {
+            orders
+                .iter()
+                .find(|o| o.id == fill.order_id)
+                .map(|o| fill.fill_frac * o.budget.to_f64())
+        })
+        .sum();
+    filled / submitted
+}
@@ -1,5 +1,5 @@
-use convexfx_solver::{QpModel, QpSolution, VarMeta};
-use convexfx_types::{AssetId, Result};
+use convexfx_solver::{ConstraintMeta, QpConstraint, QpModel, QpSolution, VarMeta};
+use convexfx_types::{AccountId, AssetId, Result};
 use nalgebra::{DMatrix, DVector};
 use std::collections::BTreeMap;
 
@@ -17,14 +17,22 @@ impl QpBuilder {
     ) -> Result<QpModel> {
         let assets = AssetId::all();
         let n_assets = assets.len();
-        let n_orders = inst.orders.len();
-        let n_vars = n_assets + n_orders; // y (with USD fixed at 0) + alpha
+        let n_orders = inst.num_orders();
+        let n_pairs = inst.orders.len();
+        let n_vars = n_assets + n_orders; // y (with the numeraire fixed at 0) + alpha (pairs, then baskets)
 
         // Build Hessian P = diag([W, 0]) + diag([Γ, 0]) from inventory linearization
         // Simplified: P = diag([W_diag, zeros])
+        let tracking_weight = inst.objective_weights.tracking;
+        let w_diag: Vec<f64> = inst
+            .risk
+            .tracking_weights(&inst.ref_prices.y_ref)
+            .iter()
+            .map(|w| tracking_weight * w)
+            .collect();
         let mut p_diag = vec![0.0; n_vars];
         for (i, _asset) in assets.iter().enumerate() {
-            p_diag[i] = inst.risk.w_diag[i];
+            p_diag[i] = w_diag[i];
         }
 
         // Add very small regularization to improve numerical stability
@@ -33,71 +41,137 @@ impl QpBuilder {
             p_diag[i] += regularization;
         }
 
-        let p = DMatrix::from_diagonal(&DVector::from_vec(p_diag));
+        let mut p = DMatrix::from_diagonal(&DVector::from_vec(p_diag));
 
         // Build linear term q
         let mut q_vec = vec![0.0; n_vars];
 
-        // Price tracking term: W * (y - y_ref)
+        // Price tracking term: 0.5 * (y - y_ref)' W (y - y_ref). The y columns
+        // are absolute log-prices, not steps from y_current, and the term is
+        // already quadratic, so its linear part is -W y_ref at every iterate
         for (i, asset) in assets.iter().enumerate() {
             let y_ref = inst.ref_prices.get_ref(*asset);
-            let y_curr = y_current.get(asset).copied().unwrap_or(0.0);
-            q_vec[i] = inst.risk.w_diag[i] * (y_curr - y_ref);
+            q_vec[i] = -w_diag[i] * y_ref;
         }
 
-        // Fill incentive: -eta * B_k * beta_k^(t)
-        for (k, order) in inst.orders.iter().enumerate() {
+        // Fill incentive: -eta * B_k * beta_k^(t), summed over basket legs
+        let eta = inst.effective_eta();
+        for (k, order) in inst.order_legs().iter().enumerate() {
             let y_j = y_current.get(&order.pay).copied().unwrap_or(0.0);
-            let y_i = y_current.get(&order.receive).copied().unwrap_or(0.0);
-            let beta_k = (y_j - y_i).exp();
-            let budget = order.budget.to_f64();
+            let mut incentive = 0.0;
+            for (receive, share) in &order.legs {
+                let y_i = y_current.get(receive).copied().unwrap_or(0.0);
+                let beta_k = (y_j - y_i).exp();
 
-            // Clamp beta_k to avoid extreme values that can cause numerical issues
-            let beta_k_clamped = beta_k.max(1e-10).min(1e10);
-            q_vec[n_assets + k] = -inst.risk.eta * budget * beta_k_clamped;
+                // Clamp beta_k to avoid extreme values that can cause numerical issues
+                let beta_k_clamped = beta_k.clamp(1e-10, 1e10);
+                incentive += order.budget * share * beta_k_clamped;
+            }
+            q_vec[n_assets + k] = -eta * order.priority * incentive;
         }
 
         // Build constraint matrix A and bounds l, u
-        let n_constraints = n_assets + n_orders + 1 + inst.orders.iter().filter(|o| o.has_limit()).count();
+        let n_constraints = n_assets + n_orders + 1 + inst.pair_columns().filter(|(_, o)| o.has_limit()).count();
         let mut a_data = vec![vec![0.0; n_vars]; n_constraints];
         let mut l_vec = vec![0.0; n_constraints];
         let mut u_vec = vec![0.0; n_constraints];
+        let mut constraint_meta = Vec::with_capacity(n_constraints);
 
         let mut row = 0;
 
-        // USD numeraire
-        let usd_idx = AssetId::USD.index();
-        a_data[row][usd_idx] = 1.0;
+        // Numeraire
+        let numeraire_idx = inst.numeraire.index();
+        a_data[row][numeraire_idx] = 1.0;
         l_vec[row] = 0.0;
         u_vec[row] = 0.0;
+        constraint_meta.push(ConstraintMeta::Numeraire);
         row += 1;
 
         // Price bands with adaptive trust regions
+        let band_half = bands / 10000.0; // Convert bps to decimal
+
+        // For very tight bands, use a more reasonable minimum to avoid numerical issues
+        // but still allow tight constraints to be satisfied
+        let min_band = if band_half < 1e-4 { band_half * 10.0 } else { 1e-6 };
+        let effective_band = band_half.max(min_band);
+
+        // Assets the oracle is less sure of get proportionally wider bands
+        let asset_band = |asset: AssetId| effective_band * inst.ref_prices.band_widening(asset);
+
         for (i, asset) in assets.iter().enumerate() {
             let y_ref = inst.ref_prices.get_ref(*asset);
-            let band_half = bands / 10000.0; // Convert bps to decimal
-
-            // For very tight bands, use a more reasonable minimum to avoid numerical issues
-            // but still allow tight constraints to be satisfied
-            let min_band = if band_half < 1e-4 { band_half * 10.0 } else { 1e-6 };
-            let effective_band = band_half.max(min_band);
 
             a_data[row][i] = 1.0;
-            l_vec[row] = y_ref - effective_band;
-            u_vec[row] = y_ref + effective_band;
+            l_vec[row] = y_ref - asset_band(*asset);
+            u_vec[row] = y_ref + asset_band(*asset);
+            constraint_meta.push(ConstraintMeta::PriceBand(*asset));
             row += 1;
         }
 
-        // Fill bounds
-        for k in 0..n_orders {
+        // A limit y_recv - y_pay <= log(limit_ratio) that no price inside the
+        // trust region satisfies cannot be filled; pin those orders at zero
+        // instead of letting the limit row drag prices or make the QP infeasible
+        let band_edge = |asset: AssetId, dir: f64| {
+            if asset == inst.numeraire {
+                0.0
+            } else {
+                inst.ref_prices.get_ref(asset) + dir * asset_band(asset)
+            }
+        };
+        let mut limit_attainable = vec![true; n_orders];
+        for (k, order) in inst.pair_columns() {
+            if let Some(log_limit) = order.log_limit() {
+                limit_attainable[k] = band_edge(order.receive, -1.0) - band_edge(order.pay, 1.0) <= log_limit;
+            }
+        }
+
+        if inst.pro_rata_weight > 0.0 {
+            Self::add_pro_rata_fairness(&mut p, inst, n_assets, &limit_attainable);
+        }
+
+        // The pool cannot pay out an asset it holds at or below its minimum
+        // without crossing the bound; pin orders receiving it at zero
+        // Fill bounds, damped inside the inventory hysteresis band
+        let legs = inst.order_legs();
+        for (k, order) in inst.orders.iter().enumerate() {
             a_data[row][n_assets + k] = 1.0;
             l_vec[row] = 0.0;
-            u_vec[row] = 1.0;
+            u_vec[row] = if limit_attainable[k] && !inst.is_depleted(order.receive) {
+                inst.hysteresis_cap(&legs[k])
+            } else {
+                0.0
+            };
+            constraint_meta.push(ConstraintMeta::FillBound(order.id.clone()));
+            row += 1;
+        }
+        for (b, basket) in inst.baskets.iter().enumerate() {
+            a_data[row][n_assets + n_pairs + b] = 1.0;
+            l_vec[row] = 0.0;
+            let pays_depleted = basket
+                .normalized_weights()
+                .iter()
+                .any(|(asset, share)| *share > 0.0 && inst.is_depleted(*asset));
+            u_vec[row] = if pays_depleted { 0.0 } else { inst.hysteresis_cap(&legs[n_pairs + b]) };
+            constraint_meta.push(ConstraintMeta::FillBound(basket.id.clone()));
+            row += 1;
+        }
+        // Pool orders are reduce-only: never past the pool's excess over target
+        let pool_start = n_pairs + inst.baskets.len();
+        for (p, order) in inst.pool_orders.iter().enumerate() {
+            let k = pool_start + p;
+            a_data[row][n_assets + k] = 1.0;
+            l_vec[row] = 0.0;
+            u_vec[row] = if limit_attainable[k] && !inst.is_depleted(order.receive) {
+                inst.reduce_only_cap(order)
+            } else {
+                0.0
+            };
+            constraint_meta.push(ConstraintMeta::FillBound(order.id.clone()));
             row += 1;
         }
 
-        // Limit constraints
-        for (_k, order) in inst.orders.iter().enumerate() {
+        // Limit constraints: p_recv / p_pay <= limit_ratio, exact in log space
+        for (k, order) in inst.pair_columns() {
             if let Some(log_limit) = order.log_limit() {
                 let i_idx = order.receive.index();
                 let j_idx = order.pay.index();
@@ -105,7 +179,8 @@ impl QpBuilder {
                 a_data[row][i_idx] = 1.0;
                 a_data[row][j_idx] = -1.0;
                 l_vec[row] = f64::NEG_INFINITY;
-                u_vec[row] = log_limit;
+                u_vec[row] = if limit_attainable[k] { log_limit } else { f64::INFINITY };
+                constraint_meta.push(ConstraintMeta::Limit(order.id.clone()));
                 row += 1;
             }
         }
@@ -120,8 +195,266 @@ impl QpBuilder {
         for order in &inst.orders {
             var_meta.push(VarMeta::FillFraction(order.id.clone()));
         }
+        for basket in &inst.baskets {
+            var_meta.push(VarMeta::FillFraction(basket.id.clone()));
+        }
+        for order in &inst.pool_orders {
+            var_meta.push(VarMeta::FillFraction(order.id.clone()));
+        }
+
+        Ok(QpModel::new(p, DVector::from_vec(q_vec), a, DVector::from_vec(l_vec.clone()), DVector::from_vec(u_vec.clone()), var_meta)
+            .with_constraint_meta(constraint_meta))
+    }
+
+    /// Add the pro-rata fairness regularizer to the Hessian
+    ///
+    /// For each receive asset, penalizes weight * sum_k (alpha_k - mean)^2
+    /// over the pair orders receiving it, i.e. adds 2 * weight * (I - 11'/n)
+    /// to their block of P. Orders pinned at zero by an unattainable limit
+    /// are left out so they do not drag the others down.
+    fn add_pro_rata_fairness(
+        p: &mut DMatrix<f64>,
+        inst: &EpochInstance,
+        n_assets: usize,
+        limit_attainable: &[bool],
+    ) {
+        let mut groups: BTreeMap<AssetId, Vec<usize>> = BTreeMap::new();
+        for (k, order) in inst.orders.iter().enumerate() {
+            if limit_attainable[k] {
+                groups.entry(order.receive).or_default().push(n_assets + k);
+            }
+        }
+
+        for members in groups.values().filter(|members| members.len() > 1) {
+            let n = members.len() as f64;
+            for &i in members {
+                for &j in members {
+                    let identity = if i == j { 1.0 } else { 0.0 };
+                    p[(i, j)] += 2.0 * inst.pro_rata_weight * (identity - 1.0 / n);
+                }
+            }
+        }
+    }
+
+    /// Append linearized inventory bound constraints to a QP
+    ///
+    /// For each asset with a finite bound that some order touches, adds the
+    /// row q_min - q <= sum_k dq_k * alpha_k <= q_max - q, where dq_k is +B_k
+    /// on the pay asset and -B_k * beta_k on each receive leg (scaled by the
+    /// leg's basket share) at `y_current`.
+    /// Bounds include the ghost inventory cushion and are relaxed to include
+    /// the current inventory so that not trading is always feasible.
+    pub fn add_inventory_constraints(
+        model: QpModel,
+        inst: &EpochInstance,
+        y_current: &BTreeMap<AssetId, f64>,
+    ) -> QpModel {
+        let n_assets = AssetId::all().len();
+
+        let mut coeffs_by_asset: BTreeMap<AssetId, BTreeMap<usize, f64>> = BTreeMap::new();
+        for (k, order) in inst.order_legs().iter().enumerate() {
+            let y_j = y_current.get(&order.pay).copied().unwrap_or(0.0);
+            *coeffs_by_asset.entry(order.pay).or_default().entry(n_assets + k).or_insert(0.0) += order.budget;
+            for (receive, share) in &order.legs {
+                let y_i = y_current.get(receive).copied().unwrap_or(0.0);
+                *coeffs_by_asset.entry(*receive).or_default().entry(n_assets + k).or_insert(0.0) -=
+                    order.budget * share * (y_j - y_i).exp();
+            }
+        }
+        coeffs_by_asset.retain(|asset, _| {
+            inst.risk.min_bound(*asset).is_finite() || inst.risk.max_bound(*asset).is_finite()
+        });
+
+        if coeffs_by_asset.is_empty() {
+            return model;
+        }
+
+        let n_vars = model.num_vars();
+        let m_base = model.num_constraints();
+        let n_constraints = m_base + coeffs_by_asset.len();
+
+        let mut a = DMatrix::zeros(n_constraints, n_vars);
+        a.view_mut((0, 0), (m_base, n_vars)).copy_from(&model.a);
+        let mut l = DVector::zeros(n_constraints);
+        let mut u = DVector::zeros(n_constraints);
+        l.rows_mut(0, m_base).copy_from(&model.l);
+        u.rows_mut(0, m_base).copy_from(&model.u);
+
+        let mut added_meta = Vec::new();
+        for (r, (asset, coeffs)) in coeffs_by_asset.iter().enumerate() {
+            let row = m_base + r;
+            let q = inst.inventory_q.get(asset).copied().unwrap_or(0.0);
+            for (col, coeff) in coeffs {
+                a[(row, *col)] = *coeff;
+            }
+            l[row] = (inst.risk.soft_min_bound(*asset) - q).min(0.0);
+            u[row] = (inst.risk.soft_max_bound(*asset) - q).max(0.0);
+            added_meta.push(ConstraintMeta::InventoryBound(*asset));
+        }
+
+        let mut constraint_meta = model.constraint_meta;
+        if !constraint_meta.is_empty() {
+            constraint_meta.extend(added_meta);
+        }
+
+        QpModel::new(model.p, model.q, a, l, u, model.var_meta).with_constraint_meta(constraint_meta)
+    }
+
+    /// Append per-trader position limit constraints to a QP
+    ///
+    /// For each (trader, asset) pair with a limit in
+    /// `risk.trader_position_limits`, adds -L <= sum_k dp_k * alpha_k <= L,
+    /// where dp_k is +B_k * beta_k if order k receives the asset and -B_k if
+    /// it pays it, linearized at `y_current`.
+    pub fn add_position_limit_constraints(
+        model: QpModel,
+        inst: &EpochInstance,
+        y_current: &BTreeMap<AssetId, f64>,
+    ) -> QpModel {
+        let n_assets = AssetId::all().len();
+
+        let mut coeffs_by_position: BTreeMap<(AccountId, AssetId), BTreeMap<usize, f64>> = BTreeMap::new();
+        for (k, order) in inst.order_legs().iter().enumerate() {
+            let y_j = y_current.get(&order.pay).copied().unwrap_or(0.0);
+            for (receive, share) in &order.legs {
+                let y_i = y_current.get(receive).copied().unwrap_or(0.0);
+                *coeffs_by_position
+                    .entry((order.trader.clone(), *receive))
+                    .or_default()
+                    .entry(n_assets + k)
+                    .or_insert(0.0) += order.budget * share * (y_j - y_i).exp();
+            }
+            *coeffs_by_position
+                .entry((order.trader.clone(), order.pay))
+                .or_default()
+                .entry(n_assets + k)
+                .or_insert(0.0) -= order.budget;
+        }
+        coeffs_by_position.retain(|(_, asset), _| inst.risk.position_limit(*asset).is_finite());
+
+        if coeffs_by_position.is_empty() {
+            return model;
+        }
+
+        let n_vars = model.num_vars();
+        let m_base = model.num_constraints();
+        let n_constraints = m_base + coeffs_by_position.len();
+
+        let mut a = DMatrix::zeros(n_constraints, n_vars);
+        a.view_mut((0, 0), (m_base, n_vars)).copy_from(&model.a);
+        let mut l = DVector::zeros(n_constraints);
+        let mut u = DVector::zeros(n_constraints);
+        l.rows_mut(0, m_base).copy_from(&model.l);
+        u.rows_mut(0, m_base).copy_from(&model.u);
+
+        let mut added_meta = Vec::new();
+        for (r, ((trader, asset), coeffs)) in coeffs_by_position.iter().enumerate() {
+            let row = m_base + r;
+            let limit = inst.risk.position_limit(*asset).abs();
+            for (col, coeff) in coeffs {
+                a[(row, *col)] = *coeff;
+            }
+            l[row] = -limit;
+            u[row] = limit;
+            added_meta.push(ConstraintMeta::PositionLimit(trader.clone(), *asset));
+        }
+
+        let mut constraint_meta = model.constraint_meta;
+        if !constraint_meta.is_empty() {
+            constraint_meta.extend(added_meta);
+        }
+
+        QpModel::new(model.p, model.q, a, l, u, model.var_meta).with_constraint_meta(constraint_meta)
+    }
+
+    /// Append explicit triangular coherence constraints to a QP
+    ///
+    /// Each distinct leg (i, j) of the given triangles gets a cross-rate
+    /// variable tied to the prices by the equality y_ij = y_i - y_j, and each
+    /// triangle (a, b, c) is closed with |y_ab + y_bc - y_ac| <= tolerance.
+    /// Any cost or constraint placed on a y_ij therefore acts on the prices
+    /// it is tied to. The new variables are appended after the existing
+    /// ones, so `extract_solution` is unaffected.
+    pub fn add_coherence_constraints(
+        model: QpModel,
+        triangles: &[(AssetId, AssetId, AssetId)],
+        tolerance: f64,
+    ) -> QpModel {
+        if triangles.is_empty() {
+            return model;
+        }
+
+        let n_base = model.num_vars();
+        let m_base = model.num_constraints();
+
+        // Assign one cross-rate variable per distinct leg
+        let mut legs: BTreeMap<(AssetId, AssetId), usize> = BTreeMap::new();
+        for (a, b, c) in triangles {
+            for leg in [(*a, *b), (*b, *c), (*a, *c)] {
+                let next = n_base + legs.len();
+                legs.entry(leg).or_insert(next);
+            }
+        }
+
+        let mut constraints = Vec::new();
+        let mut added_meta = Vec::new();
+        for ((i, j), ij) in &legs {
+            constraints.push(QpConstraint::cross_rate(i.index(), j.index(), *ij, 0.0));
+            added_meta.push(ConstraintMeta::CrossRate(*i, *j));
+        }
+        for (a, b, c) in triangles {
+            let mut coeffs = BTreeMap::new();
+            *coeffs.entry(legs[&(*a, *b)]).or_insert(0.0) += 1.0;
+            *coeffs.entry(legs[&(*b, *c)]).or_insert(0.0) += 1.0;
+            *coeffs.entry(legs[&(*a, *c)]).or_insert(0.0) -= 1.0;
+            constraints.push(QpConstraint::ineq(coeffs, -tolerance, tolerance));
+            added_meta.push(ConstraintMeta::Triangle(*a, *b, *c));
+        }
+
+        let n_vars = n_base + legs.len();
+        let n_constraints = m_base + constraints.len();
+
+        // Extend P with a small regularization on the auxiliary variables
+        let mut p = DMatrix::zeros(n_vars, n_vars);
+        p.view_mut((0, 0), (n_base, n_base)).copy_from(&model.p);
+        for k in n_base..n_vars {
+            p[(k, k)] = 1e-10;
+        }
+
+        let mut q = DVector::zeros(n_vars);
+        q.rows_mut(0, n_base).copy_from(&model.q);
+
+        let mut a = DMatrix::zeros(n_constraints, n_vars);
+        a.view_mut((0, 0), (m_base, n_base)).copy_from(&model.a);
+        let mut l = DVector::zeros(n_constraints);
+        let mut u = DVector::zeros(n_constraints);
+        l.rows_mut(0, m_base).copy_from(&model.l);
+        u.rows_mut(0, m_base).copy_from(&model.u);
+
+        for (r, constraint) in constraints.iter().enumerate() {
+            let row = m_base + r;
+            for (col, coeff) in &constraint.coeffs {
+                a[(row, *col)] = *coeff;
+            }
+            l[row] = constraint.lower;
+            u[row] = constraint.upper;
+        }
+
+        let mut var_meta = model.var_meta;
+        let mut leg_meta: Vec<(usize, VarMeta)> = legs
+            .iter()
+            .map(|((i, j), idx)| (*idx, VarMeta::CrossRate(*i, *j)))
+            .collect();
+        leg_meta.sort_by_key(|(idx, _)| *idx);
+        var_meta.extend(leg_meta.into_iter().map(|(_, meta)| meta));
+
+        // Keep row labels aligned if the base model was labeled
+        let mut constraint_meta = model.constraint_meta;
+        if !constraint_meta.is_empty() {
+            constraint_meta.extend(added_meta);
+        }
 
-        Ok(QpModel::new(p, DVector::from_vec(q_vec), a, DVector::from_vec(l_vec.clone()), DVector::from_vec(u_vec.clone()), var_meta))
+        QpModel::new(p, q, a, l, u, var_meta).with_constraint_meta(constraint_meta)
     }
 
     /// Extract y and alpha from QP solution
@@ -131,7 +464,7 @@ impl QpBuilder {
     ) -> Result<(BTreeMap<AssetId, f64>, Vec<f64>)> {
         let assets = AssetId::all();
         let n_assets = assets.len();
-        let n_orders = inst.orders.len();
+        let n_orders = inst.num_orders();
 
         // Check for NaN values in solution
         for (i, &val) in solution.x.iter().enumerate() {
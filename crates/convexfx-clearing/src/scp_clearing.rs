@@ -1,13 +1,56 @@
-use convexfx_solver::{SolverBackend, SimpleQpSolver, OsqpSolver};
-use convexfx_types::{AssetId, Fill, Result};
+use convexfx_solver::{SolverBackend, SimpleQpSolver, ClarabelSolver, DeterministicSolver, QpModel, QpSolution, QpStatus};
+#[cfg(feature = "osqp")]
+use convexfx_solver::OsqpSolver;
+use convexfx_types::{AccountId, AssetId, ConvexFxError, Fill, PairOrder, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::Instant;
 
-use crate::epoch_instance::EpochInstance;
+use crate::epoch_instance::{EpochInstance, OrderLegs};
 use crate::epoch_solution::{Diagnostics, EpochSolution, ObjectiveTerms};
 use crate::qp_builder::QpBuilder;
 
+/// Relative slack allowed on an order's limit ratio before its fill is dropped
+const LIMIT_TOLERANCE: f64 = 1e-3;
+
+/// Coherence tolerance (bps) between rounded and cleared cross rates when
+/// no explicit `coherence_tolerance_bps` is configured
+const ROUNDED_COHERENCE_TOLERANCE_BPS: f64 = 1.0;
+
+/// Slack below an order's min_fill_fraction before it counts as missed
+pub(crate) const MIN_FILL_TOLERANCE: f64 = 1e-6;
+
+/// Slack (bps) above an order's max_slippage_bps before its fill is rejected
+const SLIPPAGE_CAP_TOLERANCE_BPS: f64 = 1e-6;
+
+/// Largest constraint violation at which a QP solution still counts as
+/// feasible when choosing between the backend and the fallback solver
+const FALLBACK_FEASIBILITY_TOLERANCE: f64 = 1e-6;
+
+/// Relative slack on an inventory bound before an iterate counts as breaching it
+const BOUND_TOLERANCE: f64 = 1e-9;
+
+/// Step used for the finite-difference directional derivative in the Armijo test
+const ARMIJO_PROBE_STEP: f64 = 1e-6;
+
+/// How an SCP step whose exact (nonlinear) inventory breaches the bounds is
+/// handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BoundRepair {
+    /// Take the full QP step as proposed
+    #[default]
+    None,
+    /// Backtracking line search: halve the step, up to
+    /// `line_search_max_steps` times, until it stays within bounds. If no
+    /// step does, the smallest one is taken.
+    Reject,
+    /// Project the proposed inventory onto the bounds by scaling back the
+    /// fills that push each breached asset out, falling back to `Reject`
+    /// if that does not restore feasibility
+    Project,
+}
+
 /// Parameters for SCP algorithm
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScpParams {
@@ -15,6 +58,77 @@ pub struct ScpParams {
     pub tolerance_y: f64,
     pub tolerance_alpha: f64,
     pub line_search_max_steps: usize,
+    /// Tolerance for explicit triangular coherence constraints (bps).
+    /// `None` disables them and relies on the log-price parameterization alone.
+    pub coherence_tolerance_bps: Option<f64>,
+    /// Triangles (a, b, c) checked by the coherence constraints
+    pub coherence_triangles: Vec<(AssetId, AssetId, AssetId)>,
+    /// Round cleared prices to this many significant figures and reprice
+    /// fills at the rounded rates. `None` keeps full f64 precision.
+    pub price_sig_figs: Option<u32>,
+    /// Add linearized inventory bound constraints to each QP subproblem.
+    /// When off, bounds are only discouraged through the inventory penalty,
+    /// except that an asset already at its minimum is never paid out.
+    pub enforce_inventory_bounds: bool,
+    /// Trust region (bps) for the first iteration of a cold start.
+    /// `None` uses 40% of the widen band, at least 5 bps.
+    #[serde(default)]
+    pub initial_band_bps: Option<f64>,
+    /// Trust region (bps) for the first iteration of a warm start and after
+    /// a step larger than the widen threshold.
+    /// `None` uses the risk price band, at least 5 bps.
+    #[serde(default)]
+    pub widen_band_bps: Option<f64>,
+    /// Trust region (bps) for the remaining iterations.
+    /// `None` uses 80% of the widen band, at least the initial band.
+    #[serde(default)]
+    pub normal_band_bps: Option<f64>,
+    /// Widen the trust region when the previous price step exceeded
+    /// `tolerance_y` times this multiplier
+    #[serde(default = "default_widen_threshold_multiplier")]
+    pub widen_threshold_multiplier: f64,
+    /// Extra SCP runs allowed when `max_iterations` is exhausted without
+    /// converging. Each retry continues from the last iterate.
+    #[serde(default)]
+    pub max_retries: usize,
+    /// Factor applied to every trust region before each retry
+    #[serde(default = "default_band_relaxation_factor")]
+    pub band_relaxation_factor: f64,
+    /// Handling of steps that breach the inventory bounds
+    #[serde(default)]
+    pub bound_repair: BoundRepair,
+    /// Armijo sufficient-decrease constant in (0, 1). When set, the line
+    /// search also requires the exact objective to fall by at least this
+    /// fraction of the decrease its directional derivative predicts, and
+    /// runs even with `BoundRepair::None`. `None` accepts any step that
+    /// passes the bound check.
+    #[serde(default)]
+    pub armijo_c: Option<f64>,
+    /// Largest triangular coherence error (bps) a cleared epoch may show
+    /// before `clear_epoch` rejects it as `Infeasible`
+    #[serde(default = "default_max_coherence_error_bps")]
+    pub max_coherence_error_bps: f64,
+    /// Re-solve a QP subproblem with `SimpleQpSolver` when the backend
+    /// stops at `MaxIterations` or `Unsolved`, keeping whichever solution
+    /// is feasible
+    #[serde(default)]
+    pub fallback_to_simple_solver: bool,
+    /// Repair a non-PSD Γ with `RiskParams::repair_gamma` before the
+    /// instance is validated, instead of rejecting it
+    #[serde(default)]
+    pub repair_gamma: bool,
+}
+
+fn default_widen_threshold_multiplier() -> f64 {
+    10.0
+}
+
+fn default_max_coherence_error_bps() -> f64 {
+    1.0
+}
+
+fn default_band_relaxation_factor() -> f64 {
+    2.0
 }
 
 impl Default for ScpParams {
@@ -24,6 +138,21 @@ impl Default for ScpParams {
             tolerance_y: 1e-4,   // Relaxed from 1e-5 for numerical stability with tight constraints
             tolerance_alpha: 1e-5, // Relaxed from 1e-6 for numerical stability
             line_search_max_steps: 10,
+            coherence_tolerance_bps: None,
+            coherence_triangles: Vec::new(),
+            price_sig_figs: None,
+            enforce_inventory_bounds: false,
+            initial_band_bps: None,
+            widen_band_bps: None,
+            normal_band_bps: None,
+            widen_threshold_multiplier: default_widen_threshold_multiplier(),
+            max_retries: 0,
+            band_relaxation_factor: default_band_relaxation_factor(),
+            bound_repair: BoundRepair::None,
+            armijo_c: None,
+            max_coherence_error_bps: default_max_coherence_error_bps(),
+            fallback_to_simple_solver: false,
+            repair_gamma: false,
         }
     }
 }
@@ -48,7 +177,8 @@ impl ScpClearing {
         ScpClearing { backend, params }
     }
 
-    /// Create with OSQP solver (production default)
+    /// Create with OSQP solver (ADMM, requires the `osqp` feature)
+    #[cfg(feature = "osqp")]
     pub fn with_osqp_solver() -> Self {
         ScpClearing {
             backend: Arc::new(OsqpSolver::new()),
@@ -56,10 +186,10 @@ impl ScpClearing {
         }
     }
 
-    /// Create with Clarabel solver (pure Rust alternative)
+    /// Create with Clarabel solver (interior point, pure Rust)
     pub fn with_clarabel() -> Self {
         ScpClearing {
-            backend: Arc::new(OsqpSolver::new()),  // Uses Clarabel backend
+            backend: Arc::new(ClarabelSolver::new()),
             params: ScpParams::default(),
         }
     }
@@ -72,10 +202,364 @@ impl ScpClearing {
         }
     }
 
+    /// Create with the closed-form single-order solver (for tests only)
+    pub fn with_deterministic_solver() -> Self {
+        ScpClearing {
+            backend: Arc::new(DeterministicSolver::new()),
+            params: ScpParams::default(),
+        }
+    }
+
+    /// Replace the SCP parameters, keeping the backend
+    pub fn with_params(mut self, params: ScpParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Name of the QP backend this engine solves with
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    /// Solve a tiny canned QP to confirm the backend is functional
+    ///
+    /// Minimizes `0.5 x^2 - x` subject to `0 <= x <= 2`, whose optimum is
+    /// `x = 1`. Returns an error if the backend fails, reports a non-optimal
+    /// status or lands away from the known optimum.
+    pub fn check_backend(&self) -> Result<()> {
+        use convexfx_solver::{QpModel, VarMeta};
+        use nalgebra::{DMatrix, DVector};
+
+        let model = QpModel::new(
+            DMatrix::identity(1, 1),
+            DVector::from_element(1, -1.0),
+            DMatrix::identity(1, 1),
+            DVector::from_element(1, 0.0),
+            DVector::from_element(1, 2.0),
+            vec![VarMeta::LogPrice(AssetId::USD)],
+        );
+        let solution = self.backend.solve_qp(&model)?;
+        if solution.status != QpStatus::Optimal {
+            return Err(ConvexFxError::SolverError(format!(
+                "{} health check returned status {}",
+                self.backend.name(),
+                solution.status
+            )));
+        }
+        match solution.x.first() {
+            Some(x) if (x - 1.0).abs() < 1e-3 => Ok(()),
+            other => Err(ConvexFxError::SolverError(format!(
+                "{} health check returned x = {:?}, expected 1",
+                self.backend.name(),
+                other
+            ))),
+        }
+    }
+
     /// Clear an epoch with hot-starting and adaptive trust regions
     pub fn clear_epoch(&self, inst: &EpochInstance) -> Result<EpochSolution> {
+        self.clear_epoch_warm(inst, None)
+    }
+
+    /// Clear an epoch, warm-starting from a previous epoch's solution
+    ///
+    /// When `prev_solution` is provided, `y` is seeded from its `y_star` and
+    /// `alpha` from the fills of orders with matching ids. Orders not present
+    /// in the previous solution start at zero. With `None` this is identical
+    /// to `clear_epoch`.
+    ///
+    /// `min_fill_fraction` is all-or-nothing: an order that comes back
+    /// partially filled below its minimum is dropped and the epoch is solved
+    /// again without it, so the remaining fills and inventory never account
+    /// for the rejected order. Dropped orders still appear in `fills` with
+    /// `fill_frac = 0`. A pair order whose fill exceeds its `max_slippage_bps`
+    /// against `inst.ref_prices` is dropped the same way, as are limit orders
+    /// that conflict with each other (see `solve_without`). The epoch only
+    /// fails as `Infeasible` when it stays infeasible with every limit order
+    /// dropped.
+    pub fn clear_epoch_warm(
+        &self,
+        inst: &EpochInstance,
+        prev_solution: Option<&EpochSolution>,
+    ) -> Result<EpochSolution> {
+        let epoch_span = tracing::info_span!(
+            "clear_epoch",
+            epoch_id = inst.epoch_id,
+            orders = inst.num_orders(),
+            warm_started = prev_solution.is_some(),
+            iterations = tracing::field::Empty,
+            converged = tracing::field::Empty,
+            solve_time_ms = tracing::field::Empty,
+        );
+        let _epoch_guard = epoch_span.enter();
+
+        let repaired;
+        let inst = if self.params.repair_gamma && !inst.risk.gamma_is_psd() {
+            let mut copy = inst.clone();
+            copy.risk.repair_gamma();
+            repaired = copy;
+            &repaired
+        } else {
+            inst
+        };
+
+        inst.validate()?;
+        let mut solution = self.solve_with_order_conditions(inst, prev_solution)?;
+        let diagnostics = &solution.diagnostics;
+        epoch_span.record("iterations", diagnostics.iterations);
+        epoch_span.record("converged", diagnostics.convergence_achieved);
+        epoch_span.record("solve_time_ms", diagnostics.solve_time_ms);
+
+        if let Some(sig_figs) = self.params.price_sig_figs {
+            let error_bps = solution.round_prices(inst, sig_figs);
+
+            let tolerance = self
+                .params
+                .coherence_tolerance_bps
+                .unwrap_or(ROUNDED_COHERENCE_TOLERANCE_BPS);
+            if error_bps > tolerance {
+                return Err(ConvexFxError::SolverError(format!(
+                    "rounded prices violate coherence: {:.4} bps > {:.4} bps",
+                    error_bps, tolerance
+                )));
+            }
+        }
+
+        self.check_coherence(&mut solution)?;
+
+        Ok(solution)
+    }
+
+    /// Record the solution's worst coherence error in its diagnostics and
+    /// fail if it exceeds `max_coherence_error_bps`
+    ///
+    /// Prices are derived from a single log-price vector, so any error here
+    /// points at a solver or post-processing bug rather than at the market.
+    pub fn check_coherence(&self, solution: &mut EpochSolution) -> Result<()> {
+        let error_bps = solution.coherence_error_bps();
+        solution.diagnostics.max_coherence_error_bps = error_bps;
+        if error_bps.is_nan() || error_bps > self.params.max_coherence_error_bps {
+            return Err(ConvexFxError::Infeasible(format!(
+                "epoch {}: cleared prices violate coherence: {:.4} bps > {:.4} bps",
+                solution.epoch_id, error_bps, self.params.max_coherence_error_bps
+            )));
+        }
+        Ok(())
+    }
+
+    /// Solve, re-solving without any order that missed its minimum fill or
+    /// exceeded its slippage cap
+    fn solve_with_order_conditions(
+        &self,
+        inst: &EpochInstance,
+        prev_solution: Option<&EpochSolution>,
+    ) -> Result<EpochSolution> {
+        let mut rejected: Vec<&str> = Vec::new();
+        let mut solution = self.solve_without(inst, prev_solution, &mut rejected)?;
+
+        let orders = inst.order_legs();
+        let slippage_caps: BTreeMap<&str, f64> = inst
+            .orders
+            .iter()
+            .filter_map(|order| Some((order.id.as_str(), order.max_slippage_bps?)))
+            .collect();
+
+        // Each pass rejects at least one order, so this terminates
+        loop {
+            let missed: Vec<&str> = orders
+                .iter()
+                .filter(|order| !rejected.contains(&order.id))
+                .filter(|order| {
+                    solution.fills.iter().any(|fill| {
+                        fill.order_id == order.id
+                            && fill.fill_frac > 1e-10
+                            && (fill.fill_frac + MIN_FILL_TOLERANCE < order.min_fill
+                                || slippage_caps.get(order.id).is_some_and(|cap| {
+                                    solution.fill_slippage_bps(inst, fill) > cap + SLIPPAGE_CAP_TOLERANCE_BPS
+                                }))
+                    })
+                })
+                .map(|order| order.id)
+                .collect();
+            if missed.is_empty() {
+                break;
+            }
+            rejected.extend(missed);
+            solution = self.solve_without(inst, prev_solution, &mut rejected)?;
+        }
+
+        if rejected.is_empty() {
+            return Ok(solution);
+        }
+
+        // Restore the fills of every order (one per basket leg), in canonical order
+        let mut solved: BTreeMap<String, Vec<Fill>> = BTreeMap::new();
+        for fill in solution.fills.drain(..) {
+            solved.entry(fill.order_id.clone()).or_default().push(fill);
+        }
+        solution.fills = inst
+            .fill_order()
+            .into_iter()
+            .flat_map(|k| {
+                let order = &orders[k];
+                solved.remove(order.id).unwrap_or_else(|| {
+                    order
+                        .legs
+                        .iter()
+                        .map(|(receive, _)| Fill {
+                            order_id: order.id.to_string(),
+                            fill_frac: 0.0,
+                            pay_asset: order.pay,
+                            recv_asset: *receive,
+                            pay_units: 0.0,
+                            recv_units: 0.0,
+                            fees_paid: BTreeMap::new(),
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+
+        Ok(solution)
+    }
+
+    /// Solve the epoch without the `rejected` orders
+    ///
+    /// Limit rows are hard constraints on prices, so two limits that are
+    /// each attainable can still exclude each other. While the QP is
+    /// infeasible, one of the conflicting limit orders picked by
+    /// `conflicting_limit` is added to `rejected` and the epoch is solved
+    /// again from scratch. One aggressive limit therefore costs only its own
+    /// fill, not the batch's, though each drop costs a full re-solve.
+    fn solve_without<'a>(
+        &self,
+        inst: &'a EpochInstance,
+        prev_solution: Option<&EpochSolution>,
+        rejected: &mut Vec<&'a str>,
+    ) -> Result<EpochSolution> {
+        loop {
+            let result = if rejected.is_empty() {
+                self.solve_epoch(inst, prev_solution)
+            } else {
+                let mut reduced = inst.clone();
+                reduced.orders.retain(|order| !rejected.contains(&order.id.as_str()));
+                reduced.baskets.retain(|basket| !rejected.contains(&basket.id.as_str()));
+                reduced.pool_orders.retain(|order| !rejected.contains(&order.id.as_str()));
+                self.solve_epoch(&reduced, prev_solution)
+            };
+            let reason = match result {
+                Err(ConvexFxError::Infeasible(reason)) => reason,
+                result => return result,
+            };
+
+            let Some(order) = Self::conflicting_limit(inst, rejected) else {
+                return Err(ConvexFxError::Infeasible(reason));
+            };
+            tracing::warn!(
+                epoch_id = inst.epoch_id,
+                order_id = %order.id,
+                "QP subproblem infeasible, dropping conflicting limit order"
+            );
+            rejected.push(order.id.as_str());
+        }
+    }
+
+    /// The limit order to drop from an infeasible epoch
+    ///
+    /// Limit rows `y_recv - y_pay <= log_limit` and the price bands are
+    /// difference constraints on log prices, so limits that exclude each
+    /// other close a negative cycle in their constraint graph. Only orders
+    /// on such a cycle, found with Bellman-Ford at the configured band, are
+    /// candidates; if none shows up, every remaining limit order is. Of the
+    /// candidates, the one whose limit sits furthest through its reference
+    /// rate goes; ties go to the larger order id so the choice does not
+    /// depend on submission order.
+    fn conflicting_limit<'a>(inst: &'a EpochInstance, rejected: &[&str]) -> Option<&'a PairOrder> {
+        let band = |asset: AssetId| inst.risk.price_band_bps / 10000.0 * inst.ref_prices.band_widening(asset);
+        let band_edge = |asset: AssetId, dir: f64| {
+            if asset == inst.numeraire {
+                0.0
+            } else {
+                inst.ref_prices.get_ref(asset) + dir * band(asset)
+            }
+        };
+
+        // A limit no price in the band satisfies is pinned at zero by the QP
+        // and cannot be what makes it infeasible
+        let limits: Vec<(&PairOrder, f64)> = inst
+            .orders
+            .iter()
+            .chain(&inst.pool_orders)
+            .filter(|order| !rejected.contains(&order.id.as_str()))
+            .filter_map(|order| Some((order, order.log_limit()?)))
+            .filter(|(order, log_limit)| band_edge(order.receive, -1.0) - band_edge(order.pay, 1.0) <= *log_limit)
+            .collect();
+
+        // Edge (from, to, w) encodes y_to - y_from <= w; node `n` is a fixed
+        // zero the bands and the numeraire hang off
+        let n = AssetId::all().len();
+        let mut edges: Vec<(usize, usize, f64, Option<&PairOrder>)> = limits
+            .iter()
+            .map(|(order, log_limit)| (order.pay.index(), order.receive.index(), *log_limit, Some(*order)))
+            .collect();
+        for asset in AssetId::all() {
+            let (low, high) = (band_edge(*asset, -1.0), band_edge(*asset, 1.0));
+            edges.push((n, asset.index(), high, None));
+            edges.push((asset.index(), n, -low, None));
+        }
+
+        let mut dist = vec![0.0; n + 1];
+        let mut pred: Vec<Option<usize>> = vec![None; n + 1];
+        let mut last_relaxed = None;
+        for _ in 0..=n {
+            last_relaxed = None;
+            for (e, (from, to, w, _)) in edges.iter().enumerate() {
+                if dist[*from] + w < dist[*to] - 1e-12 {
+                    dist[*to] = dist[*from] + w;
+                    pred[*to] = Some(e);
+                    last_relaxed = Some(*to);
+                }
+            }
+        }
+
+        let mut on_cycle: Vec<&PairOrder> = Vec::new();
+        if let Some(mut node) = last_relaxed {
+            // Walking back n + 1 predecessors is sure to land on the cycle
+            for _ in 0..=n {
+                node = edges[pred[node]?].0;
+            }
+            let start = node;
+            loop {
+                let (from, _, _, order) = edges[pred[node]?];
+                on_cycle.extend(order);
+                node = from;
+                if node == start {
+                    break;
+                }
+            }
+        }
+
+        let through_ref = |order: &PairOrder, log_limit: f64| {
+            inst.ref_prices.get_ref(order.receive) - inst.ref_prices.get_ref(order.pay) - log_limit
+        };
+        limits
+            .into_iter()
+            .filter(|(order, _)| on_cycle.is_empty() || on_cycle.iter().any(|o| o.id == order.id))
+            .max_by(|(a, a_limit), (b, b_limit)| {
+                through_ref(a, *a_limit).total_cmp(&through_ref(b, *b_limit)).then_with(|| a.id.cmp(&b.id))
+            })
+            .map(|(order, _)| order)
+    }
+
+    /// Run the SCP loop and assemble the full-precision solution
+    fn solve_epoch(
+        &self,
+        inst: &EpochInstance,
+        prev_solution: Option<&EpochSolution>,
+    ) -> Result<EpochSolution> {
+        let solve_start = Instant::now();
         let _assets = AssetId::all();
-        let n_orders = inst.orders.len();
+        let n_orders = inst.num_orders();
 
         // Trivial case: no orders in the batch. Return oracle prices and the
         // current inventory without iterating through SCP.
@@ -88,11 +572,13 @@ impl ScpClearing {
             let q_post = inst.inventory_q.clone();
             let fills = Vec::new();
 
+            let inventory_risk = inst.objective_weights.risk * inst.risk.inventory_penalty(&q_post);
             let objective_terms = ObjectiveTerms {
-                inventory_risk: inst.risk.inventory_penalty(&q_post),
+                inventory_risk,
                 price_tracking: 0.0,
                 fill_incentive: 0.0,
-                total: inst.risk.inventory_penalty(&q_post),
+                total: inventory_risk,
+                weights: inst.objective_weights,
             };
 
             let diagnostics = Diagnostics {
@@ -100,7 +586,13 @@ impl ScpClearing {
                 convergence_achieved: true,
                 final_step_norm_y: 0.0,
                 final_step_norm_alpha: 0.0,
-                qp_status: "Skipped".to_string(),
+                qp_status: QpStatus::Skipped,
+                solve_time_ms: solve_start.elapsed().as_millis() as u64,
+                iteration_times_us: Vec::new(),
+                binding_constraints: Vec::new(),
+                objective_trajectory: Vec::new(),
+                max_coherence_error_bps: 0.0,
+                qp_backend: self.backend_name().to_string(),
             };
 
             return Ok(EpochSolution {
@@ -111,6 +603,7 @@ impl ScpClearing {
                 fills,
                 objective_terms,
                 diagnostics,
+                numeraire: inst.numeraire,
             });
         }
 
@@ -119,100 +612,184 @@ impl ScpClearing {
             .ref_prices
             .y_ref
             .iter()
-            .map(|(asset, y)| (*asset, *y))
+            .map(|(asset, y)| {
+                let y_prev = prev_solution.and_then(|prev| prev.y_star.get(asset).copied());
+                (*asset, y_prev.unwrap_or(*y))
+            })
             .collect();
 
-        let mut alpha_current: Vec<f64> = vec![0.0; n_orders];
+        let mut alpha_current: Vec<f64> = match prev_solution {
+            Some(prev) => {
+                let prev_alpha: BTreeMap<&str, f64> = prev
+                    .fills
+                    .iter()
+                    .map(|fill| (fill.order_id.as_str(), fill.fill_frac))
+                    .collect();
+                inst.order_legs()
+                    .iter()
+                    .map(|order| {
+                        prev_alpha
+                            .get(order.id)
+                            .copied()
+                            .unwrap_or(0.0)
+                            .clamp(0.0, 1.0)
+                    })
+                    .collect()
+            }
+            None => vec![0.0; n_orders],
+        };
 
 
+        let warm_started = prev_solution.is_some();
         let mut iterations = 0;
         let mut converged = false;
         let mut final_step_norm_y = 0.0;
         let mut final_step_norm_alpha = 0.0;
-        let mut qp_status = String::new();
-
-        let max_band = inst.risk.price_band_bps.max(5.0);
-        let tight_band = (max_band * 0.4).max(5.0);
-        let normal_band = (max_band * 0.8).max(tight_band);
-
-        for iter in 0..self.params.max_iterations {
-            iterations = iter + 1;
-
-            // Adaptive trust regions: start tight, widen if needed
-            let adaptive_bands = if iter == 0 {
-                // First iteration: tight bands for stability
-                tight_band
-            } else if final_step_norm_y > self.params.tolerance_y * 10.0 {
-                // Large steps in previous iteration: widen bands for flexibility
-                max_band
-            } else {
-                // Normal iterations: use moderate bands
-                normal_band
-            };
+        let mut qp_status = QpStatus::Unsolved;
+        let mut qp_backend = self.backend.name();
+        let mut iteration_times_us = Vec::new();
+        let mut binding_constraints = Vec::new();
+        let mut objective_trajectory = Vec::new();
 
-            // Build linearized QP with adaptive trust regions
-            let qp_model = QpBuilder::build_qp_with_bands(inst, &y_current, adaptive_bands)?;
+        let mut max_band = self.params.widen_band_bps.unwrap_or(inst.risk.price_band_bps.max(5.0));
+        let mut tight_band = self.params.initial_band_bps.unwrap_or((max_band * 0.4).max(5.0));
+        let mut normal_band = self.params.normal_band_bps.unwrap_or((max_band * 0.8).max(tight_band));
+        let widen_threshold = self.params.tolerance_y * self.params.widen_threshold_multiplier;
 
-            // Solve QP
-            let solution = self.backend.solve_qp(&qp_model)?;
-            qp_status = format!("{:?}", solution.status);
+        'attempts: for attempt in 0..=self.params.max_retries {
+            if attempt > 0 {
+                // Not converged: relax every trust region and re-run from the last iterate
+                let factor = self.params.band_relaxation_factor;
+                max_band *= factor;
+                tight_band *= factor;
+                normal_band *= factor;
+            }
 
-            // Extract y~ and alpha~ from solution
-            let (y_new, alpha_new): (BTreeMap<AssetId, f64>, Vec<f64>) = QpBuilder::extract_solution(&solution, inst)?;
+            for iter in 0..self.params.max_iterations {
+                iterations += 1;
+                let iter_start = Instant::now();
 
-            // Simple line search: use full step (λ=1) for now
-            // In production, implement backtracking line search for exact nonlinear feasibility
-            let lambda = 1.0;
+                // Adaptive trust regions: start tight, widen if needed
+                let adaptive_bands = if iter == 0 && (warm_started || attempt > 0) {
+                    // Warm start or retry: the current iterate was reached inside the
+                    // full band, so clamping it back into the tight band would undo it
+                    max_band
+                } else if iter == 0 {
+                    // First iteration: tight bands for stability
+                    tight_band
+                } else if final_step_norm_y > widen_threshold {
+                    // Large steps in previous iteration: widen bands for flexibility
+                    max_band
+                } else {
+                    // Normal iterations: use moderate bands
+                    normal_band
+                };
 
-            let y_next: BTreeMap<AssetId, f64> = y_current
-                .iter()
-                .map(|(asset, y_old)| {
-                    let y_step = y_new.get(asset).copied().unwrap_or(0.0) - y_old;
-                    (*asset, y_old + lambda * y_step)
-                })
-                .collect();
+                let iteration_span = tracing::info_span!(
+                    "scp_iteration",
+                    iteration = iterations,
+                    retry = attempt,
+                    band_bps = adaptive_bands,
+                    step_norm_y = tracing::field::Empty,
+                    step_norm_alpha = tracing::field::Empty,
+                    qp_status = tracing::field::Empty,
+                );
+                let _iteration_guard = iteration_span.enter();
 
-            let alpha_next: Vec<f64> = alpha_current
-                .iter()
-                .zip(alpha_new.iter())
-                .map(|(a_old, a_step)| {
-                    let step = a_step - a_old;
-                    a_old + lambda * step
-                })
-                .collect();
+                // Build linearized QP with adaptive trust regions
+                let mut qp_model = QpBuilder::build_qp_with_bands(inst, &y_current, adaptive_bands)?;
+                if self.params.enforce_inventory_bounds {
+                    qp_model = QpBuilder::add_inventory_constraints(qp_model, inst, &y_current);
+                }
+                if !inst.risk.trader_position_limits.is_empty() {
+                    qp_model = QpBuilder::add_position_limit_constraints(qp_model, inst, &y_current);
+                }
+                if let Some(tolerance_bps) = self.params.coherence_tolerance_bps {
+                    qp_model = QpBuilder::add_coherence_constraints(
+                        qp_model,
+                        &self.params.coherence_triangles,
+                        tolerance_bps / 10000.0,
+                    );
+                }
 
-            // Compute step norms
-            let step_norm_y = y_next
-                .iter()
-                .map(|(asset, y)| {
-                    let y_old = y_current.get(asset).copied().unwrap_or(0.0);
-                    (y - y_old).abs()
-                })
-                .fold(0.0, f64::max);
+                // Solve QP
+                let (solution, backend) = self.solve_qp_with_fallback(&qp_model)?;
+                qp_status = solution.status;
+                qp_backend = backend;
+                binding_constraints = solution.binding.clone();
+                iteration_span.record("qp_status", tracing::field::debug(&qp_status));
 
-            let step_norm_alpha = alpha_next
-                .iter()
-                .zip(alpha_current.iter())
-                .map(|(a_new, a_old)| (a_new - a_old).abs())
-                .fold(0.0, f64::max);
+                // Extract y~ and alpha~ from solution
+                let (y_new, alpha_new): (BTreeMap<AssetId, f64>, Vec<f64>) = QpBuilder::extract_solution(&solution, inst)?;
 
-            final_step_norm_y = step_norm_y;
-            final_step_norm_alpha = step_norm_alpha;
+                let (y_next, alpha_next) = match self.params.bound_repair {
+                    BoundRepair::None if self.params.armijo_c.is_none() => {
+                        Self::step(&y_current, &alpha_current, &y_new, &alpha_new, 1.0)
+                    }
+                    BoundRepair::None | BoundRepair::Reject => {
+                        self.backtracking_line_search(inst, &y_current, &alpha_current, &y_new, &alpha_new)?
+                    }
+                    BoundRepair::Project => {
+                        let (y_next, mut alpha_next) = Self::step(&y_current, &alpha_current, &y_new, &alpha_new, 1.0);
+                        if self.project_onto_bounds(inst, &y_next, &mut alpha_next)?
+                            && self.sufficient_decrease(inst, &y_current, &alpha_current, &y_next, &alpha_next)?
+                        {
+                            (y_next, alpha_next)
+                        } else {
+                            self.backtracking_line_search(inst, &y_current, &alpha_current, &y_new, &alpha_new)?
+                        }
+                    }
+                };
 
-            // Update iterates
-            y_current = y_next.clone();
-            alpha_current = alpha_next.clone();
+                // Compute step norms
+                let step_norm_y = y_next
+                    .iter()
+                    .map(|(asset, y)| {
+                        let y_old = y_current.get(asset).copied().unwrap_or(0.0);
+                        (y - y_old).abs()
+                    })
+                    .fold(0.0, f64::max);
 
+                let step_norm_alpha = alpha_next
+                    .iter()
+                    .zip(alpha_current.iter())
+                    .map(|(a_new, a_old)| (a_new - a_old).abs())
+                    .fold(0.0, f64::max);
 
-            // Check convergence
-            if step_norm_y < self.params.tolerance_y && step_norm_alpha < self.params.tolerance_alpha {
-                converged = true;
-                break;
+                final_step_norm_y = step_norm_y;
+                final_step_norm_alpha = step_norm_alpha;
+                iteration_span.record("step_norm_y", step_norm_y);
+                iteration_span.record("step_norm_alpha", step_norm_alpha);
+
+                // Update iterates
+                y_current = y_next.clone();
+                alpha_current = alpha_next.clone();
+
+                iteration_times_us.push(iter_start.elapsed().as_micros() as u64);
+
+                let (q_iter, fills_iter) = self.compute_fills_and_inventory(inst, &y_current, &alpha_current, false)?;
+                objective_trajectory.push(
+                    self.compute_objective_terms(inst, &q_iter, &y_current, &fills_iter).total,
+                );
+
+                // Check convergence
+                if step_norm_y < self.params.tolerance_y && step_norm_alpha < self.params.tolerance_alpha {
+                    converged = true;
+                    break 'attempts;
+                }
             }
         }
 
+        // An infeasible final subproblem has no meaningful iterate to report
+        if qp_status == QpStatus::PrimalInfeasible {
+            return Err(ConvexFxError::Infeasible(format!(
+                "epoch {}: QP subproblem primal infeasible at iteration {}",
+                inst.epoch_id, iterations
+            )));
+        }
+
         // Compute final quantities with exact nonlinear formulas
-        let (q_post, fills) = self.compute_fills_and_inventory(inst, &y_current, &alpha_current)?;
+        let (q_post, fills) = self.compute_fills_and_inventory(inst, &y_current, &alpha_current, true)?;
 
         // Compute prices (linear space)
         let prices: BTreeMap<AssetId, f64> = y_current
@@ -229,6 +806,12 @@ impl ScpClearing {
             final_step_norm_y,
             final_step_norm_alpha,
             qp_status,
+            solve_time_ms: solve_start.elapsed().as_millis() as u64,
+            iteration_times_us,
+            binding_constraints,
+            objective_trajectory,
+            max_coherence_error_bps: 0.0,
+            qp_backend: qp_backend.to_string(),
         };
 
         Ok(EpochSolution {
@@ -239,54 +822,437 @@ impl ScpClearing {
             fills,
             objective_terms,
             diagnostics,
+            numeraire: inst.numeraire,
         })
     }
 
+    /// Solve a QP subproblem, retrying with `SimpleQpSolver` when enabled
+    /// and the backend gave up before reaching an optimum
+    ///
+    /// The fallback's solution is used when it is feasible and either the
+    /// backend's is not or the fallback reached an optimum. Returns the
+    /// solution and the name of the backend that produced it.
+    fn solve_qp_with_fallback(&self, model: &QpModel) -> Result<(QpSolution, &'static str)> {
+        let solution = self.backend.solve_qp(model)?;
+        if !self.params.fallback_to_simple_solver
+            || !matches!(solution.status, QpStatus::MaxIterations | QpStatus::Unsolved)
+        {
+            return Ok((solution, self.backend.name()));
+        }
+
+        let fallback_backend = SimpleQpSolver::new();
+        let mut fallback = fallback_backend.solve_qp(model)?;
+        model.clamp_to_box_rows(&mut fallback.x);
+        let feasible = |s: &QpSolution| model.max_violation(&s.x) <= FALLBACK_FEASIBILITY_TOLERANCE;
+        if feasible(&fallback) && (!feasible(&solution) || fallback.status == QpStatus::Optimal) {
+            tracing::warn!(
+                backend = self.backend.name(),
+                status = %solution.status,
+                "QP backend did not converge, using simple solver fallback"
+            );
+            Ok((fallback, fallback_backend.name()))
+        } else {
+            Ok((solution, self.backend.name()))
+        }
+    }
+
+    /// Move `lambda` of the way from the current iterate to the QP solution
+    fn step(
+        y_current: &BTreeMap<AssetId, f64>,
+        alpha_current: &[f64],
+        y_new: &BTreeMap<AssetId, f64>,
+        alpha_new: &[f64],
+        lambda: f64,
+    ) -> (BTreeMap<AssetId, f64>, Vec<f64>) {
+        let y_next = y_current
+            .iter()
+            .map(|(asset, y_old)| {
+                let y_step = y_new.get(asset).copied().unwrap_or(0.0) - y_old;
+                (*asset, y_old + lambda * y_step)
+            })
+            .collect();
+
+        let alpha_next = alpha_current
+            .iter()
+            .zip(alpha_new)
+            .map(|(a_old, a_new)| a_old + lambda * (a_new - a_old))
+            .collect();
+
+        (y_next, alpha_next)
+    }
+
+    /// Halve the step until it is acceptable: the exact post-trade inventory
+    /// is within bounds (unless `bound_repair` is `None`) and, with
+    /// `armijo_c` set, the exact objective decreases sufficiently
+    ///
+    /// If no step is acceptable, returns the current iterate when `armijo_c`
+    /// is set and the smallest step tried otherwise.
+    fn backtracking_line_search(
+        &self,
+        inst: &EpochInstance,
+        y_current: &BTreeMap<AssetId, f64>,
+        alpha_current: &[f64],
+        y_new: &BTreeMap<AssetId, f64>,
+        alpha_new: &[f64],
+    ) -> Result<(BTreeMap<AssetId, f64>, Vec<f64>)> {
+        let armijo = match self.params.armijo_c {
+            Some(c) => {
+                let (f0, slope) = self.directional_slope(inst, y_current, alpha_current, y_new, alpha_new)?;
+                Some((c, f0, slope))
+            }
+            None => None,
+        };
+
+        let mut lambda = 1.0;
+        for _ in 0..self.params.line_search_max_steps {
+            let (y_next, alpha_next) = Self::step(y_current, alpha_current, y_new, alpha_new, lambda);
+            let feasible = self.params.bound_repair == BoundRepair::None
+                || self.within_bounds(inst, &y_next, &alpha_next)?;
+            let decreases = match armijo {
+                Some((c, f0, slope)) => {
+                    self.exact_objective(inst, &y_next, &alpha_next)? <= f0 + c * lambda * slope.min(0.0)
+                }
+                None => true,
+            };
+            if feasible && decreases {
+                return Ok((y_next, alpha_next));
+            }
+            lambda *= 0.5;
+        }
+        if armijo.is_some() {
+            // No step along the QP direction improves the exact objective:
+            // stay put so the zero step ends the SCP loop
+            return Ok((y_current.clone(), alpha_current.to_vec()));
+        }
+        Ok(Self::step(y_current, alpha_current, y_new, alpha_new, lambda))
+    }
+
+    /// Armijo check for a step taken outside the line search; always true
+    /// when `armijo_c` is unset
+    fn sufficient_decrease(
+        &self,
+        inst: &EpochInstance,
+        y_current: &BTreeMap<AssetId, f64>,
+        alpha_current: &[f64],
+        y_next: &BTreeMap<AssetId, f64>,
+        alpha_next: &[f64],
+    ) -> Result<bool> {
+        let Some(c) = self.params.armijo_c else {
+            return Ok(true);
+        };
+        let (f0, slope) = self.directional_slope(inst, y_current, alpha_current, y_next, alpha_next)?;
+        Ok(self.exact_objective(inst, y_next, alpha_next)? <= f0 + c * slope.min(0.0))
+    }
+
+    /// Exact objective at the current iterate and its finite-difference
+    /// derivative along the step towards (`y_target`, `alpha_target`)
+    fn directional_slope(
+        &self,
+        inst: &EpochInstance,
+        y_current: &BTreeMap<AssetId, f64>,
+        alpha_current: &[f64],
+        y_target: &BTreeMap<AssetId, f64>,
+        alpha_target: &[f64],
+    ) -> Result<(f64, f64)> {
+        let f0 = self.exact_objective(inst, y_current, alpha_current)?;
+        let (y_probe, alpha_probe) = Self::step(y_current, alpha_current, y_target, alpha_target, ARMIJO_PROBE_STEP);
+        let slope = (self.exact_objective(inst, &y_probe, &alpha_probe)? - f0) / ARMIJO_PROBE_STEP;
+        Ok((f0, slope))
+    }
+
+    /// Exact nonlinear objective at (`y`, `alpha`)
+    fn exact_objective(&self, inst: &EpochInstance, y: &BTreeMap<AssetId, f64>, alpha: &[f64]) -> Result<f64> {
+        let (q_post, fills) = self.compute_fills_and_inventory(inst, y, alpha, false)?;
+        Ok(self.compute_objective_terms(inst, &q_post, y, &fills).total)
+    }
+
+    /// Scale back fills in place so the inventory at prices `y` lands on the
+    /// bound box, returning whether the exact inventory is then feasible
+    ///
+    /// Inventory is linear in `alpha` at fixed prices, so for each breached
+    /// asset the fills moving it outwards are scaled by the common factor
+    /// that puts it exactly on the bound. Scaling one asset's fills can move
+    /// others, so this repeats for up to `line_search_max_steps` passes.
+    fn project_onto_bounds(
+        &self,
+        inst: &EpochInstance,
+        y: &BTreeMap<AssetId, f64>,
+        alpha: &mut [f64],
+    ) -> Result<bool> {
+        let coeffs = Self::inventory_coefficients(inst, y);
+
+        for _ in 0..self.params.line_search_max_steps {
+            let mut q = inst.inventory_q.clone();
+            for (alpha_k, coeffs_k) in alpha.iter().zip(&coeffs) {
+                for (asset, c) in coeffs_k {
+                    *q.entry(*asset).or_insert(0.0) += alpha_k * c;
+                }
+            }
+
+            let mut repaired = false;
+            for (asset, q_a) in &q {
+                let (low, high) = Self::bounds_for(inst, *asset);
+                // Fills with a positive coefficient raise the asset, so they
+                // are the ones to scale when it is above its max
+                let (excess, raises) = if *q_a > high + BOUND_TOLERANCE * (1.0 + high.abs()) {
+                    (q_a - high, true)
+                } else if *q_a < low - BOUND_TOLERANCE * (1.0 + low.abs()) {
+                    (low - q_a, false)
+                } else {
+                    continue;
+                };
+
+                let outward = |c: f64| if raises { c > 0.0 } else { c < 0.0 };
+                let push: f64 = alpha
+                    .iter()
+                    .zip(&coeffs)
+                    .filter_map(|(alpha_k, coeffs_k)| coeffs_k.get(asset).map(|c| (alpha_k, *c)))
+                    .filter(|(_, c)| outward(*c))
+                    .map(|(alpha_k, c)| alpha_k * c.abs())
+                    .sum();
+                if push <= 0.0 {
+                    continue;
+                }
+
+                let factor = ((push - excess) / push).clamp(0.0, 1.0);
+                for (alpha_k, coeffs_k) in alpha.iter_mut().zip(&coeffs) {
+                    if coeffs_k.get(asset).is_some_and(|c| outward(*c)) {
+                        *alpha_k *= factor;
+                    }
+                }
+                repaired = true;
+            }
+
+            if !repaired {
+                break;
+            }
+        }
+
+        self.within_bounds(inst, y, alpha)
+    }
+
+    /// Change in each asset's inventory per unit fill of each order leg at
+    /// prices `y`
+    fn inventory_coefficients(inst: &EpochInstance, y: &BTreeMap<AssetId, f64>) -> Vec<BTreeMap<AssetId, f64>> {
+        inst.order_legs()
+            .iter()
+            .map(|order| {
+                let mut coeffs = BTreeMap::new();
+                let y_j = y.get(&order.pay).copied().unwrap_or(0.0);
+                for (receive, share) in &order.legs {
+                    let y_i = y.get(receive).copied().unwrap_or(0.0);
+                    let pay = order.budget * share;
+                    *coeffs.entry(order.pay).or_insert(0.0) += pay;
+                    *coeffs.entry(*receive).or_insert(0.0) -= pay * (y_j - y_i).exp();
+                }
+                coeffs
+            })
+            .collect()
+    }
+
+    /// Inventory bounds an iterate must respect: the (ghost-cushioned) risk
+    /// bounds, widened to include the starting inventory if it is already
+    /// outside them
+    fn bounds_for(inst: &EpochInstance, asset: AssetId) -> (f64, f64) {
+        let q0 = inst.inventory_q.get(&asset).copied().unwrap_or(0.0);
+        (
+            inst.risk.soft_min_bound(asset).min(q0),
+            inst.risk.soft_max_bound(asset).max(q0),
+        )
+    }
+
+    /// Whether the exact post-trade inventory at (`y`, `alpha`) is within bounds
+    fn within_bounds(&self, inst: &EpochInstance, y: &BTreeMap<AssetId, f64>, alpha: &[f64]) -> Result<bool> {
+        let (q_post, _) = self.compute_fills_and_inventory(inst, y, alpha, false)?;
+        Ok(q_post.iter().all(|(asset, q)| {
+            let (low, high) = Self::bounds_for(inst, *asset);
+            *q >= low - BOUND_TOLERANCE * (1.0 + low.abs()) && *q <= high + BOUND_TOLERANCE * (1.0 + high.abs())
+        }))
+    }
+
+    /// Zero the fill of every pair order whose limit is violated at `y`
+    ///
+    /// The QP only enforces limits on linearized prices, so the exact
+    /// clearing rate can overshoot a limit; beyond `LIMIT_TOLERANCE` the
+    /// order gets nothing rather than a fill worse than it asked for.
+    /// Baskets carry no limit.
+    fn drop_violated_limits(inst: &EpochInstance, y: &BTreeMap<AssetId, f64>, alpha: &mut [f64]) {
+        for (k, order) in inst.pair_columns() {
+            if let Some(limit_ratio) = order.limit_ratio {
+                let y_j = y.get(&order.pay).copied().unwrap_or(0.0);
+                let y_i = y.get(&order.receive).copied().unwrap_or(0.0);
+                if (y_i - y_j).exp() > limit_ratio * (1.0 + LIMIT_TOLERANCE) {
+                    alpha[k] = 0.0;
+                }
+            }
+        }
+    }
+
+    /// Scale back the orders paying out of an asset so its exact post-trade
+    /// inventory stays at or above `q_min`
+    ///
+    /// The QP only bounds linearized inventory, so exact fills can overdraw
+    /// an asset. Payouts of each asset are capped by its headroom above the
+    /// minimum plus what the batch pays into it, scaling every order that
+    /// receives it by the same factor; an order scaled below its minimum
+    /// fill gets nothing. Scaling an order also cuts what it pays in, so
+    /// this repeats, and a last pass that counts no inflows at all
+    /// guarantees the bound.
+    fn cap_at_min_inventory(
+        inst: &EpochInstance,
+        y: &BTreeMap<AssetId, f64>,
+        orders: &[OrderLegs],
+        alpha: &mut [f64],
+    ) {
+        let passes = AssetId::all().len() + 1;
+        for pass in 0..=passes {
+            let count_inflows = pass < passes;
+            let mut inflow: BTreeMap<AssetId, f64> = BTreeMap::new();
+            let mut outflow: BTreeMap<AssetId, f64> = BTreeMap::new();
+            for (order, alpha_k) in orders.iter().zip(alpha.iter()) {
+                let y_j = y.get(&order.pay).copied().unwrap_or(0.0);
+                let pay = alpha_k * order.budget;
+                *inflow.entry(order.pay).or_insert(0.0) += pay;
+                for (receive, share) in &order.legs {
+                    let y_i = y.get(receive).copied().unwrap_or(0.0);
+                    *outflow.entry(*receive).or_insert(0.0) += pay * share * (y_j - y_i).exp();
+                }
+            }
+
+            let factors: BTreeMap<AssetId, f64> = outflow
+                .iter()
+                .filter_map(|(asset, out)| {
+                    let q0 = inst.inventory_q.get(asset).copied().unwrap_or(0.0);
+                    let inflow = if count_inflows { inflow.get(asset).copied().unwrap_or(0.0) } else { 0.0 };
+                    let available = (q0 - inst.risk.min_bound(*asset) + inflow).max(0.0);
+                    (*out > available + BOUND_TOLERANCE * (1.0 + q0.abs())).then(|| (*asset, available / out))
+                })
+                .collect();
+            if factors.is_empty() {
+                return;
+            }
+
+            for (order, alpha_k) in orders.iter().zip(alpha.iter_mut()) {
+                let f = order
+                    .legs
+                    .iter()
+                    .filter(|(_, share)| *share > 0.0)
+                    .filter_map(|(receive, _)| factors.get(receive))
+                    .fold(1.0_f64, |f, factor| f.min(*factor));
+                if f < 1.0 {
+                    *alpha_k = if *alpha_k * f + MIN_FILL_TOLERANCE < order.min_fill { 0.0 } else { *alpha_k * f };
+                }
+            }
+        }
+    }
+
     /// Compute fills and post-trade inventory using exact formulas
+    ///
+    /// Pair orders get one fill each; a basket gets one fill per leg, all
+    /// sharing the basket's fill fraction and splitting its paid amount by
+    /// weight, so inventory is conserved leg by leg. With `cap_at_min` the
+    /// fills are also capped by each asset's headroom above `q_min`; SCP
+    /// iterates leave it off so the merit function stays smooth.
     fn compute_fills_and_inventory(
         &self,
         inst: &EpochInstance,
         y: &BTreeMap<AssetId, f64>,
         alpha: &[f64],
+        cap_at_min: bool,
     ) -> Result<(BTreeMap<AssetId, f64>, Vec<Fill>)> {
         let mut q_post = inst.inventory_q.clone();
         let mut fills = Vec::new();
 
-        for (k, order) in inst.orders.iter().enumerate() {
-            let alpha_k = alpha[k];
-            
-            // Always create a Fill entry to maintain alignment with orders
-            let (pay_units, recv_units) = if alpha_k < 1e-10 {
-                (0.0, 0.0)
-            } else {
+        let orders = inst.order_legs();
+
+        let mut alpha: Vec<f64> = alpha.to_vec();
+        Self::drop_violated_limits(inst, y, &mut alpha);
+
+        // The QP only bounds linearized positions; scale back any trader still
+        // over a position limit at the exact clearing prices
+        if !inst.risk.trader_position_limits.is_empty() {
+            let mut positions: BTreeMap<(&AccountId, AssetId), f64> = BTreeMap::new();
+            for (order, alpha_k) in orders.iter().zip(&alpha) {
                 let y_j = y.get(&order.pay).copied().unwrap_or(0.0);
-                let y_i = y.get(&order.receive).copied().unwrap_or(0.0);
+                let pay = alpha_k * order.budget;
+                for (receive, share) in &order.legs {
+                    let y_i = y.get(receive).copied().unwrap_or(0.0);
+                    *positions.entry((order.trader, *receive)).or_insert(0.0) += pay * share * (y_j - y_i).exp();
+                }
+                *positions.entry((order.trader, order.pay)).or_insert(0.0) -= pay;
+            }
+
+            let mut scale: BTreeMap<&AccountId, f64> = BTreeMap::new();
+            for ((trader, asset), position) in &positions {
+                let limit = inst.risk.position_limit(*asset).abs();
+                if position.abs() > limit {
+                    let factor = scale.entry(*trader).or_insert(1.0);
+                    *factor = factor.min(limit / position.abs());
+                }
+            }
+
+            for (order, alpha_k) in orders.iter().zip(alpha.iter_mut()) {
+                if let Some(factor) = scale.get(order.trader) {
+                    *alpha_k *= factor;
+                }
+            }
+        }
+
+        // Nothing is paid out of an asset held at or below its minimum; the QP
+        // pins these fills at zero, this drops any solver residue
+        for (order, alpha_k) in orders.iter().zip(alpha.iter_mut()) {
+            if order.legs.iter().any(|(receive, share)| *share > 0.0 && inst.is_depleted(*receive)) {
+                *alpha_k = 0.0;
+            }
+        }
+        if cap_at_min {
+            Self::cap_at_min_inventory(inst, y, &orders, &mut alpha);
+        }
+
+        for k in inst.fill_order() {
+            let order = &orders[k];
+            let alpha_k = alpha[k];
+
+            // Always create a Fill entry per leg, in canonical fill order
+            for (receive, share) in &order.legs {
+                let (pay_units, recv_units) = if alpha_k < 1e-10 {
+                    (0.0, 0.0)
+                } else {
+                    let y_j = y.get(&order.pay).copied().unwrap_or(0.0);
+                    let y_i = y.get(receive).copied().unwrap_or(0.0);
 
-                let pay = alpha_k * order.budget.to_f64();
-                let recv = pay * (y_j - y_i).exp();
+                    let pay = alpha_k * order.budget * share;
+                    let recv = pay * (y_j - y_i).exp();
+
+                    (pay, recv)
+                };
+
+                let fill = Fill {
+                    order_id: order.id.to_string(),
+                    fill_frac: alpha_k,
+                    pay_asset: order.pay,
+                    recv_asset: *receive,
+                    pay_units,
+                    recv_units,
+                    fees_paid: BTreeMap::new(), // Fees computed separately
+                };
 
                 // Update inventory
-                *q_post.entry(order.pay).or_insert(0.0) += pay;
-                *q_post.entry(order.receive).or_insert(0.0) -= recv;
-                
-                (pay, recv)
-            };
+                if alpha_k >= 1e-10 {
+                    for (asset, delta) in fill.inventory_deltas() {
+                        *q_post.entry(asset).or_insert(0.0) += delta;
+                    }
+                }
 
-            fills.push(Fill {
-                order_id: order.id.clone(),
-                fill_frac: alpha_k,
-                pay_asset: order.pay,
-                recv_asset: order.receive,
-                pay_units,
-                recv_units,
-                fees_paid: BTreeMap::new(), // Fees computed separately
-            });
+                fills.push(fill);
+            }
         }
 
         Ok((q_post, fills))
     }
 
-    /// Compute objective function terms
+    /// Compute objective function terms, scaled by the instance's
+    /// `objective_weights`
     fn compute_objective_terms(
         &self,
         inst: &EpochInstance,
@@ -294,13 +1260,21 @@ impl ScpClearing {
         y: &BTreeMap<AssetId, f64>,
         fills: &[Fill],
     ) -> ObjectiveTerms {
-        let inventory_risk = inst.risk.inventory_penalty(q_post);
-        let price_tracking = inst.risk.tracking_penalty(y, &inst.ref_prices.y_ref);
+        let weights = inst.objective_weights;
+        let inventory_risk = weights.risk * inst.risk.inventory_penalty(q_post);
+        let price_tracking = weights.tracking * inst.risk.tracking_penalty(y, &inst.ref_prices.y_ref);
 
-        let fill_incentive = -inst.risk.eta
+        // The incentive the QP maximizes: η · priority · B_k · β_k · α_k,
+        // which is the weighted units received
+        let priority: BTreeMap<&str, f64> = inst
+            .order_legs()
+            .iter()
+            .map(|order| (order.id, order.priority))
+            .collect();
+        let fill_incentive = -inst.effective_eta()
             * fills
                 .iter()
-                .map(|fill| fill.pay_units * (fill.pay_asset as i32) as f64) // Placeholder
+                .map(|fill| priority.get(fill.order_id.as_str()).copied().unwrap_or(1.0) * fill.recv_units)
                 .sum::<f64>();
 
         let total = inventory_risk + price_tracking + fill_incentive;
@@ -310,6 +1284,7 @@ impl ScpClearing {
             price_tracking,
             fill_incentive,
             total,
+            weights,
         }
     }
 }
@@ -319,6 +1294,48 @@ mod tests {
     use super::*;
     use convexfx_oracle::{MockOracle, Oracle};
     use convexfx_risk::RiskParams;
+    use convexfx_types::Amount;
+
+    #[test]
+    fn test_violated_limits_get_no_fill() {
+        let ref_prices = MockOracle::new().current_prices().unwrap();
+        let inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+        let order = |id: &str, limit_ratio: Option<f64>| PairOrder {
+            id: id.to_string(),
+            trader: AccountId::new(id),
+            pay: AssetId::USD,
+            receive: AssetId::EUR,
+            budget: Amount::from_units(1),
+            limit_ratio,
+            min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
+            metadata: serde_json::json!({}),
+        };
+
+        // EUR clears at 1.1 USD
+        let rate = 1.1;
+        let orders = vec![
+            order("violated", Some(rate * (1.0 - 10.0 * LIMIT_TOLERANCE))),
+            order("within_tolerance", Some(rate * (1.0 - 0.5 * LIMIT_TOLERANCE))),
+            order("met", Some(rate * 1.01)),
+            order("no_limit", None),
+        ];
+        let inst = EpochInstance::new(1, inventory, orders, ref_prices, RiskParams::default_demo());
+        let y: BTreeMap<AssetId, f64> = [(AssetId::USD, 0.0), (AssetId::EUR, f64::ln(rate))].into_iter().collect();
+
+        let mut alpha = vec![1.0; 4];
+        ScpClearing::drop_violated_limits(&inst, &y, &mut alpha);
+        assert_eq!(alpha, vec![0.0, 1.0, 1.0, 1.0]);
+
+        let (q_post, fills) = ScpClearing::with_simple_solver()
+            .compute_fills_and_inventory(&inst, &y, &[1.0; 4], true)
+            .unwrap();
+        for fill in &fills {
+            assert_eq!(fill.pay_units > 0.0, fill.order_id != "violated", "{}", fill.order_id);
+        }
+        assert!((q_post[&AssetId::USD] - 13.0).abs() < 1e-9);
+    }
 
     #[test]
     fn test_empty_orders() {
@@ -0,0 +1,15 @@
+/// Nearest-rank percentile of `values`, ignoring non-finite entries
+///
+/// The result is the smallest value with at least `fraction` of the ranked
+/// values at or below it, so the p90 of 10 values is the 9th, not the max.
+/// Returns 0.0 when there is nothing to rank, so metrics of empty or
+/// unfilled batches stay finite.
+pub fn percentile(values: &[f64], fraction: f64) -> f64 {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    sorted.sort_by(f64::total_cmp);
+    let rank = (sorted.len() as f64 * fraction).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
@@ -2,37 +2,46 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::{ScpClearing, EpochInstance};
-    use convexfx_oracle::{MockOracle, Oracle};
+    use crate::{BoundRepair, ScpClearing, ScpParams, EpochInstance, FillReason};
+    use crate::{Diagnostics, EpochSolution, ObjectiveTerms, ObjectiveWeights, QpStatus, ValidationTolerances};
+    use convexfx_oracle::{MockOracle, Oracle, RefPrices};
     use convexfx_risk::RiskParams;
-    use convexfx_types::{AccountId, Amount, AssetId, PairOrder};
+    use convexfx_solver::{BindingSide, ConstraintMeta};
+    use convexfx_types::{AccountId, Amount, AssetId, BasketOrder, ConvexFxError, Fill, Order, PairOrder};
     use std::collections::BTreeMap;
+    use std::sync::Arc;
     use serde_json;
 
-    #[test]
-    fn test_single_order_clearing() {
-        let oracle = MockOracle::new();
-        let ref_prices = oracle.reference_prices(1).unwrap();
-        let risk = RiskParams::default_demo();
-
-        let mut inventory = BTreeMap::new();
-        for asset in AssetId::all() {
-            inventory.insert(*asset, 10.0);
-        }
-
-        // Single EUR buy order
-        let order = PairOrder {
-            id: "order1".to_string(),
+    /// Market order from `trader1` with no limit, minimum fill or slippage cap
+    fn pair_order(id: &str, pay: AssetId, receive: AssetId, budget: i64) -> PairOrder {
+        PairOrder {
+            id: id.to_string(),
             trader: AccountId::new("trader1"),
-            pay: AssetId::USD,
-            receive: AssetId::EUR,
-            budget: Amount::from_units(100),
+            pay,
+            receive,
+            budget: Amount::from_units(budget),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
-        };
+        }
+    }
 
-        let inst = EpochInstance::new(1, inventory, vec![order], ref_prices, risk);
+    /// Epoch 1 instance over 10 units of every asset, mock oracle prices and
+    /// the demo risk parameters
+    fn demo_instance(orders: Vec<PairOrder>) -> EpochInstance {
+        let ref_prices = MockOracle::new().reference_prices(1).unwrap();
+        let inventory = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+        EpochInstance::new(1, inventory, orders, ref_prices, RiskParams::default_demo())
+    }
+
+    #[test]
+    fn test_single_order_clearing() {
+        // Single EUR buy order
+        let order = pair_order("order1", AssetId::USD, AssetId::EUR, 100);
+
+        let inst = demo_instance(vec![order]);
 
         let clearing = ScpClearing::with_simple_solver();
         let solution = clearing.clear_epoch(&inst).unwrap();
@@ -44,16 +53,7 @@ mod tests {
 
     #[test]
     fn test_empty_orders() {
-        let oracle = MockOracle::new();
-        let ref_prices = oracle.reference_prices(1).unwrap();
-        let risk = RiskParams::default_demo();
-
-        let mut inventory = BTreeMap::new();
-        for asset in AssetId::all() {
-            inventory.insert(*asset, 10.0);
-        }
-
-        let inst = EpochInstance::new(1, inventory, vec![], ref_prices, risk);
+        let inst = demo_instance(vec![]);
 
         let clearing = ScpClearing::with_simple_solver();
         let solution = clearing.clear_epoch(&inst).unwrap();
@@ -70,7 +70,52 @@ mod tests {
     }
 
     #[test]
-    fn test_multiple_orders() {
+    fn test_unconstrained_prices_track_oracle() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        // No limit: nothing in the QP pulls prices away from the oracle
+        let order = pair_order("order1", AssetId::USD, AssetId::EUR, 1);
+
+        let inst = EpochInstance::new(1, inventory, vec![order], ref_prices, risk);
+        let solution = ScpClearing::with_clarabel().clear_epoch(&inst).unwrap();
+
+        for asset in AssetId::all() {
+            let y_ref = inst.ref_prices.get_ref(*asset);
+            assert!((solution.y_star[asset] - y_ref).abs() < 1e-6, "{} drifted from oracle", asset);
+        }
+    }
+
+    #[test]
+    fn test_tracking_gradient_vanishes_at_reference() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let inventory = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+        let inst = EpochInstance::new(1, inventory, vec![], ref_prices, RiskParams::default_demo());
+
+        // With no orders the objective is pure tracking, so its gradient
+        // P y + q must vanish at y_ref wherever the QP is linearized. A
+        // step-style term W (y_curr - y_ref) leaves W y_ref at y_curr = y_ref
+        // and pulls every non-numeraire price toward zero.
+        let y_ref = inst.ref_prices.y_ref.clone();
+        let shifted: BTreeMap<AssetId, f64> = y_ref.iter().map(|(a, y)| (*a, y + 0.01)).collect();
+        for y_current in [&y_ref, &shifted] {
+            let model = crate::qp_builder::QpBuilder::build_qp_with_bands(&inst, y_current, 20.0).unwrap();
+            for (i, asset) in AssetId::all().iter().enumerate() {
+                let gradient = model.p[(i, i)] * y_ref[asset] + model.q[i];
+                assert!(gradient.abs() < 1e-8, "{} tracking gradient {} at y_ref", asset, gradient);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixed_prices_bypass_oracle() {
         let oracle = MockOracle::new();
         let ref_prices = oracle.reference_prices(1).unwrap();
         let risk = RiskParams::default_demo();
@@ -80,41 +125,68 @@ mod tests {
             inventory.insert(*asset, 10.0);
         }
 
+        let order = pair_order("usd_to_jpy", AssetId::USD, AssetId::JPY, 1);
+
+        // Reported regime: EUR and JPY quotes swapped, so JPY trades above USD
+        let mut pinned = BTreeMap::new();
+        for asset in AssetId::all() {
+            pinned.insert(*asset, ref_prices.get_ref(*asset).exp());
+        }
+        pinned.insert(AssetId::EUR, 0.01);
+        pinned.insert(AssetId::JPY, 1.1);
+        pinned.insert(AssetId::USD, 2.0); // ignored: USD is the numeraire
+
+        let inst = EpochInstance::new(1, inventory, vec![order], ref_prices, risk)
+            .with_fixed_prices(pinned.clone())
+            .unwrap();
+        assert_eq!(inst.ref_prices.providers, vec![crate::FIXED_PRICE_PROVIDER.to_string()]);
+        assert_eq!(inst.ref_prices.get_ref(AssetId::USD), 0.0);
+
+        let solution = ScpClearing::with_clarabel().clear_epoch(&inst).unwrap();
+
+        // Clearing tracks the pinned prices, not the oracle
+        assert!((solution.prices[&AssetId::JPY] - 1.1).abs() < 1e-6);
+        assert!((solution.prices[&AssetId::EUR] - 0.01).abs() < 1e-6);
+
+        // One USD buys less than one JPY at the pinned rate
+        let fill = &solution.fills[0];
+        assert!(fill.fill_frac > 0.0);
+        assert!((fill.recv_units - fill.pay_units / 1.1).abs() < 1e-6);
+
+        // Prices that have no log are refused rather than pinned as NaN
+        for bad in [0.0, -1.1, f64::NAN, f64::INFINITY] {
+            let mut prices = pinned.clone();
+            prices.insert(AssetId::JPY, bad);
+            let err = inst.clone().with_fixed_prices(prices).unwrap_err();
+            assert!(matches!(err, ConvexFxError::InvalidPrice(ref msg) if msg.contains("JPY")), "{}", err);
+        }
+
+        // A partial map is refused rather than leaving the rest at 1, while
+        // the numeraire may be omitted
+        let mut partial = pinned.clone();
+        partial.remove(&AssetId::GBP);
+        let err = inst.clone().with_fixed_prices(partial).unwrap_err();
+        assert!(matches!(err, ConvexFxError::InvalidPrice(ref msg) if msg.contains("GBP")), "{}", err);
+        pinned.remove(&AssetId::USD);
+        assert!(inst.with_fixed_prices(pinned).is_ok());
+    }
+
+    #[test]
+    fn test_multiple_orders() {
         // Create 3 orders
         let orders = vec![
+            pair_order("order1", AssetId::USD, AssetId::EUR, 50),
             PairOrder {
-                id: "order1".to_string(),
-                trader: AccountId::new("trader1"),
-                pay: AssetId::USD,
-                receive: AssetId::EUR,
-                budget: Amount::from_units(50),
-                limit_ratio: None,
-                min_fill_fraction: None,
-                metadata: serde_json::json!({}),
-            },
-            PairOrder {
-                id: "order2".to_string(),
                 trader: AccountId::new("trader2"),
-                pay: AssetId::USD,
-                receive: AssetId::EUR,
-                budget: Amount::from_units(75),
-                limit_ratio: None,
-                min_fill_fraction: None,
-                metadata: serde_json::json!({}),
+                ..pair_order("order2", AssetId::USD, AssetId::EUR, 75)
             },
             PairOrder {
-                id: "order3".to_string(),
                 trader: AccountId::new("trader3"),
-                pay: AssetId::EUR,
-                receive: AssetId::GBP,
-                budget: Amount::from_units(100),
-                limit_ratio: None,
-                min_fill_fraction: None,
-                metadata: serde_json::json!({}),
+                ..pair_order("order3", AssetId::EUR, AssetId::GBP, 100)
             },
         ];
 
-        let inst = EpochInstance::new(1, inventory, orders, ref_prices, risk);
+        let inst = demo_instance(orders);
 
         let clearing = ScpClearing::with_simple_solver();
         let solution = clearing.clear_epoch(&inst).unwrap();
@@ -124,27 +196,9 @@ mod tests {
 
     #[test]
     fn test_usd_numeraire_preserved() {
-        let oracle = MockOracle::new();
-        let ref_prices = oracle.reference_prices(1).unwrap();
-        let risk = RiskParams::default_demo();
-
-        let mut inventory = BTreeMap::new();
-        for asset in AssetId::all() {
-            inventory.insert(*asset, 10.0);
-        }
-
-        let order = PairOrder {
-            id: "order1".to_string(),
-            trader: AccountId::new("trader1"),
-            pay: AssetId::EUR,
-            receive: AssetId::JPY,
-            budget: Amount::from_units(100),
-            limit_ratio: None,
-            min_fill_fraction: None,
-            metadata: serde_json::json!({}),
-        };
+        let order = pair_order("order1", AssetId::EUR, AssetId::JPY, 100);
 
-        let inst = EpochInstance::new(1, inventory, vec![order], ref_prices, risk);
+        let inst = demo_instance(vec![order]);
 
         let clearing = ScpClearing::with_simple_solver();
         let solution = clearing.clear_epoch(&inst).unwrap();
@@ -155,7 +209,7 @@ mod tests {
     }
 
     #[test]
-    fn test_convergence_achieved() {
+    fn test_eur_numeraire_keeps_cross_rates_coherent() {
         let oracle = MockOracle::new();
         let ref_prices = oracle.reference_prices(1).unwrap();
         let risk = RiskParams::default_demo();
@@ -165,18 +219,50 @@ mod tests {
             inventory.insert(*asset, 10.0);
         }
 
-        let order = PairOrder {
-            id: "order1".to_string(),
-            trader: AccountId::new("trader1"),
-            pay: AssetId::USD,
-            receive: AssetId::EUR,
-            budget: Amount::from_units(50),
-            limit_ratio: None,
-            min_fill_fraction: None,
-            metadata: serde_json::json!({}),
-        };
+        let orders = vec![
+            pair_order("eur_jpy", AssetId::EUR, AssetId::JPY, 1),
+            PairOrder {
+                trader: AccountId::new("trader2"),
+                ..pair_order("usd_gbp", AssetId::USD, AssetId::GBP, 2)
+            },
+        ];
 
-        let inst = EpochInstance::new(1, inventory, vec![order], ref_prices, risk);
+        let usd_inst = EpochInstance::new(1, inventory.clone(), orders, ref_prices, risk);
+        let eur_inst = usd_inst.clone().with_numeraire(AssetId::EUR);
+        assert_eq!(eur_inst.ref_prices.get_ref(AssetId::EUR), 0.0);
+
+        let clearing = ScpClearing::new();
+        let usd_solution = clearing.clear_epoch(&usd_inst).unwrap();
+        let eur_solution = clearing.clear_epoch(&eur_inst).unwrap();
+
+        assert_eq!(eur_solution.numeraire, AssetId::EUR);
+        assert!(eur_solution.y_star[&AssetId::EUR].abs() < 1e-9);
+        assert!((eur_solution.prices[&AssetId::EUR] - 1.0).abs() < 1e-9);
+
+        // Cross rates do not depend on the numeraire
+        let assets = AssetId::all();
+        for a in assets {
+            for b in assets {
+                let usd_cross = usd_solution.y_star[a] - usd_solution.y_star[b];
+                let eur_cross = eur_solution.y_star[a] - eur_solution.y_star[b];
+                assert!((usd_cross - eur_cross).abs() < 1e-6, "{}/{}: {} vs {}", a, b, usd_cross, eur_cross);
+            }
+        }
+        assert!(eur_solution.coherence_error_bps() < 1e-6);
+        for (usd_fill, eur_fill) in usd_solution.fills.iter().zip(&eur_solution.fills) {
+            assert!((usd_fill.fill_frac - eur_fill.fill_frac).abs() < 1e-6);
+        }
+
+        let tol = ValidationTolerances::default();
+        eur_solution.validate_prices(&eur_inst.ref_prices, &tol).unwrap();
+        eur_solution.validate(&inventory, &eur_inst.ref_prices, &tol).unwrap();
+    }
+
+    #[test]
+    fn test_convergence_achieved() {
+        let order = pair_order("order1", AssetId::USD, AssetId::EUR, 50);
+
+        let inst = demo_instance(vec![order]);
 
         let clearing = ScpClearing::with_simple_solver();
         let solution = clearing.clear_epoch(&inst).unwrap();
@@ -187,27 +273,9 @@ mod tests {
 
     #[test]
     fn test_price_within_bands() {
-        let oracle = MockOracle::new();
-        let ref_prices = oracle.reference_prices(1).unwrap();
-        let risk = RiskParams::default_demo();
-
-        let mut inventory = BTreeMap::new();
-        for asset in AssetId::all() {
-            inventory.insert(*asset, 10.0);
-        }
-
-        let order = PairOrder {
-            id: "order1".to_string(),
-            trader: AccountId::new("trader1"),
-            pay: AssetId::USD,
-            receive: AssetId::EUR,
-            budget: Amount::from_units(100),
-            limit_ratio: None,
-            min_fill_fraction: None,
-            metadata: serde_json::json!({}),
-        };
+        let order = pair_order("order1", AssetId::USD, AssetId::EUR, 100);
 
-        let inst = EpochInstance::new(1, inventory, vec![order], ref_prices, risk);
+        let inst = demo_instance(vec![order]);
 
         let clearing = ScpClearing::with_simple_solver();
         let solution = clearing.clear_epoch(&inst).unwrap();
@@ -268,16 +336,7 @@ mod tests {
         ];
 
         for (pay, recv) in pairs {
-            let order = PairOrder {
-                id: format!("order_{}{}", pay, recv),
-                trader: AccountId::new("trader1"),
-                pay,
-                receive: recv,
-                budget: Amount::from_units(50),
-                limit_ratio: None,
-                min_fill_fraction: None,
-                metadata: serde_json::json!({}),
-            };
+            let order = pair_order(&format!("order_{}{}", pay, recv), pay, recv, 50);
 
             let inst = EpochInstance::new(1, inventory.clone(), vec![order], ref_prices.clone(), risk.clone());
 
@@ -290,6 +349,23 @@ mod tests {
 
     #[test]
     fn test_limit_order_constraint() {
+        // Order with very tight limit
+        let order = PairOrder {
+            limit_ratio: Some(1.05), // Tight limit
+            ..pair_order("limited", AssetId::USD, AssetId::EUR, 100)
+        };
+
+        let inst = demo_instance(vec![order]);
+
+        let clearing = ScpClearing::with_simple_solver();
+        let solution = clearing.clear_epoch(&inst).unwrap();
+
+        // Solution should respect the limit
+        assert!(solution.diagnostics.iterations > 0);
+    }
+
+    #[test]
+    fn test_warm_start_reduces_iterations() {
         let oracle = MockOracle::new();
         let ref_prices = oracle.reference_prices(1).unwrap();
         let risk = RiskParams::default_demo();
@@ -298,25 +374,1916 @@ mod tests {
         for asset in AssetId::all() {
             inventory.insert(*asset, 10.0);
         }
+        // Enough JPY for order2's ~125 JPY to fill in full
+        inventory.insert(AssetId::JPY, 200.0);
 
-        // Order with very tight limit
-        let order = PairOrder {
-            id: "limited".to_string(),
-            trader: AccountId::new("trader1"),
-            pay: AssetId::USD,
-            receive: AssetId::EUR,
-            budget: Amount::from_units(100),
-            limit_ratio: Some(1.05), // Tight limit
-            min_fill_fraction: None,
-            metadata: serde_json::json!({}),
-        };
+        let orders = vec![
+            pair_order("order1", AssetId::USD, AssetId::EUR, 2),
+            PairOrder {
+                trader: AccountId::new("trader2"),
+                ..pair_order("order2", AssetId::GBP, AssetId::JPY, 1)
+            },
+        ];
 
-        let inst = EpochInstance::new(1, inventory, vec![order], ref_prices, risk);
+        let inst = EpochInstance::new(1, inventory, orders, ref_prices, risk);
+        let clearing = ScpClearing::with_simple_solver();
+
+        let cold = clearing.clear_epoch(&inst).unwrap();
+        let warm = clearing.clear_epoch_warm(&inst, Some(&cold)).unwrap();
+
+        // Same order flow, unchanged prices: warm start should need fewer iterations
+        assert!(
+            warm.diagnostics.iterations < cold.diagnostics.iterations,
+            "warm {} vs cold {}",
+            warm.diagnostics.iterations,
+            cold.diagnostics.iterations
+        );
+        assert_eq!(warm.fills.len(), cold.fills.len());
+    }
+
+    #[test]
+    fn test_diagnostics_timing() {
+        let order = pair_order("order1", AssetId::USD, AssetId::EUR, 1);
+
+        let inst = demo_instance(vec![order]);
 
         let clearing = ScpClearing::with_simple_solver();
         let solution = clearing.clear_epoch(&inst).unwrap();
 
-        // Solution should respect the limit
-        assert!(solution.diagnostics.iterations > 0);
+        // One timing entry per SCP iteration, bounded by the total
+        let diag = &solution.diagnostics;
+        assert_eq!(diag.iteration_times_us.len(), diag.iterations);
+        let iter_total_ms = diag.iteration_times_us.iter().sum::<u64>() / 1000;
+        assert!(iter_total_ms <= diag.solve_time_ms);
+    }
+
+    #[test]
+    fn test_coherence_constraints_with_perturbed_warm_start() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        let order = pair_order("order1", AssetId::EUR, AssetId::JPY, 1);
+
+        let inst = EpochInstance::new(1, inventory, vec![order], ref_prices, risk);
+
+        let triangles = vec![
+            (AssetId::USD, AssetId::EUR, AssetId::JPY),
+            (AssetId::EUR, AssetId::GBP, AssetId::CHF),
+        ];
+        let params = ScpParams {
+            coherence_tolerance_bps: Some(0.1),
+            coherence_triangles: triangles,
+            ..ScpParams::default()
+        };
+        let clearing = ScpClearing::with_backend(
+            Arc::new(convexfx_solver::SimpleQpSolver::new()),
+            params,
+        );
+
+        // Warm start from a solution whose prices are pushed off the oracle
+        let cold = clearing.clear_epoch(&inst).unwrap();
+        let mut prev = cold.clone();
+        for (i, y) in prev.y_star.values_mut().enumerate() {
+            *y += 0.002 * (i as f64 - 2.5);
+        }
+
+        let solution = clearing.clear_epoch_warm(&inst, Some(&prev)).unwrap();
+
+        // The perturbed start must not leak past the price bands
+        for (asset, y) in &solution.y_star {
+            let low = inst.ref_prices.band_low[asset];
+            let high = inst.ref_prices.band_high[asset];
+            assert!(
+                *y >= low - 1e-9 && *y <= high + 1e-9,
+                "{} at {} outside band [{}, {}]",
+                asset, y, low, high
+            );
+        }
+
+        // ...and must land on the same clearing as a cold start
+        assert_eq!(solution.fills.len(), cold.fills.len());
+        for (warm, cold) in solution.fills.iter().zip(&cold.fills) {
+            assert_eq!(warm.order_id, cold.order_id);
+            assert!(
+                (warm.fill_frac - cold.fill_frac).abs() < 1e-3,
+                "{} filled {} warm vs {} cold",
+                warm.order_id, warm.fill_frac, cold.fill_frac
+            );
+        }
+        let objective_gap = (solution.objective_terms.total - cold.objective_terms.total).abs();
+        assert!(
+            objective_gap <= 1e-4 * cold.objective_terms.total.abs().max(1.0),
+            "objective {} warm vs {} cold",
+            solution.objective_terms.total, cold.objective_terms.total
+        );
+    }
+
+    #[test]
+    fn test_qp_tracking_targets_oracle_from_any_iterate() {
+        use convexfx_solver::SolverBackend;
+
+        let inst = demo_instance(Vec::new());
+
+        // Linearize 10 bps away from the oracle, inside a 20 bps band: the
+        // tracking term must still pull the absolute log-prices to y_ref
+        for offset in [-0.001, 0.001] {
+            let y_current: BTreeMap<AssetId, f64> = inst
+                .ref_prices
+                .y_ref
+                .iter()
+                .map(|(asset, y)| (*asset, if *asset == AssetId::USD { *y } else { y + offset }))
+                .collect();
+            let model = crate::qp_builder::QpBuilder::build_qp_with_bands(&inst, &y_current, 20.0).unwrap();
+            let solution = convexfx_solver::ClarabelSolver::new().solve_qp(&model).unwrap();
+            for asset in AssetId::all() {
+                let y_ref = inst.ref_prices.get_ref(*asset);
+                assert!((solution.x[asset.index()] - y_ref).abs() < 1e-6, "{} pulled off the oracle", asset);
+            }
+        }
+    }
+
+    #[test]
+    fn test_coherence_cross_rates_bind_to_prices() {
+        use convexfx_solver::{SolverBackend, VarMeta};
+
+        let inst = demo_instance(Vec::new());
+
+        let model = crate::qp_builder::QpBuilder::build_qp_with_bands(&inst, &inst.ref_prices.y_ref, 20.0).unwrap();
+        let mut model = crate::qp_builder::QpBuilder::add_coherence_constraints(
+            model,
+            &[(AssetId::USD, AssetId::EUR, AssetId::JPY)],
+            1e-5,
+        );
+
+        // Reward a low EUR/JPY cross rate through its leg variable alone
+        let leg = model
+            .var_meta
+            .iter()
+            .position(|meta| matches!(meta, VarMeta::CrossRate(AssetId::EUR, AssetId::JPY)))
+            .unwrap();
+        model.q[leg] += 1.0;
+
+        let solution = convexfx_solver::ClarabelSolver::new().solve_qp(&model).unwrap();
+        assert_eq!(solution.status, QpStatus::Optimal);
+        let y = |asset: AssetId| solution.x[asset.index()];
+
+        // The leg cannot drift off the prices, so the reward moves them
+        assert!((solution.x[leg] - (y(AssetId::EUR) - y(AssetId::JPY))).abs() < 1e-6);
+        let ref_cross = inst.ref_prices.get_ref(AssetId::EUR) - inst.ref_prices.get_ref(AssetId::JPY);
+        assert!(y(AssetId::EUR) - y(AssetId::JPY) < ref_cross - 1e-3, "cross rate did not move");
+        assert!(solution.binding.iter().any(|b| {
+            b.meta == Some(ConstraintMeta::CrossRate(AssetId::EUR, AssetId::JPY)) && b.side == BindingSide::Equality
+        }));
+    }
+
+    #[test]
+    fn test_tight_trust_region_schedule_converges() {
+        // Balanced flow: matching buys and sells on each USD pair
+        let mut orders = Vec::new();
+        for asset in [AssetId::EUR, AssetId::GBP, AssetId::JPY] {
+            for (pay, receive) in [(AssetId::USD, asset), (asset, AssetId::USD)] {
+                orders.push(PairOrder {
+                    trader: AccountId::new(format!("trader_{}", pay)),
+                    ..pair_order(&format!("{}_{}", pay, receive), pay, receive, 1)
+                });
+            }
+        }
+        let inst = demo_instance(orders);
+
+        let params = ScpParams {
+            initial_band_bps: Some(2.0),
+            normal_band_bps: Some(3.0),
+            widen_band_bps: Some(5.0),
+            widen_threshold_multiplier: 5.0,
+            ..ScpParams::default()
+        };
+        let clearing = ScpClearing::with_backend(Arc::new(convexfx_solver::ClarabelSolver::new()), params);
+        let solution = clearing.clear_epoch(&inst).unwrap();
+
+        assert!(solution.diagnostics.convergence_achieved);
+        for asset in AssetId::all() {
+            let deviation_bps = (solution.y_star[asset] - inst.ref_prices.get_ref(*asset)).abs() * 10000.0;
+            assert!(deviation_bps <= 5.0 + 1e-6, "{} moved {} bps", asset, deviation_bps);
+        }
+    }
+
+    #[test]
+    fn test_retry_relaxes_bands_until_converged() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        // The market order fills on the first iteration. The limit needs EUR
+        // 2.5 bps below the oracle: out of reach of the initial trust region,
+        // inside the normal one, so the price still moves on the second.
+        let y_eur = ref_prices.get_ref(AssetId::EUR);
+        let orders = vec![
+            pair_order("gbp_market", AssetId::USD, AssetId::GBP, 1),
+            PairOrder {
+                trader: AccountId::new("trader2"),
+                limit_ratio: Some((y_eur - 0.00025).exp()),
+                ..pair_order("eur_bid", AssetId::USD, AssetId::EUR, 1)
+            },
+        ];
+        let inst = EpochInstance::new(1, inventory, orders, ref_prices, risk);
+
+        let hard = ScpParams {
+            max_iterations: 2,
+            initial_band_bps: Some(1.5),
+            normal_band_bps: Some(3.0),
+            widen_band_bps: Some(4.0),
+            ..ScpParams::default()
+        };
+        let backend = Arc::new(convexfx_solver::ClarabelSolver::new());
+
+        let without_retry = ScpClearing::with_backend(backend.clone(), hard.clone())
+            .clear_epoch(&inst)
+            .unwrap();
+        assert!(!without_retry.diagnostics.convergence_achieved);
+        assert_eq!(without_retry.diagnostics.iterations, 2);
+
+        let params = ScpParams {
+            max_retries: 2,
+            band_relaxation_factor: 2.0,
+            ..hard
+        };
+        let with_retry = ScpClearing::with_backend(backend, params)
+            .clear_epoch(&inst)
+            .unwrap();
+        assert!(with_retry.diagnostics.convergence_achieved);
+        assert!(with_retry.diagnostics.iterations > 2);
+        assert_eq!(
+            with_retry.diagnostics.iteration_times_us.len(),
+            with_retry.diagnostics.iterations
+        );
+        assert!(with_retry.fills[0].fill_frac > 0.99);
+    }
+
+    #[test]
+    fn test_one_tracing_span_per_iteration() {
+        use std::sync::Mutex;
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+        use tracing_subscriber::Layer;
+
+        /// Records the name of every span created
+        struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+
+        impl<S: tracing::Subscriber> Layer<S> for SpanNames {
+            fn on_new_span(&self, attrs: &tracing::span::Attributes<'_>, _id: &tracing::span::Id, _ctx: Context<'_, S>) {
+                self.0.lock().unwrap().push(attrs.metadata().name());
+            }
+        }
+
+        let order = pair_order("order1", AssetId::USD, AssetId::EUR, 2);
+        let inst = demo_instance(vec![order]);
+
+        let names = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(SpanNames(names.clone()));
+        let solution = tracing::subscriber::with_default(subscriber, || {
+            ScpClearing::new().clear_epoch(&inst).unwrap()
+        });
+
+        let names = names.lock().unwrap();
+        let count = |name: &str| names.iter().filter(|n| **n == name).count();
+        assert_eq!(count("clear_epoch"), 1);
+        assert_eq!(count("scp_iteration"), solution.diagnostics.iterations);
+        assert!(solution.diagnostics.iterations > 0);
+    }
+
+    #[test]
+    fn test_explain_unfilled_limit_order() {
+        // EURUSD trades at 1.10; a limit of 1.05 cannot be met inside the bands
+        let order = PairOrder {
+            limit_ratio: Some(1.05),
+            ..pair_order("limited", AssetId::USD, AssetId::EUR, 1)
+        };
+
+        let inst = demo_instance(vec![order]);
+
+        let clearing = ScpClearing::with_simple_solver();
+        let solution = clearing.clear_epoch(&inst).unwrap();
+
+        let explanation = solution.explain(&inst, "limited").unwrap();
+        assert!(!solution.fills[0].is_complete());
+        assert!(
+            explanation.cites(|r| matches!(r, FillReason::LimitNotMet { .. })),
+            "explanation should cite the limit: {:?}",
+            explanation.reasons
+        );
+
+        assert!(solution.explain(&inst, "unknown").is_none());
+    }
+
+    #[test]
+    fn test_buy_wall_reports_binding_inventory_bound() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        // Plenty of USD to pay out, so only the EUR side can bind
+        inventory.insert(AssetId::USD, 14.0);
+
+        // Wall of EUR sellers: the pool buys 8 EUR against a max of 15
+        let orders: Vec<PairOrder> = (0..8)
+            .map(|i| PairOrder {
+                trader: AccountId::new(format!("trader{}", i)),
+                ..pair_order(&format!("wall_{}", i), AssetId::EUR, AssetId::USD, 1)
+            })
+            .collect();
+
+        let inst = EpochInstance::new(1, inventory, orders, ref_prices, risk);
+        let params = ScpParams {
+            enforce_inventory_bounds: true,
+            ..ScpParams::default()
+        };
+        let clearing = ScpClearing::with_backend(
+            Arc::new(convexfx_solver::ClarabelSolver::new()),
+            params,
+        );
+        let solution = clearing.clear_epoch(&inst).unwrap();
+
+        let binding = &solution.diagnostics.binding_constraints;
+        assert!(
+            binding.iter().any(|b| b.meta == Some(ConstraintMeta::InventoryBound(AssetId::EUR))
+                && b.side == BindingSide::Upper),
+            "EUR inventory upper bound should bind: {:?}",
+            binding
+        );
+        assert!(binding.iter().any(|b| b.meta == Some(ConstraintMeta::Numeraire)));
+        assert!(solution.q_post[&AssetId::EUR] <= inst.risk.max_bound(AssetId::EUR) + 1e-6);
+    }
+
+    /// Deterministic Fisher-Yates shuffle driven by a xorshift generator
+    fn shuffle_orders(orders: &mut [PairOrder], seed: u64) {
+        let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+        for i in (1..orders.len()).rev() {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let j = (state % (i as u64 + 1)) as usize;
+            orders.swap(i, j);
+        }
+    }
+
+    /// Clear the same order set under several permutations and assert that
+    /// every order receives the same fill regardless of submission order
+    fn assert_fills_order_independent(
+        clearing: &ScpClearing,
+        inst: &EpochInstance,
+        permutations: u64,
+    ) {
+        let baseline = clearing.clear_epoch(inst).unwrap();
+        let baseline_fills: BTreeMap<_, _> = baseline
+            .fills
+            .iter()
+            .map(|f| (f.order_id.clone(), f))
+            .collect();
+
+        for seed in 1..=permutations {
+            let mut shuffled = inst.clone();
+            shuffle_orders(&mut shuffled.orders, seed);
+
+            let solution = clearing.clear_epoch(&shuffled).unwrap();
+            assert_eq!(solution.fills.len(), baseline_fills.len());
+
+            for fill in &solution.fills {
+                let expected = baseline_fills[&fill.order_id];
+                for (name, got, want) in [
+                    ("fill_frac", fill.fill_frac, expected.fill_frac),
+                    ("pay_units", fill.pay_units, expected.pay_units),
+                    ("recv_units", fill.recv_units, expected.recv_units),
+                ] {
+                    assert!(
+                        (got - want).abs() <= 1e-6 * want.abs().max(1.0),
+                        "permutation {} changed {} of {}: {} vs {}",
+                        seed, name, fill.order_id, got, want
+                    );
+                }
+            }
+
+            for asset in AssetId::all() {
+                let got = solution.y_star[asset];
+                let want = baseline.y_star[asset];
+                assert!(
+                    (got - want).abs() <= 1e-6,
+                    "permutation {} changed price of {}: {} vs {}",
+                    seed, asset, got, want
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fills_independent_of_submission_order() {
+        let pairs = [
+            (AssetId::USD, AssetId::EUR, 2, None),
+            (AssetId::EUR, AssetId::USD, 3, None),
+            (AssetId::USD, AssetId::GBP, 1, Some(1.5)),
+            (AssetId::GBP, AssetId::JPY, 2, None),
+            (AssetId::CHF, AssetId::USD, 1, None),
+            (AssetId::USD, AssetId::AUD, 4, Some(0.8)),
+        ];
+        let orders = pairs
+            .iter()
+            .enumerate()
+            .map(|(i, (pay, receive, budget, limit_ratio))| PairOrder {
+                trader: AccountId::new(format!("trader{}", i)),
+                limit_ratio: *limit_ratio,
+                ..pair_order(&format!("order{}", i), *pay, *receive, *budget)
+            })
+            .collect();
+
+        let inst = demo_instance(orders);
+
+        // Clarabel only: the projected-gradient SimpleQpSolver takes minutes per solve here
+        assert_fills_order_independent(&ScpClearing::new(), &inst, 4);
+    }
+
+    #[test]
+    fn test_fills_returned_in_canonical_order() {
+        let orders: Vec<PairOrder> = ["order10", "order2", "alpha", "order1", "zulu"]
+            .iter()
+            .enumerate()
+            .map(|(i, id)| {
+                let (pay, receive) = if i % 2 == 0 { (AssetId::USD, AssetId::EUR) } else { (AssetId::EUR, AssetId::USD) };
+                PairOrder {
+                    trader: AccountId::new(format!("trader{}", i)),
+                    ..pair_order(id, pay, receive, 1)
+                }
+            })
+            .collect();
+        let inst = demo_instance(orders);
+        let clearing = ScpClearing::new();
+
+        let canonical = ["alpha", "order1", "order10", "order2", "zulu"];
+        for seed in 0..4 {
+            let mut shuffled = inst.clone();
+            shuffle_orders(&mut shuffled.orders, seed);
+
+            let solution = clearing.clear_epoch(&shuffled).unwrap();
+            let ids: Vec<&str> = solution.fills.iter().map(|f| f.order_id.as_str()).collect();
+            assert_eq!(ids, canonical, "permutation {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_fill_incentive_cap_limits_volume() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        // One-sided EUR buying: every fill pushes inventory further off target
+        let orders: Vec<PairOrder> = (0..10)
+            .map(|i| PairOrder {
+                trader: AccountId::new(format!("trader{}", i)),
+                ..pair_order(&format!("eur_buy_{}", i), AssetId::USD, AssetId::EUR, 1)
+            })
+            .collect();
+
+        let volume = |eta: f64, cap: Option<f64>| {
+            let mut risk = RiskParams::default_demo();
+            risk.eta = eta;
+            risk.fill_incentive_cap = cap;
+            let inst = EpochInstance::new(1, inventory.clone(), orders.clone(), ref_prices.clone(), risk);
+            let solution = ScpClearing::new().clear_epoch(&inst).unwrap();
+            (inst.effective_eta(), solution.fills.iter().map(|f| f.pay_units).sum::<f64>())
+        };
+
+        let (eta_capped, base_volume) = volume(1.0, Some(0.5));
+        assert!(eta_capped < 1.0, "cap should bind at eta = 1, got {}", eta_capped);
+
+        // Past the cap, a larger eta changes nothing
+        for eta in [10.0, 100.0] {
+            let (effective, capped_volume) = volume(eta, Some(0.5));
+            assert!((effective - eta_capped).abs() < 1e-12);
+            assert!(
+                (capped_volume - base_volume).abs() < 1e-6,
+                "volume grew with eta = {}: {} vs {}",
+                eta, capped_volume, base_volume
+            );
+        }
+
+        // Without the cap the same eta trades at least as much
+        let (uncapped_eta, uncapped_volume) = volume(100.0, None);
+        assert_eq!(uncapped_eta, 100.0);
+        assert!(uncapped_volume >= base_volume - 1e-6);
+    }
+
+    #[test]
+    fn test_reduce_only_pool_order_unwinds_long_position() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        // Pool is 3 EUR long of its target of 10; everything else is on target
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+        inventory.insert(AssetId::EUR, 13.0);
+
+        let order = |id: &str, pay: AssetId, receive: AssetId, units: i64| PairOrder {
+            trader: AccountId::new(id),
+            ..pair_order(id, pay, receive, units)
+        };
+        let customer = order("customer", AssetId::GBP, AssetId::CHF, 1);
+        // Takes in USD for EUR, worth more than the 3 EUR excess
+        let unwind = order("pool_unwind_eur", AssetId::USD, AssetId::EUR, 5);
+        // The pool is flat in JPY, so this one may not fill at all
+        let flat = order("pool_unwind_jpy", AssetId::USD, AssetId::JPY, 1);
+
+        let inst = EpochInstance::new(1, inventory, vec![customer], ref_prices, risk)
+            .with_pool_orders(vec![unwind.clone(), flat]);
+        let cap = inst.reduce_only_cap(&unwind);
+        assert!(cap > 0.0 && cap < 1.0, "cap = {}", cap);
+
+        let solution = ScpClearing::new().clear_epoch(&inst).unwrap();
+        solution.validate(&inst.inventory_q, &inst.ref_prices, &ValidationTolerances::default()).unwrap();
+        let fill = |id: &str| solution.fills.iter().find(|f| f.order_id == id).unwrap();
+
+        assert!((fill("customer").fill_frac - 1.0).abs() < 1e-6);
+        assert!(fill("pool_unwind_jpy").fill_frac < 1e-6);
+
+        let unwound = fill("pool_unwind_eur");
+        assert!((unwound.fill_frac - cap).abs() < 1e-4, "{} vs cap {}", unwound.fill_frac, cap);
+        assert!((unwound.recv_units - 3.0).abs() < 0.01, "unwound {} EUR", unwound.recv_units);
+        assert!((solution.q_post[&AssetId::EUR] - 10.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_cleared_rates_compose() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        let orders = vec![
+            pair_order("eur_jpy", AssetId::EUR, AssetId::JPY, 2),
+            PairOrder {
+                trader: AccountId::new("trader2"),
+                ..pair_order("gbp_usd", AssetId::GBP, AssetId::USD, 1)
+            },
+        ];
+
+        let inst = EpochInstance::new(1, inventory, orders, ref_prices, RiskParams::default_demo());
+        let solution = ScpClearing::new().clear_epoch(&inst).unwrap();
+
+        assert!((solution.rate(AssetId::EUR, AssetId::USD)
+            - solution.prices[&AssetId::EUR] / solution.prices[&AssetId::USD]).abs() < 1e-12);
+        for a in AssetId::all() {
+            assert!((solution.rate(*a, *a) - 1.0).abs() < 1e-12);
+            for b in AssetId::all() {
+                for c in AssetId::all() {
+                    let composed = solution.rate(*a, *b) * solution.rate(*b, *c);
+                    let direct = solution.rate(*a, *c);
+                    assert!(
+                        ((composed - direct) / direct).abs() < 1e-12,
+                        "{}->{}->{}: {} vs {}", a, b, c, composed, direct
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_incentive_weight_sweep_raises_fill_rate() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        let orders: Vec<PairOrder> = (0..5)
+            .map(|i| PairOrder {
+                trader: AccountId::new(format!("trader{}", i)),
+                ..pair_order(&format!("eur_buy_{}", i), AssetId::USD, AssetId::EUR, 1)
+            })
+            .collect();
+        let base = EpochInstance::new(1, inventory, orders, ref_prices, RiskParams::default_demo());
+
+        let mut previous_rate = f64::NEG_INFINITY;
+        let mut rates = Vec::new();
+        for incentive in [0.0, 0.25, 1.0, 4.0] {
+            let weights = ObjectiveWeights { incentive, ..ObjectiveWeights::default() };
+            let inst = base.clone().with_objective_weights(weights);
+            let solution = ScpClearing::new().clear_epoch(&inst).unwrap();
+            assert_eq!(solution.objective_terms.weights, weights);
+
+            let rate = solution.fills.iter().map(|f| f.fill_frac).sum::<f64>() / inst.num_orders() as f64;
+            assert!(
+                rate >= previous_rate - 1e-9,
+                "fill rate fell from {} to {} at incentive weight {}",
+                previous_rate, rate, incentive
+            );
+            previous_rate = rate;
+            rates.push(rate);
+        }
+
+        // Without an incentive fills are free either way, so the solver may
+        // stop anywhere in between; a strong incentive fills the uncontested flow
+        assert!(rates[3] > rates[0], "fill rates {:?} did not rise with the incentive", rates);
+        assert!(rates[3] > 0.99, "fill rate at incentive weight 4: {}", rates[3]);
+    }
+
+    fn limit_order(id: &str, limit_ratio: f64) -> PairOrder {
+        PairOrder {
+            trader: AccountId::new(id),
+            limit_ratio: Some(limit_ratio),
+            ..pair_order(id, AssetId::USD, AssetId::EUR, 1)
+        }
+    }
+
+    #[test]
+    fn test_unattainable_limit_gets_zero_fill() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+        let band = risk.price_band_bps / 10000.0;
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        // 5 bps beyond the lowest EUR price the trust region allows, and
+        // 5 bps inside it
+        let y_eur = ref_prices.get_ref(AssetId::EUR);
+        let out_of_reach = (y_eur - band - 0.0005).exp();
+        let reachable = (y_eur - band + 0.0005).exp();
+        let orders = vec![
+            limit_order("out_of_reach", out_of_reach),
+            limit_order("reachable", reachable),
+        ];
+
+        let inst = EpochInstance::new(1, inventory, orders, ref_prices, risk);
+
+        for clearing in [ScpClearing::with_simple_solver(), ScpClearing::new()] {
+            let solution = clearing.clear_epoch(&inst).unwrap();
+
+            let unfilled = &solution.fills[0];
+            assert!(unfilled.fill_frac.abs() < 1e-9, "expected zero fill, got {}", unfilled.fill_frac);
+            assert_eq!(unfilled.pay_units, 0.0);
+            assert_eq!(unfilled.recv_units, 0.0);
+
+            // The unreachable limit must not drag EUR below its band
+            let y_eur_star = solution.y_star[&AssetId::EUR];
+            assert!(y_eur_star >= y_eur - band - 1e-9, "EUR pushed outside band: {}", y_eur_star);
+
+            // Whatever fills must respect its limit at the clearing prices
+            let filled = &solution.fills[1];
+            if filled.fill_frac > 1e-9 {
+                let clearing_ratio = solution.rate(AssetId::EUR, AssetId::USD);
+                assert!(clearing_ratio <= reachable * (1.0 + 1e-6), "{} > {}", clearing_ratio, reachable);
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_liquidity_asset_gets_zero_fill() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        // The pool holds no JPY at all
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+        inventory.insert(AssetId::JPY, 0.0);
+
+        let order = |id: &str, receive: AssetId| PairOrder {
+            trader: AccountId::new(id),
+            ..pair_order(id, AssetId::USD, receive, 2)
+        };
+        let orders = vec![order("buy_jpy", AssetId::JPY), order("buy_eur", AssetId::EUR)];
+        let inst = EpochInstance::new(1, inventory, orders, ref_prices, risk);
+        assert!(inst.is_depleted(AssetId::JPY));
+
+        for clearing in [ScpClearing::with_simple_solver(), ScpClearing::new()] {
+            let solution = clearing.clear_epoch(&inst).unwrap();
+
+            let jpy = &solution.fills[1];
+            assert_eq!(jpy.fill_frac, 0.0);
+            assert_eq!(jpy.recv_units, 0.0);
+            assert_eq!(solution.q_post[&AssetId::JPY], 0.0);
+
+            // Assets with liquidity still fill
+            assert!(solution.fills[0].fill_frac > 0.99);
+        }
+    }
+
+    #[test]
+    fn test_fill_capped_at_headroom_above_q_min() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        // 1 EUR buys ~110 JPY, far more than the 5 JPY above q_min
+        let order = pair_order("buy_jpy", AssetId::EUR, AssetId::JPY, 1);
+        let inst = EpochInstance::new(1, inventory, vec![order], ref_prices, risk.clone());
+        assert!(!inst.is_depleted(AssetId::JPY));
+
+        for clearing in [ScpClearing::with_simple_solver(), ScpClearing::new()] {
+            let solution = clearing.clear_epoch(&inst).unwrap();
+
+            let fill = &solution.fills[0];
+            assert!(fill.fill_frac > 0.0 && fill.fill_frac < 0.1, "fill_frac = {}", fill.fill_frac);
+            assert!(fill.recv_units <= 5.0 + 1e-9, "recv {} JPY", fill.recv_units);
+            assert!(solution.q_post[&AssetId::JPY] >= risk.min_bound(AssetId::JPY) - 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_min_fill_rejects_partial_fill() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        let order = |id: &str, budget: i64, min_fill_fraction: Option<f64>| PairOrder {
+            trader: AccountId::new(id),
+            min_fill_fraction,
+            ..pair_order(id, AssetId::USD, AssetId::EUR, budget)
+        };
+
+        // Hard inventory bounds cap how much EUR the pool can pay out
+        let params = ScpParams {
+            enforce_inventory_bounds: true,
+            ..ScpParams::default()
+        };
+        let clearing = ScpClearing::with_backend(
+            Arc::new(convexfx_solver::ClarabelSolver::new()),
+            params,
+        );
+
+        // Without a minimum the whale is only partially filled
+        let inst = EpochInstance::new(
+            1,
+            inventory.clone(),
+            vec![order("whale", 40, None), order("small", 1, None)],
+            ref_prices.clone(),
+            risk.clone(),
+        );
+        let partial = clearing.clear_epoch(&inst).unwrap();
+        let whale_frac = partial.fills[1].fill_frac;
+        assert!(whale_frac > 0.0 && whale_frac < 0.99, "expected partial fill, got {}", whale_frac);
+
+        // With min fill 0.99 the whale is rejected outright
+        let inst = EpochInstance::new(
+            1,
+            inventory.clone(),
+            vec![order("whale", 40, Some(0.99)), order("small", 1, None)],
+            ref_prices.clone(),
+            risk.clone(),
+        );
+        let solution = clearing.clear_epoch(&inst).unwrap();
+        assert_eq!(solution.fills.len(), 2);
+        assert_eq!(solution.fills[1].order_id, "whale");
+        assert_eq!(solution.fills[1].fill_frac, 0.0);
+        assert_eq!(solution.fills[1].pay_units, 0.0);
+
+        // The result matches clearing without the whale at all
+        let without = EpochInstance::new(1, inventory, vec![order("small", 1, None)], ref_prices, risk);
+        let expected = clearing.clear_epoch(&without).unwrap();
+        assert!((solution.fills[0].fill_frac - expected.fills[0].fill_frac).abs() < 1e-9);
+        for (asset, q) in &expected.q_post {
+            assert!((solution.q_post[asset] - q).abs() < 1e-9, "{} inventory differs", asset);
+        }
+    }
+
+    #[test]
+    fn test_trader_position_limit_caps_total_fill() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        // Alice splits her EUR purchase across three orders; Bob places one
+        let order = |id: &str, trader: &str| PairOrder {
+            trader: AccountId::new(trader),
+            ..pair_order(id, AssetId::USD, AssetId::EUR, 2)
+        };
+        let orders = vec![
+            order("alice_1", "alice"),
+            order("alice_2", "alice"),
+            order("alice_3", "alice"),
+            order("bob_1", "bob"),
+        ];
+
+        let eur_received = |solution: &crate::EpochSolution, trader: &str| {
+            solution
+                .fills
+                .iter()
+                .filter(|fill| fill.order_id.starts_with(trader))
+                .map(|fill| fill.recv_units)
+                .sum::<f64>()
+        };
+
+        let clearing = ScpClearing::new();
+
+        let unlimited = EpochInstance::new(
+            1,
+            inventory.clone(),
+            orders.clone(),
+            ref_prices.clone(),
+            RiskParams::default_demo(),
+        );
+        let solution = clearing.clear_epoch(&unlimited).unwrap();
+        assert!(eur_received(&solution, "alice") > 3.0);
+
+        let mut risk = RiskParams::default_demo();
+        risk.trader_position_limits.insert(AssetId::EUR, 3.0);
+        let limited = EpochInstance::new(1, inventory, orders, ref_prices, risk);
+        let solution = clearing.clear_epoch(&limited).unwrap();
+
+        let alice = eur_received(&solution, "alice");
+        let bob = eur_received(&solution, "bob");
+        assert!(alice <= 3.0 + 1e-4, "alice received {} EUR", alice);
+        assert!(alice > 2.9, "limit should bind rather than block: {}", alice);
+        assert!(bob > 1.5, "bob is within his limit: {}", bob);
+    }
+
+    #[test]
+    fn test_numeraire_pay_and_receive_symmetric() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+        inventory.insert(AssetId::JPY, 1000.0);
+
+        // USDJPY = 100, so 1 USD and 100 JPY carry the same notional
+        let orders = vec![
+            pair_order("usd_to_jpy", AssetId::USD, AssetId::JPY, 1),
+            PairOrder {
+                trader: AccountId::new("trader2"),
+                ..pair_order("jpy_to_usd", AssetId::JPY, AssetId::USD, 100)
+            },
+        ];
+
+        let inst = EpochInstance::new(1, inventory, orders, ref_prices, risk);
+
+        let clearing = ScpClearing::with_simple_solver();
+        let solution = clearing.clear_epoch(&inst).unwrap();
+
+        let rate = |id: &str| {
+            let fill = solution.fills.iter().find(|f| f.order_id == id).unwrap();
+            assert!(fill.pay_units > 0.0, "{} should fill", id);
+            fill.recv_units / fill.pay_units
+        };
+        let usd_jpy = rate("usd_to_jpy");
+        let jpy_usd = rate("jpy_to_usd");
+
+        // Each side executes at the rate implied by the cleared log-prices,
+        // which stays within the price band of the oracle's USDJPY of 100
+        let y = &solution.y_star;
+        let cleared_usd_jpy = (y[&AssetId::USD] - y[&AssetId::JPY]).exp();
+        let cleared_jpy_usd = (y[&AssetId::JPY] - y[&AssetId::USD]).exp();
+        assert!((usd_jpy / cleared_usd_jpy - 1.0).abs() < 1e-9, "USDJPY {} vs {}", usd_jpy, cleared_usd_jpy);
+        assert!((jpy_usd / cleared_jpy_usd - 1.0).abs() < 1e-9, "JPYUSD {} vs {}", jpy_usd, cleared_jpy_usd);
+
+        let band = inst.risk.price_band_bps / 10_000.0;
+        let oracle_usd_jpy = inst.ref_prices.mid_rate(AssetId::USD, AssetId::JPY);
+        assert!((oracle_usd_jpy - 100.0).abs() < 1e-6);
+        assert!((usd_jpy / oracle_usd_jpy).ln().abs() <= band + 1e-9, "USDJPY {} outside band", usd_jpy);
+        assert!((jpy_usd * oracle_usd_jpy).ln().abs() <= band + 1e-9, "JPYUSD {} outside band", jpy_usd);
+    }
+
+    #[test]
+    fn test_rounded_prices_remain_coherent() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        let order = pair_order("order1", AssetId::EUR, AssetId::JPY, 3);
+
+        let inst = EpochInstance::new(1, inventory, vec![order], ref_prices, risk);
+
+        let params = ScpParams {
+            price_sig_figs: Some(6),
+            ..ScpParams::default()
+        };
+        let clearing = ScpClearing::with_backend(
+            Arc::new(convexfx_solver::SimpleQpSolver::new()),
+            params,
+        );
+        let solution = clearing.clear_epoch(&inst).unwrap();
+
+        assert_eq!(solution.prices[&AssetId::USD], 1.0);
+        assert_eq!(solution.y_star[&AssetId::USD], 0.0);
+        for (asset, price) in &solution.prices {
+            // Six significant figures: scaling to [1e5, 1e6) yields an integer
+            let scaled = price * 10f64.powi(5 - price.log10().floor() as i32);
+            assert!((scaled - scaled.round()).abs() < 1e-6, "{} not rounded: {}", asset, price);
+            assert!((solution.y_star[asset] - price.ln()).abs() < 1e-12);
+        }
+
+        assert!(
+            solution.coherence_error_bps() <= 0.01,
+            "coherence error {} bps",
+            solution.coherence_error_bps()
+        );
+
+        // Fills settle at the published rates and inventory still balances
+        let fill = &solution.fills[0];
+        assert!(fill.fill_frac > 0.0);
+        let rate = solution.prices[&AssetId::EUR] / solution.prices[&AssetId::JPY];
+        assert!((fill.recv_units - fill.pay_units * rate).abs() < 1e-9);
+        for (asset, q) in &solution.q_post {
+            let delta: f64 = solution
+                .fills
+                .iter()
+                .flat_map(Fill::inventory_deltas)
+                .filter(|(a, _)| a == asset)
+                .map(|(_, d)| d)
+                .sum();
+            assert!((q - inst.inventory_q[asset] - delta).abs() < 1e-9);
+        }
+
+        // One significant figure moves cross rates by hundreds of bps
+        let params = ScpParams {
+            price_sig_figs: Some(1),
+            ..ScpParams::default()
+        };
+        let coarse = ScpClearing::with_backend(
+            Arc::new(convexfx_solver::SimpleQpSolver::new()),
+            params,
+        );
+        let err = coarse.clear_epoch(&inst).unwrap_err();
+        assert!(matches!(err, ConvexFxError::SolverError(ref msg) if msg.contains("coherence")), "{}", err);
+    }
+
+    #[test]
+    fn test_clearing_fingerprint_stable() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        let orders = vec![
+            pair_order("order1", AssetId::USD, AssetId::EUR, 2),
+            PairOrder {
+                trader: AccountId::new("trader2"),
+                ..pair_order("order2", AssetId::GBP, AssetId::JPY, 1)
+            },
+        ];
+
+        let inst = EpochInstance::new(1, inventory, orders, ref_prices, risk);
+
+        // Round-trip through serialization as a replayed instance would
+        let mut replayed: EpochInstance =
+            serde_json::from_str(&serde_json::to_string(&inst).unwrap()).unwrap();
+        // Dense risk matrices are not serialized; rebuild them from the diagonals
+        replayed.risk.rebuild_matrices().unwrap();
+
+        let clearing = ScpClearing::with_simple_solver();
+        let fingerprint = inst.clearing_fingerprint(&clearing).unwrap();
+        assert_eq!(fingerprint.len(), 64);
+        for _ in 0..3 {
+            assert_eq!(inst.clearing_fingerprint(&clearing).unwrap(), fingerprint);
+        }
+        assert_eq!(replayed.clearing_fingerprint(&clearing).unwrap(), fingerprint);
+
+        // A different instance yields a different fingerprint
+        let mut other = inst.clone();
+        other.orders.pop();
+        assert_ne!(other.clearing_fingerprint(&clearing).unwrap(), fingerprint);
+    }
+
+    #[test]
+    fn test_reject_high_slippage_fill() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        // Opposite sides of the same pair: whatever way the price moves, one
+        // side trades worse than mid and the other better
+        let orders = vec![
+            pair_order("eur_buy", AssetId::USD, AssetId::EUR, 5),
+            PairOrder {
+                trader: AccountId::new("trader2"),
+                ..pair_order("eur_sell", AssetId::EUR, AssetId::USD, 1)
+            },
+        ];
+
+        let inst = EpochInstance::new(1, inventory.clone(), orders, ref_prices, risk);
+
+        let clearing = ScpClearing::with_simple_solver();
+        let mut solution = clearing.clear_epoch(&inst).unwrap();
+        assert!(solution.fills.iter().all(|f| f.fill_frac > 0.0));
+
+        // Fees credited to the pool must follow their fill
+        for fill in solution.fills.iter_mut() {
+            fill.fees_paid.insert(fill.pay_asset, 0.01);
+        }
+
+        let mut by_slippage = solution.fills.clone();
+        by_slippage.sort_by(|a, b| {
+            solution
+                .fill_slippage_bps(&inst, a)
+                .partial_cmp(&solution.fill_slippage_bps(&inst, b))
+                .unwrap()
+        });
+        let kept = by_slippage[0].clone();
+        let bad = by_slippage[1].clone();
+        let bad_slippage = solution.fill_slippage_bps(&inst, &bad);
+        assert!(bad_slippage > 1.0, "expected an adverse fill, got {} bps", bad_slippage);
+
+        let rejected = solution.reject_slippage_above(&inst, bad_slippage / 2.0);
+        assert_eq!(rejected, vec![bad.order_id.clone()]);
+
+        for fill in &solution.fills {
+            if fill.order_id == bad.order_id {
+                assert_eq!(fill.fill_frac, 0.0);
+                assert_eq!(fill.pay_units, 0.0);
+                assert_eq!(fill.recv_units, 0.0);
+                assert!(fill.fees_paid.is_empty());
+            } else {
+                assert_eq!(fill.pay_units, kept.pay_units);
+                assert_eq!(fill.recv_units, kept.recv_units);
+            }
+        }
+
+        // Inventory conserved against the remaining fill and its fee only
+        let mut expected = inventory.clone();
+        *expected.get_mut(&kept.pay_asset).unwrap() += kept.pay_units + 0.01;
+        *expected.get_mut(&kept.recv_asset).unwrap() -= kept.recv_units;
+        for asset in AssetId::all() {
+            assert!((solution.q_post[asset] - expected[asset]).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_mixed_pair_and_basket_clearing() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        let orders = vec![
+            Order::Pair(pair_order("pair", AssetId::USD, AssetId::EUR, 2)),
+            Order::Basket(BasketOrder {
+                id: "basket".to_string(),
+                trader: AccountId::new("trader2"),
+                pay: AssetId::USD,
+                budget: Amount::from_units(3),
+                basket_weights: [(AssetId::EUR, 0.5), (AssetId::GBP, 0.3), (AssetId::JPY, 0.2)]
+                    .into_iter()
+                    .collect(),
+                min_fill_fraction: None,
+                metadata: serde_json::json!({}),
+            }),
+        ];
+
+        let inst = EpochInstance::from_orders(1, inventory.clone(), orders, ref_prices, risk);
+        assert_eq!(inst.orders.len(), 1);
+        assert_eq!(inst.baskets.len(), 1);
+        assert_eq!(inst.num_orders(), 2);
+
+        let clearing = ScpClearing::with_simple_solver();
+        let solution = clearing.clear_epoch(&inst).unwrap();
+
+        // One fill for the pair order, one per basket leg
+        assert_eq!(solution.fills.len(), 4);
+        let pair_fill = solution.fills.iter().find(|f| f.order_id == "pair").unwrap();
+        assert!(pair_fill.fill_frac > 0.0);
+
+        let basket_fills: Vec<_> = solution.fills.iter().filter(|f| f.order_id == "basket").collect();
+        assert_eq!(basket_fills.len(), 3);
+        let basket_frac = basket_fills[0].fill_frac;
+        assert!(basket_frac > 0.0);
+        assert!(basket_fills.iter().all(|f| f.fill_frac == basket_frac && f.pay_asset == AssetId::USD));
+
+        // Legs split the paid amount by weight
+        let basket_paid: f64 = basket_fills.iter().map(|f| f.pay_units).sum();
+        assert!((basket_paid - basket_frac * 3.0).abs() < 1e-9);
+        let eur_leg = basket_fills.iter().find(|f| f.recv_asset == AssetId::EUR).unwrap();
+        assert!((eur_leg.pay_units - basket_paid * 0.5).abs() < 1e-9);
+
+        // Inventory conserved across every leg of both orders
+        let mut expected = inventory.clone();
+        for fill in &solution.fills {
+            *expected.get_mut(&fill.pay_asset).unwrap() += fill.pay_units;
+            *expected.get_mut(&fill.recv_asset).unwrap() -= fill.recv_units;
+        }
+        for asset in AssetId::all() {
+            assert!((solution.q_post[asset] - expected[asset]).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_basket_over_max_legs_rejected() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let mut risk = RiskParams::default_demo();
+        risk.max_basket_legs = Some(2);
+
+        let inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+        let basket = BasketOrder {
+            id: "basket".to_string(),
+            trader: AccountId::new("trader1"),
+            pay: AssetId::USD,
+            budget: Amount::from_units(3),
+            basket_weights: [(AssetId::EUR, 0.5), (AssetId::GBP, 0.3), (AssetId::JPY, 0.2)]
+                .into_iter()
+                .collect(),
+            min_fill_fraction: None,
+            metadata: serde_json::json!({}),
+        };
+
+        let inst = EpochInstance::from_orders(1, inventory.clone(), vec![Order::Basket(basket.clone())], ref_prices.clone(), risk);
+        let err = ScpClearing::with_simple_solver().clear_epoch(&inst).unwrap_err();
+        assert!(matches!(err, ConvexFxError::InvalidOrder(_)));
+        assert!(err.to_string().contains("3 legs, maximum is 2"));
+
+        // Unnormalized weights are rejected as well
+        let mut unnormalized = basket;
+        unnormalized.basket_weights.insert(AssetId::EUR, 5.0);
+        let inst = EpochInstance::from_orders(1, inventory, vec![Order::Basket(unnormalized)], ref_prices, RiskParams::default_demo());
+        assert!(matches!(inst.validate(), Err(ConvexFxError::InvalidOrder(_))));
+    }
+
+    #[test]
+    fn test_percentile_is_nearest_rank() {
+        assert_eq!(crate::percentile(&[], 0.9), 0.0);
+        assert_eq!(crate::percentile(&[f64::NAN, f64::INFINITY], 0.5), 0.0);
+
+        let values = [3.0, f64::NAN, 1.0, 2.0];
+        assert_eq!(crate::percentile(&values, 0.0), 1.0);
+        assert_eq!(crate::percentile(&values, 0.5), 2.0);
+        assert_eq!(crate::percentile(&values, 0.99), 3.0);
+
+        // The p90 of ten values is the ninth, not the maximum
+        let ten: Vec<f64> = (1..=10).map(f64::from).collect();
+        assert_eq!(crate::percentile(&ten, 0.9), 9.0);
+        assert_eq!(crate::percentile(&ten, 1.0), 10.0);
+    }
+
+    fn validation_test_solution() -> (EpochSolution, BTreeMap<AssetId, f64>, RefPrices) {
+        let mut y_star = BTreeMap::new();
+        let mut prices = BTreeMap::new();
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            let y: f64 = if *asset == AssetId::USD { 0.0 } else { 0.1 };
+            y_star.insert(*asset, y);
+            prices.insert(*asset, y.exp());
+            inventory.insert(*asset, 10000.0);
+        }
+
+        // USD -> EUR fill with a 3 USD fee credited to the pool
+        let mut fees_paid = BTreeMap::new();
+        fees_paid.insert(AssetId::USD, 3.0);
+        let fill = Fill {
+            order_id: "test1".to_string(),
+            fill_frac: 1.0,
+            pay_asset: AssetId::USD,
+            recv_asset: AssetId::EUR,
+            pay_units: 1000.0,
+            recv_units: 860.0,
+            fees_paid,
+        };
+        let mut q_post = inventory.clone();
+        q_post.insert(AssetId::USD, 11003.0);
+        q_post.insert(AssetId::EUR, 9140.0);
+
+        let ref_prices = RefPrices::new(y_star.clone(), 20.0, 0, vec!["test".to_string()]);
+        let solution = EpochSolution {
+            epoch_id: 1,
+            y_star,
+            prices,
+            q_post,
+            fills: vec![fill],
+            objective_terms: ObjectiveTerms {
+                inventory_risk: 100.0,
+                price_tracking: 50.0,
+                fill_incentive: -20.0,
+                total: 130.0,
+                weights: ObjectiveWeights::default(),
+            },
+            diagnostics: Diagnostics {
+                iterations: 3,
+                convergence_achieved: true,
+                final_step_norm_y: 1e-6,
+                final_step_norm_alpha: 1e-7,
+                qp_status: QpStatus::Optimal,
+                solve_time_ms: 0,
+                iteration_times_us: Vec::new(),
+                binding_constraints: Vec::new(),
+                objective_trajectory: Vec::new(),
+                max_coherence_error_bps: 0.0,
+                qp_backend: "clarabel".to_string(),
+            },
+            numeraire: AssetId::USD,
+        };
+        (solution, inventory, ref_prices)
+    }
+
+    #[test]
+    fn test_validate_accepts_consistent_solution() {
+        let (solution, inventory, ref_prices) = validation_test_solution();
+        let tol = ValidationTolerances::default();
+
+        assert!(solution.validate(&inventory, &ref_prices, &tol).is_ok());
+    }
+
+    #[test]
+    fn test_validate_convergence() {
+        let (mut solution, inventory, ref_prices) = validation_test_solution();
+        let tol = ValidationTolerances::default();
+
+        solution.diagnostics.final_step_norm_y = 2e-4;
+        let err = solution.validate(&inventory, &ref_prices, &tol).unwrap_err();
+        assert!(err.to_string().contains("Price step norm"));
+
+        solution.diagnostics.convergence_achieved = false;
+        let err = solution.validate_convergence(&tol).unwrap_err();
+        assert!(err.to_string().contains("did not converge"));
+    }
+
+    #[test]
+    fn test_validate_prices() {
+        let (mut solution, _, ref_prices) = validation_test_solution();
+        let tol = ValidationTolerances::default();
+
+        // Linear price drifting from exp(y*)
+        solution.prices.insert(AssetId::EUR, 2.0);
+        assert!(solution.validate_prices(&ref_prices, &tol).is_err());
+
+        // Consistent but off-numeraire USD
+        solution.prices.insert(AssetId::EUR, 0.1_f64.exp());
+        solution.y_star.insert(AssetId::USD, 0.1);
+        solution.prices.insert(AssetId::USD, 0.1_f64.exp());
+        let err = solution.validate_prices(&ref_prices, &tol).unwrap_err();
+        assert!(err.to_string().contains("USD numeraire constraint violated"));
+
+        // Reference asset with no cleared price
+        let (mut solution, _, _) = validation_test_solution();
+        solution.y_star.remove(&AssetId::JPY);
+        solution.prices.remove(&AssetId::JPY);
+        assert!(solution.validate_prices(&ref_prices, &tol).is_err());
+    }
+
+    #[test]
+    fn test_validate_fill_fraction_range() {
+        let (mut solution, _, _) = validation_test_solution();
+        solution.fills[0].fill_frac = 1.5;
+
+        let err = solution.validate_fills().unwrap_err();
+        assert!(err.to_string().contains("Invalid fill fraction"));
+    }
+
+    #[test]
+    fn test_validate_inventory_counts_fees() {
+        let (mut solution, inventory, _) = validation_test_solution();
+        let tol = ValidationTolerances::default();
+        assert!(solution.validate_inventory(&inventory, &tol).is_ok());
+
+        // Inventory that drops the fee no longer balances
+        solution.q_post.insert(AssetId::USD, 11000.0);
+        let err = solution.validate_inventory(&inventory, &tol).unwrap_err();
+        assert!(err.to_string().contains("USD"));
+    }
+
+    #[test]
+    fn test_validate_objective_sum() {
+        let (mut solution, _, _) = validation_test_solution();
+        let tol = ValidationTolerances::default();
+        assert!(solution.validate_objective(&tol).is_ok());
+
+        solution.objective_terms.total = 131.0;
+        let err = solution.validate_objective(&tol).unwrap_err();
+        assert!(err.to_string().contains("don't sum correctly"));
+    }
+
+    #[test]
+    fn test_infeasible_instance_returns_typed_error() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+        let order = pair_order("order1", AssetId::USD, AssetId::EUR, 1);
+
+        // EUR must stay at or above 12 and at or below 8: no inventory, let
+        // alone the pool's 10, satisfies both
+        let mut risk = RiskParams::default_demo();
+        risk.q_min.insert(AssetId::EUR, 12.0);
+        risk.q_max.insert(AssetId::EUR, 8.0);
+        let inst = EpochInstance::new(1, inventory, vec![order], ref_prices, risk);
+
+        let clearing = ScpClearing::with_clarabel().with_params(ScpParams {
+            enforce_inventory_bounds: true,
+            ..ScpParams::default()
+        });
+        let err = clearing.clear_epoch(&inst).unwrap_err();
+        assert!(matches!(err, ConvexFxError::Infeasible(_)), "got {:?}", err);
+        assert!(err.to_string().contains("EUR"), "{}", err);
+
+        // With satisfiable bounds the same epoch clears with a typed status
+        let mut feasible = inst.clone();
+        feasible.risk = RiskParams::default_demo();
+        let solution = clearing.clear_epoch(&feasible).unwrap();
+        assert_eq!(solution.diagnostics.qp_status, QpStatus::Optimal);
+    }
+
+    #[test]
+    fn test_conflicting_limit_orders_zero_fill_one_order() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+
+        // A EUR buyer and a EUR seller whose limits each sit 10 bps through
+        // the mid: either is attainable inside the band, but together they
+        // need the EUR price both below and above the mid
+        let mid = ref_prices.get_ref(AssetId::EUR).exp();
+        let through = 1.0 - 10.0 / 10_000.0;
+        let order = |id: &str, pay: AssetId, receive: AssetId, limit_ratio: Option<f64>| PairOrder {
+            trader: AccountId::new(id),
+            limit_ratio,
+            ..pair_order(id, pay, receive, 1)
+        };
+        let orders = vec![
+            order("buyer", AssetId::USD, AssetId::EUR, Some(mid * through)),
+            order("seller", AssetId::EUR, AssetId::USD, Some(through / mid)),
+            order("market", AssetId::USD, AssetId::GBP, None),
+        ];
+        let inst = EpochInstance::new(1, inventory, orders, ref_prices, RiskParams::default_demo());
+
+        // The limits exclude each other, so the QP with both is infeasible
+        let model = crate::qp_builder::QpBuilder::build_qp_with_bands(&inst, &inst.ref_prices.y_ref, 20.0).unwrap();
+        let qp = convexfx_solver::ClarabelSolver::new();
+        assert_eq!(convexfx_solver::SolverBackend::solve_qp(&qp, &model).unwrap().status, QpStatus::PrimalInfeasible);
+
+        // Clearing drops one of them instead of failing the batch
+        let solution = ScpClearing::with_clarabel().clear_epoch(&inst).unwrap();
+        let frac = |id: &str| solution.fills.iter().find(|f| f.order_id == id).unwrap().fill_frac;
+        let (buyer, seller) = (frac("buyer"), frac("seller"));
+        assert!(buyer.min(seller) == 0.0 && buyer.max(seller) > 0.5, "buyer {} seller {}", buyer, seller);
+        assert!(frac("market") > 0.5, "market order fill {}", frac("market"));
+        solution.validate(&inst.inventory_q, &inst.ref_prices, &ValidationTolerances::default()).unwrap();
+
+        // Which order is dropped does not depend on submission order
+        let mut reversed = inst.clone();
+        reversed.orders.reverse();
+        let again = ScpClearing::with_clarabel().clear_epoch(&reversed).unwrap();
+        for fill in &solution.fills {
+            let other = again.fills.iter().find(|f| f.order_id == fill.order_id).unwrap();
+            assert!((fill.fill_frac - other.fill_frac).abs() < 1e-6, "{} differs", fill.order_id);
+        }
+    }
+
+    #[test]
+    fn test_conflicting_limits_spare_unrelated_limit_order() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+
+        let order = |id: &str, pay: AssetId, receive: AssetId, limit_ratio: f64| PairOrder {
+            trader: AccountId::new(id),
+            limit_ratio: Some(limit_ratio),
+            ..pair_order(id, pay, receive, 1)
+        };
+        // The EUR buyer and seller exclude each other 3 bps through the mid;
+        // the GBP buyer sits further through its own mid but conflicts with
+        // nothing, so it must not be the order dropped
+        let eur = ref_prices.get_ref(AssetId::EUR).exp();
+        let gbp = ref_prices.get_ref(AssetId::GBP).exp();
+        let through = |bps: f64| 1.0 - bps / 10_000.0;
+        let orders = vec![
+            order("eur_buyer", AssetId::USD, AssetId::EUR, eur * through(3.0)),
+            order("eur_seller", AssetId::EUR, AssetId::USD, through(3.0) / eur),
+            order("gbp_buyer", AssetId::USD, AssetId::GBP, gbp * through(8.0)),
+        ];
+        let inst = EpochInstance::new(1, inventory, orders, ref_prices, RiskParams::default_demo());
+
+        let solution = ScpClearing::with_clarabel().clear_epoch(&inst).unwrap();
+        let frac = |id: &str| solution.fills.iter().find(|f| f.order_id == id).unwrap().fill_frac;
+        let (buyer, seller) = (frac("eur_buyer"), frac("eur_seller"));
+        assert!(buyer.min(seller) == 0.0 && buyer.max(seller) > 0.5, "buyer {} seller {}", buyer, seller);
+        assert!(frac("gbp_buyer") > 0.5, "GBP limit order fill {}", frac("gbp_buyer"));
+    }
+
+    #[test]
+    fn test_non_finite_ref_prices_are_rejected() {
+        let risk = RiskParams::default_demo();
+        let inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+        let order = pair_order("order1", AssetId::USD, AssetId::EUR, 100);
+
+        for bad in [f64::NAN, f64::INFINITY] {
+            let mut y_ref = MockOracle::new().reference_prices(1).unwrap().y_ref;
+            y_ref.insert(AssetId::EUR, bad);
+            let ref_prices = RefPrices::new(y_ref, 20.0, 0, vec!["feed".to_string()]);
+            assert!(matches!(ref_prices.validate(), Err(ConvexFxError::InvalidPrice(_))));
+
+            let err = EpochInstance::try_new(1, inventory.clone(), vec![order.clone()], ref_prices.clone(), risk.clone())
+                .unwrap_err();
+            assert!(matches!(err, ConvexFxError::InvalidPrice(ref msg) if msg.contains("EUR")), "{}", err);
+
+            // Instances built without the check still fail cleanly at clearing
+            let inst = EpochInstance::new(1, inventory.clone(), vec![order.clone()], ref_prices, risk.clone());
+            let err = ScpClearing::with_clarabel().clear_epoch(&inst).unwrap_err();
+            assert!(matches!(err, ConvexFxError::InvalidPrice(_)), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_bound_projection_converges_faster_than_rejection() {
+        let ref_prices = MockOracle::new().reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        // USD sits just under its max, and the flow would push it well over
+        let mut inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+        inventory.insert(AssetId::USD, 14.5);
+        let orders: Vec<PairOrder> = (0..3)
+            .map(|i| PairOrder {
+                trader: AccountId::new(format!("trader{}", i)),
+                ..pair_order(&format!("usd_eur_{}", i), AssetId::USD, AssetId::EUR, 1)
+            })
+            .collect();
+        let inst = EpochInstance::new(1, inventory, orders, ref_prices, risk);
+        let usd_max = inst.risk.soft_max_bound(AssetId::USD);
+
+        let clear = |bound_repair| {
+            let params = ScpParams { bound_repair, ..ScpParams::default() };
+            ScpClearing::with_backend(Arc::new(convexfx_solver::ClarabelSolver::new()), params)
+                .clear_epoch(&inst)
+                .unwrap()
+        };
+        // The full step overshoots the bound
+        let full = clear(BoundRepair::None);
+        assert!(full.q_post[&AssetId::USD] > usd_max + 1.0);
+
+        // Rejection can only shrink the step, so it crawls toward the bound
+        let rejected = clear(BoundRepair::Reject);
+        // Projection lands on the bound directly
+        let projected = clear(BoundRepair::Project);
+
+        assert!(projected.diagnostics.convergence_achieved);
+        assert!(
+            projected.diagnostics.iterations < rejected.diagnostics.iterations,
+            "projection took {} iterations, rejection {}",
+            projected.diagnostics.iterations,
+            rejected.diagnostics.iterations
+        );
+        assert!((projected.q_post[&AssetId::USD] - usd_max).abs() < 1e-6);
+        assert!(projected.fills.iter().all(|f| f.fill_frac > 0.0));
+    }
+
+    #[test]
+    fn test_incoherent_prices_fail_the_self_check() {
+        let ref_prices = MockOracle::new().reference_prices(1).unwrap();
+        let inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+        let order = pair_order("order1", AssetId::USD, AssetId::EUR, 1);
+        let inst = EpochInstance::new(1, inventory, vec![order], ref_prices, RiskParams::default_demo());
+
+        // A healthy solve passes and reports its (negligible) error
+        let clearing = ScpClearing::with_clarabel();
+        let mut solution = clearing.clear_epoch(&inst).unwrap();
+        assert!(solution.diagnostics.max_coherence_error_bps < 1e-6);
+
+        // Knock EUR's log price 5 bps away from the linear price
+        *solution.y_star.get_mut(&AssetId::EUR).unwrap() += 5e-4;
+        let err = clearing.check_coherence(&mut solution).unwrap_err();
+        assert!(matches!(err, ConvexFxError::Infeasible(ref msg) if msg.contains("coherence")), "{}", err);
+        assert!((solution.diagnostics.max_coherence_error_bps - 5.0).abs() < 0.01);
+
+        // A looser threshold lets the same solution through
+        let params = ScpParams { max_coherence_error_bps: 10.0, ..ScpParams::default() };
+        let lenient = ScpClearing::with_clarabel().with_params(params);
+        assert!(lenient.check_coherence(&mut solution).is_ok());
+    }
+
+    #[test]
+    fn test_pro_rata_fairness_splits_scarce_inventory() {
+        let ref_prices = MockOracle::new().reference_prices(1).unwrap();
+
+        // EUR sits 1 unit above its minimum; two identical orders want 2 each
+        let mut inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+        inventory.insert(AssetId::EUR, 6.0);
+        let order = |id: &str| PairOrder {
+            trader: AccountId::new(id),
+            ..pair_order(id, AssetId::USD, AssetId::EUR, 2)
+        };
+        let inst = EpochInstance::new(
+            1,
+            inventory,
+            vec![order("a"), order("b")],
+            ref_prices,
+            RiskParams::default_demo(),
+        );
+        let params = ScpParams { enforce_inventory_bounds: true, ..ScpParams::default() };
+        let clearing = ScpClearing::with_simple_solver().with_params(params);
+        let fracs = |inst: &EpochInstance| {
+            let solution = clearing.clear_epoch(inst).unwrap();
+            assert!((solution.q_post[&AssetId::EUR] - 5.0).abs() < 1e-3);
+            (solution.fills[0].fill_frac, solution.fills[1].fill_frac)
+        };
+
+        // Without fairness the linear fill incentive lets one order take the bulk
+        let (a, b) = fracs(&inst);
+        assert!((a - b).abs() > 0.1, "expected an uneven split, got {} vs {}", a, b);
+
+        // With it both get the same partial fill and the same EUR is paid out
+        let (fair_a, fair_b) = fracs(&inst.clone().with_pro_rata_fairness(1.0));
+        assert!((fair_a - fair_b).abs() < 1e-3, "expected near-equal fills, got {} vs {}", fair_a, fair_b);
+        assert!(fair_a > 0.1 && fair_a < 0.9);
+        assert!((fair_a + fair_b - (a + b)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_repair_gamma_opt_in_clears_non_psd_instance() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+
+        // A correlation slightly above 1 between EUR and CHF
+        let mut risk = RiskParams::default_demo();
+        let (eur, chf) = (AssetId::EUR.index(), AssetId::CHF.index());
+        risk.gamma[(eur, chf)] = 0.11;
+        risk.gamma[(chf, eur)] = 0.11;
+        let inst = EpochInstance::new(1, inventory, Vec::new(), ref_prices, risk);
+
+        let err = ScpClearing::with_simple_solver().clear_epoch(&inst).unwrap_err();
+        assert!(matches!(err, ConvexFxError::ConfigError(_)));
+
+        let params = ScpParams { repair_gamma: true, ..ScpParams::default() };
+        let solution = ScpClearing::with_simple_solver().with_params(params).clear_epoch(&inst).unwrap();
+        assert!(solution.diagnostics.convergence_achieved);
+        assert!(!inst.risk.gamma_is_psd(), "the caller's instance is left untouched");
+    }
+
+    #[test]
+    fn test_validate_rejects_inconsistent_instances() {
+        let inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+        let order = pair_order("order1", AssetId::USD, AssetId::EUR, 1);
+        let valid = EpochInstance::new(
+            1,
+            inventory,
+            vec![order],
+            MockOracle::new().reference_prices(1).unwrap(),
+            RiskParams::default_demo(),
+        );
+        assert!(valid.validate().is_ok());
+
+        let expect_error = |inst: EpochInstance, needle: &str| {
+            let err = inst.validate().unwrap_err();
+            assert!(err.to_string().contains(needle), "expected '{}' in: {}", needle, err);
+            // clear_epoch refuses the instance with the same error
+            let cleared = ScpClearing::with_clarabel().clear_epoch(&inst).unwrap_err();
+            assert_eq!(cleared.to_string(), err.to_string());
+        };
+
+        // The order receives EUR, which has no reference price
+        let mut inst = valid.clone();
+        inst.ref_prices.y_ref.remove(&AssetId::EUR);
+        expect_error(inst, "no reference price");
+
+        // Inventory has no entry for JPY
+        let mut inst = valid.clone();
+        inst.inventory_q.remove(&AssetId::JPY);
+        expect_error(inst, "missing JPY");
+
+        // GBP's target sits below its minimum
+        let mut inst = valid.clone();
+        inst.risk.q_target.insert(AssetId::GBP, 4.0);
+        expect_error(inst, "q_min <= q_target <= q_max");
+
+        // CHF minimum above both its target and its maximum
+        let mut inst = valid.clone();
+        inst.risk.q_min.insert(AssetId::CHF, 20.0);
+        expect_error(inst, "CHF");
+
+        // Gamma with a negative eigenvalue
+        let mut inst = valid.clone();
+        inst.risk.gamma[(1, 1)] = -1.0;
+        expect_error(inst, "positive semidefinite");
+    }
+
+    #[test]
+    fn test_slippage_cap_zeroes_fill() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        let mid = ref_prices.mid_rate(AssetId::USD, AssetId::EUR);
+        let order = |max_slippage_bps: Option<f64>| PairOrder {
+            trader: AccountId::new("capped"),
+            max_slippage_bps,
+            submission_mid: max_slippage_bps.map(|_| mid),
+            ..pair_order("capped", AssetId::USD, AssetId::EUR, 5)
+        };
+
+        let clearing = ScpClearing::with_simple_solver();
+
+        // Uncapped, the order fills but moves EUR against itself
+        let inst = EpochInstance::new(1, inventory.clone(), vec![order(None)], ref_prices.clone(), risk.clone());
+        let uncapped = clearing.clear_epoch(&inst).unwrap();
+        assert!(uncapped.fills[0].fill_frac > 0.99);
+        let slippage = uncapped.fill_slippage_bps(&inst, &uncapped.fills[0]);
+        assert!(slippage > 0.0, "expected adverse slippage, got {}", slippage);
+
+        // A cap looser than the realised slippage leaves the fill alone
+        let inst = EpochInstance::new(1, inventory.clone(), vec![order(Some(slippage * 2.0))], ref_prices.clone(), risk.clone());
+        let loose = clearing.clear_epoch(&inst).unwrap();
+        assert!((loose.fills[0].fill_frac - uncapped.fills[0].fill_frac).abs() < 1e-9);
+
+        // A tighter cap zeroes the order
+        let inst = EpochInstance::new(1, inventory.clone(), vec![order(Some(slippage / 2.0))], ref_prices.clone(), risk.clone());
+        let capped = clearing.clear_epoch(&inst).unwrap();
+        assert_eq!(capped.fills.len(), 1);
+        assert_eq!(capped.fills[0].fill_frac, 0.0);
+        assert_eq!(capped.fills[0].recv_units, 0.0);
+        assert_eq!(capped.q_post[&AssetId::EUR], inventory[&AssetId::EUR]);
+
+        // The cap is measured against the mid at submission: the loose cap
+        // zeroes the order once the market has moved 1% since it was placed
+        let mut stale = order(Some(slippage * 2.0));
+        stale.submission_mid = Some(mid * 1.01);
+        let inst = EpochInstance::new(1, inventory.clone(), vec![stale], ref_prices.clone(), risk.clone());
+        let moved = clearing.clear_epoch(&inst).unwrap();
+        assert_eq!(moved.fills[0].fill_frac, 0.0);
+
+        // A cap without a recorded mid is refused
+        let mut unanchored = order(Some(slippage * 2.0));
+        unanchored.submission_mid = None;
+        let inst = EpochInstance::new(1, inventory, vec![unanchored], ref_prices, risk);
+        let err = clearing.clear_epoch(&inst).unwrap_err();
+        assert!(err.to_string().contains("no submission mid"), "{}", err);
+    }
+
+    #[test]
+    fn test_deterministic_solver_matches_clarabel_on_single_orders() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        let mut inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            inventory.insert(*asset, 10.0);
+        }
+
+        let order = |pay: AssetId, receive: AssetId, limit_ratio: Option<f64>| PairOrder {
+            limit_ratio,
+            ..pair_order("single", pay, receive, 1)
+        };
+        let eur_per_usd = (ref_prices.get_ref(AssetId::USD) - ref_prices.get_ref(AssetId::EUR)).exp();
+
+        let cases = vec![
+            order(AssetId::USD, AssetId::EUR, None),
+            order(AssetId::EUR, AssetId::GBP, None),
+            // Limit comfortably above the oracle rate fills in full
+            order(AssetId::USD, AssetId::EUR, Some(1.5 / eur_per_usd)),
+            // Limit far below the oracle rate cannot fill at all
+            order(AssetId::USD, AssetId::EUR, Some(0.5 / eur_per_usd)),
+        ];
+
+        let deterministic = ScpClearing::with_deterministic_solver();
+        let clarabel = ScpClearing::with_clarabel();
+        assert_eq!(deterministic.backend_name(), "deterministic");
+
+        for case in cases {
+            let inst = EpochInstance::new(1, inventory.clone(), vec![case.clone()], ref_prices.clone(), risk.clone());
+            let exact = deterministic.clear_epoch(&inst).unwrap();
+            let reference = clarabel.clear_epoch(&inst).unwrap();
+
+            let (e, r) = (&exact.fills[0], &reference.fills[0]);
+            assert!((e.fill_frac - r.fill_frac).abs() < 1e-4, "{:?}: {} vs {}", case.limit_ratio, e.fill_frac, r.fill_frac);
+            assert!((e.recv_units - r.recv_units).abs() < 1e-4);
+            for (asset, y) in &reference.y_star {
+                assert!((exact.y_star[asset] - y).abs() < 1e-4, "{} price differs", asset);
+            }
+
+            // The deterministic fill is exact: oracle prices and a 0 or 1 fill
+            assert!(e.fill_frac == 0.0 || e.fill_frac == 1.0);
+            for asset in AssetId::all() {
+                assert_eq!(exact.y_star[asset], ref_prices.get_ref(*asset));
+            }
+        }
+
+        // More than one order is outside the closed form
+        let inst = EpochInstance::new(
+            1,
+            inventory,
+            vec![order(AssetId::USD, AssetId::EUR, None), PairOrder { id: "second".to_string(), ..order(AssetId::EUR, AssetId::USD, None) }],
+            ref_prices,
+            risk,
+        );
+        assert!(matches!(deterministic.clear_epoch(&inst), Err(ConvexFxError::SolverError(_))));
+    }
+
+    #[test]
+    fn test_hysteresis_smooths_fills_near_inventory_bound() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+
+        let params = ScpParams {
+            enforce_inventory_bounds: true,
+            ..ScpParams::default()
+        };
+        let clearing = ScpClearing::with_backend(
+            Arc::new(convexfx_solver::ClarabelSolver::new()),
+            params,
+        );
+
+        // The same EUR buyer every epoch drains EUR toward q_min = 5
+        let run = |risk: RiskParams| -> Vec<f64> {
+            let mut inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+            (1..=10)
+                .map(|epoch| {
+                    let order = PairOrder {
+                        trader: AccountId::new("eur_buyer"),
+                        ..pair_order(&format!("buy{}", epoch), AssetId::USD, AssetId::EUR, 1)
+                    };
+                    let inst = EpochInstance::new(epoch, inventory.clone(), vec![order], ref_prices.clone(), risk.clone());
+                    let solution = clearing.clear_epoch(&inst).unwrap();
+                    assert!(solution.q_post[&AssetId::EUR] >= risk.min_bound(AssetId::EUR) - 1e-3);
+                    inventory = solution.q_post.clone();
+                    solution.fills[0].fill_frac
+                })
+                .collect()
+        };
+        let max_jump = |fills: &[f64]| fills.windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0, f64::max);
+
+        let hard = run(RiskParams::default_demo());
+        let damped = run(RiskParams::default_demo().with_hysteresis(0.5));
+
+        // Hard bounds fill in full until the edge, then cut off abruptly
+        assert!(hard[0] > 0.99);
+        assert!(max_jump(&hard) > 0.5, "hard-bound fills {:?}", hard);
+
+        // The band tapers fills as EUR approaches q_min
+        assert!(damped[0] > 0.99);
+        assert!(max_jump(&damped) < 0.3, "damped fills {:?}", damped);
+        assert!(damped.windows(2).all(|w| w[1] <= w[0] + 1e-6), "damped fills {:?}", damped);
+        assert!(damped[9] > 0.0 && damped[9] < 0.5);
+    }
+
+    #[test]
+    fn test_required_liquidity_estimate_reaches_target_fill() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let risk = RiskParams::default_demo();
+
+        // Two-way EUR/USD flow, slightly heavier on the EUR buy side
+        let order = |id: &str, pay: AssetId, receive: AssetId| PairOrder {
+            trader: AccountId::new(id),
+            ..pair_order(id, pay, receive, 2)
+        };
+        let flow = vec![
+            order("buy1", AssetId::USD, AssetId::EUR),
+            order("buy2", AssetId::USD, AssetId::EUR),
+            order("buy3", AssetId::USD, AssetId::EUR),
+            order("sell1", AssetId::EUR, AssetId::USD),
+            order("sell2", AssetId::EUR, AssetId::USD),
+        ];
+
+        let levels = crate::estimate_required_liquidity(&flow, 0.95, &risk, &ref_prices).unwrap();
+        assert!(levels[&AssetId::EUR] > 0.0 && levels[&AssetId::EUR] < risk.target(AssetId::EUR));
+
+        let clearing = ScpClearing::new().with_params(ScpParams {
+            enforce_inventory_bounds: true,
+            ..ScpParams::default()
+        });
+        let fill_rate = |scale: f64| {
+            let levels: BTreeMap<AssetId, f64> = levels.iter().map(|(a, q)| (*a, q * scale)).collect();
+            let inst = EpochInstance::new(
+                1,
+                levels.clone(),
+                flow.clone(),
+                ref_prices.clone(),
+                risk.clone().with_inventory_levels(&levels),
+            );
+            crate::liquidity::fill_rate(&flow, &clearing.clear_epoch(&inst).unwrap())
+        };
+
+        // The estimate clears the flow at the target, a much smaller pool does not
+        assert!(fill_rate(1.0) >= 0.95, "fill rate {}", fill_rate(1.0));
+        assert!(fill_rate(0.5) < 0.95, "fill rate {}", fill_rate(0.5));
+
+        assert!(crate::estimate_required_liquidity(&flow, 1.5, &risk, &ref_prices).is_err());
+    }
+
+    #[test]
+    fn test_low_confidence_asset_moves_further_from_ref() {
+        let oracle = MockOracle::new();
+        let mut confidence = BTreeMap::new();
+        confidence.insert(AssetId::EUR, 0.25);
+        confidence.insert(AssetId::GBP, 1.0);
+        let ref_prices = oracle.reference_prices(1).unwrap().with_confidence(confidence);
+        ref_prices.validate().unwrap();
+        assert!((ref_prices.band_widening(AssetId::EUR) - 4.0).abs() < 1e-12);
+        assert_eq!(ref_prices.band_widening(AssetId::CHF), 1.0);
+        assert!((ref_prices.get_high(AssetId::EUR) - ref_prices.get_ref(AssetId::EUR) - 0.008).abs() < 1e-12);
+
+        let risk = RiskParams::default_demo();
+        let inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+
+        // The same flow on EUR and GBP: buyers whose limits sit 60 bps below
+        // the oracle, beyond the 50 bps band but within EUR's widened one
+        let order = |id: &str, receive: AssetId| PairOrder {
+            trader: AccountId::new("buyer"),
+            limit_ratio: Some(ref_prices.get_ref(receive).exp() * 0.994),
+            ..pair_order(id, AssetId::USD, receive, 1)
+        };
+        let orders = vec![order("eur", AssetId::EUR), order("gbp", AssetId::GBP)];
+
+        let inst = EpochInstance::new(1, inventory, orders, ref_prices.clone(), risk);
+        let solution = ScpClearing::new().clear_epoch(&inst).unwrap();
+
+        let deviation_bps = |asset: AssetId| (solution.y_star[&asset] - ref_prices.get_ref(asset)).abs() * 10000.0;
+        let (eur, gbp) = (deviation_bps(AssetId::EUR), deviation_bps(AssetId::GBP));
+        assert!(gbp <= inst.risk.price_band_bps + 1e-6, "GBP moved {} bps", gbp);
+        assert!(eur > 55.0 && eur > gbp, "EUR moved {} bps, GBP {} bps", eur, gbp);
+
+        let fill = |id: &str| solution.fills.iter().find(|f| f.order_id == id).map_or(0.0, |f| f.fill_frac);
+        assert!(fill("eur") > 0.99 && fill("gbp") < 1e-6);
+    }
+
+    #[test]
+    fn test_simple_solver_fallback_after_clarabel_max_iterations() {
+        let oracle = MockOracle::new();
+        let ref_prices = oracle.reference_prices(1).unwrap();
+        let inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+        let order = pair_order("order1", AssetId::USD, AssetId::EUR, 1);
+        let inst = EpochInstance::new(1, inventory, vec![order], ref_prices, RiskParams::default_demo());
+
+        // One interior-point iteration is never enough to converge
+        let clearing = |fallback: bool| {
+            ScpClearing::with_backend(
+                Arc::new(convexfx_solver::ClarabelSolver::with_params(1, 1e-8)),
+                ScpParams { fallback_to_simple_solver: fallback, ..ScpParams::default() },
+            )
+        };
+
+        let stuck = clearing(false).clear_epoch(&inst).unwrap();
+        assert_eq!(stuck.diagnostics.qp_status, QpStatus::MaxIterations);
+        assert_eq!(stuck.diagnostics.qp_backend, "clarabel");
+
+        let solution = clearing(true).clear_epoch(&inst).unwrap();
+        assert_eq!(solution.diagnostics.qp_backend, "simple");
+        assert_ne!(solution.diagnostics.qp_status, QpStatus::MaxIterations);
+        solution.validate(&inst.inventory_q, &inst.ref_prices, &ValidationTolerances::default()).unwrap();
+        assert!(solution.fills[0].fill_frac > 0.5, "fill {}", solution.fills[0].fill_frac);
     }
 }
@@ -0,0 +1,134 @@
+use convexfx_local_laws::{self as local_laws, FillFlows, ObjectiveBreakdown, Tolerances, Violation};
+use convexfx_oracle::RefPrices;
+use convexfx_types::{AssetId, ConvexFxError, Result};
+use std::collections::BTreeMap;
+
+use crate::epoch_solution::EpochSolution;
+
+impl EpochSolution {
+    /// Check that the solution is a valid clearing of `initial_inventory`
+    ///
+    /// Pure check with no side effects: convergence, price consistency,
+    /// fill feasibility, inventory conservation (fees included) and the
+    /// objective breakdown. The laws themselves live in
+    /// `convexfx-local-laws`, which the SP1 program runs as well; the Delta
+    /// predicate delegates here.
+    pub fn validate(
+        &self,
+        initial_inventory: &BTreeMap<AssetId, f64>,
+        ref_prices: &RefPrices,
+        tol: &Tolerances,
+    ) -> Result<()> {
+        self.validate_convergence(tol)?;
+        self.validate_prices(ref_prices, tol)?;
+        self.validate_fills()?;
+        self.validate_inventory(initial_inventory, tol)?;
+        self.validate_objective(tol)?;
+        Ok(())
+    }
+
+    /// Check that the SCP algorithm converged within tolerance
+    pub fn validate_convergence(&self, tol: &Tolerances) -> Result<()> {
+        let diag = &self.diagnostics;
+        local_laws::check_convergence(
+            diag.convergence_achieved,
+            diag.final_step_norm_y,
+            diag.final_step_norm_alpha,
+            tol,
+        )
+        .map_err(|v| self.invalid(v))
+    }
+
+    /// Check prices: every reference asset is priced, prices = exp(y*),
+    /// y* of the numeraire is 0, and all prices are positive and finite
+    pub fn validate_prices(&self, ref_prices: &RefPrices, tol: &Tolerances) -> Result<()> {
+        for asset in ref_prices.y_ref.keys() {
+            if !self.y_star.contains_key(asset) {
+                return Err(ConvexFxError::InvalidSolution(format!(
+                    "Missing log price for asset {:?}",
+                    asset
+                )));
+            }
+        }
+
+        local_laws::check_prices(
+            &by_index(&self.y_star),
+            &by_index(&self.prices),
+            self.numeraire.index() as u8,
+            tol,
+        )
+        .map_err(|v| self.invalid(v))
+    }
+
+    /// Check fill fractions lie in [0, 1] and filled amounts are positive
+    pub fn validate_fills(&self) -> Result<()> {
+        let fees = self.fees_by_index();
+        local_laws::check_fills(&self.fill_flows(&fees)).map_err(|v| self.invalid(v))
+    }
+
+    /// Check inventory conservation: q_post = initial + pay - recv + fees
+    pub fn validate_inventory(
+        &self,
+        initial_inventory: &BTreeMap<AssetId, f64>,
+        tol: &Tolerances,
+    ) -> Result<()> {
+        let fees = self.fees_by_index();
+        local_laws::check_inventory(
+            &by_index(initial_inventory),
+            &by_index(&self.q_post),
+            &self.fill_flows(&fees),
+            tol,
+        )
+        .map_err(|v| self.invalid(v))
+    }
+
+    /// Check objective terms are well-formed and sum to the total
+    pub fn validate_objective(&self, tol: &Tolerances) -> Result<()> {
+        let obj = &self.objective_terms;
+        let breakdown = ObjectiveBreakdown {
+            inventory_risk: obj.inventory_risk,
+            price_tracking: obj.price_tracking,
+            fill_incentive: obj.fill_incentive,
+            total: obj.total,
+        };
+        local_laws::check_objective(&breakdown, tol).map_err(|v| self.invalid(v))
+    }
+
+    fn fees_by_index(&self) -> Vec<Vec<(u8, f64)>> {
+        self.fills.iter().map(|fill| by_index(&fill.fees_paid)).collect()
+    }
+
+    fn fill_flows<'a>(&self, fees: &'a [Vec<(u8, f64)>]) -> Vec<FillFlows<'a>> {
+        self.fills
+            .iter()
+            .zip(fees)
+            .map(|(fill, fees_paid)| FillFlows {
+                fill_frac: fill.fill_frac,
+                pay_asset: fill.pay_asset.index() as u8,
+                recv_asset: fill.recv_asset.index() as u8,
+                pay_units: fill.pay_units,
+                recv_units: fill.recv_units,
+                fees_paid,
+            })
+            .collect()
+    }
+
+    /// Describe a violation with asset symbols and, for a single fill, its order
+    fn invalid(&self, violation: Violation) -> ConvexFxError {
+        let message = violation.display_with(asset_symbol).to_string();
+        let message = match violation.fill().and_then(|i| self.fills.get(i)) {
+            Some(fill) => format!("{} (order {})", message, fill.order_id),
+            None => message,
+        };
+        ConvexFxError::InvalidSolution(message)
+    }
+}
+
+/// Per-asset values keyed by `AssetId::index`, as the local laws take them
+fn by_index(values: &BTreeMap<AssetId, f64>) -> Vec<(u8, f64)> {
+    values.iter().map(|(asset, value)| (asset.index() as u8, *value)).collect()
+}
+
+fn asset_symbol(index: u8) -> String {
+    AssetId::from_index(index as usize).map_or_else(|| format!("asset {}", index), |asset| asset.to_string())
+}
@@ -90,6 +90,8 @@ async fn main() {
                     budget: convexfx_types::Amount::from_f64(1000.0).unwrap(),
                     limit_ratio: Some(1.1),
                     min_fill_fraction: Some(0.5),
+                    max_slippage_bps: None,
+                    submission_mid: None,
                     metadata: serde_json::json!({"demo": true}),
                 }
             ];
@@ -158,6 +160,8 @@ async fn main() {
                     budget: convexfx_types::Amount::from_f64(500.0).unwrap(),
                     limit_ratio: Some(1.1),
                     min_fill_fraction: Some(0.5),
+                    max_slippage_bps: None,
+                    submission_mid: None,
                     metadata: serde_json::json!({"demo": true}),
                 }
             ];
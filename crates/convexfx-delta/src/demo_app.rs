@@ -313,7 +313,8 @@ impl DemoApp {
         }
 
         // Convert liquidity inventory
-        let mut inventory = BTreeMap::new();
+        // Assets the pool holds none of still need an entry for clearing
+        let mut inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|asset| (*asset, 0.0)).collect();
         for (asset_str, amount) in total_liquidity {
             if let Some(asset_id) = AssetId::from_str(&asset_str) {
                 inventory.insert(asset_id, amount);
@@ -399,92 +400,17 @@ impl DemoApp {
     }
 
     /// Preview a trade using the actual clearing engine
+    ///
+    /// Delegates to `Exchange::preview_order`, so the quote comes from the
+    /// same clearing a real batch would run. Returns the units received and
+    /// the price impact in percent.
     pub fn preview_trade(&self, from_asset: &str, to_asset: &str, amount: i64) -> std::result::Result<(f64, f64), DeltaIntegrationError> {
-        use convexfx_types::{PairOrder, Amount};
-        
-        use convexfx_oracle::RefPrices;
-        use std::collections::BTreeMap;
-        use std::time::{SystemTime, UNIX_EPOCH};
-
-        // Parse asset IDs manually
-        let pay_asset = match from_asset {
-            "USD" => AssetId::USD,
-            "EUR" => AssetId::EUR,
-            "GBP" => AssetId::GBP,
-            "JPY" => AssetId::JPY,
-            "CHF" => AssetId::CHF,
-            "AUD" => AssetId::AUD,
-            _ => return Err(DeltaIntegrationError::InvalidMessage(format!("Unknown asset: {}", from_asset))),
-        };
-        let recv_asset = match to_asset {
-            "USD" => AssetId::USD,
-            "EUR" => AssetId::EUR,
-            "GBP" => AssetId::GBP,
-            "JPY" => AssetId::JPY,
-            "CHF" => AssetId::CHF,
-            "AUD" => AssetId::AUD,
-            _ => return Err(DeltaIntegrationError::InvalidMessage(format!("Unknown asset: {}", to_asset))),
-        };
+        let (recv_amount, slippage_bps) = self
+            .exchange
+            .preview_order(from_asset, to_asset, amount as f64, None, None)
+            .map_err(|e| DeltaIntegrationError::ConvexFx(e.to_string()))?;
 
-        // Create reference prices (log-space, USD = 0)
-        // Formula: recv = pay * exp(y_pay - y_recv)
-        // Real exchange rates (as of late 2024):
-        // 1 USD = 0.86 EUR, 1 USD = 0.77 GBP, 1 USD = 149 JPY, 1 USD = 0.88 CHF, 1 USD = 1.50 AUD
-        let y_ref = BTreeMap::from([
-            (AssetId::USD, 0.0),
-            (AssetId::EUR, -(0.86_f64).ln()),     // 1 USD = 0.86 EUR
-            (AssetId::GBP, -(0.77_f64).ln()),     // 1 USD = 0.77 GBP  
-            (AssetId::JPY, -(149.0_f64).ln()),    // 1 USD = 149 JPY
-            (AssetId::CHF, -(0.88_f64).ln()),     // 1 USD = 0.88 CHF
-            (AssetId::AUD, -(1.50_f64).ln()),     // 1 USD = 1.50 AUD
-        ]);
-
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
-
-        let ref_prices = RefPrices::new(y_ref, 5.0, timestamp, vec!["mock".to_string()]); // Very tight bands for low slippage
-
-        // Create a single order for preview
-        let _order = PairOrder {
-            id: "preview".to_string(),
-            trader: "preview_user".to_string().into(),
-            pay: pay_asset,
-            receive: recv_asset,
-            budget: Amount::from_units(amount),
-            limit_ratio: None,
-            min_fill_fraction: Some(0.99), // Require at least 99% fill
-            metadata: serde_json::json!({}),
-        };
-
-        // Get actual pool liquidity from user balances
-        let pool_liquidity = self.get_pool_liquidity();
-        
-        // Convert to float for the target asset (in its native units)
-        let _pool_size = pool_liquidity.get(to_asset).copied().unwrap_or(1.0);
-        
-        // Calculate the exchange rate from reference prices
-        // recv = pay * exp(y_pay - y_recv)
-        let exchange_rate = (ref_prices.get_ref(pay_asset) - ref_prices.get_ref(recv_asset)).exp();
-        
-        // Calculate price impact based on trade size relative to pool depth
-        // We compare the trade value in USD to the pool depth
-        let trade_value_usd = amount as f64;
-        let pool_value_usd = pool_liquidity.get("USD").copied().unwrap_or(1.0);
-        let trade_fraction = trade_value_usd / pool_value_usd;
-        
-        // Price impact model: 
-        // - Trades < 0.1% of pool: ~0.01% impact
-        // - Trades = 1% of pool: ~1% impact
-        // - Trades = 10% of pool: ~10% impact (capped)
-        let price_impact = (trade_fraction * 100.0 * trade_fraction * 100.0).min(10.0); // Quadratic, cap at 10%
-        
-        // Apply price impact (reduces received amount slightly)
-        let base_recv = (amount as f64) * exchange_rate;
-        let recv_amount = base_recv * (1.0 - price_impact / 100.0);
-        
-        Ok((recv_amount, price_impact))
+        Ok((recv_amount, slippage_bps / 100.0))
     }
 }
 
@@ -4,17 +4,24 @@
 //! allowing ConvexFX to run as a full Delta executor with proving, SDL
 //! submission, and domain agreement management.
 
+use crate::sdl_generator::SdlGenerator;
 use convexfx_clearing::{EpochInstance, ScpClearing};
 use convexfx_exchange::{Exchange, ExchangeConfig};
 use convexfx_oracle::RefPrices;
 use convexfx_risk::RiskParams;
 use convexfx_types::{AssetId, PairOrder};
+use delta_base_sdk::vaults::{TokenId, TokenKind, VaultId};
 use delta_executor_sdk::execution::Execution;
+use delta_primitives::diff::{
+    types::{HoldingsDiff, StateDiffOperation},
+    StateDiff,
+};
 use delta_verifiable::types::{
     debit_allowance::SignedDebitAllowance,
     fungible::SignedMint,
     nft::SignedMint as SignedNftMint,
 };
+use sha2::{Digest, Sha256};
 use snafu::Snafu;
 use std::collections::BTreeMap;
 use std::sync::{Arc, RwLock};
@@ -62,6 +69,9 @@ pub struct ConvexFxExecutor {
     clearing_engine: ScpClearing,
     /// Risk parameters for clearing
     risk_params: RiskParams,
+    /// SDL generator whose vault nonces mint state diffs advance, so mints
+    /// and settlement diffs never reuse a nonce
+    sdl_generator: Arc<RwLock<SdlGenerator>>,
 }
 
 impl ConvexFxExecutor {
@@ -80,9 +90,24 @@ impl ConvexFxExecutor {
             current_epoch: Arc::new(RwLock::new(0)),
             clearing_engine,
             risk_params,
+            sdl_generator: Arc::new(RwLock::new(SdlGenerator::new())),
         })
     }
 
+    /// Use `generator` as the source of vault nonces
+    ///
+    /// Share it with the SDL path that settles fills so both draw from the
+    /// same per-vault counter.
+    pub fn with_sdl_generator(mut self, generator: Arc<RwLock<SdlGenerator>>) -> Self {
+        self.sdl_generator = generator;
+        self
+    }
+
+    /// The SDL generator this executor draws vault nonces from
+    pub fn sdl_generator(&self) -> Arc<RwLock<SdlGenerator>> {
+        Arc::clone(&self.sdl_generator)
+    }
+
     /// Execute a batch of orders through ConvexFX clearing
     fn execute_clearing_batch(
         &self,
@@ -121,7 +146,8 @@ impl ConvexFxExecutor {
         }
 
         // Convert liquidity inventory
-        let mut inventory = BTreeMap::new();
+        // Assets the pool holds none of still need an entry for clearing
+        let mut inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|asset| (*asset, 0.0)).collect();
         for (asset_str, amount) in total_liquidity {
             if let Some(asset_id) = AssetId::from_str(&asset_str) {
                 inventory.insert(asset_id, amount);
@@ -185,30 +211,100 @@ impl ConvexFxExecutor {
     }
 
     /// Process a FungibleTokenMint message for rewards/liquidity
-    fn process_fungible_token_mint_for_rewards(
+    ///
+    /// Credits the minted amount to the target vault so LP reward mints show
+    /// up in state.
+    pub fn process_fungible_token_mint_for_rewards(
         &self,
-        _signed_mint: &SignedMint,
+        signed_mint: &SignedMint,
     ) -> std::result::Result<Vec<delta_primitives::diff::StateDiff>, ConvexFxExecutorError> {
         tracing::info!("Processing fungible token mint for rewards");
 
-        // For demo purposes, return empty state diffs
-        // In a real implementation, this would convert the mint operation to proper state diffs
-        Ok(Vec::new())
+        let mint = &signed_mint.payload;
+        let amount = i64::try_from(mint.amount).map_err(|_| ConvexFxExecutorError::StateDiffError {
+            message: format!("Mint amount {} does not fit a holdings diff", mint.amount),
+        })?;
+
+        Ok(vec![self.mint_credit_diff(mint.credited, TokenKind::Fungible(mint.token_id), amount)?])
     }
 
     /// Process an NftMint message for position tokens
-    fn process_nft_mint_for_positions(
+    ///
+    /// Each mint credits one unit of a position token whose id is derived
+    /// from the receiving vault and the nonce of the mint.
+    pub fn process_nft_mint_for_positions(
         &self,
-        _signed_nft_mint: &SignedNftMint,
+        signed_nft_mint: &SignedNftMint,
     ) -> std::result::Result<Vec<delta_primitives::diff::StateDiff>, ConvexFxExecutorError> {
         tracing::info!("Processing NFT mint for positions");
 
-        // For demo purposes, return empty state diffs
-        // In a real implementation, this would convert the NFT mint operation to proper state diffs
-        Ok(Vec::new())
+        Ok(vec![self.position_mint_diff(signed_nft_mint.payload.credited)?])
+    }
+
+    /// Build a state diff crediting `amount` of `token` to `vault_id`, with
+    /// the vault's next nonce
+    fn mint_credit_diff(
+        &self,
+        vault_id: VaultId,
+        token: TokenKind,
+        amount: i64,
+    ) -> std::result::Result<StateDiff, ConvexFxExecutorError> {
+        if amount <= 0 {
+            return Err(ConvexFxExecutorError::StateDiffError {
+                message: format!("Mint amount must be positive, got {}", amount),
+            });
+        }
+
+        let mut token_diffs = BTreeMap::new();
+        token_diffs.insert(token, HoldingsDiff::Fungible(amount));
+
+        Ok(StateDiff {
+            vault_id,
+            new_nonce: Some(self.next_vault_nonce(&vault_id)),
+            operation: StateDiffOperation::TokenDiffs(token_diffs),
+        })
+    }
+
+    /// Build a state diff crediting a fresh position token to `vault_id`
+    fn position_mint_diff(&self, vault_id: VaultId) -> std::result::Result<StateDiff, ConvexFxExecutorError> {
+        let nonce = self.next_vault_nonce(&vault_id);
+
+        let mut token_diffs = BTreeMap::new();
+        token_diffs.insert(
+            TokenKind::NonFungible(position_token_id(&vault_id, nonce)?),
+            HoldingsDiff::Fungible(1),
+        );
+
+        Ok(StateDiff {
+            vault_id,
+            new_nonce: Some(nonce),
+            operation: StateDiffOperation::TokenDiffs(token_diffs),
+        })
+    }
+
+    /// Increment and return the nonce of a vault
+    fn next_vault_nonce(&self, vault_id: &VaultId) -> u64 {
+        self.sdl_generator.write().unwrap().increment_vault_nonce(vault_id)
     }
 }
 
+/// Derive the position token id for the mint into `vault_id` at `nonce`
+///
+/// The id is "POS" followed by the first 6 bytes (hex) of
+/// SHA-256(bincode vault id, little-endian nonce), so every mint gets a
+/// distinct, reproducible token.
+fn position_token_id(vault_id: &VaultId, nonce: u64) -> std::result::Result<TokenId, ConvexFxExecutorError> {
+    let vault_bytes = bincode::serialize(vault_id).map_err(|e| ConvexFxExecutorError::StateDiffError {
+        message: format!("Failed to encode vault id: {}", e),
+    })?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(vault_bytes);
+    hasher.update(nonce.to_le_bytes());
+    let digest = hasher.finalize();
+    Ok(TokenId::new_base(format!("POS{}", hex::encode(&digest[..6])).as_bytes()))
+}
+
 /// Implementation of the Delta Execution trait
 ///
 /// This is the core integration point - Delta SDK calls `execute` with
@@ -253,11 +349,11 @@ impl Execution for ConvexFxExecutor {
 
                     // FungibleTokenMint creates or increases token supply
                     // In a DEX context, this could be for liquidity rewards
-                    let _state_diffs = self.process_fungible_token_mint_for_rewards(&signed_mint)?;
+                    let state_diffs = self.process_fungible_token_mint_for_rewards(signed_mint)?;
 
                     results.push(delta_verifiable::types::VerifiableWithDiffs {
                         verifiable: verifiable.clone(),
-                        state_diffs: Vec::new(),
+                        state_diffs,
                     });
                 }
 
@@ -265,11 +361,11 @@ impl Execution for ConvexFxExecutor {
                     tracing::info!("Processing NftMint message {}", i);
 
                     // NFT minting for position tokens, governance, etc.
-                    let _state_diffs = self.process_nft_mint_for_positions(&signed_nft_mint)?;
+                    let state_diffs = self.process_nft_mint_for_positions(signed_nft_mint)?;
 
                     results.push(delta_verifiable::types::VerifiableWithDiffs {
                         verifiable: verifiable.clone(),
-                        state_diffs: Vec::new(),
+                        state_diffs,
                     });
                 }
             }
@@ -283,6 +379,9 @@ impl Execution for ConvexFxExecutor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use delta_base_sdk::vaults::OwnerId;
+    use delta_crypto::{ed25519::PrivKey, messages::SignedMessage, signing_key::SigningKey};
+    use delta_verifiable::types::{fungible::Mint, nft::Mint as NftMint};
 
     #[test]
     fn test_executor_creation() {
@@ -299,4 +398,90 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap().len(), 0);
     }
+
+    fn test_key() -> (PrivKey, VaultId) {
+        let priv_key = PrivKey::generate();
+        let owner_id = OwnerId::from(priv_key.pub_key().to_bytes());
+        (priv_key, VaultId::from((owner_id, 0)))
+    }
+
+    fn reward_mint(amount: u64) -> SignedMint {
+        let (priv_key, vault_id) = test_key();
+        let mint = Mint {
+            credited: vault_id,
+            token_id: TokenId::new_base(b"USD"),
+            amount,
+        };
+        SignedMessage::sign(mint, &priv_key).unwrap()
+    }
+
+    fn position_mint(vault_id: VaultId) -> SignedNftMint {
+        let (priv_key, _) = test_key();
+        SignedMessage::sign(NftMint { credited: vault_id }, &priv_key).unwrap()
+    }
+
+    #[test]
+    fn test_fungible_mint_credits_vault() {
+        let executor = ConvexFxExecutor::new().unwrap();
+        let reward = || TokenKind::Fungible(TokenId::new_base(b"USD"));
+        let signed_mint = reward_mint(2_500);
+        let vault_id = signed_mint.payload.credited;
+
+        let diffs = executor.process_fungible_token_mint_for_rewards(&signed_mint).unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].vault_id, vault_id);
+        assert_eq!(diffs[0].new_nonce, Some(1));
+        match &diffs[0].operation {
+            StateDiffOperation::TokenDiffs(token_diffs) => {
+                assert_eq!(token_diffs.len(), 1);
+                assert_eq!(token_diffs.get(&reward()), Some(&HoldingsDiff::Fungible(2_500)));
+            }
+            _ => panic!("Expected TokenDiffs operation"),
+        }
+
+        // A second mint into the same vault advances the nonce
+        let diffs = executor.process_fungible_token_mint_for_rewards(&signed_mint).unwrap();
+        assert_eq!(diffs[0].new_nonce, Some(2));
+
+        assert!(executor.process_fungible_token_mint_for_rewards(&reward_mint(0)).is_err());
+    }
+
+    #[test]
+    fn test_position_mint_uses_derived_token() {
+        let executor = ConvexFxExecutor::new().unwrap();
+        let (_, vault_id) = test_key();
+
+        let first = executor.process_nft_mint_for_positions(&position_mint(vault_id)).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].vault_id, vault_id);
+        assert_eq!(first[0].new_nonce, Some(1));
+        let position = TokenKind::NonFungible(position_token_id(&vault_id, 1).unwrap());
+        match &first[0].operation {
+            StateDiffOperation::TokenDiffs(token_diffs) => {
+                assert_eq!(token_diffs.len(), 1);
+                assert_eq!(token_diffs.get(&position), Some(&HoldingsDiff::Fungible(1)));
+            }
+            _ => panic!("Expected TokenDiffs operation"),
+        }
+
+        // Each mint gets its own position token
+        let second = executor.process_nft_mint_for_positions(&position_mint(vault_id)).unwrap();
+        assert_eq!(second[0].new_nonce, Some(2));
+        assert_ne!(position_token_id(&vault_id, 1).unwrap(), position_token_id(&vault_id, 2).unwrap());
+    }
+
+    #[test]
+    fn test_mints_share_nonces_with_sdl_generator() {
+        let (_, vault_id) = test_key();
+        let generator = Arc::new(RwLock::new(SdlGenerator::new()));
+        generator.write().unwrap().register_vault(vault_id, 5);
+        let executor = ConvexFxExecutor::new().unwrap().with_sdl_generator(Arc::clone(&generator));
+
+        let diffs = executor.process_nft_mint_for_positions(&position_mint(vault_id)).unwrap();
+        assert_eq!(diffs[0].new_nonce, Some(6));
+        assert_eq!(generator.read().unwrap().get_vault_nonce(&vault_id), 6);
+
+        // Settlement diffs for the vault continue after the mint
+        assert_eq!(generator.write().unwrap().increment_vault_nonce(&vault_id), 7);
+    }
 }
@@ -58,6 +58,9 @@ pub enum DeltaIntegrationError {
     
     #[error("Clearing failed: {0}")]
     ClearingFailed(String),
+
+    #[error("Proof verification failed: {0}")]
+    ProofVerification(String),
 }
 
 /// Result type for Delta integration operations
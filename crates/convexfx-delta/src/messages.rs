@@ -54,6 +54,8 @@ impl DeltaMessage {
             budget: self.budget,
             limit_ratio: self.limit_ratio,
             min_fill_fraction: self.min_fill_fraction,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({
                 "source": "delta_message",
                 "owner": self.owner.to_string(),
@@ -5,9 +5,9 @@
 //! being proven and submitted to the Delta base layer.
 
 use crate::{DeltaIntegrationError, Result};
-use convexfx_clearing::EpochSolution;
+use convexfx_clearing::{EpochSolution, ValidationTolerances};
 use convexfx_oracle::RefPrices;
-use convexfx_types::AssetId;
+use convexfx_types::{AssetId, ConvexFxError};
 use std::collections::BTreeMap;
 
 /// Context for predicate validation
@@ -45,225 +45,67 @@ impl Default for ScpClearingValidityPredicate {
 
 impl ScpClearingValidityPredicate {
     /// Validate that a clearing solution satisfies all SCP optimality conditions
+    ///
+    /// Delegates to `EpochSolution::validate` so the executor and the SP1
+    /// program check the same laws.
     pub fn validate(&self, solution: &EpochSolution, context: &PredicateContext) -> Result<()> {
-        // Run all validation checks
-        self.validate_convergence(solution)?;
-        self.validate_price_consistency(solution)?;
-        self.validate_fill_feasibility(solution)?;
-        self.validate_inventory_conservation(solution, context)?;
-        self.validate_objective_optimality(solution)?;
-
-        Ok(())
+        solution
+            .validate(context.initial_inventory, context.oracle_prices, &self.tolerances())
+            .map_err(clearing_failed)
     }
 
-    /// Validate that the SCP algorithm converged properly
-    fn validate_convergence(&self, solution: &EpochSolution) -> Result<()> {
-        // Check if SCP algorithm converged
-        if !solution.diagnostics.convergence_achieved {
-            return Err(DeltaIntegrationError::ClearingFailed(format!(
-                "SCP algorithm did not converge after {} iterations",
-                solution.diagnostics.iterations
-            )));
-        }
-
-        // Check step norms are within tolerance
-        if solution.diagnostics.final_step_norm_y > self.tolerance_y {
-            return Err(DeltaIntegrationError::ClearingFailed(format!(
-                "Price step norm {} exceeds tolerance {}",
-                solution.diagnostics.final_step_norm_y,
-                self.tolerance_y
-            )));
-        }
-
-        if solution.diagnostics.final_step_norm_alpha > self.tolerance_alpha {
-            return Err(DeltaIntegrationError::ClearingFailed(format!(
-                "Fill step norm {} exceeds tolerance {}",
-                solution.diagnostics.final_step_norm_alpha,
-                self.tolerance_alpha
-            )));
+    /// Tolerances passed to the shared solution checks
+    pub fn tolerances(&self) -> ValidationTolerances {
+        ValidationTolerances {
+            tolerance_y: self.tolerance_y,
+            tolerance_alpha: self.tolerance_alpha,
+            max_price_deviation: self.max_price_deviation,
+            inventory_tolerance: self.inventory_tolerance,
         }
+    }
 
-        Ok(())
+    /// Validate that the SCP algorithm converged properly
+    pub fn validate_convergence(&self, solution: &EpochSolution) -> Result<()> {
+        solution.validate_convergence(&self.tolerances()).map_err(clearing_failed)
     }
 
     /// Validate price consistency: linear prices = exp(log_prices)
-    fn validate_price_consistency(&self, solution: &EpochSolution) -> Result<()> {
-        // Check that linear prices = exp(log_prices)
-        for (asset, log_price) in &solution.y_star {
-            let expected_linear_price = log_price.exp();
-            let actual_linear_price = solution.prices.get(asset).ok_or_else(|| {
-                DeltaIntegrationError::ClearingFailed(format!(
-                    "Missing linear price for asset {:?}",
-                    asset
-                ))
-            })?;
-
-            let price_error = (expected_linear_price - actual_linear_price).abs() / actual_linear_price;
-            if price_error > self.max_price_deviation {
-                return Err(DeltaIntegrationError::ClearingFailed(format!(
-                    "Price inconsistency for {:?}: expected {:.6}, got {:.6}, error {:.2}%",
-                    asset,
-                    expected_linear_price,
-                    actual_linear_price,
-                    price_error * 100.0
-                )));
-            }
-        }
-
-        // Check USD numeraire constraint (y_USD = 0)
-        if let Some(usd_log_price) = solution.y_star.get(&AssetId::USD) {
-            if usd_log_price.abs() > self.tolerance_y {
-                return Err(DeltaIntegrationError::ClearingFailed(format!(
-                    "USD numeraire constraint violated: y_USD = {}",
-                    usd_log_price
-                )));
-            }
-        }
-
-        // Check all prices are positive and finite
-        for (asset, price) in &solution.prices {
-            if *price <= 0.0 {
-                return Err(DeltaIntegrationError::ClearingFailed(format!(
-                    "Non-positive price for {:?}: {}",
-                    asset, price
-                )));
-            }
-            if !price.is_finite() {
-                return Err(DeltaIntegrationError::ClearingFailed(format!(
-                    "Non-finite price for {:?}: {}",
-                    asset, price
-                )));
-            }
-        }
-
-        Ok(())
+    pub fn validate_price_consistency(&self, solution: &EpochSolution, context: &PredicateContext) -> Result<()> {
+        solution
+            .validate_prices(context.oracle_prices, &self.tolerances())
+            .map_err(clearing_failed)
     }
 
     /// Validate that all fills are feasible
-    fn validate_fill_feasibility(&self, solution: &EpochSolution) -> Result<()> {
-        for fill in &solution.fills {
-            // Check fill fraction is in valid range [0, 1]
-            if fill.fill_frac < 0.0 || fill.fill_frac > 1.0 {
-                return Err(DeltaIntegrationError::ClearingFailed(format!(
-                    "Invalid fill fraction {:.6} for order {}",
-                    fill.fill_frac, fill.order_id
-                )));
-            }
-
-            // Check fill amounts are positive (or zero for unfilled orders)
-            // Use a small tolerance to handle numerical precision issues
-            const MIN_FILL_AMOUNT: f64 = 1e-8;
-            if fill.fill_frac > MIN_FILL_AMOUNT {
-                if fill.pay_units <= MIN_FILL_AMOUNT {
-                    return Err(DeltaIntegrationError::ClearingFailed(format!(
-                        "Non-positive pay amount {:.6} for filled order {} (fill_frac: {})",
-                        fill.pay_units, fill.order_id, fill.fill_frac
-                    )));
-                }
-                if fill.recv_units <= MIN_FILL_AMOUNT {
-                    return Err(DeltaIntegrationError::ClearingFailed(format!(
-                        "Non-positive receive amount {:.6} for filled order {} (fill_frac: {})",
-                        fill.recv_units, fill.order_id, fill.fill_frac
-                    )));
-                }
-            }
-
-            // Check amounts are finite
-            if !fill.pay_units.is_finite() || !fill.recv_units.is_finite() {
-                return Err(DeltaIntegrationError::ClearingFailed(format!(
-                    "Non-finite amounts for order {}: pay={}, recv={}",
-                    fill.order_id, fill.pay_units, fill.recv_units
-                )));
-            }
-        }
-
-        Ok(())
+    pub fn validate_fill_feasibility(&self, solution: &EpochSolution) -> Result<()> {
+        solution.validate_fills().map_err(clearing_failed)
     }
 
     /// Validate inventory conservation: final = initial + net_flow
-    fn validate_inventory_conservation(
+    pub fn validate_inventory_conservation(
         &self,
         solution: &EpochSolution,
         context: &PredicateContext,
     ) -> Result<()> {
-        let initial_inventory = context.initial_inventory;
-        let final_inventory = &solution.q_post;
-
-        for asset in AssetId::all() {
-            let initial_q = initial_inventory.get(asset).copied().unwrap_or(0.0);
-            let final_q = final_inventory.get(asset).copied().unwrap_or(0.0);
-
-            // Calculate net flow from fills
-            let mut net_flow = 0.0;
-            for fill in &solution.fills {
-                if fill.pay_asset == *asset {
-                    net_flow += fill.pay_units; // Pool receives pay asset
-                }
-                if fill.recv_asset == *asset {
-                    net_flow -= fill.recv_units; // Pool gives receive asset
-                }
-            }
-
-            let expected_final = initial_q + net_flow;
-            let inventory_error = (final_q - expected_final).abs();
-
-            if inventory_error > self.inventory_tolerance {
-                return Err(DeltaIntegrationError::ClearingFailed(format!(
-                    "Inventory conservation violated for {:?}: initial={:.6}, net_flow={:.6}, expected={:.6}, actual={:.6}, error={:.6}",
-                    asset, initial_q, net_flow, expected_final, final_q, inventory_error
-                )));
-            }
-        }
-
-        Ok(())
+        solution
+            .validate_inventory(context.initial_inventory, &self.tolerances())
+            .map_err(clearing_failed)
     }
 
     /// Validate objective function values are reasonable
-    fn validate_objective_optimality(&self, solution: &EpochSolution) -> Result<()> {
-        let obj = &solution.objective_terms;
-
-        // Inventory risk should be non-negative
-        if obj.inventory_risk < -self.inventory_tolerance {
-            return Err(DeltaIntegrationError::ClearingFailed(format!(
-                "Negative inventory risk: {:.6}",
-                obj.inventory_risk
-            )));
-        }
-
-        // Price tracking should be non-negative
-        if obj.price_tracking < -self.inventory_tolerance {
-            return Err(DeltaIntegrationError::ClearingFailed(format!(
-                "Negative price tracking: {:.6}",
-                obj.price_tracking
-            )));
-        }
-
-        // Total objective should be finite
-        if !obj.total.is_finite() {
-            return Err(DeltaIntegrationError::ClearingFailed(format!(
-                "Non-finite objective value: {:.6}",
-                obj.total
-            )));
-        }
-
-        // Check that objective components sum correctly
-        let computed_total = obj.inventory_risk + obj.price_tracking + obj.fill_incentive;
-        let total_error = (obj.total - computed_total).abs();
-        if total_error > self.inventory_tolerance {
-            return Err(DeltaIntegrationError::ClearingFailed(format!(
-                "Objective components don't sum correctly: components={:.6}, total={:.6}, error={:.6}",
-                computed_total, obj.total, total_error
-            )));
-        }
-
-        Ok(())
+    pub fn validate_objective_optimality(&self, solution: &EpochSolution) -> Result<()> {
+        solution.validate_objective(&self.tolerances()).map_err(clearing_failed)
     }
 }
 
+fn clearing_failed(err: ConvexFxError) -> DeltaIntegrationError {
+    DeltaIntegrationError::ClearingFailed(err.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use convexfx_clearing::{Diagnostics, ObjectiveTerms};
+    use convexfx_clearing::{Diagnostics, ObjectiveTerms, ObjectiveWeights, QpStatus};
     use convexfx_types::Fill;
 
     fn create_test_solution(
@@ -297,14 +139,22 @@ mod tests {
                 price_tracking: 50.0,
                 fill_incentive: -20.0,
                 total: 130.0,
+                weights: ObjectiveWeights::default(),
             },
             diagnostics: Diagnostics {
                 iterations: 3,
                 convergence_achieved: convergence,
                 final_step_norm_y: step_norm_y,
                 final_step_norm_alpha: step_norm_alpha,
-                qp_status: "Optimal".to_string(),
+                qp_status: QpStatus::Optimal,
+                solve_time_ms: 0,
+                iteration_times_us: Vec::new(),
+                binding_constraints: Vec::new(),
+                objective_trajectory: Vec::new(),
+                max_coherence_error_bps: 0.0,
+                qp_backend: "clarabel".to_string(),
             },
+            numeraire: AssetId::USD,
         }
     }
 
@@ -340,8 +190,14 @@ mod tests {
     fn test_price_consistency_success() {
         let predicate = ScpClearingValidityPredicate::default();
         let solution = create_test_solution(true, 1e-6, 1e-7);
+        let oracle_prices = RefPrices::new(solution.y_star.clone(), 20.0, 0, vec!["test".to_string()]);
+        let initial_inventory = BTreeMap::new();
+        let context = PredicateContext {
+            oracle_prices: &oracle_prices,
+            initial_inventory: &initial_inventory,
+        };
 
-        assert!(predicate.validate_price_consistency(&solution).is_ok());
+        assert!(predicate.validate_price_consistency(&solution, &context).is_ok());
     }
 
     #[test]
@@ -350,8 +206,14 @@ mod tests {
         let mut solution = create_test_solution(true, 1e-6, 1e-7);
         solution.y_star.insert(AssetId::USD, 0.1); // Violate USD = 0
         solution.prices.insert(AssetId::USD, 0.1_f64.exp()); // Keep price consistent with log_price
+        let oracle_prices = RefPrices::new(solution.y_star.clone(), 20.0, 0, vec!["test".to_string()]);
+        let initial_inventory = BTreeMap::new();
+        let context = PredicateContext {
+            oracle_prices: &oracle_prices,
+            initial_inventory: &initial_inventory,
+        };
 
-        let result = predicate.validate_price_consistency(&solution);
+        let result = predicate.validate_price_consistency(&solution, &context);
         assert!(result.is_err());
         let error_msg = result.unwrap_err().to_string();
         assert!(error_msg.contains("USD numeraire constraint violated"));
@@ -439,6 +301,48 @@ mod tests {
             .is_ok());
     }
 
+    #[test]
+    fn test_inventory_conservation_with_fees() {
+        let predicate = ScpClearingValidityPredicate::default();
+        let mut solution = create_test_solution(true, 1e-6, 1e-7);
+
+        let mut initial_inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            initial_inventory.insert(*asset, 10000.0);
+        }
+
+        // Fill with a 3 USD fee on top of the budget
+        let mut fees_paid = BTreeMap::new();
+        fees_paid.insert(AssetId::USD, 3.0);
+        solution.fills.push(Fill {
+            order_id: "test1".to_string(),
+            fill_frac: 1.0,
+            pay_asset: AssetId::USD,
+            recv_asset: AssetId::EUR,
+            pay_units: 1000.0,
+            recv_units: 860.0,
+            fees_paid,
+        });
+        solution.q_post.insert(AssetId::EUR, 9140.0); // -860
+
+        let oracle_prices = RefPrices::new(solution.y_star.clone(), 20.0, 0, vec!["test".to_string()]);
+        let context = PredicateContext {
+            oracle_prices: &oracle_prices,
+            initial_inventory: &initial_inventory,
+        };
+
+        // Inventory that ignores the fee no longer balances
+        solution.q_post.insert(AssetId::USD, 11000.0);
+        let result = predicate.validate_inventory_conservation(&solution, &context);
+        assert!(result.unwrap_err().to_string().contains("USD"));
+
+        // Inventory that keeps the fee conserves
+        solution.q_post.insert(AssetId::USD, 11003.0);
+        assert!(predicate
+            .validate_inventory_conservation(&solution, &context)
+            .is_ok());
+    }
+
     #[test]
     fn test_objective_optimality_success() {
         let predicate = ScpClearingValidityPredicate::default();
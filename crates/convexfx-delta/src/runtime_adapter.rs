@@ -50,6 +50,8 @@ impl ConvexFxDeltaAdapter {
             budget: Amount::from_f64(1000.0).unwrap(),
             limit_ratio: Some(1.1),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({
                 "source": "delta_integration"
             }),
@@ -118,7 +120,8 @@ impl ConvexFxDeltaAdapter {
         let total_liquidity = self.exchange.get_total_liquidity()
             .map_err(|e| DeltaIntegrationError::ConvexFx(format!("Failed to get liquidity: {:?}", e)))?;
         
-        let mut inventory = BTreeMap::new();
+        // Assets the pool holds none of still need an entry for clearing
+        let mut inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|asset| (*asset, 0.0)).collect();
         for (asset_str, amount) in total_liquidity {
             if let Some(asset_id) = AssetId::from_str(&asset_str) {
                 inventory.insert(asset_id, amount);
@@ -1,5 +1,5 @@
 use crate::{DeltaIntegrationError, Result};
-use convexfx_types::{AssetId, Fill, AccountId, OrderId};
+use convexfx_types::{AssetId, AssetRegistry, Fill, AccountId, OrderId};
 use delta_base_sdk::{
     vaults::{OwnerId, VaultId, TokenKind, TokenId},
     crypto::HashDigest,
@@ -8,8 +8,24 @@ use delta_primitives::{
     diff::{StateDiff, types::{StateDiffOperation, HoldingsDiff}},
 };
 // Simplified SDL generator for demo purposes
+use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 
+/// Domain separator so SDL hashes never collide with other SHA-256 uses
+const SDL_HASH_DOMAIN: &[u8] = b"convexfx-sdl-v2";
+
+/// Feed a length-prefixed field into the hasher so adjacent fields can't run together
+fn hash_field(hasher: &mut Sha256, field: &[u8]) {
+    hasher.update((field.len() as u64).to_le_bytes());
+    hasher.update(field);
+}
+
+/// Canonical bincode encoding of an SDK value for hashing
+fn encode<T: serde::Serialize>(value: &T) -> Result<Vec<u8>> {
+    bincode::serialize(value)
+        .map_err(|e| DeltaIntegrationError::InvalidMessage(format!("Failed to encode state diff: {}", e)))
+}
+
 /// SDL Generator that converts ConvexFX clearing results to Delta SDL format
 #[derive(Debug)]
 pub struct SdlGenerator {
@@ -21,6 +37,8 @@ pub struct SdlGenerator {
     asset_to_token: BTreeMap<AssetId, TokenId>,
     /// Mapping from OrderId to AccountId (for resolving fills to traders)
     order_to_account: BTreeMap<OrderId, AccountId>,
+    /// Decimal precision of each asset's minor unit
+    asset_decimals: BTreeMap<AssetId, u32>,
 }
 
 impl SdlGenerator {
@@ -35,12 +53,23 @@ impl SdlGenerator {
         asset_to_token.insert(AssetId::GBP, TokenId::new_base(b"GBP"));
         asset_to_token.insert(AssetId::CHF, TokenId::new_base(b"CHF"));
         asset_to_token.insert(AssetId::AUD, TokenId::new_base(b"AUD"));
+
+        let registry = AssetRegistry::new();
+        let asset_decimals = AssetId::all()
+            .iter()
+            .filter_map(|asset| {
+                registry
+                    .get_asset_info(asset.as_str())
+                    .map(|info| (*asset, info.decimals))
+            })
+            .collect();
         
         Self {
             account_to_owner: BTreeMap::new(),
             vault_nonces: BTreeMap::new(),
             asset_to_token,
             order_to_account: BTreeMap::new(),
+            asset_decimals,
         }
     }
 
@@ -113,6 +142,10 @@ impl SdlGenerator {
                 format!("No vault found for account: {}", account)
             ))?;
 
+        // Convert amounts before touching the nonce so a bad fill leaves the vault untouched
+        let pay_amount = -self.to_minor_units(&fill.pay_asset, fill.pay_units)?;
+        let recv_amount = self.to_minor_units(&fill.recv_asset, fill.recv_units)?;
+
         // Get current nonce and increment it
         let new_nonce = self.increment_vault_nonce(&vault_id);
 
@@ -125,7 +158,6 @@ impl SdlGenerator {
                 format!("Token not found for asset: {:?}", fill.pay_asset)
             ))?;
         let pay_token_kind = TokenKind::Fungible(*pay_token_id);
-        let pay_amount = -(fill.pay_units as i64);
         token_diffs.insert(pay_token_kind, HoldingsDiff::Fungible(pay_amount));
 
         // Credit the receive asset (positive value)
@@ -134,7 +166,6 @@ impl SdlGenerator {
                 format!("Token not found for asset: {:?}", fill.recv_asset)
             ))?;
         let recv_token_kind = TokenKind::Fungible(*recv_token_id);
-        let recv_amount = fill.recv_units as i64;
         token_diffs.insert(recv_token_kind, HoldingsDiff::Fungible(recv_amount));
 
         // Create the state diff
@@ -148,6 +179,40 @@ impl SdlGenerator {
         Ok(state_diffs)
     }
 
+    /// Convert a fill amount to integer minor units of `asset`
+    ///
+    /// Rounds to the asset's decimal precision (cents for EUR, whole yen for
+    /// JPY) instead of truncating, and rejects negative, non-finite or
+    /// out-of-range amounts that an `as` cast would silently clamp.
+    fn to_minor_units(&self, asset: &AssetId, amount: f64) -> Result<i64> {
+        let decimals = self.asset_decimals.get(asset).copied().ok_or_else(|| {
+            DeltaIntegrationError::AssetNotFound(format!("No decimals for asset: {:?}", asset))
+        })?;
+
+        if !amount.is_finite() || amount < 0.0 {
+            return Err(DeltaIntegrationError::InvalidMessage(format!(
+                "Invalid {} fill amount: {}",
+                asset, amount
+            )));
+        }
+
+        let minor = (amount * 10f64.powi(decimals as i32)).round();
+        // u64::MAX as f64 rounds up to 2^64, so anything at or above it overflows
+        if minor >= u64::MAX as f64 {
+            return Err(DeltaIntegrationError::InvalidMessage(format!(
+                "{} fill amount {} exceeds u64::MAX minor units",
+                asset, amount
+            )));
+        }
+
+        i64::try_from(minor as u64).map_err(|_| {
+            DeltaIntegrationError::InvalidMessage(format!(
+                "{} fill amount {} does not fit a holdings diff",
+                asset, amount
+            ))
+        })
+    }
+
     /// Convert ConvexFX AssetId to Delta asset identifier
     fn asset_id_to_delta(&self, asset_id: &AssetId) -> Result<String> {
         // Simple mapping - in reality this would be more sophisticated
@@ -161,13 +226,56 @@ impl SdlGenerator {
         })
     }
 
-    /// Generate SDL hash (simplified implementation)
+    /// Generate SDL hash
+    ///
+    /// Each diff is serialized canonically (bincode vault id, little-endian
+    /// nonce, then token diffs in key order, with fungible amounts as
+    /// little-endian i64) and the whole list is hashed with SHA-256, so equal
+    /// content always yields the same digest while reordering diffs or
+    /// changing any amount does not.
     pub fn calculate_sdl_hash(&self, state_diffs: &[StateDiff]) -> Result<HashDigest> {
-        // In a real implementation, this would properly hash the SDL content
-        // For now, return a mock hash based on the number of diffs
-        let mut hasher = std::collections::hash_map::DefaultHasher::new();
-        std::hash::Hash::hash(&state_diffs.len(), &mut hasher);
-        Ok(HashDigest::default()) // Simplified for demo
+        let mut hasher = Sha256::new();
+        hasher.update(SDL_HASH_DOMAIN);
+        hasher.update((state_diffs.len() as u64).to_le_bytes());
+
+        for diff in state_diffs {
+            hash_field(&mut hasher, &encode(&diff.vault_id)?);
+            match diff.new_nonce {
+                Some(nonce) => {
+                    hasher.update([1u8]);
+                    hasher.update(nonce.to_le_bytes());
+                }
+                None => hasher.update([0u8]),
+            }
+
+            match &diff.operation {
+                StateDiffOperation::TokenDiffs(token_diffs) => {
+                    hasher.update([0u8]);
+                    hasher.update((token_diffs.len() as u64).to_le_bytes());
+                    // BTreeMap iteration is already sorted by token
+                    for (token, holdings) in token_diffs {
+                        hash_field(&mut hasher, &encode(token)?);
+                        match holdings {
+                            HoldingsDiff::Fungible(amount) => {
+                                hasher.update([0u8]);
+                                hasher.update(amount.to_le_bytes());
+                            }
+                            other => {
+                                hasher.update([1u8]);
+                                hash_field(&mut hasher, &encode(other)?);
+                            }
+                        }
+                    }
+                }
+                other => {
+                    hasher.update([1u8]);
+                    hash_field(&mut hasher, &encode(other)?);
+                }
+            }
+        }
+
+        let digest: [u8; 32] = hasher.finalize().into();
+        Ok(HashDigest::from(digest))
     }
 
     /// Validate state diffs before submission
@@ -356,13 +464,84 @@ mod tests {
             StateDiffOperation::TokenDiffs(token_diffs) => {
                 assert_eq!(token_diffs.len(), 2); // Pay and receive assets
                 
-                // Check USD debit (negative amount)
+                // Check USD debit (negative amount, in cents)
                 let usd_token = TokenKind::Fungible(TokenId::new_base(b"USD"));
-                assert_eq!(token_diffs.get(&usd_token), Some(&HoldingsDiff::Fungible(-1000)));
+                assert_eq!(token_diffs.get(&usd_token), Some(&HoldingsDiff::Fungible(-100_000)));
                 
-                // Check EUR credit (positive amount)
+                // Check EUR credit (positive amount, in cents)
+                let eur_token = TokenKind::Fungible(TokenId::new_base(b"EUR"));
+                assert_eq!(token_diffs.get(&eur_token), Some(&HoldingsDiff::Fungible(90_000)));
+            }
+            _ => panic!("Expected TokenDiffs operation"),
+        }
+    }
+
+    fn registered_generator() -> (SdlGenerator, VaultId) {
+        let mut generator = SdlGenerator::new();
+        let account = AccountId::new("trader".to_string());
+        let owner = OwnerId::from(PubKey::generate().hash_sha256());
+        let vault_id = VaultId::from((owner, 0));
+        generator.register_account(account.clone(), owner);
+        generator.register_vault(vault_id, 0);
+        generator.register_order("order".to_string(), account);
+        (generator, vault_id)
+    }
+
+    fn test_fill(pay_asset: AssetId, recv_asset: AssetId, pay_units: f64, recv_units: f64) -> Fill {
+        Fill {
+            order_id: "order".to_string(),
+            fill_frac: 1.0,
+            pay_asset,
+            recv_asset,
+            pay_units,
+            recv_units,
+            fees_paid: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_nan_fill_rejected() {
+        let (mut generator, vault_id) = registered_generator();
+        let fill = test_fill(AssetId::USD, AssetId::EUR, 1000.0, f64::NAN);
+
+        let result = generator.fill_to_state_diffs(&fill);
+        assert!(matches!(result, Err(DeltaIntegrationError::InvalidMessage(_))));
+        // The vault nonce is not consumed by a rejected fill
+        assert_eq!(generator.get_vault_nonce(&vault_id), 0);
+
+        let negative = test_fill(AssetId::USD, AssetId::EUR, -1.0, 900.0);
+        assert!(matches!(
+            generator.fill_to_state_diffs(&negative),
+            Err(DeltaIntegrationError::InvalidMessage(_))
+        ));
+    }
+
+    #[test]
+    fn test_huge_fill_rejected() {
+        let (mut generator, _) = registered_generator();
+        let fill = test_fill(AssetId::USD, AssetId::JPY, 1e30, 1e32);
+
+        assert!(matches!(
+            generator.fill_to_state_diffs(&fill),
+            Err(DeltaIntegrationError::InvalidMessage(_))
+        ));
+    }
+
+    #[test]
+    fn test_fill_rounds_to_asset_decimals() {
+        let (mut generator, _) = registered_generator();
+        let fill = test_fill(AssetId::EUR, AssetId::JPY, 10.006, 1634.5);
+
+        let state_diffs = generator.fill_to_state_diffs(&fill).unwrap();
+        match &state_diffs[0].operation {
+            StateDiffOperation::TokenDiffs(token_diffs) => {
+                // EUR has 2 decimals: 10.006 rounds up to 1001 cents rather than truncating
                 let eur_token = TokenKind::Fungible(TokenId::new_base(b"EUR"));
-                assert_eq!(token_diffs.get(&eur_token), Some(&HoldingsDiff::Fungible(900)));
+                assert_eq!(token_diffs.get(&eur_token), Some(&HoldingsDiff::Fungible(-1001)));
+
+                // JPY has 0 decimals: 1634.5 rounds to 1635 yen
+                let jpy_token = TokenKind::Fungible(TokenId::new_base(b"JPY"));
+                assert_eq!(token_diffs.get(&jpy_token), Some(&HoldingsDiff::Fungible(1635)));
             }
             _ => panic!("Expected TokenDiffs operation"),
         }
@@ -387,6 +566,82 @@ mod tests {
         assert!(generator.validate_state_diffs(&[valid_diff]).is_err());
     }
 
+    fn hash_test_diff(vault_id: VaultId, nonce: u64, usd: i64, eur: i64) -> StateDiff {
+        let mut token_diffs = BTreeMap::new();
+        token_diffs.insert(TokenKind::Fungible(TokenId::new_base(b"USD")), HoldingsDiff::Fungible(usd));
+        token_diffs.insert(TokenKind::Fungible(TokenId::new_base(b"EUR")), HoldingsDiff::Fungible(eur));
+        StateDiff {
+            vault_id,
+            new_nonce: Some(nonce),
+            operation: StateDiffOperation::TokenDiffs(token_diffs),
+        }
+    }
+
+    #[test]
+    fn test_sdl_hash_deterministic() {
+        let generator = SdlGenerator::new();
+        let vault_a = VaultId::from((OwnerId::from(PubKey::generate().hash_sha256()), 0));
+        let vault_b = VaultId::from((OwnerId::from(PubKey::generate().hash_sha256()), 0));
+
+        let diffs = vec![hash_test_diff(vault_a, 1, -1000, 900), hash_test_diff(vault_b, 3, 500, -450)];
+        let rebuilt = vec![hash_test_diff(vault_a, 1, -1000, 900), hash_test_diff(vault_b, 3, 500, -450)];
+
+        let hash = generator.calculate_sdl_hash(&diffs).unwrap();
+        assert_eq!(hash, generator.calculate_sdl_hash(&rebuilt).unwrap());
+        assert_ne!(hash, HashDigest::default());
+    }
+
+    #[test]
+    fn test_sdl_hash_sensitive_to_content() {
+        let generator = SdlGenerator::new();
+        let vault_a = VaultId::from((OwnerId::from(PubKey::generate().hash_sha256()), 0));
+        let vault_b = VaultId::from((OwnerId::from(PubKey::generate().hash_sha256()), 0));
+
+        let diffs = vec![hash_test_diff(vault_a, 1, -1000, 900), hash_test_diff(vault_b, 3, 500, -450)];
+        let hash = generator.calculate_sdl_hash(&diffs).unwrap();
+
+        // Reordering the diffs changes the hash
+        let reordered = vec![hash_test_diff(vault_b, 3, 500, -450), hash_test_diff(vault_a, 1, -1000, 900)];
+        assert_ne!(hash, generator.calculate_sdl_hash(&reordered).unwrap());
+
+        // A single unit change in one amount changes the hash
+        let tweaked = vec![hash_test_diff(vault_a, 1, -1000, 901), hash_test_diff(vault_b, 3, 500, -450)];
+        assert_ne!(hash, generator.calculate_sdl_hash(&tweaked).unwrap());
+
+        // So does a different nonce
+        let renonced = vec![hash_test_diff(vault_a, 2, -1000, 900), hash_test_diff(vault_b, 3, 500, -450)];
+        assert_ne!(hash, generator.calculate_sdl_hash(&renonced).unwrap());
+    }
+
+    #[test]
+    fn test_sdl_hash_uses_canonical_encoding() {
+        let generator = SdlGenerator::new();
+        let vault_id = VaultId::from((OwnerId::from(PubKey::generate().hash_sha256()), 0));
+        let usd = || TokenKind::Fungible(TokenId::new_base(b"USD"));
+        let mut token_diffs = BTreeMap::new();
+        token_diffs.insert(usd(), HoldingsDiff::Fungible(-1000));
+        let diff = StateDiff {
+            vault_id,
+            new_nonce: Some(4),
+            operation: StateDiffOperation::TokenDiffs(token_diffs),
+        };
+
+        let mut hasher = Sha256::new();
+        hasher.update(SDL_HASH_DOMAIN);
+        hasher.update(1u64.to_le_bytes());
+        hash_field(&mut hasher, &bincode::serialize(&vault_id).unwrap());
+        hasher.update([1u8]);
+        hasher.update(4u64.to_le_bytes());
+        hasher.update([0u8]);
+        hasher.update(1u64.to_le_bytes());
+        hash_field(&mut hasher, &bincode::serialize(&usd()).unwrap());
+        hasher.update([0u8]);
+        hasher.update((-1000i64).to_le_bytes());
+        let expected: [u8; 32] = hasher.finalize().into();
+
+        assert_eq!(generator.calculate_sdl_hash(&[diff]).unwrap(), HashDigest::from(expected));
+    }
+
     #[test]
     fn test_batch_processor() {
         let mut processor = SdlBatchProcessor::new(2);
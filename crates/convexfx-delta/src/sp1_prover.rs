@@ -9,12 +9,15 @@
 
 use crate::{DeltaIntegrationError, Result};
 use convexfx_clearing::EpochSolution;
+use convexfx_local_laws::{FillFlows, ObjectiveBreakdown, Tolerances, Violation, Witness};
 use convexfx_types::AssetId;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
 #[cfg(feature = "sp1")]
-use sp1_sdk::{ProverClient, SP1Stdin};
+use sp1_sdk::{ProverClient, SP1ProofWithPublicValues, SP1Stdin};
+#[cfg(not(feature = "sp1"))]
+use sha2::{Digest, Sha256};
 
 // ELF binary of the SP1 program (only needed in production mode)
 #[cfg(feature = "sp1")]
@@ -30,6 +33,9 @@ pub struct ClearingProofInput {
     pub fills: Vec<FillData>,
     pub initial_inventory: Vec<(u8, f64)>,
     pub final_inventory: Vec<(u8, f64)>,
+    /// Asset ID whose log price is pinned at 0 (USD unless configured otherwise)
+    #[serde(default)]
+    pub numeraire: u8,
     pub convergence_achieved: bool,
     pub final_step_norm_y: f64,
     pub final_step_norm_alpha: f64,
@@ -39,6 +45,40 @@ pub struct ClearingProofInput {
     pub total_objective: f64,
 }
 
+impl ClearingProofInput {
+    /// Run the local laws the SP1 program proves, at its tolerances
+    pub fn check_local_laws(&self) -> std::result::Result<(), Violation> {
+        let fills: Vec<FillFlows<'_>> = self.fills.iter()
+            .map(|fill| FillFlows {
+                fill_frac: fill.fill_frac,
+                pay_asset: fill.pay_asset,
+                recv_asset: fill.recv_asset,
+                pay_units: fill.pay_units,
+                recv_units: fill.recv_units,
+                fees_paid: &fill.fees_paid,
+            })
+            .collect();
+        let witness = Witness {
+            converged: self.convergence_achieved,
+            final_step_norm_y: self.final_step_norm_y,
+            final_step_norm_alpha: self.final_step_norm_alpha,
+            y_star: &self.y_star,
+            prices: &self.prices,
+            numeraire: self.numeraire,
+            fills: &fills,
+            initial_inventory: &self.initial_inventory,
+            final_inventory: &self.final_inventory,
+            objective: ObjectiveBreakdown {
+                inventory_risk: self.inventory_risk,
+                price_tracking: self.price_tracking,
+                fill_incentive: self.fill_incentive,
+                total: self.total_objective,
+            },
+        };
+        convexfx_local_laws::check_local_laws(&witness, &Tolerances::DEFAULT)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FillData {
     pub fill_frac: f64,
@@ -46,6 +86,136 @@ pub struct FillData {
     pub recv_asset: u8,
     pub pay_units: f64,
     pub recv_units: f64,
+    /// Fees by asset ID, credited to the pool
+    #[serde(default)]
+    pub fees_paid: Vec<(u8, f64)>,
+}
+
+/// Number of assets, and the length of every dense per-asset array
+pub use convexfx_local_laws::NUM_ASSETS;
+
+/// Compact form of `ClearingProofInput` written to the SP1 stdin
+///
+/// Per-asset values are dense arrays indexed by `AssetId::index`, so the
+/// bincode encoding drops the per-entry asset tag and vector length that the
+/// `(u8, f64)` pairs carry. Assets missing from the solution encode as 0.0.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompactClearingProofInput {
+    pub y_star: [f64; NUM_ASSETS],
+    pub prices: [f64; NUM_ASSETS],
+    pub fills: Vec<CompactFillData>,
+    pub initial_inventory: [f64; NUM_ASSETS],
+    pub final_inventory: [f64; NUM_ASSETS],
+    pub numeraire: u8,
+    pub convergence_achieved: bool,
+    pub final_step_norm_y: f64,
+    pub final_step_norm_alpha: f64,
+    pub inventory_risk: f64,
+    pub price_tracking: f64,
+    pub fill_incentive: f64,
+    pub total_objective: f64,
+}
+
+/// Fill in `CompactClearingProofInput`
+///
+/// Fees stay sparse since a fill usually pays them in a single asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompactFillData {
+    pub fill_frac: f64,
+    pub pay_asset: u8,
+    pub recv_asset: u8,
+    pub pay_units: f64,
+    pub recv_units: f64,
+    pub fees_paid: Vec<(u8, f64)>,
+}
+
+impl CompactClearingProofInput {
+    /// Build the compact input straight from a clearing solution
+    pub fn from_solution(
+        solution: &EpochSolution,
+        initial_inventory: &BTreeMap<AssetId, f64>,
+    ) -> Self {
+        Self {
+            y_star: dense(&solution.y_star),
+            prices: dense(&solution.prices),
+            fills: solution.fills.iter()
+                .map(|fill| CompactFillData {
+                    fill_frac: fill.fill_frac,
+                    pay_asset: fill.pay_asset.index() as u8,
+                    recv_asset: fill.recv_asset.index() as u8,
+                    pay_units: fill.pay_units,
+                    recv_units: fill.recv_units,
+                    fees_paid: fill.fees_paid.iter()
+                        .map(|(asset, fee)| (asset.index() as u8, *fee))
+                        .collect(),
+                })
+                .collect(),
+            initial_inventory: dense(initial_inventory),
+            final_inventory: dense(&solution.q_post),
+            numeraire: solution.numeraire.index() as u8,
+            convergence_achieved: solution.diagnostics.convergence_achieved,
+            final_step_norm_y: solution.diagnostics.final_step_norm_y,
+            final_step_norm_alpha: solution.diagnostics.final_step_norm_alpha,
+            inventory_risk: solution.objective_terms.inventory_risk,
+            price_tracking: solution.objective_terms.price_tracking,
+            fill_incentive: solution.objective_terms.fill_incentive,
+            total_objective: solution.objective_terms.total,
+        }
+    }
+
+    /// Expand back to the `(asset, value)` form, listing every asset
+    pub fn to_input(&self) -> ClearingProofInput {
+        ClearingProofInput {
+            y_star: sparse(&self.y_star),
+            prices: sparse(&self.prices),
+            fills: self.fills.iter()
+                .map(|fill| FillData {
+                    fill_frac: fill.fill_frac,
+                    pay_asset: fill.pay_asset,
+                    recv_asset: fill.recv_asset,
+                    pay_units: fill.pay_units,
+                    recv_units: fill.recv_units,
+                    fees_paid: fill.fees_paid.clone(),
+                })
+                .collect(),
+            initial_inventory: sparse(&self.initial_inventory),
+            final_inventory: sparse(&self.final_inventory),
+            numeraire: self.numeraire,
+            convergence_achieved: self.convergence_achieved,
+            final_step_norm_y: self.final_step_norm_y,
+            final_step_norm_alpha: self.final_step_norm_alpha,
+            inventory_risk: self.inventory_risk,
+            price_tracking: self.price_tracking,
+            fill_incentive: self.fill_incentive,
+            total_objective: self.total_objective,
+        }
+    }
+
+    /// Bincode encoding, the same format `sp1_zkvm::io::read` decodes
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self).map_err(|e| {
+            DeltaIntegrationError::InvalidMessage(format!("Failed to encode proof input: {}", e))
+        })
+    }
+
+    /// Decode a compact input produced by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        bincode::deserialize(bytes).map_err(|e| {
+            DeltaIntegrationError::InvalidMessage(format!("Failed to decode proof input: {}", e))
+        })
+    }
+}
+
+fn dense(values: &BTreeMap<AssetId, f64>) -> [f64; NUM_ASSETS] {
+    let mut out = [0.0; NUM_ASSETS];
+    for (asset, value) in values {
+        out[asset.index()] = *value;
+    }
+    out
+}
+
+fn sparse(values: &[f64; NUM_ASSETS]) -> Vec<(u8, f64)> {
+    values.iter().enumerate().map(|(i, v)| (i as u8, *v)).collect()
 }
 
 /// SP1 Prover for ConvexFX local laws
@@ -112,15 +282,18 @@ impl ConvexFxSp1Prover {
         {
             tracing::info!("Generating SP1 proof for clearing solution (epoch {})", solution.epoch_id);
             
-            // Write input to SP1 stdin
+            // Write the compact input to SP1 stdin to keep the witness small
             let mut stdin = SP1Stdin::new();
-            stdin.write(&input);
+            stdin.write(&CompactClearingProofInput::from_solution(solution, initial_inventory));
             
             // Generate proof
             let (proof, _) = self.client.prove(CONVEXFX_SP1_ELF, stdin)
                 .map_err(|e| DeltaIntegrationError::DeltaSdk(format!("SP1 proof generation failed: {}", e)))?;
             
-            let proof_bytes = proof.bytes();
+            // Keep the public values with the proof so `verify_clearing` can
+            // read back the committed flag
+            let proof_bytes = bincode::serialize(&proof)
+                .map_err(|e| DeltaIntegrationError::DeltaSdk(format!("SP1 proof encoding failed: {}", e)))?;
             tracing::info!("SP1 proof generated successfully ({} bytes)", proof_bytes.len());
             Ok(proof_bytes)
         }
@@ -128,10 +301,76 @@ impl ConvexFxSp1Prover {
         #[cfg(not(feature = "sp1"))]
         {
             tracing::debug!("Returning mock proof (64 bytes) - use --features sp1 for production");
-            Ok(vec![0u8; 64])
+            Ok(self.mock_proof(&input))
+        }
+    }
+    
+    /// Verify a proof from `prove_clearing` against `expected_vkey`
+    ///
+    /// Returns the success flag the program committed to its public output.
+    /// Fails if the vkey is not this program's or the proof does not verify.
+    pub fn verify_clearing(&self, proof: &[u8], expected_vkey: &[u8]) -> Result<bool> {
+        if self.get_vkey() != expected_vkey {
+            return Err(DeltaIntegrationError::ProofVerification(
+                "verification key does not match the ConvexFX local laws program".to_string()
+            ));
+        }
+
+        #[cfg(feature = "sp1")]
+        {
+            let mut proof: SP1ProofWithPublicValues = bincode::deserialize(proof)
+                .map_err(|e| DeltaIntegrationError::ProofVerification(format!("malformed proof: {}", e)))?;
+            let (_, vkey) = self.client.setup(CONVEXFX_SP1_ELF);
+            self.client.verify(&proof, &vkey)
+                .map_err(|e| DeltaIntegrationError::ProofVerification(e.to_string()))?;
+            Ok(proof.public_values.read::<bool>())
+        }
+
+        #[cfg(not(feature = "sp1"))]
+        {
+            if proof.len() != 64 {
+                return Err(DeltaIntegrationError::ProofVerification(format!(
+                    "expected a 64 byte mock proof, got {} bytes", proof.len()
+                )));
+            }
+            let (input_digest, tag) = proof.split_at(32);
+            [true, false]
+                .into_iter()
+                .find(|&committed| Self::mock_tag(expected_vkey, input_digest, committed) == tag)
+                .ok_or_else(|| DeltaIntegrationError::ProofVerification(
+                    "mock proof tag does not match".to_string()
+                ))
         }
     }
     
+    /// Deterministic stub proof bound to the proof input
+    ///
+    /// A domain-separated SHA256 digest of the JSON-encoded input, so
+    /// identical inputs give identical stubs and any change to the input
+    /// changes the stub, followed by a tag over the vkey, that digest and
+    /// the committed success flag for `verify_clearing` to check.
+    #[cfg(not(feature = "sp1"))]
+    fn mock_proof(&self, input: &ClearingProofInput) -> Vec<u8> {
+        let encoded = serde_json::to_vec(input).unwrap_or_default();
+        let mut hasher = Sha256::new();
+        hasher.update(b"convexfx-mock-proof-0");
+        hasher.update(&encoded);
+        let mut proof = hasher.finalize().to_vec();
+        let tag = Self::mock_tag(&self.get_vkey(), &proof, true);
+        proof.extend_from_slice(&tag);
+        proof
+    }
+
+    #[cfg(not(feature = "sp1"))]
+    fn mock_tag(vkey: &[u8], input_digest: &[u8], committed: bool) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(b"convexfx-mock-proof-1");
+        hasher.update(vkey);
+        hasher.update(input_digest);
+        hasher.update([committed as u8]);
+        hasher.finalize().to_vec()
+    }
+
     /// Prepare input data for the SP1 program from clearing solution
     fn prepare_input(
         &self,
@@ -152,6 +391,9 @@ impl ConvexFxSp1Prover {
                     recv_asset: fill.recv_asset.index() as u8,
                     pay_units: fill.pay_units,
                     recv_units: fill.recv_units,
+                    fees_paid: fill.fees_paid.iter()
+                        .map(|(asset, fee)| (asset.index() as u8, *fee))
+                        .collect(),
                 })
                 .collect(),
             initial_inventory: initial_inventory.iter()
@@ -160,6 +402,7 @@ impl ConvexFxSp1Prover {
             final_inventory: solution.q_post.iter()
                 .map(|(asset, qty)| (asset.index() as u8, *qty))
                 .collect(),
+            numeraire: solution.numeraire.index() as u8,
             convergence_achieved: solution.diagnostics.convergence_achieved,
             final_step_norm_y: solution.diagnostics.final_step_norm_y,
             final_step_norm_alpha: solution.diagnostics.final_step_norm_alpha,
@@ -171,32 +414,12 @@ impl ConvexFxSp1Prover {
     }
     
     /// Validate input locally before proving
-    /// This catches errors early without expensive ZKP generation
+    ///
+    /// Runs the same checks as the SP1 program, so an input that passes here
+    /// does not fail in the zkVM after an expensive proving run.
     fn validate_input(&self, input: &ClearingProofInput) -> Result<()> {
-        const TOLERANCE_Y: f64 = 1e-5;
-        const TOLERANCE_ALPHA: f64 = 1e-6;
-        
-        if !input.convergence_achieved {
-            return Err(DeltaIntegrationError::ClearingFailed(
-                "SCP algorithm did not converge".to_string()
-            ));
-        }
-        
-        if input.final_step_norm_y >= TOLERANCE_Y {
-            return Err(DeltaIntegrationError::ClearingFailed(format!(
-                "Price step norm {} exceeds tolerance {}",
-                input.final_step_norm_y, TOLERANCE_Y
-            )));
-        }
-        
-        if input.final_step_norm_alpha >= TOLERANCE_ALPHA {
-            return Err(DeltaIntegrationError::ClearingFailed(format!(
-                "Fill step norm {} exceeds tolerance {}",
-                input.final_step_norm_alpha, TOLERANCE_ALPHA
-            )));
-        }
-        
-        Ok(())
+        input.check_local_laws()
+            .map_err(|violation| DeltaIntegrationError::ClearingFailed(violation.to_string()))
     }
 }
 
@@ -209,7 +432,7 @@ impl Default for ConvexFxSp1Prover {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use convexfx_clearing::{Diagnostics, ObjectiveTerms};
+    use convexfx_clearing::{Diagnostics, ObjectiveTerms, ObjectiveWeights, QpStatus};
     
 
     fn create_test_solution() -> EpochSolution {
@@ -235,14 +458,22 @@ mod tests {
                 price_tracking: 50.0,
                 fill_incentive: -20.0,
                 total: 130.0,
+                weights: ObjectiveWeights::default(),
             },
             diagnostics: Diagnostics {
                 iterations: 3,
                 convergence_achieved: true,
                 final_step_norm_y: 1e-6,
                 final_step_norm_alpha: 1e-7,
-                qp_status: "Optimal".to_string(),
+                qp_status: QpStatus::Optimal,
+                solve_time_ms: 0,
+                iteration_times_us: Vec::new(),
+                binding_constraints: Vec::new(),
+                objective_trajectory: Vec::new(),
+                max_coherence_error_bps: 0.0,
+                qp_backend: "clarabel".to_string(),
             },
+            numeraire: AssetId::USD,
         }
     }
 
@@ -286,6 +517,92 @@ mod tests {
         assert_eq!(proof.len(), 64);
     }
 
+    #[cfg(not(feature = "sp1"))]
+    #[test]
+    fn test_mock_proof_bound_to_input() {
+        use convexfx_types::Fill;
+
+        let prover = ConvexFxSp1Prover::new();
+        let mut initial_inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            initial_inventory.insert(*asset, 10000.0);
+        }
+
+        let fill = |pay_units: f64| Fill {
+            order_id: "order_1".to_string(),
+            fill_frac: pay_units / 100.0,
+            pay_asset: AssetId::USD,
+            recv_asset: AssetId::EUR,
+            pay_units,
+            recv_units: pay_units / 1.1,
+            fees_paid: BTreeMap::new(),
+        };
+
+        // Solutions whose inventory reflects the fill, so the local laws hold
+        let with_fill = |fill: Fill| {
+            let mut solution = create_test_solution();
+            for (asset, delta) in fill.inventory_deltas() {
+                *solution.q_post.get_mut(&asset).unwrap() += delta;
+            }
+            solution.fills.push(fill);
+            solution
+        };
+        let first = with_fill(fill(50.0));
+        let second = with_fill(fill(75.0));
+
+        let proof_first = prover.prove_clearing(&first, &initial_inventory).unwrap();
+        let proof_again = prover.prove_clearing(&first, &initial_inventory).unwrap();
+        let proof_second = prover.prove_clearing(&second, &initial_inventory).unwrap();
+
+        assert_eq!(proof_first.len(), 64);
+        assert_eq!(proof_first, proof_again);
+        assert_ne!(proof_first, proof_second);
+    }
+
+    #[test]
+    fn test_compact_input_is_smaller_and_roundtrips() {
+        use convexfx_types::Fill;
+
+        let prover = ConvexFxSp1Prover::new();
+        let mut solution = create_test_solution();
+        for i in 0..20 {
+            solution.fills.push(Fill {
+                order_id: format!("order_{}", i),
+                fill_frac: 0.5,
+                pay_asset: AssetId::USD,
+                recv_asset: AssetId::EUR,
+                pay_units: 50.0,
+                recv_units: 45.0,
+                fees_paid: [(AssetId::USD, 0.05)].into_iter().collect(),
+            });
+        }
+        let mut initial_inventory = BTreeMap::new();
+        for asset in AssetId::all() {
+            initial_inventory.insert(*asset, 10000.0);
+        }
+
+        let input = prover.prepare_input(&solution, &initial_inventory);
+        let compact = CompactClearingProofInput::from_solution(&solution, &initial_inventory);
+
+        let verbose_len = bincode::serialize(&input).unwrap().len();
+        let bytes = compact.to_bytes().unwrap();
+        assert!(
+            bytes.len() < verbose_len,
+            "compact {} bytes vs verbose {} bytes",
+            bytes.len(),
+            verbose_len
+        );
+
+        let decoded = CompactClearingProofInput::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, compact);
+        assert_eq!(
+            serde_json::to_value(decoded.to_input()).unwrap(),
+            serde_json::to_value(&input).unwrap()
+        );
+
+        assert!(CompactClearingProofInput::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
     #[test]
     fn test_validate_input_convergence_failure() {
         let prover = ConvexFxSp1Prover::new();
@@ -22,6 +22,8 @@ fn create_test_orders() -> Vec<PairOrder> {
             budget: Amount::from_units(1000),
             limit_ratio: Some(1.1),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
         PairOrder {
@@ -32,6 +34,8 @@ fn create_test_orders() -> Vec<PairOrder> {
             budget: Amount::from_units(500),
             limit_ratio: Some(1.2),
             min_fill_fraction: Some(0.8),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
     ]
@@ -108,6 +112,8 @@ async fn test_predicate_with_demo_app() {
         budget: Amount::from_units(1000),
         limit_ratio: Some(1.1),
         min_fill_fraction: Some(0.5),
+        max_slippage_bps: None,
+        submission_mid: None,
         metadata: serde_json::json!({}),
     }];
 
@@ -169,6 +175,8 @@ async fn test_predicate_large_order_batch() {
             budget: Amount::from_units(100 + i * 10),
             limit_ratio: Some(1.1),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         });
     }
@@ -214,6 +222,8 @@ async fn test_predicate_multi_asset_trading() {
             budget: Amount::from_units(1000),
             limit_ratio: Some(1.1),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
         PairOrder {
@@ -224,6 +234,8 @@ async fn test_predicate_multi_asset_trading() {
             budget: Amount::from_units(500),
             limit_ratio: Some(1.2),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
         PairOrder {
@@ -234,6 +246,8 @@ async fn test_predicate_multi_asset_trading() {
             budget: Amount::from_units(300),
             limit_ratio: Some(1.15),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
     ];
@@ -436,6 +450,8 @@ async fn test_predicate_with_partial_fills() {
         budget: Amount::from_units(50000), // Large order
         limit_ratio: Some(1.05), // Tight limit
         min_fill_fraction: Some(0.1), // Low minimum
+        max_slippage_bps: None,
+        submission_mid: None,
         metadata: serde_json::json!({}),
     }];
 
@@ -31,6 +31,8 @@ async fn test_complete_sdl_generation_flow() {
             budget: Amount::from_units(1000),
             limit_ratio: Some(1.1),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"test": "alice_trade"}),
         },
         PairOrder {
@@ -41,6 +43,8 @@ async fn test_complete_sdl_generation_flow() {
             budget: Amount::from_units(500),
             limit_ratio: Some(1.2),
             min_fill_fraction: Some(0.8),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"test": "bob_trade"}),
         },
     ];
@@ -142,6 +146,8 @@ async fn test_multiple_users_trading() {
             budget: Amount::from_units(1000),
             limit_ratio: Some(1.1),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
         PairOrder {
@@ -152,6 +158,8 @@ async fn test_multiple_users_trading() {
             budget: Amount::from_units(800),
             limit_ratio: Some(1.2),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
         PairOrder {
@@ -162,6 +170,8 @@ async fn test_multiple_users_trading() {
             budget: Amount::from_units(50000),
             limit_ratio: Some(1.3),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
     ];
@@ -75,6 +75,8 @@ async fn test_sp1_proof_generation_valid_clearing() {
             budget: Amount::from_units(1000),
             limit_ratio: Some(1.1),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
     ];
@@ -117,6 +119,8 @@ async fn test_sp1_proof_reject_non_convergent() {
             budget: Amount::from_units(1000),
             limit_ratio: Some(1.1),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
     ];
@@ -152,6 +156,8 @@ async fn test_sp1_proof_reject_high_step_norm() {
             budget: Amount::from_units(1000),
             limit_ratio: Some(1.1),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
     ];
@@ -183,6 +189,8 @@ async fn test_sp1_with_demo_app() {
         budget: Amount::from_units(1000),
         limit_ratio: Some(1.1),
         min_fill_fraction: Some(0.5),
+        max_slippage_bps: None,
+        submission_mid: None,
         metadata: serde_json::json!({}),
     }];
     
@@ -200,6 +208,52 @@ async fn test_sp1_with_demo_app() {
     println!("   State diffs: {}", state_diffs.len());
 }
 
+#[test]
+fn test_sp1_verify_clearing_proof() {
+    let clearing_engine = ScpClearing::new();
+    let inventory = create_initial_inventory();
+    let orders = vec![PairOrder {
+        id: "verify_test".to_string(),
+        trader: "alice".to_string().into(),
+        pay: AssetId::USD,
+        receive: AssetId::EUR,
+        budget: Amount::from_units(1000),
+        limit_ratio: Some(1.1),
+        min_fill_fraction: None,
+        max_slippage_bps: None,
+        submission_mid: None,
+        metadata: serde_json::json!({}),
+    }];
+    let instance = EpochInstance::new(
+        1,
+        inventory.clone(),
+        orders,
+        create_ref_prices(),
+        RiskParams::default_demo(),
+    );
+    let solution = clearing_engine.clear_epoch(&instance).unwrap();
+
+    let prover = ConvexFxSp1Prover::new();
+    let vkey = prover.get_vkey();
+    let proof = prover.prove_clearing(&solution, &inventory).unwrap();
+
+    // A valid proof verifies and commits the success flag
+    assert!(prover.verify_clearing(&proof, &vkey).unwrap());
+
+    // Flipping any byte breaks verification
+    let mut corrupted = proof.clone();
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 0x01;
+    assert!(prover.verify_clearing(&corrupted, &vkey).is_err());
+
+    // So does checking against another program's vkey
+    let mut other_vkey = vkey.clone();
+    other_vkey[0] ^= 0x01;
+    assert!(prover.verify_clearing(&proof, &other_vkey).is_err());
+
+    println!("✅ SP1 proof verifies against the program vkey");
+}
+
 #[tokio::test]
 async fn test_sp1_proof_empty_batch() {
     let clearing_engine = ScpClearing::new();
@@ -239,6 +293,8 @@ async fn test_sp1_proof_large_batch() {
             budget: Amount::from_units(100 + i * 10),
             limit_ratio: Some(1.1),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         });
     }
@@ -271,6 +327,8 @@ async fn test_sp1_proof_multi_asset() {
             budget: Amount::from_units(1000),
             limit_ratio: Some(1.1),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
         PairOrder {
@@ -281,6 +339,8 @@ async fn test_sp1_proof_multi_asset() {
             budget: Amount::from_units(500),
             limit_ratio: Some(1.2),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
         PairOrder {
@@ -291,6 +351,8 @@ async fn test_sp1_proof_multi_asset() {
             budget: Amount::from_units(300),
             limit_ratio: Some(1.15),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
     ];
@@ -316,6 +378,7 @@ fn test_clearing_proof_input_serialization() {
         fills: vec![],
         initial_inventory: vec![(0, 10000.0), (1, 10000.0)],
         final_inventory: vec![(0, 10000.0), (1, 10000.0)],
+        numeraire: 0,
         convergence_achieved: true,
         final_step_norm_y: 1e-6,
         final_step_norm_alpha: 1e-7,
@@ -350,6 +413,8 @@ async fn test_sp1_proof_determinism() {
         budget: Amount::from_units(1000),
         limit_ratio: Some(1.1),
         min_fill_fraction: Some(0.5),
+        max_slippage_bps: None,
+        submission_mid: None,
         metadata: serde_json::json!({}),
     }];
     
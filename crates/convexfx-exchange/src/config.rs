@@ -1,9 +1,25 @@
 use serde::{Deserialize, Serialize};
+use convexfx_clearing::ScpClearing;
 use convexfx_risk::RiskParams;
 use convexfx_types::AssetId;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{ExchangeError, Result};
+
+/// Oracle tracking weight applied to every asset during warm-up
+pub const WARMUP_TRACKING_WEIGHT: f64 = 1e5;
+
+/// Price band (bps around the oracle) applied during warm-up
+pub const WARMUP_PRICE_BAND_BPS: f64 = 5.0;
 
 /// Configuration for the exchange
+///
+/// Fields missing from a config file take their `Default` values;
+/// `risk_parameters` and each `initial_assets` entry must be given in full.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ExchangeConfig {
     /// How often to run clearing batches (in seconds)
     pub batch_interval_seconds: u64,
@@ -26,13 +42,161 @@ pub struct ExchangeConfig {
     /// Solver backend to use for clearing
     pub solver_backend: SolverBackend,
 
+    /// Maximum age of oracle prices (in milliseconds) accepted for clearing
+    pub max_price_age_ms: u64,
+
+    /// Fills with adverse slippage above this cap (bps vs oracle mid) are
+    /// zeroed after clearing. `None` disables the check.
+    pub max_fill_slippage_bps: Option<f64>,
+
+    /// Per-epoch cap on the gross flow of an asset (units paid in plus paid
+    /// out), valued in the numeraire at the cleared prices. Fills are scaled
+    /// down to respect it; assets without an entry are unlimited.
+    pub max_epoch_notional: BTreeMap<AssetId, f64>,
+
+    /// Fee (bps) charged on the receive asset of each fill, by asset. The
+    /// fee is withheld from what the trader receives; assets without an
+    /// entry are free.
+    pub fee_bps: BTreeMap<AssetId, f64>,
+
+    /// Ledger account that collects fee revenue
+    pub fee_account: String,
+
+    /// Linear prices to clear against instead of the oracle, for
+    /// reproducing a specific regime (see `EpochInstance::with_fixed_prices`).
+    /// Empty uses the oracle; otherwise every non-numeraire asset must be listed.
+    pub fixed_prices: BTreeMap<AssetId, f64>,
+
     /// Risk management parameters
     pub risk_parameters: RiskParams,
 
+    /// Number of initial batches that clear with oracle tracking forced to
+    /// `WARMUP_TRACKING_WEIGHT` and the price band narrowed to
+    /// `WARMUP_PRICE_BAND_BPS`, so a thinly funded pool quotes without
+    /// moving prices. `risk_parameters` applies unchanged afterwards.
+    #[serde(default)]
+    pub warmup_epochs: u64,
+
+    /// Orders each trader may have pending in one epoch; `None` is
+    /// unlimited
+    #[serde(default)]
+    pub max_orders_per_trader_per_epoch: Option<usize>,
+
+    /// Number of most recent batches whose cleared prices are kept for
+    /// `Exchange::get_prices_at`
+    pub price_history_epochs: usize,
+
     /// Initial assets to set up when exchange starts
     pub initial_assets: Vec<InitialAsset>,
 }
 
+impl ExchangeConfig {
+    /// Parse a config from TOML and check it is usable
+    pub fn from_toml_str(toml: &str) -> Result<Self> {
+        let mut config: ExchangeConfig = toml::from_str(toml)
+            .map_err(|e| ExchangeError::Config(format!("Invalid exchange config: {}", e)))?;
+        config.risk_parameters.rebuild_matrices()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load a config from a TOML file (see `from_toml_str`)
+    pub fn from_toml_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let toml = std::fs::read_to_string(path).map_err(|e| {
+            ExchangeError::Config(format!("Cannot read config file {}: {}", path.display(), e))
+        })?;
+        Self::from_toml_str(&toml)
+    }
+
+    /// Reject `max_epoch_notional` caps that are negative or not finite
+    pub fn check_notional_caps(&self) -> Result<()> {
+        for (asset, cap) in &self.max_epoch_notional {
+            if !(cap.is_finite() && *cap >= 0.0) {
+                return Err(ExchangeError::InvalidArgument(format!(
+                    "max_epoch_notional for {} must be a non-negative number, got {}",
+                    asset, cap
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject settings the exchange cannot run with
+    pub fn validate(&self) -> Result<()> {
+        if self.batch_interval_seconds == 0 {
+            return Err(ExchangeError::Config("batch_interval_seconds must be positive".to_string()));
+        }
+
+        self.check_notional_caps()?;
+
+        for (asset, bps) in &self.fee_bps {
+            if !(bps.is_finite() && (0.0..10_000.0).contains(bps)) {
+                return Err(ExchangeError::Config(format!(
+                    "fee_bps for {} must be in [0, 10000), got {}",
+                    asset, bps
+                )));
+            }
+        }
+        if self.fee_account.is_empty() || self.fee_account == crate::POOL_ACCOUNT {
+            return Err(ExchangeError::Config(format!(
+                "fee_account must be a non-empty account other than {}",
+                crate::POOL_ACCOUNT
+            )));
+        }
+
+        let n_assets = AssetId::all().len();
+        let risk = &self.risk_parameters;
+        for (name, len) in [("gamma_diag", risk.gamma_diag.len()), ("w_diag", risk.w_diag.len())] {
+            if len != n_assets {
+                return Err(ExchangeError::Config(format!(
+                    "risk_parameters.{} has {} entries, expected one per asset ({})",
+                    name, len, n_assets
+                )));
+            }
+        }
+
+        for asset in &self.initial_assets {
+            if AssetId::from_str(&asset.symbol).is_none() {
+                return Err(ExchangeError::Config(format!(
+                    "initial_assets: unknown asset symbol {}",
+                    asset.symbol
+                )));
+            }
+            if !(asset.initial_price.is_finite() && asset.initial_price > 0.0) {
+                return Err(ExchangeError::Config(format!(
+                    "initial_assets: {} has invalid initial_price {}",
+                    asset.symbol, asset.initial_price
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fee withheld from `recv_units` of `asset` received in a fill
+    pub fn fee_on(&self, asset: AssetId, recv_units: f64) -> f64 {
+        recv_units * self.fee_bps.get(&asset).copied().unwrap_or(0.0) / 10000.0
+    }
+
+    /// Risk parameters for the batch cleared after `batches_cleared` earlier ones
+    ///
+    /// Within the warm-up period the tracking weights are raised to at least
+    /// `WARMUP_TRACKING_WEIGHT` and the price band is capped at
+    /// `WARMUP_PRICE_BAND_BPS`; after it the configured parameters are used.
+    pub fn risk_parameters_for_batch(&self, batches_cleared: u64) -> Result<RiskParams> {
+        let mut risk = self.risk_parameters.clone();
+        if batches_cleared < self.warmup_epochs {
+            for w in risk.w_diag.iter_mut() {
+                *w = w.max(WARMUP_TRACKING_WEIGHT);
+            }
+            risk.price_band_bps = risk.price_band_bps.min(WARMUP_PRICE_BAND_BPS);
+        }
+        risk.rebuild_matrices()?;
+        Ok(risk)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InitialAsset {
     pub symbol: String,
@@ -44,11 +208,47 @@ pub struct InitialAsset {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SolverBackend {
+    /// Clarabel interior-point solver (default)
     Clarabel,
+    /// OSQP ADMM solver, requires the `osqp` feature
     OSQP,
+    /// Projected-gradient solver, for debugging only
     Simple,
 }
 
+impl SolverBackend {
+    /// SCP clearing engine running on this backend
+    pub fn clearing_engine(&self) -> Result<ScpClearing> {
+        match self {
+            SolverBackend::Simple => Ok(ScpClearing::with_simple_solver()),
+            SolverBackend::Clarabel => Ok(ScpClearing::with_clarabel()),
+            #[cfg(feature = "osqp")]
+            SolverBackend::OSQP => Ok(ScpClearing::with_osqp_solver()),
+            #[cfg(not(feature = "osqp"))]
+            SolverBackend::OSQP => Err(ExchangeError::Config(
+                "OSQP backend requires the `osqp` feature".to_string(),
+            )),
+        }
+    }
+}
+
+impl FromStr for SolverBackend {
+    type Err = ExchangeError;
+
+    /// Parse a backend name, ignoring case: `clarabel`, `osqp` or `simple`
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "clarabel" => Ok(SolverBackend::Clarabel),
+            "osqp" => Ok(SolverBackend::OSQP),
+            "simple" => Ok(SolverBackend::Simple),
+            other => Err(ExchangeError::InvalidArgument(format!(
+                "Unknown solver backend '{}' (expected simple, clarabel or osqp)",
+                other
+            ))),
+        }
+    }
+}
+
 impl Default for ExchangeConfig {
     fn default() -> Self {
         Self {
@@ -58,7 +258,13 @@ impl Default for ExchangeConfig {
             websocket_port: 8080,
             enable_api_server: true,
             api_port: 3000,
-            solver_backend: SolverBackend::OSQP,
+            solver_backend: SolverBackend::Clarabel,
+            max_price_age_ms: 60_000, // Refuse to clear on prices older than a minute
+            max_fill_slippage_bps: None,
+            max_epoch_notional: BTreeMap::new(),
+            fee_bps: BTreeMap::new(),
+            fee_account: "fees".to_string(),
+            fixed_prices: BTreeMap::new(),
             risk_parameters: {
                 let mut risk = RiskParams::default_demo();
                 // Use more balanced parameters for stability
@@ -71,9 +277,12 @@ impl Default for ExchangeConfig {
                 risk.w_diag = vec![100.0; 6]; // Moderate oracle tracking
                 risk.eta = 1.0;
                 // Keep default price_band_bps for compatibility
-                risk.rebuild_matrices();
+                risk.rebuild_matrices().expect("diagonal risk matrices always rebuild");
                 risk
             },
+            warmup_epochs: 0,
+            max_orders_per_trader_per_epoch: None,
+            price_history_epochs: 1000,
             initial_assets: vec![
                 InitialAsset {
                     symbol: "USD".to_string(),
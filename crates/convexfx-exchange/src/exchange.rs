@@ -1,11 +1,16 @@
-use crate::{ExchangeConfig, ExchangeState, ExchangeError, Result, SystemStatus};
+use crate::{ExchangeConfig, ExchangeState, ExchangeError, Result, StateSnapshot, SystemStatus};
 use convexfx_types::{AccountId, AssetId, Amount, PairOrder, OrderId, Fill, EpochId};
-use convexfx_clearing::EpochInstance;
-use convexfx_oracle::Oracle;
+use convexfx_clearing::{EpochInstance, EpochSolution};
+use convexfx_oracle::{Oracle, RefPrices};
+use convexfx_risk::RiskParams;
 use convexfx_ledger::Ledger;
 use std::collections::BTreeMap;
+use std::path::Path;
 use chrono::{DateTime, Utc};
 
+/// Ledger account holding the pool side of every settled trade
+pub const POOL_ACCOUNT: &str = "pool";
+
 /// Main exchange abstraction that provides simple APIs for operating the exchange
 pub struct Exchange {
     state: ExchangeState,
@@ -25,6 +30,13 @@ impl Exchange {
         Ok(Self { state, config })
     }
 
+    /// Create a new exchange backed by a specific oracle
+    pub fn with_oracle(config: ExchangeConfig, oracle: convexfx_oracle::MockOracle) -> Result<Self> {
+        let mut exchange = Self::new(config)?;
+        exchange.state.oracle = oracle;
+        Ok(exchange)
+    }
+
     /// Start the exchange (begin periodic batch processing)
     pub async fn start(&mut self) -> Result<()> {
         self.state.is_running = true;
@@ -47,100 +59,75 @@ impl Exchange {
     }
 
     /// Add a new asset/currency to the exchange
+    ///
+    /// The asset is recorded in the oracle's registry. Only assets the
+    /// clearing engine supports receive an oracle price.
     pub fn add_asset(
         &mut self,
         symbol: &str,
         name: &str,
-        _decimals: u32,
-        _is_base_currency: bool,
-        _initial_price: f64,
+        decimals: u32,
+        is_base_currency: bool,
+        initial_price: f64,
     ) -> Result<()> {
-        // TODO: In a full implementation, we would add the asset to the oracle
-        // For now, just log that the asset was added
+        self.state.oracle.registry.lock().unwrap()
+            .add_asset(symbol.to_string(), name.to_string(), decimals, is_base_currency)
+            .map_err(ExchangeError::InvalidArgument)?;
+
+        if let Some(asset_id) = AssetId::from_str(symbol) {
+            self.state.oracle.set_price(asset_id, initial_price);
+        }
+
         println!("✅ Added asset: {} ({})", symbol, name);
         Ok(())
     }
 
     /// Remove an asset from the exchange (only if no liquidity)
     pub fn remove_asset(&mut self, symbol: &str) -> Result<()> {
-        let asset_id = AssetId::from_str(symbol)
-            .ok_or_else(|| ExchangeError::NotFound(format!("Asset {} not found", symbol)))?;
+        if self.state.oracle.registry.lock().unwrap().get_asset_info(symbol).is_none() {
+            return Err(ExchangeError::NotFound(format!("Asset {} not found", symbol)));
+        }
 
         // Check if there's any liquidity for this asset
-        let inventory = self.state.ledger.inventory();
-        let amount = inventory.get(asset_id);
-        if amount.to_f64() > 0.0 {
-            return Err(ExchangeError::InvalidArgument(
-                format!("Cannot remove asset {} - still has liquidity", symbol)
-            ));
+        if let Some(asset_id) = AssetId::from_str(symbol) {
+            let inventory = self.state.ledger.inventory();
+            let amount = inventory.get(asset_id);
+            if amount.to_f64() > 0.0 {
+                return Err(ExchangeError::InvalidArgument(
+                    format!("Cannot remove asset {} - still has liquidity", symbol)
+                ));
+            }
         }
 
-        // TODO: Implement asset removal in oracle
+        self.state.oracle.registry.lock().unwrap()
+            .remove_asset(symbol)
+            .map_err(ExchangeError::NotFound)?;
         println!("✅ Removed asset: {}", symbol);
         Ok(())
     }
 
     /// List all available assets
     pub fn list_assets(&self) -> Result<Vec<AssetInfo>> {
-        // For now, return hardcoded assets that are available in the system
-        let mut assets = Vec::new();
-
-        // USD
-        assets.push(AssetInfo {
-            symbol: "USD".to_string(),
-            name: "US Dollar".to_string(),
-            decimals: 2,
-            is_base_currency: true,
-            current_price: Some(1.0),
-        });
-
-        // EUR
-        assets.push(AssetInfo {
-            symbol: "EUR".to_string(),
-            name: "Euro".to_string(),
-            decimals: 2,
-            is_base_currency: false,
-            current_price: Some(1.05),
-        });
-
-        // JPY
-        assets.push(AssetInfo {
-            symbol: "JPY".to_string(),
-            name: "Japanese Yen".to_string(),
-            decimals: 0,
-            is_base_currency: false,
-            current_price: Some(0.009),
-        });
-
-        Ok(assets)
+        let symbols = self.state.oracle.registry.lock().unwrap().get_all_assets();
+        symbols.iter().map(|symbol| self.get_asset_info(symbol)).collect()
     }
 
     /// Get information about a specific asset
+    ///
+    /// `current_price` is `None` for assets without an oracle price.
     pub fn get_asset_info(&self, symbol: &str) -> Result<AssetInfo> {
-        match symbol {
-            "USD" => Ok(AssetInfo {
-                symbol: "USD".to_string(),
-                name: "US Dollar".to_string(),
-                decimals: 2,
-                is_base_currency: true,
-                current_price: Some(1.0),
-            }),
-            "EUR" => Ok(AssetInfo {
-                symbol: "EUR".to_string(),
-                name: "Euro".to_string(),
-                decimals: 2,
-                is_base_currency: false,
-                current_price: Some(1.05),
-            }),
-            "JPY" => Ok(AssetInfo {
-                symbol: "JPY".to_string(),
-                name: "Japanese Yen".to_string(),
-                decimals: 0,
-                is_base_currency: false,
-                current_price: Some(0.009),
-            }),
-            _ => Err(ExchangeError::NotFound(format!("Asset {} not found", symbol))),
-        }
+        let info = self.state.oracle.registry.lock().unwrap()
+            .get_asset_info(symbol)
+            .cloned()
+            .ok_or_else(|| ExchangeError::NotFound(format!("Asset {} not found", symbol)))?;
+
+        Ok(AssetInfo {
+            symbol: symbol.to_string(),
+            name: info.name,
+            decimals: info.decimals,
+            is_base_currency: info.is_base_currency,
+            current_price: self.get_asset_price(symbol).ok(),
+        })
     }
 
     /// Add liquidity to the exchange (LP deposits assets)
@@ -158,7 +145,7 @@ impl Exchange {
         let new_balance = self.state.ledger.balance(&account, asset_id);
 
         println!("✅ Added liquidity: {} {} for account {}",
-                 amount, asset_symbol, account_id);
+                 amount_obj.to_display_string(asset_id), asset_symbol, account_id);
 
         Ok(LiquidityUpdate {
             account_id: account_id.to_string(),
@@ -168,6 +155,44 @@ impl Exchange {
         })
     }
 
+    /// Add liquidity for several assets in one call
+    ///
+    /// All amounts are validated before anything is deposited, and the ledger
+    /// is rolled back if any deposit fails, so either every asset is credited
+    /// or none is. Returns the account's updated balances.
+    pub fn add_liquidity_multi(
+        &mut self,
+        account_id: &str,
+        amounts: BTreeMap<AssetId, f64>,
+    ) -> Result<BTreeMap<AssetId, f64>> {
+        let account = AccountId::new(account_id.to_string());
+
+        let mut deposits = Vec::with_capacity(amounts.len());
+        for (asset_id, amount) in &amounts {
+            if *amount < 0.0 {
+                return Err(ExchangeError::InvalidArgument(format!(
+                    "Invalid amount for {}: {} is negative",
+                    asset_id, amount
+                )));
+            }
+            let amount_obj = Amount::from_f64(*amount).map_err(|e| {
+                ExchangeError::InvalidArgument(format!("Invalid amount for {}: {}", asset_id, e))
+            })?;
+            deposits.push((*asset_id, amount_obj));
+        }
+
+        self.state.ledger.with_transaction(|tx| {
+            deposits
+                .into_iter()
+                .try_for_each(|(asset_id, amount_obj)| tx.deposit(&account, asset_id, amount_obj))
+        })?;
+
+        println!("✅ Added liquidity in {} assets for account {}",
+                 amounts.len(), account_id);
+
+        Ok(self.state.ledger.account_balances(&account).to_f64_map())
+    }
+
     /// Remove liquidity from the exchange (LP withdraws assets)
     pub fn remove_liquidity(&mut self, account_id: &str, asset_symbol: &str, amount: f64) -> Result<LiquidityUpdate> {
         let account = AccountId::new(account_id.to_string());
@@ -190,7 +215,7 @@ impl Exchange {
         let new_balance = self.state.ledger.balance(&account, asset_id);
 
         println!("✅ Removed liquidity: {} {} from account {}",
-                 amount, asset_symbol, account_id);
+                 amount_obj.to_display_string(asset_id), asset_symbol, account_id);
 
         Ok(LiquidityUpdate {
             account_id: account_id.to_string(),
@@ -228,6 +253,12 @@ impl Exchange {
     }
 
     /// Submit a trade order
+    ///
+    /// The current reference mid is recorded on the order; `max_slippage_bps`
+    /// is measured against it when the batch clears. Stale oracle prices
+    /// refuse the order as they would refuse the batch. A trader already
+    /// holding `max_orders_per_trader_per_epoch` pending orders is refused.
+    #[allow(clippy::too_many_arguments)]
     pub fn submit_order(
         &mut self,
         trader_id: &str,
@@ -236,6 +267,7 @@ impl Exchange {
         budget: f64,
         limit_ratio: Option<f64>,
         min_fill_fraction: Option<f64>,
+        max_slippage_bps: Option<f64>,
     ) -> Result<OrderSubmission> {
         let trader = AccountId::new(trader_id.to_string());
         let pay_asset_id = AssetId::from_str(pay_asset)
@@ -246,16 +278,11 @@ impl Exchange {
         let budget_amount = Amount::from_f64(budget)
             .map_err(|e| ExchangeError::InvalidArgument(format!("Invalid budget: {}", e)))?;
 
-        // Check if trader has sufficient balance
-        if !self.state.ledger.has_sufficient(&trader, pay_asset_id, budget_amount) {
-            return Err(ExchangeError::InsufficientLiquidity(
-                format!("Insufficient balance for {} order", pay_asset)
-            ));
-        }
+        let ref_prices = self.reference_prices()?;
 
         // Create order
         let order_id = format!("order_{}", Utc::now().timestamp_nanos_opt().unwrap_or_default());
-        let _order = PairOrder {
+        let order = PairOrder {
             id: order_id.clone(),
             trader: trader.clone(),
             pay: pay_asset_id,
@@ -263,13 +290,37 @@ impl Exchange {
             budget: budget_amount,
             limit_ratio,
             min_fill_fraction,
+            max_slippage_bps,
+            submission_mid: Some(ref_prices.mid_rate(pay_asset_id, receive_asset_id)),
             metadata: serde_json::json!({}),
         };
+        convexfx_orders::validate_order(&order)
+            .map_err(|e| ExchangeError::OrderValidation(e.to_string()))?;
+
+        if let Some(max) = self.config.max_orders_per_trader_per_epoch {
+            let count = self.state.pending_orders.iter().filter(|o| o.trader == trader).count();
+            if count >= max {
+                return Err(ExchangeError::OrderValidation(format!(
+                    "trader {} already has {} orders in epoch {} (max {} per epoch)",
+                    trader, count, self.state.current_epoch, max
+                )));
+            }
+        }
+
+        // The trader must cover this order on top of their other pending orders
+        let committed = self.state.pending_orders.iter()
+            .filter(|o| o.trader == trader && o.pay == pay_asset_id)
+            .fold(budget_amount, |total, o| total + o.budget);
+        if !self.state.ledger.has_sufficient(&trader, pay_asset_id, committed) {
+            return Err(ExchangeError::InsufficientLiquidity(
+                format!("Insufficient balance for {} order", pay_asset)
+            ));
+        }
 
-        // TODO: For now, directly add to orderbook (in production, use commit-reveal)
-        // For simplicity, we'll add directly to the orderbook
+        self.state.order_owners.insert(order_id.clone(), trader);
+        self.state.pending_orders.push(order);
         println!("✅ Submitted order: {} ({} -> {} for {})",
-                 order_id, pay_asset, receive_asset, budget);
+                 order_id, pay_asset, receive_asset, budget_amount.to_display_string(pay_asset_id));
 
         Ok(OrderSubmission {
             order_id,
@@ -283,7 +334,11 @@ impl Exchange {
 
     /// Cancel a pending order
     pub fn cancel_order(&mut self, order_id: &str) -> Result<()> {
-        // TODO: Implement order cancellation
+        let index = self.state.pending_orders.iter()
+            .position(|o| o.id == order_id)
+            .ok_or_else(|| ExchangeError::NotFound(format!("Pending order {} not found", order_id)))?;
+        self.state.pending_orders.remove(index);
+        self.state.order_owners.remove(order_id);
         println!("✅ Cancelled order: {}", order_id);
         Ok(())
     }
@@ -304,22 +359,130 @@ impl Exchange {
         Ok(Vec::new())
     }
 
+    /// Preview a single order against current inventory without trading
+    ///
+    /// Runs the same clearing as `execute_batch` (oracle prices, risk
+    /// parameters, solver backend and slippage cap) on a batch containing
+    /// only this order. Nothing is settled and the epoch does not advance.
+    /// Returns the units of `receive_asset` the order would get and its
+    /// slippage versus the oracle mid in bps.
+    pub fn preview_order(
+        &self,
+        pay_asset: &str,
+        receive_asset: &str,
+        budget: f64,
+        limit_ratio: Option<f64>,
+        min_fill_fraction: Option<f64>,
+    ) -> Result<(f64, f64)> {
+        let pay_asset_id = AssetId::from_str(pay_asset)
+            .ok_or_else(|| ExchangeError::NotFound(format!("Pay asset {} not found", pay_asset)))?;
+        let receive_asset_id = AssetId::from_str(receive_asset)
+            .ok_or_else(|| ExchangeError::NotFound(format!("Receive asset {} not found", receive_asset)))?;
+
+        let budget_amount = Amount::from_f64(budget)
+            .map_err(|e| ExchangeError::InvalidArgument(format!("Invalid budget: {}", e)))?;
+
+        let order = PairOrder {
+            id: "preview".to_string(),
+            trader: AccountId::new("preview"),
+            pay: pay_asset_id,
+            receive: receive_asset_id,
+            budget: budget_amount,
+            limit_ratio,
+            min_fill_fraction,
+            max_slippage_bps: None,
+            submission_mid: None,
+            metadata: serde_json::json!({}),
+        };
+
+        let (instance, solution) = self.clear_orders(vec![order])?;
+        let fill = solution.fills.first().ok_or_else(|| {
+            ExchangeError::Clearing("Clearing returned no fill for preview order".to_string())
+        })?;
+
+        Ok((fill.recv_units, solution.fill_slippage_bps(&instance, fill)))
+    }
+
     /// Execute a clearing batch (run the SCP algorithm)
+    ///
+    /// Clears every pending order against the pool and settles the fills.
+    /// If clearing or settlement fails the orders stay pending.
     pub fn execute_batch(&mut self) -> Result<BatchResult> {
-        use convexfx_clearing::EpochInstance;
+        let (_, clearing_result) = self.clear_orders(self.state.pending_orders.clone())?;
 
-        // Get current prices from oracle
-        let oracle = &self.state.oracle;
-        let ref_prices = oracle.current_prices()
+        // Move funds between traders and the pool
+        let fees = self.settle_fills(&clearing_result.fills)?;
+        self.state.pending_orders.clear();
+
+        // Update epoch
+        self.state.current_epoch += 1;
+        self.state.ledger.set_epoch(self.state.current_epoch);
+        self.state.last_batch_time = Some(Utc::now());
+
+        tracing::info!(epoch_id = self.state.current_epoch - 1, fills = clearing_result.fills.len(), "executed batch");
+
+        let batch = BatchResult {
+            epoch_id: self.state.current_epoch - 1,
+            fills: clearing_result.fills.clone(),
+            fees,
+            prices: clearing_result.prices.clone(),
+            execution_time_ms: clearing_result.diagnostics.solve_time_ms,
+        };
+        self.state.record_prices(batch.epoch_id, batch.prices.clone(), self.config.price_history_epochs);
+        let pool_holdings = self.state.ledger.account_balances(&AccountId::new(POOL_ACCOUNT)).to_f64_map();
+        self.state.record_pool_holdings(batch.epoch_id, pool_holdings, self.config.price_history_epochs);
+        self.state.last_solution = Some(clearing_result);
+
+        Ok(batch)
+    }
+
+    /// Risk parameters for the next batch, warm-up included
+    fn batch_risk_parameters(&self) -> Result<RiskParams> {
+        self.config.risk_parameters_for_batch(self.state.current_epoch.saturating_sub(1))
+    }
+
+    /// Reference prices orders are quoted and cleared against
+    ///
+    /// Fresh oracle prices, refused when older than `max_price_age_ms`. With
+    /// `fixed_prices` configured the oracle is bypassed and those prices are
+    /// pinned through `EpochInstance::with_fixed_prices`.
+    fn reference_prices(&self) -> Result<RefPrices> {
+        let now_ms = Utc::now().timestamp_millis().max(0) as u64;
+
+        if !self.config.fixed_prices.is_empty() {
+            let unpinned = RefPrices::new(BTreeMap::new(), 0.0, now_ms, Vec::new());
+            let pinned = EpochInstance::new(self.state.current_epoch, BTreeMap::new(), Vec::new(), unpinned, self.batch_risk_parameters()?)
+                .with_fixed_prices(self.config.fixed_prices.clone())?;
+            return Ok(pinned.ref_prices);
+        }
+
+        let ref_prices = self.state.oracle.current_prices()
             .map_err(|e| ExchangeError::Oracle(e.to_string()))?;
 
-        // TODO: Get pending orders from orderbook
-        // For now, create empty orders list
-        let orders = Vec::new();
+        // Refuse to clear against stale prices
+        if ref_prices.is_stale(now_ms, self.config.max_price_age_ms) {
+            return Err(ExchangeError::Oracle(format!(
+                "Oracle prices are {} ms old (max {} ms)",
+                ref_prices.age_ms(now_ms),
+                self.config.max_price_age_ms
+            )));
+        }
 
-        // Get current inventory
-        let inventory = self.state.ledger.inventory();
-        let inventory_f64 = inventory.to_f64_map();
+        Ok(ref_prices)
+    }
+
+    /// Clear `orders` against the pool's holdings without touching state
+    ///
+    /// Shared by `execute_batch` and `preview_order` so a preview sees
+    /// exactly the clearing a real batch would run.
+    fn clear_orders(&self, orders: Vec<PairOrder>) -> Result<(EpochInstance, EpochSolution)> {
+        let ref_prices = self.reference_prices()?;
+
+        self.config.check_notional_caps()?;
+
+        // The pool is the counterparty of every fill, so only its holdings count
+        let inventory = self.state.ledger.account_balances(&AccountId::new(POOL_ACCOUNT));
+        let inventory_f64 = inventory.to_solver_map();
 
         // Create epoch instance
         let instance = EpochInstance::new(
@@ -327,31 +490,91 @@ impl Exchange {
             inventory_f64,
             orders,
             ref_prices,
-            self.config.risk_parameters.clone(),
+            self.batch_risk_parameters()?,
         );
 
         // Run clearing with the configured solver backend
-        let clearing_engine = match self.config.solver_backend {
-            crate::SolverBackend::Simple => convexfx_clearing::ScpClearing::with_simple_solver(),
-            crate::SolverBackend::Clarabel => convexfx_clearing::ScpClearing::new(),
-            crate::SolverBackend::OSQP => convexfx_clearing::ScpClearing::with_osqp_solver(),
-        };
+        let clearing_engine = self.config.solver_backend.clearing_engine()?;
 
-        let clearing_result = clearing_engine.clear_epoch(&instance)?;
+        let mut solution = clearing_engine.clear_epoch(&instance)?;
 
-        // Update epoch
-        self.state.current_epoch += 1;
-        self.state.last_batch_time = Some(Utc::now());
+        // Protect traders from bad prints
+        if let Some(max_slippage_bps) = self.config.max_fill_slippage_bps {
+            let rejected = solution.reject_slippage_above(&instance, max_slippage_bps);
+            for order_id in &rejected {
+                tracing::warn!(order_id = %order_id, max_slippage_bps, "rejected fill: slippage above cap");
+            }
+        }
 
-        println!("✅ Executed batch #{} with {} fills",
-                 self.state.current_epoch - 1, clearing_result.fills.len());
+        // Circuit breaker on per-asset flow
+        if !self.config.max_epoch_notional.is_empty() {
+            for asset in solution.throttle_flows(&instance, &self.config.max_epoch_notional) {
+                tracing::warn!(asset = %asset, "throttled fills: epoch notional cap reached");
+            }
+        }
 
-        Ok(BatchResult {
-            epoch_id: self.state.current_epoch - 1,
-            fills: clearing_result.fills,
-            prices: clearing_result.prices,
-            execution_time_ms: 0, // TODO: Track execution time
-        })
+        Ok((instance, solution))
+    }
+
+    /// Apply a batch of fills to the ledger
+    ///
+    /// Each trader pays `pay_units` of the pay asset into the pool and
+    /// receives `recv_units` of the receive asset from it, then pays the
+    /// configured fee on those units to the fee account. The pool's holdings
+    /// therefore move by exactly `pay_units - recv_units`, with no fee
+    /// accruing to it. Settlement runs in one ledger transaction: if any
+    /// trader or the pool lacks the balance for its leg, it is rolled back
+    /// and no fill is applied. Settled orders are no longer pending.
+    ///
+    /// Returns the fees credited to the fee account, by asset.
+    pub fn settle_fills(&mut self, fills: &[Fill]) -> Result<BTreeMap<AssetId, f64>> {
+        let pool = AccountId::new(POOL_ACCOUNT);
+        let fee_account = AccountId::new(self.config.fee_account.as_str());
+
+        let mut legs = Vec::with_capacity(fills.len());
+        let mut fees = BTreeMap::new();
+        for fill in fills {
+            let trader = self.state.order_owners.get(&fill.order_id).cloned().ok_or_else(|| {
+                ExchangeError::NotFound(format!("Order {} not found", fill.order_id))
+            })?;
+            let pay = Amount::from_f64(fill.pay_units).map_err(|e| {
+                ExchangeError::InvalidArgument(format!("Invalid pay units for {}: {}", fill.order_id, e))
+            })?;
+            let recv = Amount::from_f64(fill.recv_units).map_err(|e| {
+                ExchangeError::InvalidArgument(format!("Invalid receive units for {}: {}", fill.order_id, e))
+            })?;
+            let fee = Amount::from_f64(self.config.fee_on(fill.recv_asset, fill.recv_units)).map_err(|e| {
+                ExchangeError::InvalidArgument(format!("Invalid fee for {}: {}", fill.order_id, e))
+            })?;
+            if !fee.is_zero() {
+                *fees.entry(fill.recv_asset).or_insert(0.0) += fee.to_f64();
+            }
+            legs.push((trader, fill, pay, recv, fee));
+        }
+
+        self.state.ledger.with_transaction(|tx| -> Result<()> {
+            for (trader, fill, pay, recv, fee) in legs {
+                tx.transfer(&trader, &pool, fill.pay_asset, pay)
+                    .and_then(|_| tx.transfer(&pool, &trader, fill.recv_asset, recv))
+                    .and_then(|_| {
+                        if fee.is_zero() {
+                            Ok(())
+                        } else {
+                            tx.transfer(&trader, &fee_account, fill.recv_asset, fee)
+                        }
+                    })
+                    .map_err(|e| {
+                        ExchangeError::InsufficientLiquidity(format!(
+                            "Cannot settle {}: {}",
+                            fill.order_id, e
+                        ))
+                    })?;
+            }
+            Ok(())
+        })?;
+
+        self.state.pending_orders.retain(|o| fills.iter().all(|f| f.order_id != o.id));
+        Ok(fees)
     }
 
     /// Get current epoch information
@@ -369,21 +592,61 @@ impl Exchange {
         Ok(vec![self.get_current_epoch()])
     }
 
-    /// Get current prices for all assets
+    /// Get current prices for all assets the oracle has priced
+    ///
+    /// Assets missing from the oracle are omitted rather than reported at
+    /// USD parity.
     pub fn get_current_prices(&self) -> Result<BTreeMap<String, f64>> {
         let oracle = &self.state.oracle;
         let prices = oracle.current_prices()
             .map_err(|e| ExchangeError::Oracle(e.to_string()))?;
 
         let mut result = BTreeMap::new();
-        for asset in AssetId::all() {
-            let y = prices.get_ref(*asset);
+        for (asset, y) in &prices.y_ref {
             result.insert(asset.to_string(), y.exp());
         }
 
         Ok(result)
     }
 
+    /// Cleared prices of a past batch
+    ///
+    /// Only the last `price_history_epochs` batches are kept; older or
+    /// not yet cleared epochs are `NotFound`.
+    pub fn get_prices_at(&self, epoch_id: u64) -> Result<BTreeMap<String, f64>> {
+        let prices = self.state.price_history.get(&epoch_id).ok_or_else(|| {
+            ExchangeError::NotFound(format!("No cleared prices retained for epoch {}", epoch_id))
+        })?;
+        Ok(prices.iter().map(|(asset, price)| (asset.to_string(), *price)).collect())
+    }
+
+    /// Mark-to-market PnL of the pool account, in the numeraire
+    ///
+    /// The pool's current holdings valued at the latest cleared prices, minus
+    /// its holdings after the oldest retained batch valued at that batch's
+    /// prices. Cleared prices are quoted in the clearing numeraire, so the
+    /// result is in units of it. Deposits into or withdrawals from the pool
+    /// account after that batch count toward the result. 0 until a batch has
+    /// been executed.
+    pub fn pool_pnl(&self) -> f64 {
+        let history = &self.state.price_history;
+        let (Some((start_epoch, start_prices)), Some((_, prices))) =
+            (history.first_key_value(), history.last_key_value())
+        else {
+            return 0.0;
+        };
+        let start_holdings = self.state.pool_history.get(start_epoch).cloned().unwrap_or_default();
+        let holdings = self.state.ledger.account_balances(&AccountId::new(POOL_ACCOUNT)).to_f64_map();
+
+        let value = |holdings: &BTreeMap<AssetId, f64>, prices: &BTreeMap<AssetId, f64>| -> f64 {
+            holdings
+                .iter()
+                .map(|(asset, amount)| amount * prices.get(asset).copied().unwrap_or(0.0))
+                .sum()
+        };
+        value(&holdings, prices) - value(&start_holdings, start_prices)
+    }
+
     /// Get price for a specific asset
     pub fn get_asset_price(&self, symbol: &str) -> Result<f64> {
         let oracle = &self.state.oracle;
@@ -393,10 +656,47 @@ impl Exchange {
         let asset_id = AssetId::from_str(symbol)
             .ok_or_else(|| ExchangeError::NotFound(format!("Asset {} not found", symbol)))?;
 
-        let y = prices.get_ref(asset_id);
+        let y = prices.y_ref.get(&asset_id)
+            .ok_or_else(|| ExchangeError::Oracle(format!("No oracle price for asset {}", symbol)))?;
         Ok(y.exp())
     }
 
+    /// Cleared rate of the last batch: units of `to` per unit of `from`
+    pub fn get_cleared_rate(&self, from: &str, to: &str) -> Result<f64> {
+        let from_id = AssetId::from_str(from)
+            .ok_or_else(|| ExchangeError::NotFound(format!("Asset {} not found", from)))?;
+        let to_id = AssetId::from_str(to)
+            .ok_or_else(|| ExchangeError::NotFound(format!("Asset {} not found", to)))?;
+
+        let solution = self.state.last_solution.as_ref()
+            .ok_or_else(|| ExchangeError::NotFound("No batch has been cleared yet".to_string()))?;
+        Ok(solution.rate(from_id, to_id))
+    }
+
+    /// Write a snapshot of the exchange state to `path` as JSON
+    pub fn save_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(&self.state.snapshot())
+            .map_err(|e| ExchangeError::System(format!("Cannot serialize state: {}", e)))?;
+        std::fs::write(path, json).map_err(|e| {
+            ExchangeError::System(format!("Cannot write state to {}: {}", path.display(), e))
+        })
+    }
+
+    /// Replace the exchange state with a snapshot written by `save_to`
+    pub fn load_from(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            ExchangeError::System(format!("Cannot read state from {}: {}", path.display(), e))
+        })?;
+        let snapshot: StateSnapshot = serde_json::from_str(&json).map_err(|e| {
+            ExchangeError::System(format!("Invalid state snapshot in {}: {}", path.display(), e))
+        })?;
+        self.state.restore(snapshot)?;
+        tracing::info!(epoch_id = self.state.current_epoch, path = %path.display(), "restored exchange state");
+        Ok(())
+    }
+
     /// Update exchange configuration
     pub fn configure(&mut self, config: ExchangeConfig) -> Result<()> {
         self.config = config;
@@ -486,6 +786,9 @@ pub struct OrderInfo {
 pub struct BatchResult {
     pub epoch_id: u64,
     pub fills: Vec<Fill>,
+    /// Fees credited to the fee account, by asset. They are paid out of the
+    /// traders' proceeds, not by the pool, so fills carry no `fees_paid`.
+    pub fees: BTreeMap<AssetId, f64>,
     pub prices: BTreeMap<AssetId, f64>,
     pub execution_time_ms: u64,
 }
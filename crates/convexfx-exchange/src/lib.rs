@@ -4,10 +4,10 @@ mod state;
 mod error;
 mod websocket;
 
-pub use exchange::Exchange;
-pub use config::{ExchangeConfig, SolverBackend};
+pub use exchange::{Exchange, POOL_ACCOUNT};
+pub use config::{ExchangeConfig, SolverBackend, WARMUP_PRICE_BAND_BPS, WARMUP_TRACKING_WEIGHT};
 pub use error::{ExchangeError, Result};
-pub use state::{ExchangeState, SystemStatus};
+pub use state::{ExchangeState, StateSnapshot, SystemStatus};
 
 #[cfg(test)]
 mod tests;
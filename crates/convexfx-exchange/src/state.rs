@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use chrono::{DateTime, Utc};
-use convexfx_types::{AccountId, AssetId, Amount, Inventory, EpochId, OrderId, Fill};
+use convexfx_types::{AccountId, AssetId, EpochId, OrderId, PairOrder};
+use convexfx_ledger::{Ledger, LedgerSnapshot};
+use convexfx_orders::OrderBook;
 
 /// Current system status and metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +27,29 @@ pub enum ExchangeStatus {
     Error(String),
 }
 
+/// Serializable copy of the exchange state, for crash recovery
+///
+/// Covers balances, pending orders and their owners, oracle prices, the
+/// epoch counter and the cleared price and pool holdings history. The ledger's change log and the asset registry are not
+/// included.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub ledger: LedgerSnapshot,
+    pub orderbook: OrderBook,
+    pub order_owners: BTreeMap<OrderId, AccountId>,
+    #[serde(default)]
+    pub pending_orders: Vec<PairOrder>,
+    pub oracle_prices: BTreeMap<AssetId, f64>,
+    pub current_epoch: EpochId,
+    pub start_time: DateTime<Utc>,
+    pub last_batch_time: Option<DateTime<Utc>>,
+    pub last_solution: Option<convexfx_clearing::EpochSolution>,
+    #[serde(default)]
+    pub price_history: BTreeMap<EpochId, BTreeMap<AssetId, f64>>,
+    #[serde(default)]
+    pub pool_history: BTreeMap<EpochId, BTreeMap<AssetId, f64>>,
+}
+
 /// Internal exchange state management
 pub struct ExchangeState {
     pub ledger: convexfx_ledger::MemoryLedger,
@@ -32,9 +57,19 @@ pub struct ExchangeState {
     pub oracle: convexfx_oracle::MockOracle,
     pub clearing_engine: convexfx_clearing::ScpClearing,
     pub reporter: convexfx_report::MemoryReporter,
+    /// Trader that submitted each order, used to settle fills
+    pub order_owners: BTreeMap<OrderId, AccountId>,
+    /// Orders waiting for the next batch, in submission order
+    pub pending_orders: Vec<PairOrder>,
     pub current_epoch: EpochId,
     pub start_time: DateTime<Utc>,
     pub last_batch_time: Option<DateTime<Utc>>,
+    /// Clearing solution of the most recently executed batch
+    pub last_solution: Option<convexfx_clearing::EpochSolution>,
+    /// Cleared prices of the most recent batches, by epoch
+    pub price_history: BTreeMap<EpochId, BTreeMap<AssetId, f64>>,
+    /// Pool account holdings after each of the most recent batches, by epoch
+    pub pool_history: BTreeMap<EpochId, BTreeMap<AssetId, f64>>,
     pub is_running: bool,
 }
 
@@ -46,20 +81,79 @@ impl ExchangeState {
             oracle: convexfx_oracle::MockOracle::new(),
             clearing_engine: convexfx_clearing::ScpClearing::new(),
             reporter: convexfx_report::MemoryReporter::new(),
+            order_owners: BTreeMap::new(),
+            pending_orders: Vec::new(),
             current_epoch: 1,
             start_time: Utc::now(),
             last_batch_time: None,
+            last_solution: None,
+            price_history: BTreeMap::new(),
+            pool_history: BTreeMap::new(),
             is_running: false,
         }
     }
 
+    /// Capture the state needed to resume after a restart
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            ledger: self.ledger.snapshot(),
+            orderbook: self.orderbook.clone(),
+            order_owners: self.order_owners.clone(),
+            pending_orders: self.pending_orders.clone(),
+            oracle_prices: self.oracle.prices().clone(),
+            current_epoch: self.current_epoch,
+            start_time: self.start_time,
+            last_batch_time: self.last_batch_time,
+            last_solution: self.last_solution.clone(),
+            price_history: self.price_history.clone(),
+            pool_history: self.pool_history.clone(),
+        }
+    }
+
+    /// Record the cleared prices of `epoch`, keeping only the latest `retention` epochs
+    pub fn record_prices(&mut self, epoch: EpochId, prices: BTreeMap<AssetId, f64>, retention: usize) {
+        self.price_history.insert(epoch, prices);
+        while self.price_history.len() > retention {
+            self.price_history.pop_first();
+        }
+    }
+
+    /// Record the pool holdings after `epoch`, keeping only the latest `retention` epochs
+    pub fn record_pool_holdings(&mut self, epoch: EpochId, holdings: BTreeMap<AssetId, f64>, retention: usize) {
+        self.pool_history.insert(epoch, holdings);
+        while self.pool_history.len() > retention {
+            self.pool_history.pop_first();
+        }
+    }
+
+    /// Load a snapshot taken by `snapshot`
+    ///
+    /// The oracle keeps its configuration but takes the snapshot's prices.
+    /// The exchange is left stopped.
+    pub fn restore(&mut self, snapshot: StateSnapshot) -> convexfx_types::Result<()> {
+        self.ledger.set_epoch(snapshot.current_epoch);
+        self.ledger.restore(&snapshot.ledger)?;
+        self.orderbook = snapshot.orderbook;
+        self.order_owners = snapshot.order_owners;
+        self.pending_orders = snapshot.pending_orders;
+        for (asset, price) in snapshot.oracle_prices {
+            self.oracle.set_price(asset, price);
+        }
+        self.current_epoch = snapshot.current_epoch;
+        self.start_time = snapshot.start_time;
+        self.last_batch_time = snapshot.last_batch_time;
+        self.last_solution = snapshot.last_solution;
+        self.price_history = snapshot.price_history;
+        self.pool_history = snapshot.pool_history;
+        self.is_running = false;
+        Ok(())
+    }
+
     pub fn get_uptime_seconds(&self) -> u64 {
         (Utc::now() - self.start_time).num_seconds() as u64
     }
 
     pub fn get_status(&self) -> SystemStatus {
-        use convexfx_ledger::Ledger;
-
         let inventory = self.ledger.inventory();
         let f64_map = inventory.to_f64_map();
         let mut total_liquidity = BTreeMap::new();
@@ -76,7 +170,7 @@ impl ExchangeState {
             },
             current_epoch: self.current_epoch,
             total_accounts: self.ledger.list_accounts().len(),
-            total_orders_pending: self.orderbook.commitment_count(),
+            total_orders_pending: self.pending_orders.len(),
             total_liquidity,
             uptime_seconds: self.get_uptime_seconds(),
             last_batch_execution: self.last_batch_time,
@@ -1,5 +1,8 @@
-use convexfx_exchange::{Exchange, ExchangeConfig};
-use convexfx_types::AssetId;
+use convexfx_exchange::{Exchange, ExchangeConfig, ExchangeError, SolverBackend, POOL_ACCOUNT};
+use convexfx_oracle::{MockOracle, Oracle};
+use convexfx_types::{AssetId, ConvexFxError, Fill};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Test that the high-level Exchange API produces the same results as the low-level clearing tests
 #[test]
@@ -10,12 +13,12 @@ fn test_exchange_api_basic_clearing() {
     let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
 
     // Add initial liquidity (simulating the test setup)
-    exchange.add_liquidity("lp_1", "USD", 10.0).unwrap();
-    exchange.add_liquidity("lp_1", "EUR", 10.0).unwrap();
-    exchange.add_liquidity("lp_1", "JPY", 10.0).unwrap();
-    exchange.add_liquidity("lp_1", "GBP", 10.0).unwrap();
-    exchange.add_liquidity("lp_1", "CHF", 10.0).unwrap();
-    exchange.add_liquidity("lp_1", "AUD", 10.0).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "USD", 10.0).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "EUR", 10.0).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "JPY", 10.0).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "GBP", 10.0).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "CHF", 10.0).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "AUD", 10.0).unwrap();
 
     // Add liquidity to trader accounts
     exchange.add_liquidity("trader1", "USD", 100.0).unwrap();
@@ -29,7 +32,8 @@ fn test_exchange_api_basic_clearing() {
         "EUR",
         1.0,
         None,  // No limit
-        None   // No min fill
+        None,  // No min fill
+        None   // No slippage cap
     ).unwrap();
 
     println!("✅ Order submitted: {}", order_result.order_id);
@@ -41,8 +45,12 @@ fn test_exchange_api_basic_clearing() {
 
     // Verify results
     assert_eq!(batch_result.epoch_id, 1);
-    // Note: In current implementation, orders are submitted but not yet integrated into clearing
-    // This test demonstrates the API works correctly, but full order integration is a future enhancement
+    assert_eq!(batch_result.fills.len(), 1);
+    assert_eq!(batch_result.fills[0].order_id, order_result.order_id);
+    assert!(batch_result.fills[0].recv_units > 0.0);
+    assert_eq!(exchange.get_status().total_orders_pending, 0);
+    let trader = exchange.get_liquidity("trader1").unwrap();
+    assert!((trader["EUR"] - batch_result.fills[0].recv_units).abs() < 1e-9);
 
     // Check that USD is still the numeraire (linear price should be 1.0)
     if let Some(usd_price) = batch_result.prices.get(&AssetId::USD) {
@@ -109,19 +117,18 @@ fn test_exchange_api_cross_rate_consistency() {
 
     println!("✅ Balanced liquidity added across all assets");
 
+    // No batch cleared yet, so there is no cleared rate
+    assert!(matches!(exchange.get_cleared_rate("EUR", "USD"), Err(ExchangeError::NotFound(_))));
+
     // Execute batch with no orders to test cross-rate consistency
-    let batch_result = exchange.execute_batch().unwrap();
+    exchange.execute_batch().unwrap();
 
     println!("✅ Batch executed with no orders (testing cross-rate consistency)");
 
     // Verify no arbitrage: EURJPY should equal EURUSD * USDJPY
-    let eur_price = batch_result.prices.get(&AssetId::EUR).unwrap();
-    let jpy_price = batch_result.prices.get(&AssetId::JPY).unwrap();
-    let usd_price = batch_result.prices.get(&AssetId::USD).unwrap();
-
-    let eurusd = eur_price / usd_price;
-    let usdjpy = usd_price / jpy_price;  // USDJPY = 1/JPYUSD
-    let eurjpy_direct = eur_price / jpy_price;
+    let eurusd = exchange.get_cleared_rate("EUR", "USD").unwrap();
+    let usdjpy = exchange.get_cleared_rate("USD", "JPY").unwrap();
+    let eurjpy_direct = exchange.get_cleared_rate("EUR", "JPY").unwrap();
     let eurjpy_cross = eurusd * usdjpy;
 
     let arbitrage_error = ((eurjpy_direct - eurjpy_cross) / eurjpy_direct).abs();
@@ -148,7 +155,7 @@ fn test_exchange_api_complex_trading_scenario() {
 
     // Add substantial liquidity (equivalent to 20M units each)
     for asset in AssetId::all() {
-        exchange.add_liquidity("lp_1", &asset.to_string(), 20.0).unwrap();
+        exchange.add_liquidity(POOL_ACCOUNT, &asset.to_string(), 20.0).unwrap();
     }
 
     // Add liquidity to trader accounts for their orders
@@ -169,25 +176,25 @@ fn test_exchange_api_complex_trading_scenario() {
     let mut orders_submitted = 0;
 
     // EUR/USD orders
-    exchange.submit_order("alice", "USD", "EUR", 1.0, Some(1.15), Some(0.5)).unwrap();
+    exchange.submit_order("alice", "USD", "EUR", 1.0, Some(1.15), Some(0.5), None).unwrap();
     orders_submitted += 1;
 
-    exchange.submit_order("bob", "USD", "EUR", 0.75, None, None).unwrap();
+    exchange.submit_order("bob", "USD", "EUR", 0.75, None, None, None).unwrap();
     orders_submitted += 1;
 
     // GBP/USD order
-    exchange.submit_order("charlie", "GBP", "USD", 0.5, Some(0.85), None).unwrap();
+    exchange.submit_order("charlie", "GBP", "USD", 0.5, Some(0.85), None, None).unwrap();
     orders_submitted += 1;
 
-    // JPY/USD order
-    exchange.submit_order("diana", "USD", "JPY", 1.2, Some(105.0), Some(0.3)).unwrap();
+    // JPY/USD order, sized so the pool's 20 JPY units can pay it out
+    exchange.submit_order("diana", "USD", "JPY", 0.12, Some(105.0), Some(0.3), None).unwrap();
     orders_submitted += 1;
 
     // Cross-pair orders
-    exchange.submit_order("eve", "EUR", "GBP", 0.6, None, None).unwrap();
+    exchange.submit_order("eve", "EUR", "GBP", 0.6, None, None, None).unwrap();
     orders_submitted += 1;
 
-    exchange.submit_order("frank", "USD", "CHF", 0.8, Some(1.12), None).unwrap();
+    exchange.submit_order("frank", "USD", "CHF", 0.8, Some(1.12), None, None).unwrap();
     orders_submitted += 1;
 
     println!("✅ Submitted {} diverse orders across multiple currency pairs", orders_submitted);
@@ -198,8 +205,8 @@ fn test_exchange_api_complex_trading_scenario() {
     println!("✅ Complex batch executed with {} fills", batch_result.fills.len());
 
     // Verify comprehensive results
-    // Note: In current implementation, orders are submitted but not yet integrated into clearing
-    // This test demonstrates the API works correctly with complex scenarios
+    assert_eq!(batch_result.fills.len(), orders_submitted);
+    assert!(batch_result.fills.iter().any(|f| f.fill_frac > 0.0));
 
     // USD should still be numeraire (linear price should be 1.0)
     if let Some(usd_price) = batch_result.prices.get(&AssetId::USD) {
@@ -219,8 +226,8 @@ fn test_exchange_api_complex_trading_scenario() {
         assert!(amount >= -0.1, "Asset {} has negative liquidity: {}", asset, amount);
     }
 
-    // Verify that fills represent actual trades (pay and receive amounts > 0)
-    for fill in &batch_result.fills {
+    // Verify that executed fills represent actual trades (pay and receive amounts > 0)
+    for fill in batch_result.fills.iter().filter(|f| f.fill_frac > 0.0) {
         assert!(fill.pay_units > 0.0, "Fill should have positive pay amount");
         assert!(fill.recv_units > 0.0, "Fill should have positive receive amount");
     }
@@ -308,6 +315,35 @@ fn test_exchange_api_asset_management() {
     println!("   - Decimal precision tracking");
 }
 
+#[test]
+fn test_exchange_api_dynamic_assets() {
+    let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
+
+    // All six default currencies are listed with oracle prices
+    let symbols: Vec<String> = exchange.list_assets().unwrap().into_iter().map(|a| a.symbol).collect();
+    for asset in AssetId::all() {
+        assert!(symbols.contains(&asset.to_string()), "{} missing", asset);
+    }
+    let chf = exchange.get_asset_info("CHF").unwrap();
+    assert!((chf.current_price.unwrap() - exchange.get_asset_price("CHF").unwrap()).abs() < 1e-12);
+
+    // Added assets show up in the listing
+    exchange.add_asset("NZD", "New Zealand Dollar", 2, false, 0.6).unwrap();
+    let assets = exchange.list_assets().unwrap();
+    assert_eq!(assets.len(), 7);
+    let nzd = assets.iter().find(|a| a.symbol == "NZD").unwrap();
+    assert_eq!(nzd.name, "New Zealand Dollar");
+    assert_eq!(nzd.decimals, 2);
+    assert!(!nzd.is_base_currency);
+    assert!(exchange.add_asset("NZD", "Duplicate", 2, false, 0.6).is_err());
+
+    // Removal updates the registry
+    exchange.remove_asset("NZD").unwrap();
+    assert!(exchange.get_asset_info("NZD").is_err());
+    assert_eq!(exchange.list_assets().unwrap().len(), 6);
+    assert!(matches!(exchange.remove_asset("NZD"), Err(ExchangeError::NotFound(_))));
+}
+
 #[test]
 fn test_exchange_api_error_handling() {
     println!("\n=== Exchange API: Error Handling Test ===\n");
@@ -315,11 +351,11 @@ fn test_exchange_api_error_handling() {
     let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
 
     // Test invalid asset error
-    let _result = exchange.submit_order("trader", "INVALID", "USD", 100.0, None, None);
+    let _result = exchange.submit_order("trader", "INVALID", "USD", 100.0, None, None, None);
     assert!(_result.is_err(), "Should fail with invalid asset");
 
     // Test insufficient liquidity error
-    let _result2 = exchange.submit_order("trader", "USD", "EUR", 1_000_000.0, None, None);
+    let _result2 = exchange.submit_order("trader", "USD", "EUR", 1_000_000.0, None, None, None);
     // This might succeed or fail depending on initial liquidity, but should handle gracefully
 
     // Test asset removal with liquidity (should fail)
@@ -332,3 +368,813 @@ fn test_exchange_api_error_handling() {
     println!("   - Insufficient liquidity handling");
     println!("   - Asset removal restrictions");
 }
+
+#[test]
+fn test_exchange_api_rejects_invalid_orders() {
+    let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
+    exchange.add_liquidity("trader", "USD", 100.0).unwrap();
+
+    let rejected = |result: convexfx_exchange::Result<_>, reason: &str| match result {
+        Err(ExchangeError::OrderValidation(msg)) => assert!(msg.contains(reason), "{}", msg),
+        Err(other) => panic!("expected validation error, got {}", other),
+        Ok(_) => panic!("order mentioning {} was accepted", reason),
+    };
+
+    rejected(exchange.submit_order("trader", "USD", "USD", 10.0, None, None, None), "different");
+    rejected(exchange.submit_order("trader", "USD", "EUR", 0.0, None, None, None), "budget");
+    rejected(exchange.submit_order("trader", "USD", "EUR", 10.0, Some(0.0), None, None), "limit ratio");
+    rejected(exchange.submit_order("trader", "USD", "EUR", 10.0, None, Some(0.0), None), "min fill");
+
+    assert!(exchange.submit_order("trader", "USD", "EUR", 10.0, Some(0.9), Some(1.0), None).is_ok());
+}
+
+#[test]
+fn test_exchange_api_pending_orders() {
+    let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "EUR", 20.0).unwrap();
+    exchange.add_liquidity("trader", "USD", 5.0).unwrap();
+
+    // Pending orders reserve the trader's balance
+    let first = exchange.submit_order("trader", "USD", "EUR", 3.0, None, None, None).unwrap().order_id;
+    assert!(matches!(
+        exchange.submit_order("trader", "USD", "EUR", 3.0, None, None, None),
+        Err(ExchangeError::InsufficientLiquidity(_))
+    ));
+    assert_eq!(exchange.get_status().total_orders_pending, 1);
+
+    // A cancelled order is not cleared
+    exchange.cancel_order(&first).unwrap();
+    assert_eq!(exchange.get_status().total_orders_pending, 0);
+    assert!(matches!(exchange.cancel_order(&first), Err(ExchangeError::NotFound(_))));
+    assert!(exchange.execute_batch().unwrap().fills.is_empty());
+
+    // Once cancelled, its budget is free again
+    exchange.submit_order("trader", "USD", "EUR", 3.0, None, None, None).unwrap();
+    assert_eq!(exchange.execute_batch().unwrap().fills.len(), 1);
+    assert_eq!(exchange.get_status().total_orders_pending, 0);
+}
+
+#[test]
+fn test_exchange_api_pending_orders_survive_failed_batch_and_restore() {
+    let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "EUR", 20.0).unwrap();
+    exchange.add_liquidity("trader", "USD", 5.0).unwrap();
+    let order_id = exchange.submit_order("trader", "USD", "EUR", 2.0, None, None, None).unwrap().order_id;
+
+    // A batch that cannot clear keeps its orders queued for the next one
+    let mut broken = ExchangeConfig::default();
+    broken.max_epoch_notional.insert(AssetId::EUR, -1.0);
+    exchange.configure(broken).unwrap();
+    assert!(exchange.execute_batch().is_err());
+    assert_eq!(exchange.get_status().total_orders_pending, 1);
+    assert_eq!(exchange.get_current_epoch().epoch_id, 1);
+
+    // Pending orders are part of the snapshot
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json");
+    exchange.save_to(&path).unwrap();
+    let mut restored = Exchange::new(ExchangeConfig::default()).unwrap();
+    restored.load_from(&path).unwrap();
+    assert_eq!(restored.get_status().total_orders_pending, 1);
+
+    let batch = restored.execute_batch().unwrap();
+    assert_eq!(batch.fills.len(), 1);
+    assert_eq!(batch.fills[0].order_id, order_id);
+    assert_eq!(restored.get_status().total_orders_pending, 0);
+}
+
+#[test]
+fn test_exchange_api_cancel_order_removes_only_that_order() {
+    let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "EUR", 20.0).unwrap();
+    exchange.add_liquidity("alice", "USD", 5.0).unwrap();
+    exchange.add_liquidity("bob", "USD", 5.0).unwrap();
+
+    let alice = exchange.submit_order("alice", "USD", "EUR", 2.0, None, None, None).unwrap().order_id;
+    let bob = exchange.submit_order("bob", "USD", "EUR", 2.0, None, None, None).unwrap().order_id;
+    exchange.cancel_order(&alice).unwrap();
+    assert_eq!(exchange.get_status().total_orders_pending, 1);
+    assert!(matches!(exchange.cancel_order("order_unknown"), Err(ExchangeError::NotFound(_))));
+
+    // Only the remaining order settles, and the cancelled trader keeps their funds
+    let batch = exchange.execute_batch().unwrap();
+    assert_eq!(batch.fills.len(), 1);
+    assert_eq!(batch.fills[0].order_id, bob);
+    assert_eq!(exchange.get_liquidity("alice").unwrap()["USD"], 5.0);
+    assert!(exchange.get_liquidity("bob").unwrap()["USD"] < 5.0);
+}
+
+#[test]
+fn test_exchange_api_clears_against_pool_holdings_only() {
+    let batch_with_bystander = |bystander_eur: f64| {
+        let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
+        for asset in AssetId::all() {
+            exchange.add_liquidity(POOL_ACCOUNT, &asset.to_string(), 20.0).unwrap();
+        }
+        exchange.add_liquidity("trader", "USD", 5.0).unwrap();
+        if bystander_eur > 0.0 {
+            exchange.add_liquidity("bystander", "EUR", bystander_eur).unwrap();
+        }
+        exchange.submit_order("trader", "USD", "EUR", 2.0, None, None, None).unwrap();
+        let batch = exchange.execute_batch().unwrap();
+        (exchange, batch)
+    };
+
+    // Other accounts' balances are not inventory: the fill is the same with
+    // or without a large EUR holder, and only the pool pays out EUR
+    let (_, baseline) = batch_with_bystander(0.0);
+    let (exchange, batch) = batch_with_bystander(1000.0);
+    assert_eq!(batch.fills.len(), 1);
+    let fill = &batch.fills[0];
+    assert!((fill.recv_units - baseline.fills[0].recv_units).abs() < 1e-9);
+    assert_eq!(exchange.get_liquidity("bystander").unwrap()["EUR"], 1000.0);
+    let pool_eur = exchange.get_liquidity(POOL_ACCOUNT).unwrap()["EUR"];
+    assert!((pool_eur - (20.0 - fill.recv_units)).abs() < 1e-9, "pool EUR {}", pool_eur);
+}
+
+#[test]
+fn test_exchange_api_limits_orders_per_trader() {
+    let config = ExchangeConfig {
+        max_orders_per_trader_per_epoch: Some(2),
+        ..ExchangeConfig::default()
+    };
+    let mut exchange = Exchange::new(config).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "EUR", 20.0).unwrap();
+    exchange.add_liquidity("spammer", "USD", 10.0).unwrap();
+    exchange.add_liquidity("trader", "USD", 10.0).unwrap();
+
+    for _ in 0..2 {
+        exchange.submit_order("spammer", "USD", "EUR", 1.0, None, None, None).unwrap();
+    }
+    match exchange.submit_order("spammer", "USD", "EUR", 1.0, None, None, None) {
+        Err(ExchangeError::OrderValidation(message)) => assert!(message.contains("already has 2 orders"), "{}", message),
+        other => panic!("expected the third order to be refused, got {:?}", other.map(|_| ())),
+    }
+
+    // Another trader is unaffected, and the count resets with the next epoch
+    exchange.submit_order("trader", "USD", "EUR", 1.0, None, None, None).unwrap();
+    exchange.execute_batch().unwrap();
+    exchange.submit_order("spammer", "USD", "EUR", 1.0, None, None, None).unwrap();
+}
+
+#[test]
+fn test_exchange_api_partial_oracle_prices() {
+    // Oracle only prices USD and EUR
+    let mut prices = BTreeMap::new();
+    prices.insert(AssetId::USD, 1.0);
+    prices.insert(AssetId::EUR, 1.1);
+    let oracle = MockOracle::with_prices(prices);
+
+    let exchange = Exchange::with_oracle(ExchangeConfig::default(), oracle).unwrap();
+
+    let current = exchange.get_current_prices().unwrap();
+    assert_eq!(current.len(), 2);
+    assert!((current["USD"] - 1.0).abs() < 1e-12);
+    assert!((current["EUR"] - 1.1).abs() < 1e-12);
+
+    // Unpriced assets must not be reported at USD parity
+    assert!(!current.contains_key("JPY"));
+    assert!(exchange.get_asset_price("JPY").is_err());
+    assert!((exchange.get_asset_price("EUR").unwrap() - 1.1).abs() < 1e-12);
+}
+
+#[test]
+fn test_exchange_api_stale_oracle_prices() {
+    let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+    let config = ExchangeConfig {
+        max_price_age_ms: 60_000,
+        ..ExchangeConfig::default()
+    };
+
+    // Well inside the limit: clearing proceeds
+    let fresh = MockOracle::new().with_fixed_timestamp(now_ms - 1_000);
+    let mut exchange = Exchange::with_oracle(config.clone(), fresh).unwrap();
+    assert!(exchange.execute_batch().is_ok());
+
+    // Past the limit: clearing is refused with an oracle error
+    let stale = MockOracle::new().with_fixed_timestamp(now_ms - 120_000);
+    let mut exchange = Exchange::with_oracle(config, stale).unwrap();
+    match exchange.execute_batch() {
+        Err(ExchangeError::Oracle(msg)) => assert!(msg.contains("old"), "{}", msg),
+        other => panic!("expected stale oracle error, got {:?}", other.map(|r| r.epoch_id)),
+    }
+}
+
+#[test]
+fn test_exchange_api_multi_asset_deposit() {
+    let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
+
+    let amounts: BTreeMap<AssetId, f64> = AssetId::all()
+        .iter()
+        .enumerate()
+        .map(|(i, asset)| (*asset, 10.0 + i as f64))
+        .collect();
+
+    let balances = exchange.add_liquidity_multi("lp_1", amounts.clone()).unwrap();
+    assert_eq!(balances.len(), 6);
+
+    let total = exchange.get_total_liquidity().unwrap();
+    for (asset, amount) in &amounts {
+        assert!((balances[asset] - amount).abs() < 1e-9);
+        assert!((total[&asset.to_string()] - amount).abs() < 1e-9);
+    }
+
+    // One invalid amount rejects the whole deposit
+    let mut bad = BTreeMap::new();
+    bad.insert(AssetId::USD, 5.0);
+    bad.insert(AssetId::EUR, f64::NAN);
+    assert!(exchange.add_liquidity_multi("lp_2", bad).is_err());
+    assert!(exchange.get_liquidity("lp_2").unwrap().values().all(|v| *v == 0.0));
+    assert!((exchange.get_total_liquidity().unwrap()["USD"] - amounts[&AssetId::USD]).abs() < 1e-9);
+}
+
+#[test]
+fn test_exchange_api_settle_fills() {
+    let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "EUR", 100.0).unwrap();
+    exchange.add_liquidity("alice", "USD", 50.0).unwrap();
+    exchange.add_liquidity("bob", "USD", 50.0).unwrap();
+
+    let alice_order = exchange.submit_order("alice", "USD", "EUR", 10.0, None, None, None).unwrap().order_id;
+    let bob_order = exchange.submit_order("bob", "USD", "EUR", 10.0, None, None, None).unwrap().order_id;
+
+    let fill = |order_id: &str, pay_units: f64, recv_units: f64| Fill {
+        order_id: order_id.to_string(),
+        fill_frac: 1.0,
+        pay_asset: AssetId::USD,
+        recv_asset: AssetId::EUR,
+        pay_units,
+        recv_units,
+        fees_paid: BTreeMap::new(),
+    };
+
+    // Known fill: alice pays 10 USD for 9 EUR
+    exchange.settle_fills(&[fill(&alice_order, 10.0, 9.0)]).unwrap();
+    let alice = exchange.get_liquidity("alice").unwrap();
+    assert!((alice["USD"] - 40.0).abs() < 1e-9);
+    assert!((alice["EUR"] - 9.0).abs() < 1e-9);
+    let pool = exchange.get_liquidity(POOL_ACCOUNT).unwrap();
+    assert!((pool["USD"] - 10.0).abs() < 1e-9);
+    assert!((pool["EUR"] - 91.0).abs() < 1e-9);
+
+    // Second fill overdraws the pool, so neither fill in the batch applies
+    let before_alice = exchange.get_liquidity("alice").unwrap();
+    let before_bob = exchange.get_liquidity("bob").unwrap();
+    let before_pool = exchange.get_liquidity(POOL_ACCOUNT).unwrap();
+    let result = exchange.settle_fills(&[
+        fill(&alice_order, 10.0, 9.0),
+        fill(&bob_order, 10.0, 500.0),
+    ]);
+    assert!(matches!(result, Err(ExchangeError::InsufficientLiquidity(_))));
+    assert_eq!(exchange.get_liquidity("alice").unwrap(), before_alice);
+    assert_eq!(exchange.get_liquidity("bob").unwrap(), before_bob);
+    assert_eq!(exchange.get_liquidity(POOL_ACCOUNT).unwrap(), before_pool);
+
+    // Fills for unknown orders are rejected
+    assert!(exchange.settle_fills(&[fill("order_unknown", 1.0, 1.0)]).is_err());
+}
+
+#[test]
+fn test_exchange_api_warmup_tracks_oracle() {
+    use convexfx_clearing::{EpochInstance, ScpClearing};
+    use convexfx_oracle::Oracle;
+    use convexfx_types::{AccountId, Amount, PairOrder};
+
+    let config = ExchangeConfig {
+        warmup_epochs: 2,
+        ..ExchangeConfig::default()
+    };
+    let ref_prices = MockOracle::new().reference_prices(1).unwrap();
+    let inventory: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|a| (*a, 10.0)).collect();
+
+    // An EUR bid whose limit can only be met by moving EUR 20 bps below the oracle
+    let ref_ratio = ref_prices.get_ref(AssetId::EUR).exp();
+    let orders = vec![PairOrder {
+        id: "eur_bid".to_string(),
+        trader: AccountId::new("trader1"),
+        pay: AssetId::USD,
+        receive: AssetId::EUR,
+        budget: Amount::from_units(1),
+        limit_ratio: Some(ref_ratio * (-0.002f64).exp()),
+        min_fill_fraction: None,
+        max_slippage_bps: None,
+        submission_mid: None,
+        metadata: serde_json::json!({}),
+    }];
+
+    let clear = |batches_cleared: u64| {
+        let instance = EpochInstance::new(
+            batches_cleared + 1,
+            inventory.clone(),
+            orders.clone(),
+            ref_prices.clone(),
+            config.risk_parameters_for_batch(batches_cleared).unwrap(),
+        );
+        let solution = ScpClearing::with_clarabel().clear_epoch(&instance).unwrap();
+        let max_deviation_bps = AssetId::all()
+            .iter()
+            .map(|a| (solution.y_star[a] - ref_prices.get_ref(*a)).abs() * 10000.0)
+            .fold(0.0, f64::max);
+        (max_deviation_bps, solution.fills[0].fill_frac)
+    };
+
+    // Warm-up batches stay pinned to the oracle and leave the order unfilled
+    for batches_cleared in 0..2 {
+        let (deviation_bps, fill_frac) = clear(batches_cleared);
+        assert!(deviation_bps < 0.1, "warm-up deviation {} bps", deviation_bps);
+        assert!(fill_frac < 1e-6);
+    }
+
+    // Afterwards the configured band lets clearing move EUR to meet the limit
+    let (deviation_bps, fill_frac) = clear(2);
+    assert!(deviation_bps > 19.0, "post warm-up deviation {} bps", deviation_bps);
+    assert!(fill_frac > 0.5);
+}
+
+#[test]
+fn test_exchange_api_warmup_batches_clear_at_oracle() {
+    let config = ExchangeConfig {
+        warmup_epochs: 2,
+        ..ExchangeConfig::default()
+    };
+    let mut exchange = Exchange::new(config).unwrap();
+    for asset in AssetId::all() {
+        exchange.add_liquidity(POOL_ACCOUNT, &asset.to_string(), 10.0).unwrap();
+    }
+    exchange.add_liquidity("trader1", "USD", 10.0).unwrap();
+
+    let oracle = exchange.get_current_prices().unwrap();
+    // Same EUR bid each batch: its limit needs EUR 20 bps below the oracle
+    let limit_ratio = oracle["EUR"] * (-0.002f64).exp();
+    let run_batch = |exchange: &mut Exchange| {
+        exchange.submit_order("trader1", "USD", "EUR", 1.0, Some(limit_ratio), None, None).unwrap();
+        let batch = exchange.execute_batch().unwrap();
+        let max_deviation_bps = batch
+            .prices
+            .iter()
+            .map(|(asset, price)| (price / oracle[&asset.to_string()]).ln().abs() * 10_000.0)
+            .fold(0.0, f64::max);
+        let filled: f64 = batch.fills.iter().map(|f| f.fill_frac).sum();
+        (max_deviation_bps, filled)
+    };
+
+    // Warm-up batches stay pinned to the oracle and leave the bid unfilled
+    for _ in 0..2 {
+        let (deviation_bps, filled) = run_batch(&mut exchange);
+        assert!(deviation_bps < 0.1, "warm-up deviation {} bps", deviation_bps);
+        assert!(filled < 1e-6, "warm-up fill {}", filled);
+    }
+
+    // The third batch uses the configured band and fills the bid
+    let (deviation_bps, filled) = run_batch(&mut exchange);
+    assert!(deviation_bps > 19.0, "post warm-up deviation {} bps", deviation_bps);
+    assert!(filled > 0.5, "post warm-up fill {}", filled);
+}
+
+#[test]
+fn test_exchange_api_slippage_cap_rejects_fill_without_moving_funds() {
+    let config = ExchangeConfig {
+        max_fill_slippage_bps: Some(10.0),
+        ..ExchangeConfig::default()
+    };
+    let mut exchange = Exchange::new(config).unwrap();
+    for asset in AssetId::all() {
+        exchange.add_liquidity(POOL_ACCOUNT, &asset.to_string(), 10.0).unwrap();
+    }
+    exchange.add_liquidity("bidder", "USD", 5.0).unwrap();
+    exchange.add_liquidity("seller", "EUR", 5.0).unwrap();
+    let before_total = exchange.get_total_liquidity().unwrap();
+
+    // The bid's limit moves EUR 20 bps below the oracle, which is a 20 bps
+    // adverse print for a market order selling EUR in the same batch
+    let oracle = exchange.get_current_prices().unwrap();
+    let limit_ratio = oracle["EUR"] * (-0.002f64).exp();
+    let bid = exchange.submit_order("bidder", "USD", "EUR", 1.0, Some(limit_ratio), None, None).unwrap().order_id;
+    let sell = exchange.submit_order("seller", "EUR", "USD", 1.0, None, None, None).unwrap().order_id;
+
+    let batch = exchange.execute_batch().unwrap();
+    let fill = |order_id: &str| batch.fills.iter().find(|f| f.order_id == order_id).unwrap();
+    let (bid_fill, sell_fill) = (fill(&bid), fill(&sell));
+    assert!(bid_fill.fill_frac > 0.5);
+    assert_eq!(sell_fill.fill_frac, 0.0);
+
+    // The rejected seller keeps exactly what they had; only the bid settles
+    let seller = exchange.get_liquidity("seller").unwrap();
+    assert_eq!(seller.get("EUR").copied(), Some(5.0));
+    assert_eq!(seller.get("USD").copied().unwrap_or(0.0), 0.0);
+    let pool = exchange.get_liquidity(POOL_ACCOUNT).unwrap();
+    assert!((pool["USD"] - (10.0 + bid_fill.pay_units)).abs() < 1e-6, "pool USD {}", pool["USD"]);
+    assert!((pool["EUR"] - (10.0 - bid_fill.recv_units)).abs() < 1e-6, "pool EUR {}", pool["EUR"]);
+    for (asset, total) in exchange.get_total_liquidity().unwrap() {
+        assert!((total - before_total[&asset]).abs() < 1e-6, "{} not conserved", asset);
+    }
+}
+
+#[test]
+fn test_exchange_api_preview_matches_batch() {
+    let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
+    for asset in AssetId::all() {
+        exchange.add_liquidity(POOL_ACCOUNT, &asset.to_string(), 20.0).unwrap();
+    }
+    exchange.add_liquidity("trader1", "USD", 5.0).unwrap();
+
+    let before_total = exchange.get_total_liquidity().unwrap();
+    let before_epoch = exchange.get_current_epoch().epoch_id;
+
+    let (recv_units, slippage_bps) = exchange.preview_order("USD", "EUR", 2.0, None, None).unwrap();
+    assert!(recv_units > 0.0);
+    assert!(slippage_bps.is_finite());
+
+    // Previewing moves no funds and does not advance the epoch
+    assert_eq!(exchange.get_total_liquidity().unwrap(), before_total);
+    assert_eq!(exchange.get_current_epoch().epoch_id, before_epoch);
+    assert_eq!(exchange.get_liquidity(POOL_ACCOUNT).unwrap()["EUR"], 20.0);
+
+    // Executing a batch with the same order produces the previewed fill
+    let order_id = exchange.submit_order("trader1", "USD", "EUR", 2.0, None, None, None).unwrap().order_id;
+    let batch = exchange.execute_batch().unwrap();
+    assert_eq!(batch.fills.len(), 1);
+    let fill = &batch.fills[0];
+    assert_eq!(fill.order_id, order_id);
+    assert!((fill.recv_units - recv_units).abs() < 1e-9, "{} vs {}", fill.recv_units, recv_units);
+
+    let oracle_rate = exchange.get_asset_price("USD").unwrap() / exchange.get_asset_price("EUR").unwrap();
+    let executed_slippage_bps = (oracle_rate / exchange.get_cleared_rate("USD", "EUR").unwrap()).ln() * 10_000.0;
+    assert!((executed_slippage_bps - slippage_bps).abs() < 1e-6, "{} vs {}", executed_slippage_bps, slippage_bps);
+    assert!((exchange.get_liquidity("trader1").unwrap()["EUR"] - recv_units).abs() < 1e-9);
+
+    // Unknown assets are rejected rather than quoted
+    assert!(matches!(
+        exchange.preview_order("USD", "XYZ", 1.0, None, None),
+        Err(ExchangeError::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_exchange_api_fixed_prices_pin_preview() {
+    let funded = |config: ExchangeConfig| {
+        let mut exchange = Exchange::new(config).unwrap();
+        for asset in AssetId::all() {
+            exchange.add_liquidity(POOL_ACCOUNT, &asset.to_string(), 20.0).unwrap();
+        }
+        exchange
+    };
+    let pinned = |eur: f64| {
+        let mut config = ExchangeConfig::default();
+        for asset in AssetId::all() {
+            let price = MockOracle::new().current_prices().unwrap().get_ref(*asset).exp();
+            config.fixed_prices.insert(*asset, price);
+        }
+        config.fixed_prices.insert(AssetId::EUR, eur);
+        config
+    };
+
+    // Preview clears against the pinned EUR price, not the oracle's 1.1
+    let (recv_units, slippage_bps) = funded(pinned(1.25)).preview_order("USD", "EUR", 2.0, None, None).unwrap();
+    assert!((recv_units - 2.0 / 1.25).abs() < 0.02, "{} EUR", recv_units);
+    assert!(slippage_bps.abs() < 100.0, "{} bps", slippage_bps);
+
+    // A price with no logarithm is refused instead of clearing on NaN
+    for bad in [0.0, -1.0, f64::NAN] {
+        assert!(matches!(
+            funded(pinned(bad)).preview_order("USD", "EUR", 2.0, None, None),
+            Err(ExchangeError::Asset(ConvexFxError::InvalidPrice(_)))
+        ));
+    }
+}
+
+#[test]
+fn test_exchange_api_epoch_notional_cap_throttles_fills() {
+    let funded = |config: ExchangeConfig| {
+        let mut exchange = Exchange::new(config).unwrap();
+        for asset in AssetId::all() {
+            exchange.add_liquidity(POOL_ACCOUNT, &asset.to_string(), 20.0).unwrap();
+        }
+        exchange
+    };
+
+    let (uncapped_recv, _) = funded(ExchangeConfig::default())
+        .preview_order("USD", "EUR", 2.0, None, None)
+        .unwrap();
+    assert!(uncapped_recv > 1.0);
+
+    // Caps are in numeraire (USD) value at the batch's own cleared prices:
+    // a 0.5 USD cap on EUR allows about a quarter of the fill
+    let mut config = ExchangeConfig::default();
+    config.max_epoch_notional.insert(AssetId::EUR, 0.5);
+    let (eur_capped, _) = funded(config.clone()).preview_order("USD", "EUR", 2.0, None, None).unwrap();
+    assert!(eur_capped > 0.0 && eur_capped < uncapped_recv / 2.0, "{} vs {}", eur_capped, uncapped_recv);
+
+    let mut exchange = funded(config.clone());
+    exchange.add_liquidity("trader1", "USD", 5.0).unwrap();
+    exchange.submit_order("trader1", "USD", "EUR", 2.0, None, None, None).unwrap();
+    let batch = exchange.execute_batch().unwrap();
+    let eur_outflow = batch.fills[0].recv_units * batch.prices[&AssetId::EUR];
+    assert!((eur_outflow - 0.5).abs() < 1e-9, "EUR outflow {} USD vs cap 0.5", eur_outflow);
+    assert!((batch.fills[0].recv_units - eur_capped).abs() < 1e-9);
+
+    // Throttling below an order's minimum fill rejects it instead
+    let (min_fill_capped, _) = funded(config.clone()).preview_order("USD", "EUR", 2.0, None, Some(0.5)).unwrap();
+    assert_eq!(min_fill_capped, 0.0);
+    let (min_fill_met, _) = funded(config).preview_order("USD", "EUR", 2.0, None, Some(0.2)).unwrap();
+    assert!((min_fill_met - eur_capped).abs() < 1e-9);
+
+    // A cap on the pay asset scales the whole fill, at unchanged prices
+    let mut config = ExchangeConfig::default();
+    config.max_epoch_notional.insert(AssetId::USD, 1.0);
+    let (usd_capped, _) = funded(config).preview_order("USD", "EUR", 2.0, None, None).unwrap();
+    // (the uncapped order fills to solver accuracy, not exactly)
+    assert!((usd_capped - uncapped_recv / 2.0).abs() < 1e-6, "{} vs {}", usd_capped, uncapped_recv / 2.0);
+
+    // An all-or-nothing order is rejected by any throttling
+    let mut config = ExchangeConfig::default();
+    config.max_epoch_notional.insert(AssetId::USD, 1.99);
+    let (all_or_nothing, _) = funded(config).preview_order("USD", "EUR", 2.0, None, Some(1.0)).unwrap();
+    assert_eq!(all_or_nothing, 0.0);
+
+    // A cap on an asset the order does not touch changes nothing
+    let mut config = ExchangeConfig::default();
+    config.max_epoch_notional.insert(AssetId::JPY, 0.0);
+    let (untouched, _) = funded(config).preview_order("USD", "EUR", 2.0, None, None).unwrap();
+    assert!((untouched - uncapped_recv).abs() < 1e-9);
+
+    // Invalid caps reject the batch
+    let mut config = ExchangeConfig::default();
+    config.max_epoch_notional.insert(AssetId::EUR, -1.0);
+    assert!(matches!(funded(config).execute_batch(), Err(ExchangeError::InvalidArgument(_))));
+}
+
+const SAMPLE_TOML_CONFIG: &str = r#"
+batch_interval_seconds = 15
+solver_backend = "Simple"
+max_fill_slippage_bps = 25.0
+
+[risk_parameters]
+q_target = { USD = 20.0, EUR = 20.0, JPY = 20.0, GBP = 20.0, CHF = 20.0, AUD = 20.0 }
+q_min = { USD = 5.0, EUR = 5.0, JPY = 5.0, GBP = 5.0, CHF = 5.0, AUD = 5.0 }
+q_max = { USD = 40.0, EUR = 40.0, JPY = 40.0, GBP = 40.0, CHF = 40.0, AUD = 40.0 }
+gamma_diag = [1.0, 1.0, 1.0, 1.0, 1.0, 1.0]
+w_diag = [250.0, 250.0, 250.0, 250.0, 250.0, 250.0]
+eta = 2.0
+price_band_bps = 30.0
+ghost_inventory_weight = 0.0
+
+[[initial_assets]]
+symbol = "USD"
+name = "US Dollar"
+decimals = 2
+is_base_currency = true
+initial_price = 1.0
+
+[[initial_assets]]
+symbol = "GBP"
+name = "British Pound"
+decimals = 2
+is_base_currency = false
+initial_price = 1.27
+"#;
+
+#[test]
+fn test_exchange_config_from_toml() {
+    let config = ExchangeConfig::from_toml_str(SAMPLE_TOML_CONFIG).unwrap();
+
+    assert_eq!(config.batch_interval_seconds, 15);
+    assert!(matches!(config.solver_backend, SolverBackend::Simple));
+    assert_eq!(config.max_fill_slippage_bps, Some(25.0));
+    // Omitted fields fall back to the defaults
+    assert_eq!(config.api_port, ExchangeConfig::default().api_port);
+
+    let risk = &config.risk_parameters;
+    assert_eq!(risk.target(AssetId::GBP), 20.0);
+    assert_eq!(risk.max_bound(AssetId::JPY), 40.0);
+    assert_eq!(risk.eta, 2.0);
+    assert_eq!(risk.price_band_bps, 30.0);
+    // The skipped matrices are rebuilt from their diagonals
+    assert_eq!(risk.w_track[(2, 2)], 250.0);
+
+    let symbols: Vec<&str> = config.initial_assets.iter().map(|a| a.symbol.as_str()).collect();
+    assert_eq!(symbols, ["USD", "GBP"]);
+    assert_eq!(config.initial_assets[1].initial_price, 1.27);
+
+    // The same file loads from disk, and the loaded config runs an exchange
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("exchange.toml");
+    std::fs::write(&path, SAMPLE_TOML_CONFIG).unwrap();
+    let from_file = ExchangeConfig::from_toml_file(&path).unwrap();
+    assert_eq!(from_file.batch_interval_seconds, 15);
+    assert!(Exchange::new(from_file).is_ok());
+
+    assert!(matches!(
+        ExchangeConfig::from_toml_file(dir.path().join("missing.toml")),
+        Err(ExchangeError::Config(_))
+    ));
+}
+
+#[test]
+fn test_exchange_config_rejects_invalid_toml() {
+    let invalid_backend = SAMPLE_TOML_CONFIG.replace(r#"solver_backend = "Simple""#, r#"solver_backend = "Gurobi""#);
+    match ExchangeConfig::from_toml_str(&invalid_backend) {
+        Err(ExchangeError::Config(message)) => {
+            assert!(message.contains("Gurobi"), "{}", message);
+            assert!(message.contains("Clarabel"), "{}", message);
+        }
+        other => panic!("expected a config error, got {:?}", other.map(|_| ())),
+    }
+
+    let zero_interval = SAMPLE_TOML_CONFIG.replace("batch_interval_seconds = 15", "batch_interval_seconds = 0");
+    assert!(matches!(ExchangeConfig::from_toml_str(&zero_interval), Err(ExchangeError::Config(_))));
+
+    let short_weights = SAMPLE_TOML_CONFIG.replace("w_diag = [250.0, 250.0, 250.0, 250.0, 250.0, 250.0]", "w_diag = [250.0]");
+    match ExchangeConfig::from_toml_str(&short_weights) {
+        Err(ExchangeError::Config(message)) => assert!(message.contains("w_diag"), "{}", message),
+        other => panic!("expected a config error, got {:?}", other.map(|_| ())),
+    }
+
+    let unknown_asset = SAMPLE_TOML_CONFIG.replace(r#"symbol = "GBP""#, r#"symbol = "XYZ""#);
+    assert!(matches!(ExchangeConfig::from_toml_str(&unknown_asset), Err(ExchangeError::Config(_))));
+}
+
+#[test]
+fn test_exchange_api_snapshot_restore_continues_epochs() {
+    let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "EUR", 100.0).unwrap();
+    exchange.add_liquidity("alice", "USD", 50.0).unwrap();
+    exchange.submit_order("alice", "USD", "EUR", 10.0, None, None, None).unwrap();
+
+    let first = exchange.execute_batch().unwrap();
+    assert_eq!(first.epoch_id, 1);
+    assert_eq!(exchange.get_current_epoch().epoch_id, 2);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json");
+    exchange.save_to(&path).unwrap();
+
+    // A fresh process picks up where the old one stopped
+    let mut restored = Exchange::new(ExchangeConfig::default()).unwrap();
+    restored.load_from(&path).unwrap();
+    assert_eq!(restored.get_current_epoch().epoch_id, 2);
+    assert_eq!(restored.get_liquidity("alice").unwrap(), exchange.get_liquidity("alice").unwrap());
+    assert_eq!(restored.get_liquidity(POOL_ACCOUNT).unwrap(), exchange.get_liquidity(POOL_ACCOUNT).unwrap());
+    assert_eq!(
+        restored.get_cleared_rate("USD", "EUR").unwrap(),
+        exchange.get_cleared_rate("USD", "EUR").unwrap()
+    );
+
+    let second = restored.execute_batch().unwrap();
+    assert_eq!(second.epoch_id, 2);
+    assert_eq!(restored.get_current_epoch().epoch_id, 3);
+
+    // Garbage on disk is an error, not a silently reset exchange
+    std::fs::write(&path, "not a snapshot").unwrap();
+    assert!(Exchange::new(ExchangeConfig::default()).unwrap().load_from(&path).is_err());
+}
+
+#[test]
+fn test_exchange_api_prices_at_past_epochs() {
+    let config = ExchangeConfig {
+        price_history_epochs: 2,
+        ..ExchangeConfig::default()
+    };
+    let mut exchange = Exchange::new(config).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "EUR", 100.0).unwrap();
+
+    let batches: Vec<_> = (0..3).map(|_| exchange.execute_batch().unwrap()).collect();
+    assert_eq!(batches[2].epoch_id, 3);
+
+    // The latest two batches are retained with their cleared prices
+    for batch in &batches[1..] {
+        let prices = exchange.get_prices_at(batch.epoch_id).unwrap();
+        assert_eq!(prices.len(), batch.prices.len());
+        for (asset, price) in &batch.prices {
+            assert_eq!(prices[&asset.to_string()], *price);
+        }
+    }
+
+    // The first batch has aged out and epoch 4 has not been cleared yet
+    assert!(matches!(exchange.get_prices_at(1), Err(ExchangeError::NotFound(_))));
+    assert!(matches!(exchange.get_prices_at(4), Err(ExchangeError::NotFound(_))));
+
+    // With the default retention the first batch is still available
+    let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
+    exchange.add_liquidity(POOL_ACCOUNT, "EUR", 100.0).unwrap();
+    let first = exchange.execute_batch().unwrap();
+    exchange.execute_batch().unwrap();
+    exchange.execute_batch().unwrap();
+    let prices = exchange.get_prices_at(first.epoch_id).unwrap();
+    assert_eq!(prices["EUR"], first.prices[&AssetId::EUR]);
+}
+
+#[test]
+fn test_exchange_api_pool_pnl() {
+    // Runs five batches; each epoch settles `flow(eur_price)` before clearing
+    let run = |flow: &dyn Fn(f64) -> Vec<(&'static str, AssetId, AssetId, f64, f64)>| -> Vec<f64> {
+        let mut exchange = Exchange::new(ExchangeConfig::default()).unwrap();
+        exchange.add_liquidity(POOL_ACCOUNT, "USD", 1000.0).unwrap();
+        exchange.add_liquidity(POOL_ACCOUNT, "EUR", 1000.0).unwrap();
+        exchange.add_liquidity("alice", "USD", 1000.0).unwrap();
+        exchange.add_liquidity("bob", "EUR", 1000.0).unwrap();
+        assert_eq!(exchange.pool_pnl(), 0.0);
+
+        exchange.execute_batch().unwrap();
+        let eur_price = exchange.get_asset_price("EUR").unwrap();
+        (0..5)
+            .map(|_| {
+                let fills: Vec<Fill> = flow(eur_price)
+                    .into_iter()
+                    .map(|(trader, pay_asset, recv_asset, pay_units, recv_units)| {
+                        let order = exchange
+                            .submit_order(trader, &pay_asset.to_string(), &recv_asset.to_string(), pay_units, None, None, None)
+                            .unwrap();
+                        Fill {
+                            order_id: order.order_id,
+                            fill_frac: 1.0,
+                            pay_asset,
+                            recv_asset,
+                            pay_units,
+                            recv_units,
+                            fees_paid: BTreeMap::new(),
+                        }
+                    })
+                    .collect();
+                exchange.settle_fills(&fills).unwrap();
+                exchange.execute_batch().unwrap();
+                exchange.pool_pnl()
+            })
+            .collect()
+    };
+
+    // Balanced flow at the oracle rate leaves the pool flat
+    let balanced = run(&|p| {
+        vec![
+            ("alice", AssetId::USD, AssetId::EUR, 10.0, 10.0 / p),
+            ("bob", AssetId::EUR, AssetId::USD, 10.0 / p, 10.0),
+        ]
+    });
+    assert!(balanced.iter().all(|pnl| pnl.abs() < 1e-6), "balanced pnl {:?}", balanced);
+
+    // Buying EUR from the pool 10% below the oracle costs it 1/0.9 - 1 of
+    // every 10 USD traded, epoch after epoch
+    let mispriced = run(&|p| vec![("alice", AssetId::USD, AssetId::EUR, 10.0, 10.0 / (0.9 * p))]);
+    for (epoch, pnl) in mispriced.iter().enumerate() {
+        let expected = -(epoch as f64 + 1.0) * 10.0 * (1.0 / 0.9 - 1.0);
+        assert!((pnl - expected).abs() < 1e-6, "epoch {}: pnl {} vs {}", epoch, pnl, expected);
+    }
+}
+
+#[test]
+fn test_exchange_api_per_asset_fees() {
+    let config = ExchangeConfig {
+        fee_bps: BTreeMap::from([(AssetId::EUR, 10.0), (AssetId::JPY, 50.0)]),
+        ..ExchangeConfig::default()
+    };
+    let mut exchange = Exchange::new(config).unwrap();
+    for asset in AssetId::all() {
+        exchange.add_liquidity(POOL_ACCOUNT, &asset.to_string(), 20.0).unwrap();
+    }
+    exchange.add_liquidity(POOL_ACCOUNT, "JPY", 180.0).unwrap();
+    exchange.add_liquidity("alice", "USD", 10.0).unwrap();
+
+    for recv_asset in ["EUR", "JPY", "GBP"] {
+        exchange.submit_order("alice", "USD", recv_asset, 1.0, None, None, None).unwrap();
+    }
+    let pool_before = exchange.get_liquidity(POOL_ACCOUNT).unwrap();
+    let batch = exchange.execute_batch().unwrap();
+    assert_eq!(batch.fills.len(), 3);
+
+    // 10 bps on EUR, 50 bps on JPY, nothing on GBP, withheld from what alice receives
+    let recv = |asset: AssetId| batch.fills.iter().find(|f| f.recv_asset == asset).unwrap().recv_units;
+    let alice = exchange.get_liquidity("alice").unwrap();
+    let fees = exchange.get_liquidity("fees").unwrap();
+    for (asset, bps) in [(AssetId::EUR, 10.0), (AssetId::JPY, 50.0), (AssetId::GBP, 0.0)] {
+        let symbol = asset.to_string();
+        let fee = recv(asset) * bps / 10000.0;
+        assert!(recv(asset) > 0.0, "{} not filled", symbol);
+        assert!((alice[&symbol] - (recv(asset) - fee)).abs() < 1e-9, "alice {} {}", symbol, alice[&symbol]);
+        assert!((fees.get(&symbol).copied().unwrap_or(0.0) - fee).abs() < 1e-9, "fee {}", symbol);
+        assert!((batch.fees.get(&asset).copied().unwrap_or(0.0) - fee).abs() < 1e-9, "reported fee {}", symbol);
+    }
+    assert!(!batch.fees.contains_key(&AssetId::GBP));
+
+    // The pool's holdings move by the fills alone, as clearing's conservation
+    // check expects: fees are paid by the traders, not credited to the pool
+    let pool_after = exchange.get_liquidity(POOL_ACCOUNT).unwrap();
+    let mut expected = pool_before.clone();
+    for fill in &batch.fills {
+        assert!(fill.fees_paid.is_empty());
+        for (asset, delta) in fill.inventory_deltas() {
+            *expected.entry(asset.to_string()).or_insert(0.0) += delta;
+        }
+    }
+    for (symbol, amount) in &expected {
+        // Each ledger transfer rounds to the unit scale
+        assert!((pool_after[symbol] - amount).abs() < 1e-8, "pool {} {} vs {}", symbol, pool_after[symbol], amount);
+    }
+
+    let bad = ExchangeConfig {
+        fee_bps: BTreeMap::from([(AssetId::EUR, -1.0)]),
+        ..ExchangeConfig::default()
+    };
+    assert!(bad.validate().is_err());
+    let bad = ExchangeConfig { fee_account: POOL_ACCOUNT.to_string(), ..ExchangeConfig::default() };
+    assert!(bad.validate().is_err());
+}
@@ -48,6 +48,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(1.0).unwrap(),
             limit_ratio: Some(1.15),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "market_making"}),
         },
         PairOrder {
@@ -58,6 +60,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(0.75).unwrap(),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "retail"}),
         },
         // GBP/USD orders
@@ -69,6 +73,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(0.5).unwrap(),
             limit_ratio: Some(0.85), // Max USDGBP = 0.85 (min GBPUSD = 1/0.85 = 1.176)
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "hedge"}),
         },
         // JPY/USD orders (smaller size due to JPY denomination)
@@ -80,6 +86,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(0.15).unwrap(), // Smaller budget for JPY
             limit_ratio: Some(105.0), // Max JPYUSD
             min_fill_fraction: Some(0.3),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "institutional"}),
         },
         // Cross-pair: EUR/GBP
@@ -91,6 +99,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(0.6).unwrap(),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "arb"}),
         },
         // CHF orders
@@ -102,6 +112,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(0.8).unwrap(),
             limit_ratio: Some(1.12),
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "flight_to_quality"}),
         },
         // AUD orders (new 6th asset)
@@ -113,6 +125,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(0.9).unwrap(),
             limit_ratio: Some(1.35), // Max AUDUSD
             min_fill_fraction: Some(0.2),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "commodity_proxy"}),
         },
         PairOrder {
@@ -123,6 +137,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(1.5).unwrap(),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "risk_off"}),
         },
         // Complex cross: JPY/EUR (smaller size)
@@ -134,6 +150,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(1.0).unwrap(), // 1M JPY (~0.01M USD equivalent)
             limit_ratio: Some(125.0),
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "carry_trade"}),
         },
         // AUD/JPY cross
@@ -145,6 +163,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(0.4).unwrap(),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "risk_reversal"}),
         },
     ];
@@ -177,7 +197,7 @@ fn test_complex_6_asset_clearing() {
     }
     risk.gamma_diag = vec![1.0; 6];
     risk.w_diag = vec![100.0; 6];
-    risk.rebuild_matrices();
+    risk.rebuild_matrices().unwrap();
 
     // Create epoch instance
     let instance = EpochInstance::new(1, inventory.clone(), orders.clone(), ref_prices, risk);
@@ -259,9 +279,9 @@ fn test_complex_6_asset_clearing() {
             println!("=== No-Arbitrage Verification ===");
             
             // Triangle 1: EUR/USD/JPY
-            let eurusd = sol.prices.get(&AssetId::EUR).unwrap() / sol.prices.get(&AssetId::USD).unwrap();
-            let usdjpy = 1.0 / (sol.prices.get(&AssetId::JPY).unwrap() / sol.prices.get(&AssetId::USD).unwrap());
-            let eurjpy_direct = sol.prices.get(&AssetId::EUR).unwrap() / sol.prices.get(&AssetId::JPY).unwrap();
+            let eurusd = sol.rate(AssetId::EUR, AssetId::USD);
+            let usdjpy = sol.rate(AssetId::USD, AssetId::JPY);
+            let eurjpy_direct = sol.rate(AssetId::EUR, AssetId::JPY);
             let eurjpy_cross = eurusd * usdjpy;
             let arb_error_1 = ((eurjpy_direct - eurjpy_cross) / eurjpy_direct * 10000.0).abs();
             
@@ -273,8 +293,8 @@ fn test_complex_6_asset_clearing() {
             println!("  Arbitrage error: {:.4} bps", arb_error_1);
             
             // Triangle 2: AUD/USD/JPY
-            let audusd = sol.prices.get(&AssetId::AUD).unwrap() / sol.prices.get(&AssetId::USD).unwrap();
-            let audjpy_direct = sol.prices.get(&AssetId::AUD).unwrap() / sol.prices.get(&AssetId::JPY).unwrap();
+            let audusd = sol.rate(AssetId::AUD, AssetId::USD);
+            let audjpy_direct = sol.rate(AssetId::AUD, AssetId::JPY);
             let audjpy_cross = audusd * usdjpy;
             let arb_error_2 = ((audjpy_direct - audjpy_cross) / audjpy_direct * 10000.0).abs();
             
@@ -286,8 +306,8 @@ fn test_complex_6_asset_clearing() {
             println!("  Arbitrage error: {:.4} bps", arb_error_2);
             
             // Triangle 3: EUR/GBP/USD
-            let gbpusd = sol.prices.get(&AssetId::GBP).unwrap() / sol.prices.get(&AssetId::USD).unwrap();
-            let eurgbp_direct = sol.prices.get(&AssetId::EUR).unwrap() / sol.prices.get(&AssetId::GBP).unwrap();
+            let gbpusd = sol.rate(AssetId::GBP, AssetId::USD);
+            let eurgbp_direct = sol.rate(AssetId::EUR, AssetId::GBP);
             let eurgbp_cross = eurusd / gbpusd;
             let arb_error_3 = ((eurgbp_direct - eurgbp_cross) / eurgbp_direct * 10000.0).abs();
             
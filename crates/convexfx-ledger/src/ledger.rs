@@ -1,4 +1,4 @@
-use convexfx_types::{AccountId, Amount, AssetId, Inventory, Result};
+use convexfx_types::{AccountId, Amount, AssetId, EpochId, Inventory, Result};
 
 /// Ledger trait for managing account balances and transfers
 /// Provides an abstraction that can be implemented for in-memory, database, or on-chain storage
@@ -43,6 +43,45 @@ pub trait Ledger {
 
     /// Restore from a snapshot
     fn restore(&mut self, snapshot: &LedgerSnapshot) -> Result<()>;
+
+    /// Run several operations as one atomic transaction
+    ///
+    /// Changes made by `f` (log entries included) are kept only if it
+    /// returns `Ok`; on `Err` the ledger is left untouched. The default
+    /// runs `f` on a copy of the whole ledger; implementations that can
+    /// undo their own writes should override it. The `&mut self` borrow
+    /// is held for the whole transaction, so behind a `Mutex` no deposit or
+    /// withdrawal can interleave with it.
+    fn with_transaction<T, E>(&mut self, f: impl FnOnce(&mut Self) -> std::result::Result<T, E>) -> std::result::Result<T, E>
+    where
+        Self: Clone + Sized,
+    {
+        let mut tx = self.clone();
+        let out = f(&mut tx)?;
+        *self = tx;
+        Ok(out)
+    }
+
+    /// Balance changes recorded for an account, oldest first
+    fn history(&self, account: &AccountId) -> Vec<LedgerEntry>;
+
+    /// Rebuild a ledger by applying a log of balance changes in order
+    fn replay(entries: &[LedgerEntry]) -> Self
+    where
+        Self: Sized;
+}
+
+/// Single balance change in the ledger's append-only log
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LedgerEntry {
+    pub account: AccountId,
+    pub asset: AssetId,
+    /// Signed change in balance (negative for debits)
+    pub delta: Amount,
+    /// Epoch the change was recorded in
+    pub epoch_id: EpochId,
+    /// Wall-clock time in milliseconds
+    pub timestamp: u64,
 }
 
 /// Snapshot of ledger state for checkpoint/restore
@@ -1,7 +1,7 @@
 mod ledger;
 mod memory;
 
-pub use ledger::Ledger;
+pub use ledger::{Ledger, LedgerEntry, LedgerSnapshot};
 pub use memory::MemoryLedger;
 
 #[cfg(test)]
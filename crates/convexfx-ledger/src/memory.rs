@@ -1,13 +1,30 @@
-use convexfx_types::{AccountId, Amount, AssetId, ConvexFxError, Inventory, Result};
+use convexfx_types::{AccountId, Amount, AssetId, ConvexFxError, EpochId, Inventory, Result};
 use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::ledger::{Ledger, LedgerSnapshot};
+use crate::ledger::{Ledger, LedgerEntry, LedgerSnapshot};
 
 /// In-memory ledger implementation
 /// Suitable for testing and demo purposes
 #[derive(Debug, Clone)]
 pub struct MemoryLedger {
     accounts: BTreeMap<AccountId, Inventory>,
+    /// Append-only log of every balance change
+    history: Vec<LedgerEntry>,
+    /// Epoch stamped on new log entries
+    current_epoch: EpochId,
+    /// Undo state of the innermost running `with_transaction`
+    journal: Option<Journal>,
+}
+
+/// What a transaction needs to roll back: the prior balances of the
+/// accounts it touched (`None` for accounts it created) and the log length
+/// and epoch when it started
+#[derive(Debug, Clone)]
+struct Journal {
+    accounts: BTreeMap<AccountId, Option<Inventory>>,
+    history_len: usize,
+    current_epoch: EpochId,
 }
 
 impl MemoryLedger {
@@ -15,16 +32,64 @@ impl MemoryLedger {
     pub fn new() -> Self {
         MemoryLedger {
             accounts: BTreeMap::new(),
+            history: Vec::new(),
+            current_epoch: 0,
+            journal: None,
         }
     }
 
     /// Initialize with pre-funded accounts
     pub fn with_accounts(accounts: BTreeMap<AccountId, Inventory>) -> Self {
-        MemoryLedger { accounts }
+        let mut ledger = Self::new();
+        for (account, inventory) in &accounts {
+            for (asset, amount) in &inventory.units {
+                ledger.record(account, *asset, *amount);
+            }
+        }
+        ledger.accounts = accounts;
+        ledger
+    }
+
+    /// Set the epoch stamped on subsequent log entries
+    pub fn set_epoch(&mut self, epoch_id: EpochId) {
+        self.current_epoch = epoch_id;
+    }
+
+    /// Full log of balance changes across all accounts, oldest first
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.history
+    }
+
+    /// Append a balance change to the log
+    fn record(&mut self, account: &AccountId, asset: AssetId, delta: Amount) {
+        if delta.is_zero() {
+            return;
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        self.history.push(LedgerEntry {
+            account: account.clone(),
+            asset,
+            delta,
+            epoch_id: self.current_epoch,
+            timestamp,
+        });
+    }
+
+    /// Save an account's balances before a running transaction first changes it
+    fn stage(&mut self, account: &AccountId) {
+        if let Some(journal) = &mut self.journal {
+            if !journal.accounts.contains_key(account) {
+                journal.accounts.insert(account.clone(), self.accounts.get(account).cloned());
+            }
+        }
     }
 
     /// Get mutable reference to account inventory (creates if not exists)
     fn get_or_create_account_mut(&mut self, account: &AccountId) -> &mut Inventory {
+        self.stage(account);
         self.accounts
             .entry(account.clone())
             .or_insert_with(Inventory::new)
@@ -50,8 +115,9 @@ impl Ledger for MemoryLedger {
             ));
         }
 
-        let account = self.get_or_create_account_mut(lp);
-        account.add(asset, amount);
+        let new_balance = self.balance(lp, asset).checked_add(amount)?;
+        self.get_or_create_account_mut(lp).set(asset, new_balance);
+        self.record(lp, asset, amount);
         Ok(())
     }
 
@@ -62,16 +128,16 @@ impl Ledger for MemoryLedger {
             ));
         }
 
-        let current_balance = self.balance(lp, asset);
-        if current_balance < amount {
+        let remaining = self.balance(lp, asset).checked_sub(amount)?;
+        if remaining.is_negative() {
             return Err(ConvexFxError::InsufficientBalance(
                 lp.to_string(),
                 asset.to_string(),
             ));
         }
 
-        let account = self.get_or_create_account_mut(lp);
-        account.sub(asset, amount);
+        self.get_or_create_account_mut(lp).set(asset, remaining);
+        self.record(lp, asset, -amount);
         Ok(())
     }
 
@@ -93,23 +159,22 @@ impl Ledger for MemoryLedger {
         }
 
         // Check sufficient balance
-        let from_balance = self.balance(from, asset);
-        if from_balance < amount {
+        let from_remaining = self.balance(from, asset).checked_sub(amount)?;
+        if from_remaining.is_negative() {
             return Err(ConvexFxError::InsufficientBalance(
                 from.to_string(),
                 asset.to_string(),
             ));
         }
 
+        let to_current = if from == to { from_remaining } else { self.balance(to, asset) };
+        let to_balance = to_current.checked_add(amount)?;
+
         // Perform transfer
-        {
-            let from_account = self.get_or_create_account_mut(from);
-            from_account.sub(asset, amount);
-        }
-        {
-            let to_account = self.get_or_create_account_mut(to);
-            to_account.add(asset, amount);
-        }
+        self.get_or_create_account_mut(from).set(asset, from_remaining);
+        self.get_or_create_account_mut(to).set(asset, to_balance);
+        self.record(from, asset, -amount);
+        self.record(to, asset, amount);
 
         Ok(())
     }
@@ -140,6 +205,7 @@ impl Ledger for MemoryLedger {
     }
 
     fn create_account(&mut self, account: &AccountId) -> Result<()> {
+        self.stage(account);
         self.accounts
             .entry(account.clone())
             .or_insert_with(Inventory::new);
@@ -157,9 +223,91 @@ impl Ledger for MemoryLedger {
     }
 
     fn restore(&mut self, snapshot: &LedgerSnapshot) -> Result<()> {
+        // The log is append-only: record compensating entries for every
+        // balance the restore changes so replay still matches
+        let mut deltas = Vec::new();
+        let accounts = self.accounts.keys().chain(snapshot.accounts.keys());
+        for account in accounts.cloned().collect::<std::collections::BTreeSet<_>>() {
+            let current = self.account_balances(&account);
+            let target = snapshot.accounts.get(&account).cloned().unwrap_or_default();
+            for asset in AssetId::all() {
+                let delta = target.get(*asset) - current.get(*asset);
+                if !delta.is_zero() {
+                    deltas.push((account.clone(), *asset, delta));
+                }
+            }
+        }
+        for (account, asset, delta) in deltas {
+            self.record(&account, asset, delta);
+        }
+        let accounts: Vec<AccountId> = self.accounts.keys().chain(snapshot.accounts.keys()).cloned().collect();
+        for account in &accounts {
+            self.stage(account);
+        }
+
         self.accounts = snapshot.accounts.clone();
         Ok(())
     }
+
+    /// Instead of copying the whole ledger, the transaction records the
+    /// prior balances of each account it touches and the log length, and
+    /// rolls back only those on `Err`. Nested transactions roll back to
+    /// their own start.
+    fn with_transaction<T, E>(&mut self, f: impl FnOnce(&mut Self) -> std::result::Result<T, E>) -> std::result::Result<T, E>
+    where
+        Self: Clone + Sized,
+    {
+        let outer = self.journal.replace(Journal {
+            accounts: BTreeMap::new(),
+            history_len: self.history.len(),
+            current_epoch: self.current_epoch,
+        });
+        let out = f(self);
+        let journal = std::mem::replace(&mut self.journal, outer).expect("journal installed above");
+
+        match out {
+            Ok(value) => {
+                // The enclosing transaction must still be able to undo these changes
+                if let Some(outer) = &mut self.journal {
+                    for (account, before) in journal.accounts {
+                        outer.accounts.entry(account).or_insert(before);
+                    }
+                }
+                Ok(value)
+            }
+            Err(err) => {
+                for (account, before) in journal.accounts {
+                    match before {
+                        Some(inventory) => self.accounts.insert(account, inventory),
+                        None => self.accounts.remove(&account),
+                    };
+                }
+                self.history.truncate(journal.history_len);
+                self.current_epoch = journal.current_epoch;
+                Err(err)
+            }
+        }
+    }
+
+    fn history(&self, account: &AccountId) -> Vec<LedgerEntry> {
+        self.history
+            .iter()
+            .filter(|e| &e.account == account)
+            .cloned()
+            .collect()
+    }
+
+    fn replay(entries: &[LedgerEntry]) -> Self {
+        let mut ledger = Self::new();
+        for entry in entries {
+            ledger
+                .get_or_create_account_mut(&entry.account)
+                .add(entry.asset, entry.delta);
+            ledger.history.push(entry.clone());
+            ledger.current_epoch = entry.epoch_id;
+        }
+        ledger
+    }
 }
 
 #[cfg(test)]
@@ -243,6 +391,38 @@ mod tests {
         assert_eq!(inv.get(AssetId::EUR), Amount::from_units(800));
     }
 
+    #[test]
+    fn test_history_replay() {
+        let mut ledger = MemoryLedger::new();
+        let lp = AccountId::new("lp1");
+        let trader = AccountId::new("trader1");
+
+        ledger.deposit(&lp, AssetId::USD, Amount::from_units(1000)).unwrap();
+        ledger.deposit(&lp, AssetId::EUR, Amount::from_units(500)).unwrap();
+        ledger.set_epoch(1);
+        ledger.transfer(&lp, &trader, AssetId::EUR, Amount::from_units(120)).unwrap();
+        ledger.withdraw(&lp, AssetId::USD, Amount::from_units(250)).unwrap();
+
+        // A rolled-back deposit is logged as a compensating entry
+        let snapshot = ledger.snapshot();
+        ledger.deposit(&trader, AssetId::GBP, Amount::from_units(70)).unwrap();
+        ledger.restore(&snapshot).unwrap();
+
+        let lp_history = ledger.history(&lp);
+        assert_eq!(lp_history.len(), 4);
+        assert_eq!(lp_history[2].delta, -Amount::from_units(120));
+        assert_eq!(lp_history[2].epoch_id, 1);
+        assert_eq!(ledger.history(&trader).len(), 3);
+
+        let replayed = MemoryLedger::replay(ledger.entries());
+        assert_eq!(replayed.snapshot().accounts, ledger.snapshot().accounts);
+        for account in [&lp, &trader] {
+            assert_eq!(replayed.account_balances(account), ledger.account_balances(account));
+            assert_eq!(replayed.history(account), ledger.history(account));
+        }
+        assert_eq!(replayed.balance(&trader, AssetId::GBP), Amount::ZERO);
+    }
+
     #[test]
     fn test_snapshot_restore() {
         let mut ledger = MemoryLedger::new();
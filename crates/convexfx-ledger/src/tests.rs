@@ -114,6 +114,26 @@ mod tests {
         assert_eq!(ledger.balance(&acc, AssetId::EUR), Amount::from_units(100));
     }
 
+    #[test]
+    fn test_balance_overflow_rejected() {
+        let mut ledger = MemoryLedger::new();
+        let acc = AccountId::new("acc");
+        let other = AccountId::new("other");
+
+        let near_max = Amount::from_raw(i128::MAX - 10);
+        ledger.deposit(&acc, AssetId::USD, near_max).unwrap();
+        ledger.deposit(&other, AssetId::USD, Amount::from_units(1)).unwrap();
+        let entries = ledger.entries().len();
+
+        // Neither a deposit nor an incoming transfer may wrap the balance
+        assert!(ledger.deposit(&acc, AssetId::USD, Amount::from_units(1)).is_err());
+        assert!(ledger.transfer(&other, &acc, AssetId::USD, Amount::from_units(1)).is_err());
+
+        assert_eq!(ledger.balance(&acc, AssetId::USD), near_max);
+        assert_eq!(ledger.balance(&other, AssetId::USD), Amount::from_units(1));
+        assert_eq!(ledger.entries().len(), entries);
+    }
+
     #[test]
     fn test_negative_amount_rejection() {
         let mut ledger = MemoryLedger::new();
@@ -242,4 +262,132 @@ mod tests {
         let balances = ledger.account_balances(&acc);
         assert_eq!(balances.get(AssetId::USD), Amount::ZERO);
     }
+
+    #[test]
+    fn test_transaction_rollback_leaves_no_trace() {
+        let mut ledger = MemoryLedger::new();
+        let acc = AccountId::new("acc");
+        ledger.deposit(&acc, AssetId::USD, Amount::from_units(100)).unwrap();
+
+        let result: Result<()> = ledger.with_transaction(|tx| {
+            tx.withdraw(&acc, AssetId::USD, Amount::from_units(60))?;
+            tx.withdraw(&acc, AssetId::USD, Amount::from_units(60))
+        });
+        assert!(result.is_err());
+        assert_eq!(ledger.balance(&acc, AssetId::USD), Amount::from_units(100));
+        assert_eq!(ledger.entries().len(), 1);
+
+        ledger
+            .with_transaction(|tx| tx.withdraw(&acc, AssetId::USD, Amount::from_units(60)))
+            .unwrap();
+        assert_eq!(ledger.balance(&acc, AssetId::USD), Amount::from_units(40));
+    }
+
+    #[test]
+    fn test_nested_transaction_rolls_back_to_its_own_start() {
+        let mut ledger = MemoryLedger::new();
+        let (alice, bob, carol) = (AccountId::new("alice"), AccountId::new("bob"), AccountId::new("carol"));
+        ledger.deposit(&alice, AssetId::USD, Amount::from_units(100)).unwrap();
+        ledger.deposit(&bob, AssetId::EUR, Amount::from_units(50)).unwrap();
+
+        let result: Result<()> = ledger.with_transaction(|tx| {
+            tx.transfer(&alice, &bob, AssetId::USD, Amount::from_units(30))?;
+
+            // The inner failure undoes only the inner writes, including the
+            // account it created and the epoch it set
+            let inner: Result<()> = tx.with_transaction(|inner| {
+                inner.set_epoch(7);
+                inner.deposit(&carol, AssetId::GBP, Amount::from_units(5))?;
+                inner.withdraw(&bob, AssetId::EUR, Amount::from_units(80))
+            });
+            assert!(inner.is_err());
+            assert_eq!(tx.balance(&bob, AssetId::USD), Amount::from_units(30));
+            assert!(!tx.list_accounts().contains(&carol));
+            assert_eq!(tx.entries().len(), 4);
+
+            tx.with_transaction(|inner| inner.deposit(&carol, AssetId::GBP, Amount::from_units(5)))?;
+            tx.withdraw(&bob, AssetId::EUR, Amount::from_units(80))
+        });
+        assert!(result.is_err());
+
+        // The outer rollback also undoes the committed inner transaction
+        assert_eq!(ledger.list_accounts(), vec![alice.clone(), bob.clone()]);
+        assert_eq!(ledger.balance(&alice, AssetId::USD), Amount::from_units(100));
+        assert_eq!(ledger.balance(&bob, AssetId::USD), Amount::ZERO);
+        assert_eq!(ledger.entries().len(), 2);
+
+        ledger.set_epoch(3);
+        ledger
+            .with_transaction(|tx| tx.transfer(&alice, &bob, AssetId::USD, Amount::from_units(30)))
+            .unwrap();
+        assert_eq!(ledger.balance(&bob, AssetId::USD), Amount::from_units(30));
+        assert_eq!(ledger.history(&bob).last().unwrap().epoch_id, 3);
+    }
+
+    #[test]
+    fn test_concurrent_deposits_and_settlement() {
+        use std::sync::{Arc, Mutex};
+        use std::thread;
+
+        let pool = AccountId::new("pool");
+        let trader = AccountId::new("trader");
+        let ledger = Arc::new(Mutex::new(MemoryLedger::new()));
+        {
+            let mut l = ledger.lock().unwrap();
+            l.deposit(&pool, AssetId::EUR, Amount::from_units(1_000)).unwrap();
+            l.deposit(&trader, AssetId::USD, Amount::from_units(1_000)).unwrap();
+        }
+
+        let depositors: Vec<_> = (0..4)
+            .map(|i| {
+                let ledger = Arc::clone(&ledger);
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    let lp = AccountId::new(format!("lp{}", i));
+                    for _ in 0..200 {
+                        let mut l = ledger.lock().unwrap();
+                        l.deposit(&lp, AssetId::USD, Amount::from_units(1)).unwrap();
+                        l.deposit(&pool, AssetId::USD, Amount::from_units(1)).unwrap();
+                    }
+                })
+            })
+            .collect();
+
+        // Settlements alternate between committing and rolling back
+        let settler = {
+            let ledger = Arc::clone(&ledger);
+            let (pool, trader) = (pool.clone(), trader.clone());
+            thread::spawn(move || {
+                for round in 0..100 {
+                    let mut l = ledger.lock().unwrap();
+                    let _ = l.with_transaction(|tx| {
+                        tx.transfer(&trader, &pool, AssetId::USD, Amount::from_units(5))?;
+                        tx.transfer(&pool, &trader, AssetId::EUR, Amount::from_units(4))?;
+                        if round % 2 == 1 {
+                            return Err(ConvexFxError::Internal("abort".to_string()));
+                        }
+                        Ok(())
+                    });
+                    drop(l);
+                    thread::yield_now();
+                }
+            })
+        };
+
+        for handle in depositors {
+            handle.join().unwrap();
+        }
+        settler.join().unwrap();
+
+        // 50 committed settlements; no deposit lost to a rollback
+        let l = ledger.lock().unwrap();
+        for i in 0..4 {
+            assert_eq!(l.balance(&AccountId::new(format!("lp{}", i)), AssetId::USD), Amount::from_units(200));
+        }
+        assert_eq!(l.balance(&pool, AssetId::USD), Amount::from_units(800 + 250));
+        assert_eq!(l.balance(&pool, AssetId::EUR), Amount::from_units(800));
+        assert_eq!(l.balance(&trader, AssetId::USD), Amount::from_units(750));
+        assert_eq!(l.balance(&trader, AssetId::EUR), Amount::from_units(200));
+        assert_eq!(MemoryLedger::replay(l.entries()).snapshot().accounts, l.snapshot().accounts);
+    }
 }
@@ -0,0 +1,384 @@
+//! ConvexFX local laws
+//!
+//! The checks a clearing solution must pass before it is settled or proven:
+//! convergence, price consistency, fill feasibility, inventory conservation
+//! and the objective breakdown. This crate is `no_std` and allocation free so
+//! the SP1 program runs the exact code the host runs in
+//! `EpochSolution::validate`.
+//!
+//! Assets are identified by their `AssetId::index`, and per-asset values are
+//! passed as `(asset, value)` pairs.
+
+#![no_std]
+
+use core::fmt;
+
+/// Number of assets, and the asset ids `0..NUM_ASSETS` that inventory
+/// conservation is checked for
+pub const NUM_ASSETS: usize = 6;
+
+/// Fill fractions at or below this are treated as unfilled
+const MIN_FILL_AMOUNT: f64 = 1e-8;
+
+/// Tolerances for the local laws
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerances {
+    /// Tolerance for price convergence (log-space)
+    pub tolerance_y: f64,
+    /// Tolerance for fill fraction convergence
+    pub tolerance_alpha: f64,
+    /// Maximum relative error between linear prices and exp(y*)
+    pub max_price_deviation: f64,
+    /// Tolerance for numerical errors in inventory conservation and the
+    /// objective breakdown
+    pub inventory_tolerance: f64,
+}
+
+impl Tolerances {
+    /// Tolerances the SP1 program proves against
+    pub const DEFAULT: Tolerances = Tolerances {
+        tolerance_y: 1e-4,         // Matches SCP convergence tolerance
+        tolerance_alpha: 1e-5,     // Matches SCP convergence tolerance
+        max_price_deviation: 0.01, // 1%
+        inventory_tolerance: 1e-4, // Relaxed for numerical stability
+    };
+}
+
+impl Default for Tolerances {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A fill as the pool sees it
+#[derive(Debug, Clone, Copy)]
+pub struct FillFlows<'a> {
+    pub fill_frac: f64,
+    pub pay_asset: u8,
+    pub recv_asset: u8,
+    pub pay_units: f64,
+    pub recv_units: f64,
+    /// Fees credited to the pool, by asset
+    pub fees_paid: &'a [(u8, f64)],
+}
+
+/// Objective terms reported by the solver
+#[derive(Debug, Clone, Copy)]
+pub struct ObjectiveBreakdown {
+    pub inventory_risk: f64,
+    pub price_tracking: f64,
+    pub fill_incentive: f64,
+    pub total: f64,
+}
+
+/// Everything the local laws look at
+#[derive(Debug, Clone, Copy)]
+pub struct Witness<'a> {
+    pub converged: bool,
+    pub final_step_norm_y: f64,
+    pub final_step_norm_alpha: f64,
+    /// Log prices y*
+    pub y_star: &'a [(u8, f64)],
+    /// Linear prices, exp(y*)
+    pub prices: &'a [(u8, f64)],
+    /// Asset whose log price is pinned at 0
+    pub numeraire: u8,
+    pub fills: &'a [FillFlows<'a>],
+    pub initial_inventory: &'a [(u8, f64)],
+    /// Post-trade inventory (q_post)
+    pub final_inventory: &'a [(u8, f64)],
+    pub objective: ObjectiveBreakdown,
+}
+
+/// A broken local law
+///
+/// `fill` fields index into the witness's fills and `asset` fields are asset
+/// ids; see `Violation::display_with` for rendering them by name.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Violation {
+    NotConverged,
+    PriceStepNorm { norm: f64, tolerance: f64 },
+    FillStepNorm { norm: f64, tolerance: f64 },
+    MissingPrice { asset: u8 },
+    PriceInconsistency { asset: u8, expected: f64, actual: f64, error: f64 },
+    NumeraireNotPinned { numeraire: u8, log_price: f64 },
+    NonPositivePrice { asset: u8, price: f64 },
+    NonFinitePrice { asset: u8, price: f64 },
+    InvalidFillFraction { fill: usize, fill_frac: f64 },
+    NonPositivePay { fill: usize, pay_units: f64 },
+    NonPositiveReceive { fill: usize, recv_units: f64 },
+    NonFiniteAmounts { fill: usize, pay_units: f64, recv_units: f64 },
+    InventoryNotConserved { asset: u8, initial: f64, net_flow: f64, expected: f64, actual: f64, error: f64 },
+    NegativeInventoryRisk { value: f64 },
+    NegativePriceTracking { value: f64 },
+    NonFiniteObjective { value: f64 },
+    ObjectiveMismatch { components: f64, total: f64, error: f64 },
+}
+
+impl Violation {
+    /// Index of the offending fill, for violations about a single fill
+    pub fn fill(&self) -> Option<usize> {
+        match *self {
+            Violation::InvalidFillFraction { fill, .. }
+            | Violation::NonPositivePay { fill, .. }
+            | Violation::NonPositiveReceive { fill, .. }
+            | Violation::NonFiniteAmounts { fill, .. } => Some(fill),
+            _ => None,
+        }
+    }
+
+    /// Render the violation, naming assets with `asset_name`
+    pub fn display_with<'a, F, N>(&'a self, asset_name: F) -> impl fmt::Display + 'a
+    where
+        F: Fn(u8) -> N + 'a,
+        N: fmt::Display,
+    {
+        Described { violation: self, asset_name }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display_with(|asset| asset).fmt(f)
+    }
+}
+
+struct Described<'a, F> {
+    violation: &'a Violation,
+    asset_name: F,
+}
+
+impl<F, N> fmt::Display for Described<'_, F>
+where
+    F: Fn(u8) -> N,
+    N: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = &self.asset_name;
+        match *self.violation {
+            Violation::NotConverged => write!(f, "SCP algorithm did not converge"),
+            Violation::PriceStepNorm { norm, tolerance } => {
+                write!(f, "Price step norm {} exceeds tolerance {}", norm, tolerance)
+            }
+            Violation::FillStepNorm { norm, tolerance } => {
+                write!(f, "Fill step norm {} exceeds tolerance {}", norm, tolerance)
+            }
+            Violation::MissingPrice { asset } => {
+                write!(f, "Missing linear price for asset {}", name(asset))
+            }
+            Violation::PriceInconsistency { asset, expected, actual, error } => write!(
+                f,
+                "Price inconsistency for {}: expected {:.6}, got {:.6}, error {:.2}%",
+                name(asset),
+                expected,
+                actual,
+                error * 100.0
+            ),
+            Violation::NumeraireNotPinned { numeraire, log_price } => write!(
+                f,
+                "{} numeraire constraint violated: y_{} = {}",
+                name(numeraire),
+                name(numeraire),
+                log_price
+            ),
+            Violation::NonPositivePrice { asset, price } => {
+                write!(f, "Non-positive price for {}: {}", name(asset), price)
+            }
+            Violation::NonFinitePrice { asset, price } => {
+                write!(f, "Non-finite price for {}: {}", name(asset), price)
+            }
+            Violation::InvalidFillFraction { fill, fill_frac } => {
+                write!(f, "Invalid fill fraction {:.6} for fill {}", fill_frac, fill)
+            }
+            Violation::NonPositivePay { fill, pay_units } => {
+                write!(f, "Non-positive pay amount {:.6} for fill {}", pay_units, fill)
+            }
+            Violation::NonPositiveReceive { fill, recv_units } => {
+                write!(f, "Non-positive receive amount {:.6} for fill {}", recv_units, fill)
+            }
+            Violation::NonFiniteAmounts { fill, pay_units, recv_units } => write!(
+                f,
+                "Non-finite amounts for fill {}: pay={}, recv={}",
+                fill, pay_units, recv_units
+            ),
+            Violation::InventoryNotConserved { asset, initial, net_flow, expected, actual, error } => write!(
+                f,
+                "Inventory conservation violated for {}: initial={:.6}, net_flow={:.6}, expected={:.6}, actual={:.6}, error={:.6}",
+                name(asset),
+                initial,
+                net_flow,
+                expected,
+                actual,
+                error
+            ),
+            Violation::NegativeInventoryRisk { value } => {
+                write!(f, "Negative inventory risk: {:.6}", value)
+            }
+            Violation::NegativePriceTracking { value } => {
+                write!(f, "Negative price tracking: {:.6}", value)
+            }
+            Violation::NonFiniteObjective { value } => {
+                write!(f, "Non-finite objective value: {:.6}", value)
+            }
+            Violation::ObjectiveMismatch { components, total, error } => write!(
+                f,
+                "Objective components don't sum correctly: components={:.6}, total={:.6}, error={:.6}",
+                components, total, error
+            ),
+        }
+    }
+}
+
+/// Check every local law, in the order the predicates are numbered
+pub fn check_local_laws(witness: &Witness<'_>, tol: &Tolerances) -> Result<(), Violation> {
+    check_convergence(witness.converged, witness.final_step_norm_y, witness.final_step_norm_alpha, tol)?;
+    check_prices(witness.y_star, witness.prices, witness.numeraire, tol)?;
+    check_fills(witness.fills)?;
+    check_inventory(witness.initial_inventory, witness.final_inventory, witness.fills, tol)?;
+    check_objective(&witness.objective, tol)
+}
+
+/// Predicate 1: the SCP algorithm converged within tolerance
+pub fn check_convergence(
+    converged: bool,
+    final_step_norm_y: f64,
+    final_step_norm_alpha: f64,
+    tol: &Tolerances,
+) -> Result<(), Violation> {
+    if !converged {
+        return Err(Violation::NotConverged);
+    }
+    if exceeds(final_step_norm_y, tol.tolerance_y) {
+        return Err(Violation::PriceStepNorm { norm: final_step_norm_y, tolerance: tol.tolerance_y });
+    }
+    if exceeds(final_step_norm_alpha, tol.tolerance_alpha) {
+        return Err(Violation::FillStepNorm { norm: final_step_norm_alpha, tolerance: tol.tolerance_alpha });
+    }
+    Ok(())
+}
+
+/// Predicate 2: prices = exp(y*), y* of the numeraire is 0, and all prices
+/// are positive and finite
+pub fn check_prices(
+    y_star: &[(u8, f64)],
+    prices: &[(u8, f64)],
+    numeraire: u8,
+    tol: &Tolerances,
+) -> Result<(), Violation> {
+    for &(asset, log_price) in y_star {
+        let expected = libm::exp(log_price);
+        let actual = lookup(prices, asset).ok_or(Violation::MissingPrice { asset })?;
+        let error = libm::fabs(expected - actual) / actual;
+        if exceeds(error, tol.max_price_deviation) {
+            return Err(Violation::PriceInconsistency { asset, expected, actual, error });
+        }
+    }
+
+    if let Some(log_price) = lookup(y_star, numeraire) {
+        if exceeds(libm::fabs(log_price), tol.tolerance_y) {
+            return Err(Violation::NumeraireNotPinned { numeraire, log_price });
+        }
+    }
+
+    for &(asset, price) in prices {
+        if price <= 0.0 {
+            return Err(Violation::NonPositivePrice { asset, price });
+        }
+        if !price.is_finite() {
+            return Err(Violation::NonFinitePrice { asset, price });
+        }
+    }
+    Ok(())
+}
+
+/// Predicate 3: fill fractions lie in [0, 1] and filled amounts are
+/// positive and finite
+pub fn check_fills(fills: &[FillFlows<'_>]) -> Result<(), Violation> {
+    for (fill, flows) in fills.iter().enumerate() {
+        if !(0.0..=1.0).contains(&flows.fill_frac) {
+            return Err(Violation::InvalidFillFraction { fill, fill_frac: flows.fill_frac });
+        }
+        if flows.fill_frac > MIN_FILL_AMOUNT {
+            if flows.pay_units <= MIN_FILL_AMOUNT {
+                return Err(Violation::NonPositivePay { fill, pay_units: flows.pay_units });
+            }
+            if flows.recv_units <= MIN_FILL_AMOUNT {
+                return Err(Violation::NonPositiveReceive { fill, recv_units: flows.recv_units });
+            }
+        }
+        if !flows.pay_units.is_finite() || !flows.recv_units.is_finite() {
+            return Err(Violation::NonFiniteAmounts {
+                fill,
+                pay_units: flows.pay_units,
+                recv_units: flows.recv_units,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Predicate 4: final inventory = initial + pay - recv + fees, for every
+/// asset. Assets missing from either inventory count as 0.
+pub fn check_inventory(
+    initial_inventory: &[(u8, f64)],
+    final_inventory: &[(u8, f64)],
+    fills: &[FillFlows<'_>],
+    tol: &Tolerances,
+) -> Result<(), Violation> {
+    for asset in 0..NUM_ASSETS as u8 {
+        let initial = lookup(initial_inventory, asset).unwrap_or(0.0);
+        let actual = lookup(final_inventory, asset).unwrap_or(0.0);
+
+        let mut net_flow = 0.0;
+        for flows in fills {
+            if flows.pay_asset == asset {
+                net_flow += flows.pay_units; // Pool receives pay asset
+            }
+            if flows.recv_asset == asset {
+                net_flow -= flows.recv_units; // Pool gives receive asset
+            }
+            // Fees accrue to the pool
+            net_flow += lookup(flows.fees_paid, asset).unwrap_or(0.0);
+        }
+
+        let expected = initial + net_flow;
+        let error = libm::fabs(actual - expected);
+        if exceeds(error, tol.inventory_tolerance) {
+            return Err(Violation::InventoryNotConserved { asset, initial, net_flow, expected, actual, error });
+        }
+    }
+    Ok(())
+}
+
+/// Predicate 5: objective terms are well-formed and sum to the total
+pub fn check_objective(objective: &ObjectiveBreakdown, tol: &Tolerances) -> Result<(), Violation> {
+    if objective.inventory_risk < -tol.inventory_tolerance {
+        return Err(Violation::NegativeInventoryRisk { value: objective.inventory_risk });
+    }
+    if objective.price_tracking < -tol.inventory_tolerance {
+        return Err(Violation::NegativePriceTracking { value: objective.price_tracking });
+    }
+    if !objective.total.is_finite() {
+        return Err(Violation::NonFiniteObjective { value: objective.total });
+    }
+
+    let components = objective.inventory_risk + objective.price_tracking + objective.fill_incentive;
+    let error = libm::fabs(objective.total - components);
+    if exceeds(error, tol.inventory_tolerance) {
+        return Err(Violation::ObjectiveMismatch { components, total: objective.total, error });
+    }
+    Ok(())
+}
+
+/// Value for `asset`, if listed
+fn lookup(values: &[(u8, f64)], asset: u8) -> Option<f64> {
+    values.iter().find(|(id, _)| *id == asset).map(|(_, value)| *value)
+}
+
+/// Whether `value` is above `limit`; NaN always is
+fn exceeds(value: f64, limit: f64) -> bool {
+    value.is_nan() || value > limit
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,80 @@
+use super::*;
+
+const Y_STAR: [(u8, f64); 2] = [(0, 0.0), (1, 0.1)];
+const FEES: [(u8, f64); 1] = [(0, 3.0)];
+const INITIAL: [(u8, f64); 2] = [(0, 10000.0), (1, 10000.0)];
+const FINAL: [(u8, f64); 2] = [(0, 11003.0), (1, 9140.0)];
+
+fn fill() -> FillFlows<'static> {
+    FillFlows {
+        fill_frac: 1.0,
+        pay_asset: 0,
+        recv_asset: 1,
+        pay_units: 1000.0,
+        recv_units: 860.0,
+        fees_paid: &FEES,
+    }
+}
+
+fn check(prices: &[(u8, f64)], fills: &[FillFlows<'_>], final_inventory: &[(u8, f64)]) -> Result<(), Violation> {
+    let witness = Witness {
+        converged: true,
+        final_step_norm_y: 1e-6,
+        final_step_norm_alpha: 1e-7,
+        y_star: &Y_STAR,
+        prices,
+        numeraire: 0,
+        fills,
+        initial_inventory: &INITIAL,
+        final_inventory,
+        objective: ObjectiveBreakdown {
+            inventory_risk: 100.0,
+            price_tracking: 50.0,
+            fill_incentive: -20.0,
+            total: 130.0,
+        },
+    };
+    check_local_laws(&witness, &Tolerances::DEFAULT)
+}
+
+#[test]
+fn test_consistent_witness_passes() {
+    let prices = [(0, 1.0), (1, libm::exp(0.1))];
+    assert_eq!(check(&prices, &[fill()], &FINAL), Ok(()));
+}
+
+#[test]
+fn test_each_law_is_checked() {
+    let prices = [(0, 1.0), (1, libm::exp(0.1))];
+
+    let drifted = [(0, 1.0), (1, 2.0)];
+    assert!(matches!(
+        check(&drifted, &[fill()], &FINAL),
+        Err(Violation::PriceInconsistency { asset: 1, .. })
+    ));
+
+    let overfilled = FillFlows { fill_frac: 1.5, ..fill() };
+    assert_eq!(
+        check(&prices, &[fill(), overfilled], &FINAL).map_err(|v| v.fill()),
+        Err(Some(1))
+    );
+
+    // Dropping the fee unbalances the pay asset
+    let no_fee = FillFlows { fees_paid: &[], ..fill() };
+    assert!(matches!(
+        check(&prices, &[no_fee], &FINAL),
+        Err(Violation::InventoryNotConserved { asset: 0, .. })
+    ));
+
+    // A NaN step norm is not within tolerance
+    assert!(matches!(
+        check_convergence(true, f64::NAN, 0.0, &Tolerances::DEFAULT),
+        Err(Violation::PriceStepNorm { .. })
+    ));
+
+    let unbalanced = ObjectiveBreakdown { inventory_risk: 1.0, price_tracking: 1.0, fill_incentive: 0.0, total: 3.0 };
+    assert!(matches!(
+        check_objective(&unbalanced, &Tolerances::DEFAULT),
+        Err(Violation::ObjectiveMismatch { .. })
+    ));
+}
@@ -0,0 +1,90 @@
+use convexfx_types::{AssetId, ConvexFxError, EpochId, Result};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::oracle::Oracle;
+use crate::reference_prices::RefPrices;
+
+/// Source of linear prices, e.g. an exchange rate API
+pub trait PriceFeed: Send + Sync {
+    /// Fetch the latest linear price of each asset
+    fn fetch_prices(&self) -> Result<BTreeMap<AssetId, f64>>;
+
+    /// Provider name recorded on the reference prices
+    fn name(&self) -> &str {
+        "feed"
+    }
+}
+
+/// Oracle that builds reference prices from a boxed `PriceFeed`
+///
+/// Every call fetches fresh prices, converts them to log-space and rebases
+/// them so USD sits at 0, whatever unit the feed quotes in.
+pub struct FeedOracle {
+    feed: Box<dyn PriceFeed>,
+    band_bps: f64,
+}
+
+impl FeedOracle {
+    pub fn new(feed: Box<dyn PriceFeed>) -> Self {
+        FeedOracle {
+            feed,
+            band_bps: 20.0,
+        }
+    }
+
+    /// Set the band half-width around each reference price
+    pub fn with_band_bps(mut self, band_bps: f64) -> Self {
+        self.band_bps = band_bps;
+        self
+    }
+}
+
+impl std::fmt::Debug for FeedOracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FeedOracle")
+            .field("feed", &self.feed.name())
+            .field("band_bps", &self.band_bps)
+            .finish()
+    }
+}
+
+/// Log-prices of `prices` with USD pinned at 0
+///
+/// Rejects non-positive or non-finite prices, which have no log, and maps
+/// missing any asset: without USD there is nothing to rebase on, and an
+/// absent asset would have no reference price at all.
+pub fn to_usd_log_prices(prices: &BTreeMap<AssetId, f64>) -> Result<BTreeMap<AssetId, f64>> {
+    if let Some(asset) = AssetId::all().iter().find(|asset| !prices.contains_key(asset)) {
+        return Err(ConvexFxError::InvalidPrice(format!("feed has no price for {}", asset)));
+    }
+    if let Some((asset, price)) = prices.iter().find(|(_, p)| !(p.is_finite() && **p > 0.0)) {
+        return Err(ConvexFxError::InvalidPrice(format!(
+            "feed price for {} must be positive and finite, got {}",
+            asset, price
+        )));
+    }
+    let usd = prices[&AssetId::USD].ln();
+    let mut y_ref: BTreeMap<AssetId, f64> = prices
+        .iter()
+        .map(|(asset, price)| (*asset, price.ln() - usd))
+        .collect();
+    y_ref.insert(AssetId::USD, 0.0);
+    Ok(y_ref)
+}
+
+impl Oracle for FeedOracle {
+    fn reference_prices(&self, _at: EpochId) -> Result<RefPrices> {
+        let y_ref = to_usd_log_prices(&self.feed.fetch_prices()?)?;
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        Ok(RefPrices::new(
+            y_ref,
+            self.band_bps,
+            timestamp_ms,
+            vec![self.feed.name().to_string()],
+        ))
+    }
+}
@@ -0,0 +1,95 @@
+use convexfx_types::{AssetId, ConvexFxError, Result};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::feed::PriceFeed;
+
+/// Price feed that polls a JSON endpoint over HTTP
+///
+/// Each asset is read from the response body at a JSON pointer, by default
+/// `/<SYMBOL>` for every asset (e.g. `{"USD": 1.0, "EUR": 1.1, ...}`).
+/// Values may be JSON numbers or numeric strings.
+#[derive(Debug, Clone)]
+pub struct HttpPriceFeed {
+    url: String,
+    fields: BTreeMap<AssetId, String>,
+    timeout: Duration,
+}
+
+impl HttpPriceFeed {
+    pub fn new(url: impl Into<String>) -> Self {
+        HttpPriceFeed {
+            url: url.into(),
+            fields: AssetId::all()
+                .iter()
+                .map(|asset| (*asset, format!("/{}", asset)))
+                .collect(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Read `asset` from the JSON pointer `pointer`, e.g. `/rates/EUR`
+    pub fn with_field(mut self, asset: AssetId, pointer: impl Into<String>) -> Self {
+        self.fields.insert(asset, pointer.into());
+        self
+    }
+
+    /// Replace the field mapping; assets left out are not fetched, so a
+    /// `FeedOracle` rejects the feed's prices unless every asset is mapped
+    pub fn with_fields(mut self, fields: BTreeMap<AssetId, String>) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// Give up on a request after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Extract the mapped prices from a response body
+    fn parse(&self, body: &serde_json::Value) -> Result<BTreeMap<AssetId, f64>> {
+        self.fields
+            .iter()
+            .map(|(asset, pointer)| {
+                let value = body.pointer(pointer).ok_or_else(|| {
+                    ConvexFxError::InvalidPrice(format!(
+                        "{} missing from feed response at {}",
+                        asset, pointer
+                    ))
+                })?;
+                let price = match value {
+                    serde_json::Value::Number(n) => n.as_f64(),
+                    serde_json::Value::String(s) => s.trim().parse().ok(),
+                    _ => None,
+                };
+                price.map(|p| (*asset, p)).ok_or_else(|| {
+                    ConvexFxError::InvalidPrice(format!(
+                        "{} at {} is not a number: {}",
+                        asset, pointer, value
+                    ))
+                })
+            })
+            .collect()
+    }
+}
+
+impl PriceFeed for HttpPriceFeed {
+    fn fetch_prices(&self) -> Result<BTreeMap<AssetId, f64>> {
+        let agent = ureq::AgentBuilder::new().timeout(self.timeout).build();
+        let body = agent
+            .get(&self.url)
+            .call()
+            .map_err(|e| ConvexFxError::IoError(format!("price feed {}: {}", self.url, e)))?
+            .into_string()
+            .map_err(|e| ConvexFxError::IoError(format!("price feed {}: {}", self.url, e)))?;
+        let body: serde_json::Value = serde_json::from_str(&body).map_err(|e| {
+            ConvexFxError::SerializationError(format!("price feed {}: {}", self.url, e))
+        })?;
+        self.parse(&body)
+    }
+
+    fn name(&self) -> &str {
+        &self.url
+    }
+}
@@ -1,10 +1,16 @@
 mod oracle;
 mod mock;
 mod reference_prices;
+mod feed;
+#[cfg(feature = "http")]
+mod http_feed;
 
 pub use oracle::Oracle;
 pub use mock::MockOracle;
-pub use reference_prices::{RefPrices, PriceBand};
+pub use reference_prices::{RefPrices, PriceBand, MIN_CONFIDENCE};
+pub use feed::{to_usd_log_prices, FeedOracle, PriceFeed};
+#[cfg(feature = "http")]
+pub use http_feed::HttpPriceFeed;
 
 #[cfg(test)]
 mod tests;
@@ -12,9 +12,85 @@ use crate::reference_prices::RefPrices;
 pub struct MockOracle {
     prices: BTreeMap<AssetId, f64>,
     band_bps: f64,
+    fixed_timestamp_ms: Option<u64>,
+    walk: Option<Mutex<RandomWalk>>,
     pub registry: Mutex<AssetRegistry>,
 }
 
+/// Seeded Gaussian random walk applied on top of the configured log-prices
+#[derive(Debug)]
+struct RandomWalk {
+    rng_state: u64,
+    /// Per-step standard deviation of each log-price (bps)
+    vol_bps: f64,
+    /// EMA smoothing factor in (0, 1]; `None` returns the raw walk
+    ema_alpha: Option<f64>,
+    /// Cumulative shocks per asset (log-space)
+    offsets: BTreeMap<AssetId, f64>,
+    /// Last smoothed log-prices, seeded on the first step
+    smoothed: Option<BTreeMap<AssetId, f64>>,
+}
+
+impl RandomWalk {
+    fn new(seed: u64, vol_bps: f64) -> Self {
+        RandomWalk {
+            rng_state: seed,
+            vol_bps,
+            ema_alpha: None,
+            offsets: BTreeMap::new(),
+            smoothed: None,
+        }
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        // Linear congruential generator
+        self.rng_state = self
+            .rng_state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.rng_state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normal sample (Box-Muller)
+    fn next_gaussian(&mut self) -> f64 {
+        let u1 = self.next_f64().max(f64::MIN_POSITIVE);
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    /// Advance the walk one step and return the (smoothed) log-prices
+    fn step(&mut self, base: &BTreeMap<AssetId, f64>) -> BTreeMap<AssetId, f64> {
+        let sigma = self.vol_bps / 10000.0;
+        let mut y = BTreeMap::new();
+        for (asset, y_base) in base {
+            if *asset == AssetId::USD {
+                y.insert(*asset, 0.0);
+                continue;
+            }
+            let shock = sigma * self.next_gaussian();
+            let offset = self.offsets.entry(*asset).or_insert(0.0);
+            *offset += shock;
+            y.insert(*asset, y_base + *offset);
+        }
+
+        let Some(alpha) = self.ema_alpha else {
+            return y;
+        };
+        let smoothed = match self.smoothed.take() {
+            Some(prev) => y
+                .iter()
+                .map(|(asset, y_raw)| {
+                    let y_prev = prev.get(asset).copied().unwrap_or(*y_raw);
+                    (*asset, alpha * y_raw + (1.0 - alpha) * y_prev)
+                })
+                .collect(),
+            None => y,
+        };
+        self.smoothed = Some(smoothed.clone());
+        smoothed
+    }
+}
+
 impl MockOracle {
     /// Create a new mock oracle with default FX prices
     pub fn new() -> Self {
@@ -29,6 +105,8 @@ impl MockOracle {
         MockOracle {
             prices,
             band_bps: 20.0, // ±20 bps default
+            fixed_timestamp_ms: None,
+            walk: None,
             registry: Mutex::new(AssetRegistry::new()),
         }
     }
@@ -38,16 +116,49 @@ impl MockOracle {
         MockOracle {
             prices,
             band_bps: 20.0,
+            fixed_timestamp_ms: None,
+            walk: None,
             registry: Mutex::new(AssetRegistry::new()),
         }
     }
 
+    /// Create with default prices that drift by a seeded Gaussian random walk
+    ///
+    /// Every call to `reference_prices` advances each non-USD log-price by a
+    /// normal step with standard deviation `vol_bps`. USD stays pinned at 0.
+    pub fn with_random_walk(seed: u64, vol_bps: f64) -> Self {
+        let mut oracle = Self::new();
+        oracle.walk = Some(Mutex::new(RandomWalk::new(seed, vol_bps)));
+        oracle
+    }
+
+    /// Smooth random-walk prices with an EMA (`alpha` in (0, 1], 1 = no smoothing)
+    ///
+    /// Has no effect unless the oracle was created with `with_random_walk`.
+    pub fn with_ema_smoothing(mut self, alpha: f64) -> Self {
+        if let Some(walk) = self.walk.as_mut() {
+            walk.get_mut().unwrap().ema_alpha = Some(alpha.clamp(f64::MIN_POSITIVE, 1.0));
+        }
+        self
+    }
+
     /// Set price band in basis points
     pub fn with_band_bps(mut self, band_bps: f64) -> Self {
         self.band_bps = band_bps;
         self
     }
 
+    /// Freeze the timestamp reported with prices (wall clock otherwise)
+    pub fn with_fixed_timestamp(mut self, timestamp_ms: u64) -> Self {
+        self.fixed_timestamp_ms = Some(timestamp_ms);
+        self
+    }
+
+    /// Configured linear prices, before any random walk
+    pub fn prices(&self) -> &BTreeMap<AssetId, f64> {
+        &self.prices
+    }
+
     /// Update a price
     pub fn set_price(&mut self, asset: AssetId, price: f64) {
         self.prices.insert(asset, price);
@@ -107,8 +218,13 @@ impl Default for MockOracle {
 
 impl Oracle for MockOracle {
     fn reference_prices(&self, _at: EpochId) -> Result<RefPrices> {
-        let y_ref = self.to_log_prices();
-        let timestamp_ms = Self::current_timestamp_ms();
+        let mut y_ref = self.to_log_prices();
+        if let Some(walk) = &self.walk {
+            y_ref = walk.lock().unwrap().step(&y_ref);
+        }
+        let timestamp_ms = self
+            .fixed_timestamp_ms
+            .unwrap_or_else(Self::current_timestamp_ms);
 
         Ok(RefPrices::new(
             y_ref,
@@ -164,6 +280,55 @@ mod tests {
         assert!((eur_log - 1.2_f64.ln()).abs() < 1e-10);
     }
 
+    #[test]
+    fn test_random_walk_drifts_coherently() {
+        let oracle = MockOracle::with_random_walk(7, 10.0);
+        let start = MockOracle::new().reference_prices(0).unwrap();
+
+        let mut last = None;
+        for epoch in 1..=50 {
+            let prices = oracle.reference_prices(epoch).unwrap();
+            assert_eq!(prices.get_ref(AssetId::USD), 0.0);
+
+            // Cross rates stay coherent across triangles
+            for (a, b, c) in [
+                (AssetId::EUR, AssetId::JPY, AssetId::GBP),
+                (AssetId::AUD, AssetId::CHF, AssetId::USD),
+            ] {
+                let ab = (prices.get_ref(a) - prices.get_ref(b)).exp();
+                let bc = (prices.get_ref(b) - prices.get_ref(c)).exp();
+                let ac = (prices.get_ref(a) - prices.get_ref(c)).exp();
+                assert!((ab * bc / ac - 1.0).abs() < 1e-12);
+            }
+            last = Some(prices);
+        }
+
+        let last = last.unwrap();
+        for asset in [AssetId::EUR, AssetId::GBP, AssetId::JPY] {
+            assert_ne!(last.get_ref(asset), start.get_ref(asset), "{} did not drift", asset);
+        }
+
+        // Same seed reproduces the same path
+        let first = MockOracle::with_random_walk(7, 10.0).reference_prices(1).unwrap();
+        let again = MockOracle::with_random_walk(7, 10.0).reference_prices(1).unwrap();
+        assert_eq!(first.y_ref, again.y_ref);
+    }
+
+    #[test]
+    fn test_random_walk_ema_smoothing() {
+        let raw = MockOracle::with_random_walk(11, 25.0);
+        let smooth = MockOracle::with_random_walk(11, 25.0).with_ema_smoothing(0.2);
+
+        let path = |oracle: &MockOracle| -> Vec<f64> {
+            (1..=40)
+                .map(|epoch| oracle.reference_prices(epoch).unwrap().get_ref(AssetId::EUR))
+                .collect()
+        };
+        let variation = |ys: &[f64]| -> f64 { ys.windows(2).map(|w| (w[1] - w[0]).abs()).sum() };
+
+        assert!(variation(&path(&smooth)) < variation(&path(&raw)));
+    }
+
     #[test]
     fn test_set_price() {
         let mut oracle = MockOracle::new();
@@ -1,11 +1,14 @@
-use convexfx_types::AssetId;
+use convexfx_types::{AssetId, ConvexFxError, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+/// Confidence floor; lower values widen a band no further than this does
+pub const MIN_CONFIDENCE: f64 = 0.05;
+
 /// Reference prices with bands
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RefPrices {
-    /// Reference log-prices (y_ref), with USD = 0
+    /// Reference log-prices (y_ref), with the numeraire (USD by default) = 0
     pub y_ref: BTreeMap<AssetId, f64>,
     /// Lower band for each asset (log-space)
     pub band_low: BTreeMap<AssetId, f64>,
@@ -15,6 +18,9 @@ pub struct RefPrices {
     pub timestamp_ms: u64,
     /// Oracle data providers
     pub providers: Vec<String>,
+    /// Per-asset confidence in (0, 1]; assets not listed are fully trusted
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub confidence: BTreeMap<AssetId, f64>,
 }
 
 impl RefPrices {
@@ -41,7 +47,51 @@ impl RefPrices {
             band_high,
             timestamp_ms,
             providers,
+            confidence: BTreeMap::new(),
+        }
+    }
+
+    /// Attach per-asset confidence, widening each asset's band by
+    /// `PriceBand::widening` around its reference price
+    pub fn with_confidence(mut self, confidence: BTreeMap<AssetId, f64>) -> Self {
+        for (asset, c) in &confidence {
+            let scale = PriceBand::widening(*c);
+            let y = self.get_ref(*asset);
+            if let Some(low) = self.band_low.get_mut(asset) {
+                *low = y - (y - *low) * scale;
+            }
+            if let Some(high) = self.band_high.get_mut(asset) {
+                *high = y + (*high - y) * scale;
+            }
+        }
+        self.confidence = confidence;
+        self
+    }
+
+    /// Reject non-finite reference log-prices or bands
+    ///
+    /// A NaN or infinite `y_ref` from a feed would otherwise flow straight
+    /// into the QP and produce a meaningless solution.
+    pub fn validate(&self) -> Result<()> {
+        for (label, prices) in [
+            ("reference log-price", &self.y_ref),
+            ("lower band", &self.band_low),
+            ("upper band", &self.band_high),
+        ] {
+            if let Some((asset, y)) = prices.iter().find(|(_, y)| !y.is_finite()) {
+                return Err(ConvexFxError::InvalidPrice(format!(
+                    "non-finite {} for {}: {}",
+                    label, asset, y
+                )));
+            }
+        }
+        if let Some((asset, c)) = self.confidence.iter().find(|(_, c)| !(**c > 0.0 && **c <= 1.0)) {
+            return Err(ConvexFxError::InvalidPrice(format!(
+                "confidence for {} must be in (0, 1], got {}",
+                asset, c
+            )));
         }
+        Ok(())
     }
 
     /// Get reference log-price for an asset
@@ -49,6 +99,11 @@ impl RefPrices {
         self.y_ref.get(&asset).copied().unwrap_or(0.0)
     }
 
+    /// Reference mid rate: units of `to` per unit of `from`
+    pub fn mid_rate(&self, from: AssetId, to: AssetId) -> f64 {
+        (self.get_ref(from) - self.get_ref(to)).exp()
+    }
+
     /// Get lower band for an asset
     pub fn get_low(&self, asset: AssetId) -> f64 {
         self.band_low.get(&asset).copied().unwrap_or(0.0)
@@ -59,9 +114,24 @@ impl RefPrices {
         self.band_high.get(&asset).copied().unwrap_or(0.0)
     }
 
+    /// Oracle confidence in `asset`'s price (1 if not reported)
+    pub fn confidence(&self, asset: AssetId) -> f64 {
+        self.confidence.get(&asset).copied().unwrap_or(1.0)
+    }
+
+    /// Factor by which `asset`'s trust region is widened for its confidence
+    pub fn band_widening(&self, asset: AssetId) -> f64 {
+        PriceBand::widening(self.confidence(asset))
+    }
+
+    /// Age of the prices in milliseconds (zero if the timestamp is in the future)
+    pub fn age_ms(&self, now_ms: u64) -> u64 {
+        now_ms.saturating_sub(self.timestamp_ms)
+    }
+
     /// Check if data is stale (age in milliseconds)
-    pub fn is_stale(&self, current_time_ms: u64, max_age_ms: u64) -> bool {
-        current_time_ms.saturating_sub(self.timestamp_ms) > max_age_ms
+    pub fn is_stale(&self, now_ms: u64, max_age_ms: u64) -> bool {
+        self.age_ms(now_ms) > max_age_ms
     }
 }
 
@@ -72,6 +142,26 @@ pub struct PriceBand {
     pub upper_bps: f64,
 }
 
+impl PriceBand {
+    /// Band scaled for an oracle confidence in (0, 1]
+    pub fn widened(self, confidence: f64) -> PriceBand {
+        let scale = Self::widening(confidence);
+        PriceBand {
+            lower_bps: self.lower_bps * scale,
+            upper_bps: self.upper_bps * scale,
+        }
+    }
+
+    /// Width multiplier for an oracle confidence: 1 at full confidence,
+    /// 1 / confidence below it, capped at 1 / `MIN_CONFIDENCE`
+    pub fn widening(confidence: f64) -> f64 {
+        if confidence.is_nan() {
+            return 1.0 / MIN_CONFIDENCE;
+        }
+        1.0 / confidence.clamp(MIN_CONFIDENCE, 1.0)
+    }
+}
+
 impl Default for PriceBand {
     fn default() -> Self {
         PriceBand {
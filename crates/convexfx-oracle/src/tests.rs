@@ -18,6 +18,22 @@ mod tests {
         assert!(prices.is_stale(current_time + 120_000, 60_000));
     }
 
+    #[test]
+    fn test_staleness_boundary() {
+        let oracle = MockOracle::new().with_fixed_timestamp(1_000_000);
+        let prices = oracle.reference_prices(1).unwrap();
+        assert_eq!(prices.timestamp_ms, 1_000_000);
+
+        assert_eq!(prices.age_ms(1_060_000), 60_000);
+        // Exactly max age is still fresh, one millisecond more is stale
+        assert!(!prices.is_stale(1_060_000, 60_000));
+        assert!(prices.is_stale(1_060_001, 60_000));
+
+        // Clock skew: a timestamp in the future has zero age
+        assert_eq!(prices.age_ms(999_000), 0);
+        assert!(!prices.is_stale(999_000, 0));
+    }
+
     #[test]
     fn test_cross_rate_consistency() {
         let oracle = MockOracle::new();
@@ -153,4 +169,21 @@ mod tests {
         let gbp_price = prices.get_ref(AssetId::GBP).exp();
         assert!(gbp_price > 1.1 && gbp_price < 1.4); // GBPUSD typically 1.2-1.35
     }
+
+    #[test]
+    fn test_feed_prices_must_cover_every_asset() {
+        use std::collections::BTreeMap;
+
+        let full: BTreeMap<AssetId, f64> = AssetId::all().iter().map(|asset| (*asset, 1.0)).collect();
+        assert!(to_usd_log_prices(&full).is_ok());
+
+        // Without USD there is nothing to rebase on; without EUR, no EUR price
+        for missing in [AssetId::USD, AssetId::EUR] {
+            let mut partial = full.clone();
+            partial.remove(&missing);
+            let err = to_usd_log_prices(&partial).unwrap_err();
+            assert!(matches!(err, convexfx_types::ConvexFxError::InvalidPrice(_)));
+            assert!(err.to_string().contains(&missing.to_string()), "{}", err);
+        }
+    }
 }
@@ -0,0 +1,64 @@
+#![cfg(feature = "http")]
+
+use convexfx_oracle::{FeedOracle, HttpPriceFeed, Oracle};
+use convexfx_types::AssetId;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn test_http_feed_prices_flow_into_ref_prices() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rates"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "base": "USD",
+            "rates": {"USD": 1.0, "EUR": 1.1, "JPY": "0.01", "GBP": 1.25, "CHF": 1.08, "AUD": 0.75}
+        })))
+        .mount(&server)
+        .await;
+
+    let feed = AssetId::all().iter().fold(
+        HttpPriceFeed::new(format!("{}/rates", server.uri())),
+        |feed, asset| feed.with_field(*asset, format!("/rates/{}", asset)),
+    );
+    let oracle = FeedOracle::new(Box::new(feed)).with_band_bps(50.0);
+    let prices = oracle.reference_prices(1).unwrap();
+
+    assert_eq!(prices.get_ref(AssetId::USD), 0.0);
+    assert!((prices.get_ref(AssetId::EUR) - 1.1_f64.ln()).abs() < 1e-12);
+    assert!((prices.get_ref(AssetId::JPY) - 0.01_f64.ln()).abs() < 1e-12);
+    assert!((prices.get_high(AssetId::GBP) - prices.get_ref(AssetId::GBP) - 0.005).abs() < 1e-12);
+    assert_eq!(prices.providers, vec![format!("{}/rates", server.uri())]);
+    prices.validate().unwrap();
+}
+
+#[tokio::test]
+async fn test_http_feed_rebases_to_usd_and_rejects_bad_bodies() {
+    let server = MockServer::start().await;
+    // Quoted in EUR: USD is worth 1/1.1 EUR
+    Mock::given(path("/eur"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "USD": 1.0 / 1.1, "EUR": 1.0, "JPY": 0.01 / 1.1, "GBP": 1.25 / 1.1, "CHF": 1.08 / 1.1, "AUD": 0.75 / 1.1
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(path("/partial"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"USD": 1.0})))
+        .mount(&server)
+        .await;
+    Mock::given(path("/down"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+
+    let oracle = FeedOracle::new(Box::new(HttpPriceFeed::new(format!("{}/eur", server.uri()))));
+    let prices = oracle.reference_prices(1).unwrap();
+    assert_eq!(prices.get_ref(AssetId::USD), 0.0);
+    assert!((prices.get_ref(AssetId::EUR) - 1.1_f64.ln()).abs() < 1e-12);
+
+    for (route, expected) in [("/partial", "EUR missing"), ("/down", "503")] {
+        let oracle = FeedOracle::new(Box::new(HttpPriceFeed::new(format!("{}{}", server.uri(), route))));
+        let err = oracle.reference_prices(1).unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+}
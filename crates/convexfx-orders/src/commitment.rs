@@ -41,8 +41,12 @@ pub struct Commitment {
 }
 
 /// Compute commitment hash: H(order_json || salt)
+///
+/// `submission_mid` is recorded by the venue at reveal, after the trader
+/// committed, so it is left out of the hashed order.
 pub fn compute_commitment(order: &PairOrder, salt: &[u8]) -> Result<CommitmentHash> {
-    let order_json = serde_json::to_string(order).map_err(|e| {
+    let committed = PairOrder { submission_mid: None, ..order.clone() };
+    let order_json = serde_json::to_string(&committed).map_err(|e| {
         ConvexFxError::SerializationError(format!("failed to serialize order: {}", e))
     })?;
 
@@ -80,6 +84,8 @@ mod tests {
             budget: Amount::from_units(1000),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         };
 
@@ -104,6 +110,8 @@ mod tests {
             budget: Amount::from_units(1000),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         };
 
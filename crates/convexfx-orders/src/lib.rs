@@ -3,8 +3,8 @@ mod commitment;
 mod validation;
 
 pub use orderbook::OrderBook;
-pub use commitment::{Commitment, CommitmentHash};
-pub use validation::validate_order;
+pub use commitment::{compute_commitment, Commitment, CommitmentHash};
+pub use validation::{validate_basket_order, validate_order};
 
 #[cfg(test)]
 mod tests;
@@ -1,23 +1,36 @@
-use convexfx_types::{ConvexFxError, EpochId, OrderId, PairOrder, Result};
+use convexfx_types::{AccountId, ConvexFxError, EpochId, OrderId, PairOrder, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
 use crate::commitment::{verify_commitment, Commitment, CommitmentHash};
 use crate::validation::validate_order;
 
 /// Record of a committed order (before reveal)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct CommitRecord {
     commitment: Commitment,
     revealed: bool,
 }
 
 /// Order book for a single epoch with commit-reveal
-#[derive(Debug, Clone)]
+///
+/// Reveals are only accepted for commitments made in the open epoch.
+/// Commitments left unrevealed when the book moves on stay held against
+/// their closed epoch until `expire_unrevealed` drops them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderBook {
     pub epoch_id: EpochId,
     commits: BTreeMap<CommitmentHash, CommitRecord>,
     revealed: BTreeMap<OrderId, (PairOrder, CommitmentHash)>,
     frozen: bool,
+    /// Revealed orders per trader this epoch
+    trader_counts: BTreeMap<AccountId, usize>,
+    /// Commitments made through `commit_as` per trader this epoch
+    #[serde(default)]
+    trader_commit_counts: BTreeMap<AccountId, usize>,
+    /// Commitments and reveals accepted per trader per epoch; `None` is
+    /// unlimited
+    max_orders_per_trader_per_epoch: Option<usize>,
 }
 
 impl OrderBook {
@@ -28,9 +41,68 @@ impl OrderBook {
             commits: BTreeMap::new(),
             revealed: BTreeMap::new(),
             frozen: false,
+            trader_counts: BTreeMap::new(),
+            trader_commit_counts: BTreeMap::new(),
+            max_orders_per_trader_per_epoch: None,
         }
     }
 
+    /// Reject commitments made through `commit_as` and reveals beyond `max`
+    /// per trader in the epoch
+    pub fn with_max_orders_per_trader(mut self, max: usize) -> Self {
+        self.max_orders_per_trader_per_epoch = Some(max);
+        self
+    }
+
+    /// Empty book for `epoch_id` with the same limits; per-trader counts
+    /// start again from zero. Unrevealed commitments from earlier epochs
+    /// carry over as closed and can no longer be revealed
+    pub fn for_epoch(&self, epoch_id: EpochId) -> Self {
+        let commits = self
+            .commits
+            .iter()
+            .filter(|(_, record)| !record.revealed && record.commitment.epoch_id < epoch_id)
+            .map(|(hash, record)| (hash.clone(), record.clone()))
+            .collect();
+        OrderBook {
+            commits,
+            max_orders_per_trader_per_epoch: self.max_orders_per_trader_per_epoch,
+            ..OrderBook::new(epoch_id)
+        }
+    }
+
+    /// Drop commitments from closed epoch `epoch_id` that were never
+    /// revealed, returning them. Has no effect on the open epoch
+    pub fn expire_unrevealed(&mut self, epoch_id: EpochId) -> Vec<Commitment> {
+        if epoch_id >= self.epoch_id {
+            return Vec::new();
+        }
+        let expired: Vec<CommitmentHash> = self
+            .commits
+            .iter()
+            .filter(|(_, record)| !record.revealed && record.commitment.epoch_id == epoch_id)
+            .map(|(hash, _)| hash.clone())
+            .collect();
+        expired
+            .iter()
+            .filter_map(|hash| self.commits.remove(hash))
+            .map(|record| record.commitment)
+            .collect()
+    }
+
+    /// Commitments from `epoch_id` still awaiting a reveal
+    pub fn unrevealed_count(&self, epoch_id: EpochId) -> usize {
+        self.commits
+            .values()
+            .filter(|record| !record.revealed && record.commitment.epoch_id == epoch_id)
+            .count()
+    }
+
+    /// Orders `trader` has revealed in this epoch
+    pub fn trader_order_count(&self, trader: &AccountId) -> usize {
+        self.trader_counts.get(trader).copied().unwrap_or(0)
+    }
+
     /// Submit a commitment (during collect phase)
     pub fn commit(&mut self, commitment: Commitment) -> Result<()> {
         if self.frozen {
@@ -65,6 +137,26 @@ impl OrderBook {
         Ok(())
     }
 
+    /// Submit a commitment on behalf of `trader`
+    ///
+    /// Like `commit`, but counted against the trader's per-epoch limit, so
+    /// a trader cannot flood the book with commitments they never reveal.
+    pub fn commit_as(&mut self, trader: &AccountId, commitment: Commitment) -> Result<()> {
+        let count = self.trader_commit_counts.get(trader).copied().unwrap_or(0);
+        if let Some(max) = self.max_orders_per_trader_per_epoch {
+            if count >= max {
+                return Err(ConvexFxError::InvalidOrder(format!(
+                    "trader {} already has {} commitments in epoch {} (max {} per epoch)",
+                    trader, count, self.epoch_id, max
+                )));
+            }
+        }
+
+        self.commit(commitment)?;
+        self.trader_commit_counts.insert(trader.clone(), count + 1);
+        Ok(())
+    }
+
     /// Reveal an order (during reveal phase)
     pub fn reveal(&mut self, order: PairOrder, salt: &[u8]) -> Result<OrderId> {
         if self.frozen {
@@ -76,6 +168,16 @@ impl OrderBook {
         // Validate order
         validate_order(&order)?;
 
+        let count = self.trader_order_count(&order.trader);
+        if let Some(max) = self.max_orders_per_trader_per_epoch {
+            if count >= max {
+                return Err(ConvexFxError::InvalidOrder(format!(
+                    "trader {} already has {} orders in epoch {} (max {} per epoch)",
+                    order.trader, count, self.epoch_id, max
+                )));
+            }
+        }
+
         // Compute commitment from order and salt
         let computed_hash = crate::commitment::compute_commitment(&order, salt)?;
 
@@ -84,6 +186,14 @@ impl OrderBook {
             ConvexFxError::InvalidCommitment("commitment not found".to_string())
         })?;
 
+        // Reveals must land before the commitment's epoch closes
+        if record.commitment.epoch_id != self.epoch_id {
+            return Err(ConvexFxError::InvalidCommitment(format!(
+                "reveal deadline passed: epoch {} is closed",
+                record.commitment.epoch_id
+            )));
+        }
+
         // Check not already revealed
         if record.revealed {
             return Err(ConvexFxError::InvalidCommitment(
@@ -100,6 +210,7 @@ impl OrderBook {
 
         // Mark as revealed
         record.revealed = true;
+        self.trader_counts.insert(order.trader.clone(), count + 1);
 
         let order_id = order.id.clone();
         self.revealed
@@ -129,9 +240,12 @@ impl OrderBook {
         orders_with_hashes.into_iter().map(|(_, order)| order).collect()
     }
 
-    /// Get count of commitments
+    /// Get count of commitments in the open epoch
     pub fn commitment_count(&self) -> usize {
-        self.commits.len()
+        self.commits
+            .values()
+            .filter(|record| record.commitment.epoch_id == self.epoch_id)
+            .count()
     }
 
     /// Get count of revealed orders
@@ -159,6 +273,8 @@ mod tests {
             budget: Amount::from_units(1000),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         }
     }
@@ -188,6 +304,69 @@ mod tests {
         assert_eq!(book.revealed_count(), 1);
     }
 
+    #[test]
+    fn test_per_trader_order_limit() {
+        const MAX: usize = 3;
+        let mut book = OrderBook::new(1).with_max_orders_per_trader(MAX);
+
+        let commit_and_reveal = |book: &mut OrderBook, id: &str, trader: &str| {
+            let mut order = create_test_order(id);
+            order.trader = AccountId::new(trader);
+            let hash = crate::commitment::compute_commitment(&order, b"salt").unwrap();
+            book.commit(Commitment { hash, epoch_id: book.epoch_id, timestamp_ms: 1000 }).unwrap();
+            book.reveal(order, b"salt")
+        };
+
+        for i in 0..MAX {
+            commit_and_reveal(&mut book, &format!("spam{}", i), "spammer").unwrap();
+        }
+        let err = commit_and_reveal(&mut book, "spam_extra", "spammer").unwrap_err();
+        assert!(err.to_string().contains("spammer already has 3 orders"), "{}", err);
+        assert_eq!(book.trader_order_count(&AccountId::new("spammer")), MAX);
+
+        // Another trader is unaffected
+        commit_and_reveal(&mut book, "other", "trader2").unwrap();
+        assert_eq!(book.revealed_count(), MAX + 1);
+
+        // Counts reset in the next epoch's book, the limit carries over
+        let mut next = book.for_epoch(2);
+        assert_eq!(next.trader_order_count(&AccountId::new("spammer")), 0);
+        for i in 0..MAX {
+            commit_and_reveal(&mut next, &format!("next{}", i), "spammer").unwrap();
+        }
+        assert!(commit_and_reveal(&mut next, "next_extra", "spammer").is_err());
+    }
+
+    #[test]
+    fn test_per_trader_commitment_limit() {
+        const MAX: usize = 2;
+        let mut book = OrderBook::new(1).with_max_orders_per_trader(MAX);
+        let commitment = |id: &str| Commitment {
+            hash: crate::commitment::compute_commitment(&create_test_order(id), b"salt").unwrap(),
+            epoch_id: 1,
+            timestamp_ms: 1000,
+        };
+        let spammer = AccountId::new("spammer");
+
+        // Commitments count even if they are never revealed
+        for i in 0..MAX {
+            book.commit_as(&spammer, commitment(&format!("spam{}", i))).unwrap();
+        }
+        let err = book.commit_as(&spammer, commitment("spam_extra")).unwrap_err();
+        assert!(err.to_string().contains("spammer already has 2 commitments"), "{}", err);
+        assert_eq!(book.commitment_count(), MAX);
+
+        // A rejected duplicate does not use up a slot
+        let trader2 = AccountId::new("trader2");
+        book.commit_as(&trader2, commitment("other")).unwrap();
+        assert!(book.commit_as(&trader2, commitment("other")).is_err());
+        book.commit_as(&trader2, commitment("other2")).unwrap();
+
+        // Counts reset in the next epoch's book
+        let mut next = book.for_epoch(2);
+        next.commit_as(&spammer, Commitment { epoch_id: 2, ..commitment("next") }).unwrap();
+    }
+
     #[test]
     fn test_reveal_without_commit() {
         let mut book = OrderBook::new(1);
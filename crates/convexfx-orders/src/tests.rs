@@ -14,6 +14,8 @@ mod tests {
             budget: Amount::from_units(1000),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         }
     }
@@ -32,6 +34,8 @@ mod tests {
                 budget: Amount::from_units(1000),
                 limit_ratio: Some(1.15),
                 min_fill_fraction: Some(0.1),
+                max_slippage_bps: None,
+                submission_mid: None,
                 metadata: serde_json::json!({}),
             },
             PairOrder {
@@ -42,6 +46,8 @@ mod tests {
                 budget: Amount::from_units(500),
                 limit_ratio: None,
                 min_fill_fraction: None,
+                max_slippage_bps: None,
+                submission_mid: None,
                 metadata: serde_json::json!({}),
             },
         ];
@@ -181,6 +187,41 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_unrevealed_commitment_expires_after_epoch_closes() {
+        let mut book = OrderBook::new(1);
+        let order = create_test_order("late");
+        let salt = b"salt";
+
+        let hash = commitment::compute_commitment(&order, salt).unwrap();
+        book.commit(Commitment {
+            hash: hash.clone(),
+            epoch_id: 1,
+            timestamp_ms: 1000,
+        }).unwrap();
+        assert_eq!(book.unrevealed_count(1), 1);
+
+        // Epoch 1 closes without a reveal
+        let mut book = book.for_epoch(2);
+        assert_eq!(book.commitment_count(), 0);
+        assert_eq!(book.unrevealed_count(1), 1);
+
+        let err = book.reveal(order.clone(), salt).unwrap_err();
+        assert!(err.to_string().contains("epoch 1 is closed"), "{}", err);
+
+        // The open epoch is never expired
+        assert!(book.expire_unrevealed(2).is_empty());
+
+        let expired = book.expire_unrevealed(1);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].hash, hash);
+        assert_eq!(book.unrevealed_count(1), 0);
+
+        // Once expired the commitment is gone entirely
+        let err = book.reveal(order, salt).unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+    }
+
     #[test]
     fn test_frozen_orderbook_rejection() {
         let mut book = OrderBook::new(1);
@@ -214,6 +255,8 @@ mod tests {
             budget: Amount::from_units(100),
             limit_ratio: Some(1.2),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         };
         assert!(validate_order(&valid).is_ok());
@@ -227,6 +270,8 @@ mod tests {
             budget: Amount::ZERO,
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         };
         assert!(validate_order(&zero_budget).is_err());
@@ -240,6 +285,8 @@ mod tests {
             budget: Amount::from_units(100),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         };
         assert!(validate_order(&same_asset).is_err());
@@ -253,6 +300,8 @@ mod tests {
             budget: Amount::from_units(100),
             limit_ratio: Some(-1.0),
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         };
         assert!(validate_order(&bad_limit).is_err());
@@ -266,6 +315,8 @@ mod tests {
             budget: Amount::from_units(100),
             limit_ratio: None,
             min_fill_fraction: Some(1.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         };
         assert!(validate_order(&bad_fill).is_err());
@@ -1,6 +1,13 @@
-use convexfx_types::{ConvexFxError, PairOrder, Result};
+use convexfx_types::{BasketOrder, ConvexFxError, PairOrder, Result};
+
+/// How far basket weights may sum from 1 before the basket is rejected
+const BASKET_WEIGHT_SUM_TOLERANCE: f64 = 1e-6;
 
 /// Validate a pair order for basic consistency
+///
+/// Rejects orders whose budget is not positive, that pay and receive the
+/// same asset, whose limit ratio is not positive and finite, whose min fill
+/// fraction lies outside (0, 1], or that have an empty id.
 pub fn validate_order(order: &PairOrder) -> Result<()> {
     // Check budget is positive
     if !order.budget.is_positive() {
@@ -25,6 +32,99 @@ pub fn validate_order(order: &PairOrder) -> Result<()> {
         }
     }
 
+    // Check min fill fraction if present (zero would mean "no minimum",
+    // which is expressed by leaving it unset)
+    if let Some(min_fill) = order.min_fill_fraction {
+        if !(min_fill > 0.0 && min_fill <= 1.0) {
+            return Err(ConvexFxError::InvalidOrder(format!(
+                "min fill fraction must be in (0, 1], got {}",
+                min_fill
+            )));
+        }
+    }
+
+    // Check slippage cap if present
+    if let Some(cap) = order.max_slippage_bps {
+        if !(cap.is_finite() && cap >= 0.0) {
+            return Err(ConvexFxError::InvalidOrder(format!(
+                "max slippage must be a non-negative number of bps, got {}",
+                cap
+            )));
+        }
+    }
+
+    // Check submission mid if present
+    if let Some(mid) = order.submission_mid {
+        if !(mid.is_finite() && mid > 0.0) {
+            return Err(ConvexFxError::InvalidOrder(format!(
+                "submission mid must be positive and finite, got {}",
+                mid
+            )));
+        }
+    }
+
+    // Check order ID is not empty
+    if order.id.is_empty() {
+        return Err(ConvexFxError::InvalidOrder(
+            "order ID cannot be empty".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validate a basket order for basic consistency
+///
+/// `max_legs` is the largest basket accepted (see
+/// `RiskParams::max_basket_legs`); `None` leaves basket size unbounded.
+/// Weights must be positive and sum to 1, and no leg may receive the
+/// asset the basket pays with.
+pub fn validate_basket_order(order: &BasketOrder, max_legs: Option<usize>) -> Result<()> {
+    // Check budget is positive
+    if !order.budget.is_positive() {
+        return Err(ConvexFxError::InvalidOrder(
+            "budget must be positive".to_string(),
+        ));
+    }
+
+    // Check basket has legs and respects the size cap
+    if order.basket_weights.is_empty() {
+        return Err(ConvexFxError::InvalidOrder(
+            "basket must have at least one leg".to_string(),
+        ));
+    }
+    if let Some(max_legs) = max_legs {
+        if order.basket_weights.len() > max_legs {
+            return Err(ConvexFxError::InvalidOrder(format!(
+                "basket has {} legs, maximum is {}",
+                order.basket_weights.len(),
+                max_legs
+            )));
+        }
+    }
+
+    // Check weights
+    if order.basket_weights.values().any(|w| !w.is_finite() || *w <= 0.0) {
+        return Err(ConvexFxError::InvalidOrder(
+            "basket weights must be positive and finite".to_string(),
+        ));
+    }
+    let total: f64 = order.basket_weights.values().sum();
+    if (total - 1.0).abs() > BASKET_WEIGHT_SUM_TOLERANCE {
+        return Err(ConvexFxError::InvalidOrder(format!(
+            "basket weights must sum to 1, got {}",
+            total
+        )));
+    }
+
+    // Check the basket does not buy back its pay asset
+    if order.basket_weights.contains_key(&order.pay) {
+        return Err(ConvexFxError::InvalidOrder(format!(
+            "basket cannot include its pay asset {} as a leg",
+            order.pay
+        )));
+    }
+
     // Check min fill fraction if present
     if let Some(min_fill) = order.min_fill_fraction {
         if !(0.0..=1.0).contains(&min_fill) {
@@ -59,6 +159,8 @@ mod tests {
             budget: Amount::from_units(1000),
             limit_ratio: Some(1.2),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         };
 
@@ -75,6 +177,8 @@ mod tests {
             budget: Amount::ZERO,
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         };
 
@@ -91,11 +195,93 @@ mod tests {
             budget: Amount::from_units(100),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         };
 
         assert!(validate_order(&order).is_err());
     }
-}
 
+    #[test]
+    fn test_rejection_cases() {
+        let order = PairOrder {
+            id: "order1".to_string(),
+            trader: AccountId::new("trader1"),
+            pay: AssetId::USD,
+            receive: AssetId::EUR,
+            budget: Amount::from_units(100),
+            limit_ratio: None,
+            min_fill_fraction: Some(1.0),
+            max_slippage_bps: None,
+            submission_mid: None,
+            metadata: serde_json::json!({}),
+        };
+        assert!(validate_order(&order).is_ok());
 
+        let rejects = |modify: &dyn Fn(&mut PairOrder), reason: &str| {
+            let mut bad = order.clone();
+            modify(&mut bad);
+            let err = validate_order(&bad).unwrap_err().to_string();
+            assert!(err.contains(reason), "{} does not mention {}", err, reason);
+        };
+        rejects(&|o| o.budget = Amount::from_units(-5), "budget");
+        rejects(&|o| o.receive = AssetId::USD, "different");
+        rejects(&|o| o.limit_ratio = Some(0.0), "limit ratio");
+        rejects(&|o| o.limit_ratio = Some(f64::NAN), "limit ratio");
+        rejects(&|o| o.min_fill_fraction = Some(0.0), "min fill");
+        rejects(&|o| o.min_fill_fraction = Some(1.01), "min fill");
+        rejects(&|o| o.id.clear(), "order ID");
+    }
+
+    fn basket(legs: &[AssetId]) -> BasketOrder {
+        BasketOrder {
+            id: "basket1".to_string(),
+            trader: AccountId::new("trader1"),
+            pay: AssetId::USD,
+            budget: Amount::from_units(1000),
+            basket_weights: legs.iter().map(|a| (*a, 1.0 / legs.len() as f64)).collect(),
+            min_fill_fraction: None,
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_basket_max_legs() {
+        let within = basket(&[AssetId::EUR, AssetId::GBP]);
+        assert!(validate_basket_order(&within, Some(2)).is_ok());
+
+        let oversized = basket(&[AssetId::EUR, AssetId::GBP, AssetId::JPY]);
+        let err = validate_basket_order(&oversized, Some(2)).unwrap_err();
+        assert!(err.to_string().contains("3 legs"));
+
+        // No cap configured
+        assert!(validate_basket_order(&oversized, None).is_ok());
+    }
+
+    #[test]
+    fn test_basket_rejection_cases() {
+        let valid = basket(&[AssetId::EUR, AssetId::GBP, AssetId::JPY, AssetId::CHF]);
+        assert!(validate_basket_order(&valid, Some(4)).is_ok());
+
+        let rejects = |modify: &dyn Fn(&mut BasketOrder), reason: &str| {
+            let mut bad = valid.clone();
+            modify(&mut bad);
+            let err = validate_basket_order(&bad, Some(4)).unwrap_err().to_string();
+            assert!(err.contains(reason), "{} does not mention {}", err, reason);
+        };
+        rejects(&|b| { b.basket_weights.insert(AssetId::CHF, 0.0); }, "positive");
+        rejects(&|b| *b.basket_weights.get_mut(&AssetId::EUR).unwrap() = 0.5, "sum to 1");
+        rejects(&|b| b.basket_weights.values_mut().for_each(|w| *w *= 2.0), "sum to 1");
+        rejects(&|b| *b = basket(&[AssetId::USD, AssetId::EUR]), "pay asset");
+        rejects(
+            &|b| *b = basket(&[AssetId::EUR, AssetId::GBP, AssetId::JPY, AssetId::CHF, AssetId::AUD]),
+            "5 legs",
+        );
+
+        // Rounding in the weights is tolerated
+        let mut thirds = basket(&[AssetId::EUR, AssetId::GBP, AssetId::JPY]);
+        thirds.basket_weights.insert(AssetId::JPY, 0.333_333_3);
+        assert!(validate_basket_order(&thirds, None).is_ok());
+    }
+}
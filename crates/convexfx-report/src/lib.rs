@@ -1,7 +1,7 @@
 mod reporter;
 mod hashing;
 
-pub use reporter::{Reporter, EpochReport, ReportData, MemoryReporter};
+pub use reporter::{Reporter, EpochReport, ReportData, MemoryReporter, FileReporter, verify_chain, GENESIS_HASH};
 pub use hashing::{compute_hash, HashRef};
 
 #[cfg(test)]
@@ -1,17 +1,40 @@
 use convexfx_types::EpochId;
 use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use crate::hashing::{compute_json_hash, HashRef};
 
+/// `prev_hash` of the first report in a chain
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
 /// Epoch report with input/output hashes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EpochReport {
     pub epoch_id: EpochId,
     pub input_hash: HashRef,
     pub output_hash: HashRef,
+    /// Hash of the previous report (`GENESIS_HASH` for the first)
+    #[serde(default)]
+    pub prev_hash: HashRef,
     pub report_data: ReportData,
 }
 
+impl EpochReport {
+    /// Hash of the whole report, including its link to the previous one
+    pub fn hash(&self) -> HashRef {
+        compute_json_hash(self).expect("EpochReport serializes to JSON")
+    }
+
+    /// Check the input/output hashes match the report data
+    pub fn hashes_match_data(&self) -> bool {
+        compute_json_hash(&self.report_data.inputs).ok().as_ref() == Some(&self.input_hash)
+            && compute_json_hash(&self.report_data.outputs).ok().as_ref() == Some(&self.output_hash)
+    }
+}
+
 /// Report data structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReportData {
@@ -21,15 +44,77 @@ pub struct ReportData {
 
 /// Reporter trait
 pub trait Reporter {
-    fn publish(&self, epoch_id: EpochId, inputs: &serde_json::Value, outputs: &serde_json::Value) -> Result<EpochReport, String>;
+    /// Link `report` to the previously recorded one and store it
+    fn record(&self, report: EpochReport) -> Result<EpochReport, String>;
+
+    /// Build, link and store the report for an epoch
+    fn publish(&self, epoch_id: EpochId, inputs: &serde_json::Value, outputs: &serde_json::Value) -> Result<EpochReport, String> {
+        self.record(build_report(epoch_id, inputs, outputs)?)
+    }
+
+    /// Flush any buffered reports to their backing store
+    fn flush(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Check the stored reports form an unbroken hash chain
+    ///
+    /// Returns the index of the first report that was tampered with or
+    /// does not link to its predecessor.
+    fn verify_chain(&self) -> Result<(), usize>;
+}
+
+/// Check a sequence of reports forms an unbroken hash chain
+///
+/// Returns the index of the first report whose hashes don't match its data
+/// or whose `prev_hash` doesn't match the report before it.
+pub fn verify_chain(reports: &[EpochReport]) -> Result<(), usize> {
+    let mut expected_prev = GENESIS_HASH.to_string();
+    for (i, report) in reports.iter().enumerate() {
+        if report.prev_hash != expected_prev || !report.hashes_match_data() {
+            return Err(i);
+        }
+        expected_prev = report.hash();
+    }
+    Ok(())
+}
+
+/// Build a report with input/output hashes (not yet linked)
+fn build_report(
+    epoch_id: EpochId,
+    inputs: &serde_json::Value,
+    outputs: &serde_json::Value,
+) -> Result<EpochReport, String> {
+    let input_hash = compute_json_hash(inputs).map_err(|e| e.to_string())?;
+    let output_hash = compute_json_hash(outputs).map_err(|e| e.to_string())?;
+
+    Ok(EpochReport {
+        epoch_id,
+        input_hash,
+        output_hash,
+        prev_hash: GENESIS_HASH.to_string(),
+        report_data: ReportData {
+            inputs: inputs.clone(),
+            outputs: outputs.clone(),
+        },
+    })
 }
 
 /// Simple in-memory reporter
-pub struct MemoryReporter;
+pub struct MemoryReporter {
+    reports: Mutex<Vec<EpochReport>>,
+}
 
 impl MemoryReporter {
     pub fn new() -> Self {
-        MemoryReporter
+        MemoryReporter {
+            reports: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// All reports recorded so far, oldest first
+    pub fn reports(&self) -> Vec<EpochReport> {
+        self.reports.lock().unwrap().clone()
     }
 }
 
@@ -40,30 +125,111 @@ impl Default for MemoryReporter {
 }
 
 impl Reporter for MemoryReporter {
-    fn publish(
-        &self,
-        epoch_id: EpochId,
-        inputs: &serde_json::Value,
-        outputs: &serde_json::Value,
-    ) -> Result<EpochReport, String> {
-        let input_hash = compute_json_hash(inputs).map_err(|e| e.to_string())?;
-        let output_hash = compute_json_hash(outputs).map_err(|e| e.to_string())?;
-
-        Ok(EpochReport {
-            epoch_id,
-            input_hash,
-            output_hash,
-            report_data: ReportData {
-                inputs: inputs.clone(),
-                outputs: outputs.clone(),
-            },
+    fn record(&self, mut report: EpochReport) -> Result<EpochReport, String> {
+        let mut reports = self.reports.lock().map_err(|e| e.to_string())?;
+        report.prev_hash = reports
+            .last()
+            .map(EpochReport::hash)
+            .unwrap_or_else(|| GENESIS_HASH.to_string());
+        reports.push(report.clone());
+        Ok(report)
+    }
+
+    fn verify_chain(&self) -> Result<(), usize> {
+        verify_chain(&self.reports.lock().unwrap())
+    }
+}
+
+/// Open log file and the hash of its last report
+struct FileLog {
+    writer: BufWriter<File>,
+    last_hash: HashRef,
+}
+
+/// Reporter that appends each report to a file as one JSON line
+///
+/// The file is opened in append mode and flushed after every report, so it
+/// doubles as a durable audit log across restarts. Reopening an existing
+/// log continues its hash chain.
+pub struct FileReporter {
+    path: PathBuf,
+    log: Mutex<FileLog>,
+}
+
+impl FileReporter {
+    /// Open (or create) the report log at `path`
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("failed to open {}: {}", path.display(), e))?;
+        let last_hash = Self::read_reports(&path)?
+            .last()
+            .map(EpochReport::hash)
+            .unwrap_or_else(|| GENESIS_HASH.to_string());
+
+        Ok(FileReporter {
+            path,
+            log: Mutex::new(FileLog {
+                writer: BufWriter::new(file),
+                last_hash,
+            }),
         })
     }
+
+    /// Path of the report log
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read back every report in a log, in the order written
+    pub fn read_reports(path: impl AsRef<Path>) -> Result<Vec<EpochReport>, String> {
+        let file = File::open(path.as_ref()).map_err(|e| e.to_string())?;
+        let mut reports = Vec::new();
+        for (line_no, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let report = serde_json::from_str(&line)
+                .map_err(|e| format!("line {}: {}", line_no + 1, e))?;
+            reports.push(report);
+        }
+        Ok(reports)
+    }
+}
+
+impl Reporter for FileReporter {
+    fn record(&self, mut report: EpochReport) -> Result<EpochReport, String> {
+        let mut log = self.log.lock().map_err(|e| e.to_string())?;
+        report.prev_hash = log.last_hash.clone();
+        let line = serde_json::to_string(&report).map_err(|e| e.to_string())?;
+
+        writeln!(log.writer, "{}", line).map_err(|e| e.to_string())?;
+        log.writer.flush().map_err(|e| e.to_string())?;
+        log.last_hash = report.hash();
+
+        Ok(report)
+    }
+
+    fn flush(&self) -> Result<(), String> {
+        let mut log = self.log.lock().map_err(|e| e.to_string())?;
+        log.writer.flush().map_err(|e| e.to_string())
+    }
+
+    /// An unreadable log fails at index 0
+    fn verify_chain(&self) -> Result<(), usize> {
+        let reports = Self::read_reports(&self.path).map_err(|_| 0usize)?;
+        verify_chain(&reports)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hashing::compute_hash;
 
     #[test]
     fn test_memory_reporter() {
@@ -78,6 +244,70 @@ mod tests {
         assert_eq!(report.input_hash.len(), 64);
         assert_eq!(report.output_hash.len(), 64);
     }
+
+    #[test]
+    fn test_file_reporter_round_trip() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let reporter = FileReporter::new(file.path()).unwrap();
+
+        let mut published = Vec::new();
+        for epoch_id in 1..=3 {
+            let inputs = serde_json::json!({"epoch": epoch_id});
+            let outputs = serde_json::json!({"fills": [epoch_id * 10]});
+            published.push(reporter.publish(epoch_id, &inputs, &outputs).unwrap());
+        }
+        reporter.flush().unwrap();
+
+        let reloaded = FileReporter::read_reports(file.path()).unwrap();
+        assert_eq!(reloaded.len(), 3);
+        for (original, loaded) in published.iter().zip(&reloaded) {
+            assert_eq!(original.epoch_id, loaded.epoch_id);
+            assert_eq!(
+                compute_hash(&serde_json::to_vec(original).unwrap()),
+                compute_hash(&serde_json::to_vec(loaded).unwrap())
+            );
+        }
+
+        // Reopening appends rather than truncating
+        let reporter = FileReporter::new(file.path()).unwrap();
+        reporter.publish(4, &serde_json::json!({}), &serde_json::json!({})).unwrap();
+        let epochs: Vec<_> = FileReporter::read_reports(file.path())
+            .unwrap()
+            .iter()
+            .map(|r| r.epoch_id)
+            .collect();
+        assert_eq!(epochs, vec![1, 2, 3, 4]);
+        assert!(reporter.verify_chain().is_ok());
+    }
+
+    #[test]
+    fn test_chain_detects_tampering() {
+        let reporter = MemoryReporter::new();
+        for epoch_id in 1..=4 {
+            let inputs = serde_json::json!({"epoch": epoch_id});
+            let outputs = serde_json::json!({"fills": [epoch_id]});
+            reporter.publish(epoch_id, &inputs, &outputs).unwrap();
+        }
+        assert!(reporter.verify_chain().is_ok());
+
+        let reports = reporter.reports();
+        assert_eq!(reports[0].prev_hash, GENESIS_HASH);
+        assert_eq!(reports[2].prev_hash, reports[1].hash());
+
+        // Rewriting a middle report's outputs is caught at that report
+        let mut tampered = reports.clone();
+        tampered[2].report_data.outputs = serde_json::json!({"fills": [99]});
+        assert_eq!(verify_chain(&tampered), Err(2));
+
+        // Rehashing the tampered report breaks the link to the next one
+        tampered[2].output_hash = compute_hash(&serde_json::to_vec(&tampered[2].report_data.outputs).unwrap());
+        assert_eq!(verify_chain(&tampered), Err(3));
+
+        // Dropping a report breaks the chain too
+        let mut missing = reports;
+        missing.remove(1);
+        assert_eq!(verify_chain(&missing), Err(1));
+    }
 }
 
 
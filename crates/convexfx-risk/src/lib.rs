@@ -1,8 +1,10 @@
 mod risk_params;
 mod matrix_utils;
 
-pub use risk_params::RiskParams;
-pub use matrix_utils::{build_gamma_matrix, build_w_matrix, validate_psd};
+pub use risk_params::{RiskParams, GAMMA_MIN_EIGENVALUE};
+pub use matrix_utils::{
+    build_gamma_matrix, build_gamma_matrix_from_corr, build_w_matrix, nearest_psd, validate_psd,
+};
 
 #[cfg(test)]
 mod tests;
@@ -1,10 +1,42 @@
-use nalgebra::{DMatrix, DVector};
+use convexfx_types::{ConvexFxError, Result};
+use nalgebra::{DMatrix, DVector, SymmetricEigen};
 
 /// Build a diagonal gamma matrix from variance vector
 pub fn build_gamma_matrix(variances: &[f64]) -> DMatrix<f64> {
     DMatrix::from_diagonal(&DVector::from_vec(variances.to_vec()))
 }
 
+/// Eigenvalue floor used when a correlation-built Γ has to be projected to PSD
+const CORR_MIN_EIGENVALUE: f64 = 1e-9;
+
+/// Build a full gamma matrix Γ = diag(vol) · corr · diag(vol)
+///
+/// The result is checked with `validate_psd`; an inconsistent correlation
+/// matrix (e.g. pairwise estimates that don't fit together) is projected to
+/// the nearest PSD matrix rather than handed to the optimizer as is.
+/// Fails with `InvalidArgument` unless `corr` is `vols.len()` square.
+pub fn build_gamma_matrix_from_corr(vols: &[f64], corr: &DMatrix<f64>) -> Result<DMatrix<f64>> {
+    if corr.nrows() != vols.len() || corr.ncols() != vols.len() {
+        return Err(ConvexFxError::InvalidArgument(format!(
+            "correlation matrix must be {}x{}, got {}x{}",
+            vols.len(),
+            vols.len(),
+            corr.nrows(),
+            corr.ncols()
+        )));
+    }
+
+    let vol = DMatrix::from_diagonal(&DVector::from_vec(vols.to_vec()));
+    let gamma = &vol * corr * &vol;
+
+    if validate_psd(&gamma, 1e-12) {
+        Ok(gamma)
+    } else {
+        tracing::warn!("correlation-based inventory risk matrix is not PSD; projecting to nearest PSD");
+        Ok(nearest_psd(&gamma, CORR_MIN_EIGENVALUE))
+    }
+}
+
 /// Build a diagonal W tracking matrix from weight vector
 pub fn build_w_matrix(weights: &[f64]) -> DMatrix<f64> {
     DMatrix::from_diagonal(&DVector::from_vec(weights.to_vec()))
@@ -43,9 +75,24 @@ pub fn validate_psd(matrix: &DMatrix<f64>, tolerance: f64) -> bool {
         return matrix.diagonal().iter().all(|&x| x >= -tolerance);
     }
 
-    // For general matrices, would need eigenvalue decomposition
-    // For now, assume valid if symmetric (caller responsibility)
-    true
+    // General symmetric matrices: every eigenvalue must be non-negative
+    SymmetricEigen::new(matrix.clone())
+        .eigenvalues
+        .iter()
+        .all(|&x| x >= -tolerance)
+}
+
+/// Project a matrix onto the nearest PSD matrix (in Frobenius norm)
+///
+/// Symmetrizes the input, then clamps every eigenvalue below `epsilon` up to
+/// `epsilon` and reassembles V diag(λ) Vᵀ.
+pub fn nearest_psd(matrix: &DMatrix<f64>, epsilon: f64) -> DMatrix<f64> {
+    let symmetric = (matrix + matrix.transpose()) * 0.5;
+    let eigen = SymmetricEigen::new(symmetric);
+    let clamped = eigen.eigenvalues.map(|x| x.max(epsilon));
+    let repaired = &eigen.eigenvectors * DMatrix::from_diagonal(&clamped) * eigen.eigenvectors.transpose();
+    // Remove the asymmetry left by floating point in the reassembly
+    (&repaired + repaired.transpose()) * 0.5
 }
 
 #[cfg(test)]
@@ -65,6 +112,33 @@ mod tests {
         assert_eq!(gamma[(0, 1)], 0.0);
     }
 
+    #[test]
+    fn test_build_gamma_from_corr() {
+        let vols = vec![0.1, 0.2];
+        let corr = DMatrix::from_row_slice(2, 2, &[1.0, 0.5, 0.5, 1.0]);
+        let gamma = build_gamma_matrix_from_corr(&vols, &corr).unwrap();
+
+        assert!((gamma[(0, 0)] - 0.01).abs() < 1e-15);
+        assert!((gamma[(1, 1)] - 0.04).abs() < 1e-15);
+        assert!((gamma[(0, 1)] - 0.01).abs() < 1e-15);
+        assert_eq!(gamma[(0, 1)], gamma[(1, 0)]);
+
+        // Impossible correlations come back repaired
+        let inconsistent = DMatrix::from_row_slice(3, 3, &[
+            1.0, 0.9, 0.9,
+            0.9, 1.0, -0.9,
+            0.9, -0.9, 1.0,
+        ]);
+        let gamma = build_gamma_matrix_from_corr(&[1.0, 1.0, 1.0], &inconsistent).unwrap();
+        assert!(validate_psd(&gamma, 1e-12));
+
+        // A correlation matrix of the wrong size is an error, not a panic
+        assert!(matches!(
+            build_gamma_matrix_from_corr(&[1.0, 1.0, 1.0], &corr),
+            Err(ConvexFxError::InvalidArgument(_))
+        ));
+    }
+
     #[test]
     fn test_psd_validation() {
         let positive_diag = DMatrix::from_diagonal(&DVector::from_vec(vec![1.0, 2.0, 3.0]));
@@ -72,6 +146,24 @@ mod tests {
 
         let zero_diag = DMatrix::from_diagonal(&DVector::from_vec(vec![0.0, 0.0, 0.0]));
         assert!(validate_psd(&zero_diag, 1e-10));
+
+        // Symmetric with a negative eigenvalue (1 - 2 = -1)
+        let indefinite = DMatrix::from_row_slice(2, 2, &[1.0, 2.0, 2.0, 1.0]);
+        assert!(!validate_psd(&indefinite, 1e-10));
+
+        let correlated = DMatrix::from_row_slice(2, 2, &[1.0, 0.9, 0.9, 1.0]);
+        assert!(validate_psd(&correlated, 1e-10));
+    }
+
+    #[test]
+    fn test_nearest_psd() {
+        let indefinite = DMatrix::from_row_slice(2, 2, &[1.0, 2.0, 2.0, 1.0]);
+        let repaired = nearest_psd(&indefinite, 1e-9);
+        assert!(validate_psd(&repaired, 1e-12));
+
+        // Eigenvalues 3 and -1 -> 3 and 1e-9: the positive direction is kept
+        let expected = DMatrix::from_row_slice(2, 2, &[1.5, 1.5, 1.5, 1.5]);
+        assert!((repaired - expected).norm() < 1e-8);
     }
 }
 
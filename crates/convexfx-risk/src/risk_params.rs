@@ -1,8 +1,16 @@
-use convexfx_types::AssetId;
+use convexfx_types::{AssetId, ConvexFxError, Result};
 use nalgebra::DMatrix;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+use crate::matrix_utils::{build_gamma_matrix_from_corr, nearest_psd, validate_psd};
+
+/// Eigenvalue floor that `repair_gamma` clamps Γ's spectrum up to
+pub const GAMMA_MIN_EIGENVALUE: f64 = 1e-9;
+
+/// Negative eigenvalues smaller than this are treated as round-off, not indefiniteness
+const PSD_TOLERANCE: f64 = 1e-12;
+
 /// Risk parameters for the clearing optimization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskParams {
@@ -17,6 +25,12 @@ pub struct RiskParams {
     /// Diagonal elements of Γ (serializable)
     pub gamma_diag: Vec<f64>,
 
+    /// Row-major asset correlation matrix. When set, Γ is
+    /// diag(vol) · corr · diag(vol) with vol = sqrt(gamma_diag) instead of
+    /// diagonal.
+    #[serde(default)]
+    pub gamma_corr: Option<Vec<f64>>,
+
     /// Price tracking matrix W (PSD) for oracle tracking
     /// Penalizes deviation from y_ref
     #[serde(skip)]
@@ -28,6 +42,11 @@ pub struct RiskParams {
     /// Fill incentive weight η
     pub eta: f64,
 
+    /// Cap on the fill incentive as a multiple of the inventory risk added by
+    /// filling every order in full. `None` leaves η uncapped.
+    #[serde(default)]
+    pub fill_incentive_cap: Option<f64>,
+
     /// Minimum inventory bounds
     pub q_min: BTreeMap<AssetId, f64>,
 
@@ -37,13 +56,42 @@ pub struct RiskParams {
     /// Price band in basis points (trust region)
     pub price_band_bps: f64,
 
-    /// Ghost inventory weight (virtual cushion near bounds)
+    /// Ghost inventory weight: the hard inventory bounds are relaxed by
+    /// this fraction of each asset's target inventory, a virtual cushion
+    /// that lets a fill run slightly past a bound instead of being cut off
     pub ghost_inventory_weight: f64,
+
+    /// Scale each asset's tracking weight by the mean USD notional of the
+    /// target inventory over the asset's own, so assets held in large USD
+    /// size are not over-penalized for the same log-price move
+    #[serde(default)]
+    pub normalize_tracking_by_notional: bool,
+
+    /// Per-asset cap on any single trader's net position change within an
+    /// epoch (units received minus units paid, in either direction).
+    /// Assets without an entry are unlimited.
+    #[serde(default)]
+    pub trader_position_limits: BTreeMap<AssetId, f64>,
+
+    /// Maximum number of legs accepted in a basket order. `None` leaves
+    /// basket size unbounded.
+    #[serde(default)]
+    pub max_basket_legs: Option<usize>,
+
+    /// Width of the hysteresis band inside each inventory bound, as a
+    /// fraction of the asset's q_min..q_max range. Within the band, fills
+    /// that push inventory further toward the bound are capped in
+    /// proportion to the distance left instead of running to the hard edge.
+    /// 0 disables the band.
+    #[serde(default)]
+    pub hysteresis_buffer_frac: f64,
 }
 
 impl RiskParams {
     /// Create ultra-low slippage risk parameters (retail-optimized)
-    /// Optimized for minimal price impact while maintaining coherence
+    /// Optimized for minimal price impact while maintaining coherence:
+    /// USD-notional normalized tracking, a ghost inventory cushion on the
+    /// bounds, and a tight trust region
     pub fn ultra_low_slippage() -> Self {
         let assets = AssetId::all();
         let n = assets.len();
@@ -70,13 +118,19 @@ impl RiskParams {
             q_target,
             gamma,
             gamma_diag,
+            gamma_corr: None,
             w_track,
             w_diag,
             eta: 1.0, // Standard fill incentive
+            fill_incentive_cap: None,
             q_min,
             q_max,
             price_band_bps: 25.0, // Moderate bands for stability
-            ghost_inventory_weight: 0.01, // Small virtual cushion
+            ghost_inventory_weight: 0.05, // Ghost cushion of 5% of target past each bound
+            normalize_tracking_by_notional: true, // USD-notional normalized W
+            trader_position_limits: BTreeMap::new(),
+            max_basket_legs: None,
+            hysteresis_buffer_frac: 0.0,
         }
     }
 
@@ -108,13 +162,19 @@ impl RiskParams {
             q_target,
             gamma,
             gamma_diag,
+            gamma_corr: None,
             w_track,
             w_diag,
             eta: 0.5, // Moderate fill incentive (was 1.0)
+            fill_incentive_cap: None,
             q_min,
             q_max,
             price_band_bps: 30.0, // Moderate bands for flexibility (was 20.0)
-            ghost_inventory_weight: 0.01, // Small virtual cushion
+            ghost_inventory_weight: 0.0, // Hard inventory bounds
+            normalize_tracking_by_notional: false,
+            trader_position_limits: BTreeMap::new(),
+            max_basket_legs: None,
+            hysteresis_buffer_frac: 0.0,
         }
     }
 
@@ -146,13 +206,19 @@ impl RiskParams {
             q_target,
             gamma,
             gamma_diag,
+            gamma_corr: None,
             w_track,
             w_diag,
             eta: 2.0, // Strong fill incentive
+            fill_incentive_cap: None,
             q_min,
             q_max,
             price_band_bps: 50.0, // Wider bands for flexibility in stress
-            ghost_inventory_weight: 0.01, // Small virtual cushion
+            ghost_inventory_weight: 0.0, // Hard inventory bounds
+            normalize_tracking_by_notional: false,
+            trader_position_limits: BTreeMap::new(),
+            max_basket_legs: None,
+            hysteresis_buffer_frac: 0.0,
         }
     }
 
@@ -183,13 +249,19 @@ impl RiskParams {
             q_target,
             gamma,
             gamma_diag,
+            gamma_corr: None,
             w_track,
             w_diag,
             eta: 1.0,
+            fill_incentive_cap: None,
             q_min,
             q_max,
             price_band_bps: 50.0, // Increased for better flexibility
-            ghost_inventory_weight: 0.01, // Small virtual cushion
+            ghost_inventory_weight: 0.0, // Hard inventory bounds
+            normalize_tracking_by_notional: false,
+            trader_position_limits: BTreeMap::new(),
+            max_basket_legs: None,
+            hysteresis_buffer_frac: 0.0,
         }
     }
 
@@ -211,20 +283,120 @@ impl RiskParams {
             q_target,
             gamma,
             gamma_diag,
+            gamma_corr: None,
             w_track,
             w_diag,
             eta,
+            fill_incentive_cap: None,
             q_min,
             q_max,
             price_band_bps,
             ghost_inventory_weight,
+            normalize_tracking_by_notional: false,
+            trader_position_limits: BTreeMap::new(),
+            max_basket_legs: None,
+            hysteresis_buffer_frac: 0.0,
+        }
+    }
+
+    /// Replace the inventory risk with a correlated Γ
+    ///
+    /// `vols` are per-asset inventory risk volatilities (so the diagonal of Γ
+    /// becomes vol²) and `corr` the asset correlation matrix, both in
+    /// `AssetId::all()` order. Γ = diag(vol) · corr · diag(vol).
+    pub fn with_correlation(mut self, vols: Vec<f64>, corr: DMatrix<f64>) -> Result<Self> {
+        self.gamma_diag = vols.iter().map(|v| v * v).collect();
+        self.gamma_corr = Some(corr.transpose().as_slice().to_vec());
+        self.rebuild_matrices()?;
+        Ok(self)
+    }
+
+    /// Damp fills progressively inside a band of `buffer_frac` of each
+    /// asset's bound range, rather than clamping them at the bound
+    pub fn with_hysteresis(mut self, buffer_frac: f64) -> Self {
+        self.hysteresis_buffer_frac = buffer_frac.max(0.0);
+        self
+    }
+
+    /// Move each listed asset's target to `levels[asset]`, scaling its
+    /// bounds by the same factor so the band keeps its shape. Assets with a
+    /// zero target keep their bounds
+    pub fn with_inventory_levels(mut self, levels: &BTreeMap<AssetId, f64>) -> Self {
+        for (asset, &level) in levels {
+            let target = self.target(*asset);
+            if target != 0.0 {
+                let scale = level / target;
+                if let Some(min) = self.q_min.get_mut(asset) {
+                    *min *= scale;
+                }
+                if let Some(max) = self.q_max.get_mut(asset) {
+                    *max *= scale;
+                }
+            }
+            self.q_target.insert(*asset, level);
         }
+        self
     }
 
     /// Rebuild matrices from serialized diagonal elements
-    pub fn rebuild_matrices(&mut self) {
-        self.gamma = DMatrix::from_diagonal(&nalgebra::DVector::from_vec(self.gamma_diag.clone()));
+    /// (and the correlation matrix, if set)
+    ///
+    /// Fails with `InvalidArgument` when the correlation matrix does not
+    /// match the number of assets in `gamma_diag`.
+    pub fn rebuild_matrices(&mut self) -> Result<()> {
+        self.gamma = match &self.gamma_corr {
+            Some(corr) => {
+                let n = self.gamma_diag.len();
+                if corr.len() != n * n {
+                    return Err(ConvexFxError::InvalidArgument(format!(
+                        "correlation matrix has {} entries, expected {}x{}",
+                        corr.len(),
+                        n,
+                        n
+                    )));
+                }
+                let vols: Vec<f64> = self.gamma_diag.iter().map(|v| v.max(0.0).sqrt()).collect();
+                build_gamma_matrix_from_corr(&vols, &DMatrix::from_row_slice(n, n, corr))?
+            }
+            None => DMatrix::from_diagonal(&nalgebra::DVector::from_vec(self.gamma_diag.clone())),
+        };
         self.w_track = DMatrix::from_diagonal(&nalgebra::DVector::from_vec(self.w_diag.clone()));
+        Ok(())
+    }
+
+    /// Whether Γ is symmetric positive semidefinite, up to round-off
+    pub fn gamma_is_psd(&self) -> bool {
+        validate_psd(&self.gamma, PSD_TOLERANCE)
+    }
+
+    /// Repair Γ to the nearest PSD matrix if it is not PSD
+    ///
+    /// Negative eigenvalues are clamped up to `GAMMA_MIN_EIGENVALUE`, which
+    /// keeps a slightly indefinite (e.g. estimated correlation) Γ usable
+    /// instead of failing the clearing. `gamma_diag` is updated to match.
+    /// Returns the Frobenius norm of the correction, 0 if Γ was already PSD.
+    pub fn repair_gamma(&mut self) -> f64 {
+        if self.gamma_is_psd() {
+            return 0.0;
+        }
+
+        let repaired = nearest_psd(&self.gamma, GAMMA_MIN_EIGENVALUE);
+        let correction = (&repaired - &self.gamma).norm();
+        tracing::warn!(correction, "repaired non-PSD inventory risk matrix");
+
+        self.gamma_diag = repaired.diagonal().iter().copied().collect();
+        if self.gamma_corr.is_some() {
+            // Keep the serialized correlation in step so rebuilds reproduce the repair
+            let vols: Vec<f64> = self.gamma_diag.iter().map(|v| v.sqrt()).collect();
+            let n = vols.len();
+            self.gamma_corr = Some(
+                (0..n * n)
+                    .map(|k| repaired[(k / n, k % n)] / (vols[k / n] * vols[k % n]))
+                    .collect(),
+            );
+        }
+        self.gamma = repaired;
+        correction
     }
 
     /// Get target inventory for an asset
@@ -242,6 +414,68 @@ impl RiskParams {
         self.q_max.get(&asset).copied().unwrap_or(f64::INFINITY)
     }
 
+    /// Min bound relaxed by the ghost inventory cushion
+    pub fn soft_min_bound(&self, asset: AssetId) -> f64 {
+        self.min_bound(asset) - self.ghost_cushion(asset)
+    }
+
+    /// Max bound relaxed by the ghost inventory cushion
+    pub fn soft_max_bound(&self, asset: AssetId) -> f64 {
+        self.max_bound(asset) + self.ghost_cushion(asset)
+    }
+
+    fn ghost_cushion(&self, asset: AssetId) -> f64 {
+        self.ghost_inventory_weight.max(0.0) * self.target(asset).abs()
+    }
+
+    /// Per-asset tracking weights in `AssetId::all()` order
+    ///
+    /// With `normalize_tracking_by_notional`, each weight is scaled by
+    /// mean(N) / N_i where N_i = q*_i · exp(y_ref_i) is the USD notional of
+    /// the asset's target inventory. Assets without a positive notional keep
+    /// their raw weight.
+    pub fn tracking_weights(&self, y_ref: &BTreeMap<AssetId, f64>) -> Vec<f64> {
+        if !self.normalize_tracking_by_notional {
+            return self.w_diag.clone();
+        }
+
+        let notionals: Vec<f64> = AssetId::all()
+            .iter()
+            .map(|a| self.target(*a) * y_ref.get(a).copied().unwrap_or(0.0).exp())
+            .collect();
+        let positive: Vec<f64> = notionals.iter().copied().filter(|n| *n > 0.0).collect();
+        if positive.is_empty() {
+            return self.w_diag.clone();
+        }
+        let mean = positive.iter().sum::<f64>() / positive.len() as f64;
+
+        self.w_diag
+            .iter()
+            .zip(&notionals)
+            .map(|(w, n)| if *n > 0.0 { w * mean / n } else { *w })
+            .collect()
+    }
+
+    /// Largest fraction of a fill allowed to move `asset` from inventory `q`
+    /// toward its max bound (`increasing`) or its min bound
+    ///
+    /// 1 outside the hysteresis band, falling linearly to 0 at the bound.
+    /// Assets without both bounds finite are never damped.
+    pub fn hysteresis_damping(&self, asset: AssetId, q: f64, increasing: bool) -> f64 {
+        let (lo, hi) = (self.min_bound(asset), self.max_bound(asset));
+        let buffer = self.hysteresis_buffer_frac * (hi - lo);
+        if !(buffer.is_finite() && buffer > 0.0) {
+            return 1.0;
+        }
+        let distance = if increasing { hi - q } else { q - lo };
+        (distance / buffer).clamp(0.0, 1.0)
+    }
+
+    /// Get a single trader's position limit for an asset
+    pub fn position_limit(&self, asset: AssetId) -> f64 {
+        self.trader_position_limits.get(&asset).copied().unwrap_or(f64::INFINITY)
+    }
+
     /// Check if inventory is within bounds
     pub fn is_within_bounds(&self, q: &BTreeMap<AssetId, f64>) -> bool {
         for asset in AssetId::all() {
@@ -289,7 +523,12 @@ impl RiskParams {
         );
 
         let delta = y_vec - y_ref_vec;
-        let w_delta = self.w_track.clone() * &delta;
+        let w_delta = if self.normalize_tracking_by_notional {
+            let w = nalgebra::DVector::from_vec(self.tracking_weights(y_ref));
+            w.component_mul(&delta)
+        } else {
+            self.w_track.clone() * &delta
+        };
         0.5 * delta.dot(&w_delta)
     }
 }
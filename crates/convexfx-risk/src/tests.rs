@@ -219,4 +219,109 @@ mod tests {
         let penalty = params.tracking_penalty(&y, &y_ref);
         assert!(penalty > 0.0);
     }
+
+    #[test]
+    fn test_repair_slightly_indefinite_gamma() {
+        let mut params = RiskParams::default_demo();
+        let eur = AssetId::EUR.index();
+        let chf = AssetId::CHF.index();
+
+        // A correlation slightly above 1 between EUR and CHF: eigenvalues of
+        // that block are 0.21 and -0.01
+        params.gamma[(eur, chf)] = 0.11;
+        params.gamma[(chf, eur)] = 0.11;
+        assert!(!validate_psd(&params.gamma, 1e-12));
+
+        let correction = params.repair_gamma();
+        assert!(correction > 0.0 && correction < 0.02, "correction {}", correction);
+        assert!(validate_psd(&params.gamma, 1e-12));
+        assert_eq!(params.gamma_diag[eur], params.gamma[(eur, eur)]);
+
+        // Untouched assets keep their variances, and a PSD Γ is left alone
+        let usd = AssetId::USD.index();
+        assert!((params.gamma[(usd, usd)] - 0.1).abs() < 1e-12);
+        assert_eq!(params.repair_gamma(), 0.0);
+    }
+
+    #[test]
+    fn test_correlated_gamma_penalizes_offsetting_positions_less() {
+        let diagonal = RiskParams::default_demo();
+        let n = AssetId::all().len();
+        let eur = AssetId::EUR.index();
+        let chf = AssetId::CHF.index();
+
+        let mut corr = nalgebra::DMatrix::identity(n, n);
+        corr[(eur, chf)] = 0.9;
+        corr[(chf, eur)] = 0.9;
+        let vols: Vec<f64> = diagonal.gamma_diag.iter().map(|v| v.sqrt()).collect();
+
+        let mut correlated = RiskParams::default_demo().with_correlation(vols, corr).unwrap();
+        assert!(validate_psd(&correlated.gamma, 1e-12));
+        for (a, b) in correlated.gamma_diag.iter().zip(&diagonal.gamma_diag) {
+            assert!((a - b).abs() < 1e-15);
+        }
+
+        // Long EUR against short CHF largely hedges under correlation
+        let mut q = diagonal.q_target.clone();
+        *q.get_mut(&AssetId::EUR).unwrap() += 2.0;
+        *q.get_mut(&AssetId::CHF).unwrap() -= 2.0;
+        let diag_penalty = diagonal.inventory_penalty(&q);
+        let corr_penalty = correlated.inventory_penalty(&q);
+        assert!(corr_penalty < diag_penalty * 0.2, "{} vs {}", corr_penalty, diag_penalty);
+
+        // Same-direction positions add up instead
+        let mut same = diagonal.q_target.clone();
+        *same.get_mut(&AssetId::EUR).unwrap() += 2.0;
+        *same.get_mut(&AssetId::CHF).unwrap() += 2.0;
+        assert!(correlated.inventory_penalty(&same) > diagonal.inventory_penalty(&same));
+
+        // Rebuilding (e.g. after deserializing) keeps the correlation
+        let gamma = correlated.gamma.clone();
+        correlated.rebuild_matrices().unwrap();
+        assert!((&correlated.gamma - gamma).norm() < 1e-15);
+
+        // A correlation that doesn't fit the assets is rejected
+        let mut truncated = correlated.clone();
+        truncated.gamma_corr.as_mut().unwrap().pop();
+        assert!(matches!(
+            truncated.rebuild_matrices(),
+            Err(convexfx_types::ConvexFxError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_ultra_low_slippage_knobs() {
+        let params = RiskParams::ultra_low_slippage();
+
+        // Cheap JPY (small USD notional) gets a heavier weight than EUR
+        let mut y_ref = BTreeMap::new();
+        for asset in AssetId::all() {
+            y_ref.insert(*asset, 0.0);
+        }
+        y_ref.insert(AssetId::EUR, 1.1_f64.ln());
+        y_ref.insert(AssetId::JPY, 0.0067_f64.ln());
+        let w = params.tracking_weights(&y_ref);
+        assert!(w[AssetId::JPY.index()] > w[AssetId::EUR.index()]);
+        assert_eq!(RiskParams::default_demo().tracking_weights(&y_ref), RiskParams::default_demo().w_diag);
+
+        // Ghost cushion relaxes the hard bounds by 5% of target
+        assert!((params.soft_max_bound(AssetId::EUR) - 15.5).abs() < 1e-12);
+        assert!((params.soft_min_bound(AssetId::EUR) - 4.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_hysteresis_damping() {
+        // Bounds 5..15, so a 0.2 band is 2 units inside each edge
+        let hard = RiskParams::default_demo();
+        let params = RiskParams::default_demo().with_hysteresis(0.2);
+
+        assert_eq!(hard.hysteresis_damping(AssetId::EUR, 5.5, false), 1.0);
+        assert_eq!(params.hysteresis_damping(AssetId::EUR, 10.0, false), 1.0);
+        assert!((params.hysteresis_damping(AssetId::EUR, 6.0, false) - 0.5).abs() < 1e-12);
+        assert!((params.hysteresis_damping(AssetId::EUR, 14.5, true) - 0.25).abs() < 1e-12);
+        assert_eq!(params.hysteresis_damping(AssetId::EUR, 4.0, false), 0.0);
+
+        // Moving away from a bound is never damped
+        assert_eq!(params.hysteresis_damping(AssetId::EUR, 6.0, true), 1.0);
+    }
 }
@@ -1,4 +1,5 @@
 use convexfx_types::{AccountId, Amount, AssetId, PairOrder};
+use std::collections::BTreeMap;
 use crate::scenario::{OrderFlowPattern, ScenarioConfig};
 
 /// Order generator for simulations with various flow patterns
@@ -31,6 +32,8 @@ impl OrderGenerator {
             budget: Amount::from_units(budget_units),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         }
     }
@@ -51,7 +54,21 @@ impl OrderGenerator {
     }
     
     /// Generate orders based on scenario configuration
+    ///
+    /// Inventory-reactive patterns see a pool sitting exactly on target.
     pub fn generate_orders(&self, config: &ScenarioConfig, epoch_id: u64) -> Vec<PairOrder> {
+        self.generate_orders_for_inventory(config, epoch_id, &BTreeMap::new(), &BTreeMap::new())
+    }
+
+    /// Generate orders based on scenario configuration and the pool's
+    /// current inventory, which `MeanReverting` flow reacts to
+    pub fn generate_orders_for_inventory(
+        &self,
+        config: &ScenarioConfig,
+        epoch_id: u64,
+        inventory: &BTreeMap<AssetId, f64>,
+        target: &BTreeMap<AssetId, f64>,
+    ) -> Vec<PairOrder> {
         // Use seed + epoch for reproducibility
         let mut rng = SimpleRng::new(self.seed + epoch_id);
         
@@ -78,6 +95,9 @@ impl OrderGenerator {
             OrderFlowPattern::Basket { weights } => {
                 self.generate_basket_orders(config, weights, &mut rng)
             }
+            OrderFlowPattern::MeanReverting { strength } => {
+                self.generate_mean_reverting_orders(config, *strength, inventory, target, &mut rng)
+            }
         };
 
         // Apply limits and min-fill based on config
@@ -122,6 +142,8 @@ impl OrderGenerator {
                 budget,
                 limit_ratio: None,
                 min_fill_fraction: None,
+                max_slippage_bps: None,
+                submission_mid: None,
                 metadata: serde_json::json!({"type": "uniform"}),
             });
         }
@@ -163,6 +185,8 @@ impl OrderGenerator {
                 budget,
                 limit_ratio: None,
                 min_fill_fraction: None,
+                max_slippage_bps: None,
+                submission_mid: None,
                 metadata: serde_json::json!({"type": "concentrated_buy"}),
             });
         }
@@ -187,6 +211,8 @@ impl OrderGenerator {
                 budget,
                 limit_ratio: None,
                 min_fill_fraction: None,
+                max_slippage_bps: None,
+                submission_mid: None,
                 metadata: serde_json::json!({"type": "random"}),
             });
         }
@@ -229,6 +255,8 @@ impl OrderGenerator {
                 budget,
                 limit_ratio: None,
                 min_fill_fraction: None,
+                max_slippage_bps: None,
+                submission_mid: None,
                 metadata: serde_json::json!({"type": "biased"}),
             });
         }
@@ -252,6 +280,8 @@ impl OrderGenerator {
                 budget,
                 limit_ratio: None,
                 min_fill_fraction: None,
+                max_slippage_bps: None,
+                submission_mid: None,
                 metadata: serde_json::json!({"type": "random"}),
             });
         }
@@ -291,6 +321,8 @@ impl OrderGenerator {
                 budget,
                 limit_ratio: None,
                 min_fill_fraction: None,
+                max_slippage_bps: None,
+                submission_mid: None,
                 metadata: serde_json::json!({"type": "basket"}),
             });
         }
@@ -298,6 +330,64 @@ impl OrderGenerator {
         orders
     }
     
+    /// Generate flow that pushes inventory back toward target
+    ///
+    /// Each asset's relative imbalance b = (q - q*) / q* weights pair
+    /// selection: receive assets are drawn with weight 1 + strength * max(b, 0)
+    /// and pay assets with 1 + strength * max(-b, 0), so a pool long EUR sees
+    /// more EUR buyers and a pool short USD more USD payers.
+    fn generate_mean_reverting_orders(
+        &self,
+        config: &ScenarioConfig,
+        strength: f64,
+        inventory: &BTreeMap<AssetId, f64>,
+        target: &BTreeMap<AssetId, f64>,
+        rng: &mut SimpleRng,
+    ) -> Vec<PairOrder> {
+        let assets = AssetId::all();
+        let imbalance: Vec<f64> = assets
+            .iter()
+            .map(|asset| {
+                let q_target = target.get(asset).copied().unwrap_or(0.0);
+                if q_target.abs() < 1e-10 {
+                    return 0.0;
+                }
+                let q = inventory.get(asset).copied().unwrap_or(q_target);
+                (q - q_target) / q_target
+            })
+            .collect();
+        let strength = strength.max(0.0);
+        let receive_weights: Vec<f64> = imbalance.iter().map(|b| 1.0 + strength * b.max(0.0)).collect();
+        let pay_weights: Vec<f64> = imbalance.iter().map(|b| 1.0 + strength * (-b).max(0.0)).collect();
+
+        let mut orders = Vec::new();
+
+        for i in 0..config.num_orders {
+            let receive_idx = rng.next_weighted(&receive_weights);
+            let mut pay_idx = rng.next_weighted(&pay_weights);
+            while pay_idx == receive_idx {
+                pay_idx = rng.next_weighted(&pay_weights);
+            }
+
+            let budget = self.sample_budget(config, rng);
+
+            orders.push(PairOrder {
+                id: format!("order_{}", i),
+                trader: AccountId::new(format!("trader_{}", i % 50)),
+                pay: assets[pay_idx],
+                receive: assets[receive_idx],
+                budget,
+                limit_ratio: None,
+                min_fill_fraction: None,
+                max_slippage_bps: None,
+                submission_mid: None,
+                metadata: serde_json::json!({"type": "mean_reverting"}),
+            });
+        }
+
+        orders
+    }
+
     /// Sample budget from configured range
     fn sample_budget(&self, config: &ScenarioConfig, rng: &mut SimpleRng) -> Amount {
         let (min_m, max_m) = config.budget_range_m;
@@ -383,6 +473,19 @@ impl SimpleRng {
         }
         (self.next() as usize) % max
     }
+
+    /// Index drawn with probability proportional to its (positive) weight
+    fn next_weighted(&mut self, weights: &[f64]) -> usize {
+        let total: f64 = weights.iter().sum();
+        let mut draw = self.next_f64() * total;
+        for (i, w) in weights.iter().enumerate() {
+            if draw < *w {
+                return i;
+            }
+            draw -= w;
+        }
+        weights.len().saturating_sub(1)
+    }
 }
 
 #[cfg(test)]
@@ -3,12 +3,14 @@ mod generator;
 mod testbed;
 mod kpi;
 mod runner;
+mod trace;
 
 pub use scenario::{Scenario, ScenarioConfig, OrderFlowPattern, ExpectedOutcomes};
 pub use generator::OrderGenerator;
 pub use testbed::Testbed;
 pub use kpi::{EpochKPIs, KpiCalculator};
 pub use runner::{SimRunner, SimResult};
+pub use trace::{EpochTrace, ScenarioTrace};
 
 #[cfg(test)]
 mod tests;
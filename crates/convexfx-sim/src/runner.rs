@@ -1,17 +1,69 @@
-use crate::{EpochKPIs, KpiCalculator, Scenario};
+use crate::{EpochKPIs, EpochTrace, KpiCalculator, Scenario, ScenarioConfig, ScenarioTrace};
 use convexfx_clearing::{EpochInstance, ScpClearing};
 use convexfx_oracle::{MockOracle, Oracle};
-use convexfx_types::PairOrder;
+use convexfx_types::{AssetId, Fill, PairOrder};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
 use std::time::Instant;
 
 /// Result of a simulation run
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimResult {
     pub scenario_name: String,
+    /// Order generator seed the run used
+    #[serde(default)]
+    pub seed: u64,
     pub epochs: Vec<EpochResult>,
     pub summary: SimSummary,
+    /// Recorded epoch inputs, when the runner records traces
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<ScenarioTrace>,
+    /// Whether the run reached a steady state, for `run_until_stable` runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stabilized: Option<bool>,
+}
+
+/// Header row written by `SimResult::to_csv_string`
+const CSV_HEADER: &str = "epoch_id,fill_rate,slippage_bps_p50,slippage_bps_p90,slippage_bps_p99,slippage_bps_vwap,coherence_error_bps,iterations,runtime_ms";
+
+impl SimResult {
+    /// Render per-epoch KPIs as CSV, one row per epoch
+    pub fn to_csv_string(&self) -> String {
+        let mut out = String::new();
+        out.push_str(CSV_HEADER);
+        out.push('\n');
+        for epoch in &self.epochs {
+            let k = &epoch.kpis;
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{},{},{},{}",
+                epoch.epoch_id,
+                k.fill_rate,
+                k.slippage_bps_p50,
+                k.slippage_bps_p90,
+                k.slippage_bps_p99,
+                k.slippage_bps_vwap,
+                k.coherence_error_max_bps,
+                k.scp_iterations,
+                epoch.runtime_ms,
+            );
+        }
+        out
+    }
+
+    /// Inventory utilization of `asset` at the end of each epoch, in order
+    pub fn inventory_utilization_series(&self, asset: AssetId) -> Vec<f64> {
+        let kpis: Vec<&EpochKPIs> = self.epochs.iter().map(|e| &e.kpis).collect();
+        KpiCalculator::inventory_utilization_series(&kpis, asset)
+    }
+
+    /// Write per-epoch KPIs to a CSV file
+    pub fn to_csv(&self, path: &Path) -> io::Result<()> {
+        std::fs::write(path, self.to_csv_string())
+    }
 }
 
 /// Result of a single epoch
@@ -20,6 +72,11 @@ pub struct EpochResult {
     pub epoch_id: u64,
     pub kpis: EpochKPIs,
     pub num_orders: usize,
+    #[serde(default)]
+    pub fills: Vec<Fill>,
+    /// Post-trade inventory at the end of the epoch
+    #[serde(default)]
+    pub inventory_post: BTreeMap<AssetId, f64>,
     pub runtime_ms: f64,
 }
 
@@ -31,6 +88,10 @@ pub struct SimSummary {
     pub avg_slippage_p90_bps: f64,
     pub max_coherence_error_bps: f64,
     pub avg_iterations: f64,
+    /// Average SCP iterations weighted by each epoch's order count, so large
+    /// batches outweigh trivially converging small ones
+    #[serde(default)]
+    pub weighted_avg_iterations: f64,
     pub total_runtime_ms: f64,
     pub passed: bool,
     pub failure_reasons: Vec<String>,
@@ -39,6 +100,10 @@ pub struct SimSummary {
 /// Simulation runner
 pub struct SimRunner {
     clearing: ScpClearing,
+    /// Overrides the scenario's order generator seed when set
+    seed: Option<u64>,
+    /// Attach a `ScenarioTrace` of every epoch's inputs to results
+    record_trace: bool,
 }
 
 impl SimRunner {
@@ -51,26 +116,68 @@ impl SimRunner {
             // rates. Clarabel/OSQP delivers materially better objective values
             // and therefore more representative KPIs.
             clearing: ScpClearing::with_clarabel(),
+            seed: None,
+            record_trace: false,
+        }
+    }
+
+    /// Create a runner that generates orders from `seed` for every scenario,
+    /// ignoring the scenario's own seed
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            ..Self::new()
         }
     }
+
+    /// Clear epochs with `clearing` instead of the default Clarabel engine
+    pub fn with_clearing(mut self, clearing: ScpClearing) -> Self {
+        self.clearing = clearing;
+        self
+    }
+
+    /// Record each epoch's orders, inventory, reference prices and risk
+    /// parameters into `SimResult::trace` for replay with `run_trace`
+    pub fn with_trace_recording(mut self) -> Self {
+        self.record_trace = true;
+        self
+    }
     
     /// Run a scenario and collect KPIs
     pub fn run_scenario(&self, scenario: &Scenario) -> SimResult {
-        let _start_time = Instant::now();
+        self.run_epochs(scenario, scenario.config.num_epochs, None)
+    }
+    
+    /// Run a scenario until inventory settles, for at most `max_epochs`
+    ///
+    /// Stops after the first epoch whose largest per-asset inventory change
+    /// is below `tol` units and sets `SimResult::stabilized` accordingly.
+    /// Inventory is carried between epochs regardless of the scenario's
+    /// `carry_inventory` setting, since otherwise nothing can settle.
+    pub fn run_until_stable(&self, scenario: &Scenario, max_epochs: usize, tol: f64) -> SimResult {
+        let mut scenario = scenario.clone();
+        scenario.config.carry_inventory = true;
+        self.run_epochs(&scenario, max_epochs, Some(tol))
+    }
+    
+    /// Run up to `num_epochs` epochs, stopping early once the inventory
+    /// change falls below `stable_tol` when one is given
+    fn run_epochs(&self, scenario: &Scenario, num_epochs: usize, stable_tol: Option<f64>) -> SimResult {
         let mut epoch_results = Vec::new();
+        let mut stabilized = false;
+        let mut epoch_traces = Vec::new();
+        let seed = self.seed_for(scenario);
         
         // Setup oracle with testbed prices
-        let oracle = self.create_oracle(&scenario);
+        let oracle = self.create_oracle(scenario);
         
         // Initial inventory
         let mut current_inventory = scenario.testbed.initial_inventory.clone();
         
         // Run epochs
-        for epoch_id in 0..scenario.config.num_epochs as u64 {
-            let epoch_start = Instant::now();
-            
+        for epoch_id in 0..num_epochs as u64 {
             // Generate orders for this epoch
-            let orders = self.generate_orders(&scenario, epoch_id);
+            let orders = self.generate_orders(scenario, seed, epoch_id, &current_inventory);
             
             // Get reference prices
             let ref_prices = oracle.reference_prices(epoch_id).unwrap();
@@ -81,60 +188,143 @@ impl SimRunner {
             // Apply overrides
             if let Some(ref weights) = scenario.config.override_tracking_weights {
                 risk.w_diag = weights.clone();
-                risk.rebuild_matrices();
+                risk.rebuild_matrices().unwrap();
             }
             
-            // Create epoch instance
-            let instance = EpochInstance::new(
+            let epoch = EpochTrace {
                 epoch_id,
-                current_inventory.clone(),
-                orders.clone(),
-                ref_prices.clone(),
+                orders,
+                inventory: current_inventory.clone(),
+                ref_prices,
                 risk,
-            );
-            
-            // Clear epoch
-            let solution = match self.clearing.clear_epoch(&instance) {
-                Ok(sol) => sol,
-                Err(e) => {
-                    eprintln!("Clearing failed for epoch {}: {:?}", epoch_id, e);
-                    continue;
-                }
+            };
+            let result = self.run_epoch(&epoch, &scenario.testbed.target_inventory);
+            if self.record_trace {
+                epoch_traces.push(epoch);
+            }
+            let Some(result) = result else {
+                continue;
             };
             
-            // Calculate KPIs
-            let mut kpis = KpiCalculator::calculate_epoch_kpis(
-                &orders,
-                &solution,
-                &ref_prices,
-                &current_inventory,
-                &scenario.testbed.target_inventory,
-            );
-            
-            // Record runtime
-            kpis.qp_solve_time_ms = epoch_start.elapsed().as_millis() as f64;
+            let inventory_change = Self::max_inventory_change(&current_inventory, &result.inventory_post);
             
             // Update inventory for next epoch
-            current_inventory = solution.q_post.clone();
+            if scenario.config.carry_inventory {
+                current_inventory = result.inventory_post.clone();
+            }
             
-            epoch_results.push(EpochResult {
-                epoch_id,
-                kpis,
-                num_orders: orders.len(),
-                runtime_ms: epoch_start.elapsed().as_millis() as f64,
-            });
+            epoch_results.push(result);
+            
+            if stable_tol.is_some_and(|tol| inventory_change < tol) {
+                stabilized = true;
+                break;
+            }
         }
         
         // Calculate summary
-        let summary = self.calculate_summary(&scenario, &epoch_results);
+        let summary = self.calculate_summary(&scenario.config, &epoch_results);
         
         SimResult {
             scenario_name: scenario.config.name.clone(),
+            seed,
             epochs: epoch_results,
             summary,
+            trace: self.record_trace.then(|| ScenarioTrace {
+                config: scenario.config.clone(),
+                seed,
+                target_inventory: scenario.testbed.target_inventory.clone(),
+                epochs: epoch_traces,
+            }),
+            stabilized: stable_tol.map(|_| stabilized),
         }
     }
     
+    /// Largest absolute per-asset difference between two inventories
+    fn max_inventory_change(before: &BTreeMap<AssetId, f64>, after: &BTreeMap<AssetId, f64>) -> f64 {
+        before
+            .keys()
+            .chain(after.keys())
+            .map(|asset| {
+                let q0 = before.get(asset).copied().unwrap_or(0.0);
+                let q1 = after.get(asset).copied().unwrap_or(0.0);
+                (q1 - q0).abs()
+            })
+            .fold(0.0, f64::max)
+    }
+    
+    /// Replay recorded epoch inputs exactly, without the order generator
+    ///
+    /// Each epoch clears its recorded orders against its recorded inventory,
+    /// so a trace captured from a failing run reproduces the same fills.
+    pub fn run_trace(&self, trace: &ScenarioTrace) -> SimResult {
+        let epoch_results: Vec<EpochResult> = trace
+            .epochs
+            .iter()
+            .filter_map(|epoch| self.run_epoch(epoch, &trace.target_inventory))
+            .collect();
+        let summary = self.calculate_summary(&trace.config, &epoch_results);
+        
+        SimResult {
+            scenario_name: trace.config.name.clone(),
+            seed: trace.seed,
+            epochs: epoch_results,
+            summary,
+            trace: None,
+            stabilized: None,
+        }
+    }
+    
+    /// Clear one epoch's inputs and compute its KPIs
+    ///
+    /// Returns `None` (after logging) if clearing fails.
+    fn run_epoch(&self, epoch: &EpochTrace, target_inventory: &BTreeMap<AssetId, f64>) -> Option<EpochResult> {
+        let epoch_start = Instant::now();
+        
+        // Matrices are not serialized, so rebuild them for replayed traces
+        let mut risk = epoch.risk.clone();
+        if let Err(e) = risk.rebuild_matrices() {
+            eprintln!("Invalid risk parameters for epoch {}: {:?}", epoch.epoch_id, e);
+            return None;
+        }
+        
+        // Create epoch instance
+        let instance = EpochInstance::new(
+            epoch.epoch_id,
+            epoch.inventory.clone(),
+            epoch.orders.clone(),
+            epoch.ref_prices.clone(),
+            risk,
+        );
+        
+        // Clear epoch
+        let solution = match self.clearing.clear_epoch(&instance) {
+            Ok(sol) => sol,
+            Err(e) => {
+                eprintln!("Clearing failed for epoch {}: {:?}", epoch.epoch_id, e);
+                return None;
+            }
+        };
+        
+        // Calculate KPIs
+        let kpis = KpiCalculator::calculate_epoch_kpis(
+            &epoch.orders,
+            &solution,
+            &epoch.ref_prices,
+            &epoch.inventory,
+            target_inventory,
+            &instance.risk.q_max,
+        );
+        
+        Some(EpochResult {
+            epoch_id: epoch.epoch_id,
+            kpis,
+            num_orders: epoch.orders.len(),
+            fills: solution.fills,
+            inventory_post: solution.q_post,
+            runtime_ms: epoch_start.elapsed().as_millis() as f64,
+        })
+    }
+    
     /// Create oracle from testbed
     fn create_oracle(&self, scenario: &Scenario) -> MockOracle {
         let mut prices = BTreeMap::new();
@@ -145,19 +335,30 @@ impl SimRunner {
         MockOracle::with_prices(prices).with_band_bps(scenario.testbed.band_bps)
     }
     
-    /// Generate orders for an epoch based on scenario config
-    fn generate_orders(&self, scenario: &Scenario, epoch_id: u64) -> Vec<PairOrder> {
+    /// Order generator seed for a scenario: the runner's override, else the
+    /// scenario's own seed
+    fn seed_for(&self, scenario: &Scenario) -> u64 {
+        self.seed.or(scenario.config.seed).unwrap_or(0)
+    }
+    
+    /// Generate orders for an epoch based on scenario config and the
+    /// inventory the epoch starts from
+    fn generate_orders(
+        &self,
+        scenario: &Scenario,
+        seed: u64,
+        epoch_id: u64,
+        inventory: &BTreeMap<AssetId, f64>,
+    ) -> Vec<PairOrder> {
         use crate::generator::OrderGenerator;
         
-        let config = &scenario.config;
-        let seed = config.seed.unwrap_or(0);
         let gen = OrderGenerator::with_seed(seed);
         
-        gen.generate_orders(config, epoch_id)
+        gen.generate_orders_for_inventory(&scenario.config, epoch_id, inventory, &scenario.testbed.target_inventory)
     }
     
     /// Calculate summary statistics
-    fn calculate_summary(&self, scenario: &Scenario, epochs: &[EpochResult]) -> SimSummary {
+    fn calculate_summary(&self, config: &ScenarioConfig, epochs: &[EpochResult]) -> SimSummary {
         if epochs.is_empty() {
             return SimSummary {
                 total_epochs: 0,
@@ -165,6 +366,7 @@ impl SimRunner {
                 avg_slippage_p90_bps: 0.0,
                 max_coherence_error_bps: 0.0,
                 avg_iterations: 0.0,
+                weighted_avg_iterations: 0.0,
                 total_runtime_ms: 0.0,
                 passed: false,
                 failure_reasons: vec!["No epochs executed".to_string()],
@@ -183,13 +385,21 @@ impl SimRunner {
         
         let max_coherence_error_bps = epochs.iter()
             .map(|e| e.kpis.coherence_error_max_bps)
-            .max_by(|a, b| a.partial_cmp(b).unwrap())
-            .unwrap_or(0.0);
+            .fold(0.0, f64::max);
         
         let avg_iterations = epochs.iter()
             .map(|e| e.kpis.scp_iterations as f64)
             .sum::<f64>() / n;
         
+        let total_orders: usize = epochs.iter().map(|e| e.num_orders).sum();
+        let weighted_avg_iterations = if total_orders == 0 {
+            avg_iterations
+        } else {
+            epochs.iter()
+                .map(|e| e.kpis.scp_iterations as f64 * e.num_orders as f64)
+                .sum::<f64>() / total_orders as f64
+        };
+        
         let total_runtime_ms = epochs.iter()
             .map(|e| e.runtime_ms)
             .sum::<f64>();
@@ -197,7 +407,7 @@ impl SimRunner {
         // Check expected outcomes
         let mut failure_reasons = Vec::new();
         
-        if let Some(ref expected) = scenario.config.expected_outcomes {
+        if let Some(ref expected) = config.expected_outcomes {
             if let Some(max_iter) = expected.max_iterations {
                 let max_actual = epochs.iter()
                     .map(|e| e.kpis.scp_iterations)
@@ -246,6 +456,7 @@ impl SimRunner {
             avg_slippage_p90_bps,
             max_coherence_error_bps,
             avg_iterations,
+            weighted_avg_iterations,
             total_runtime_ms,
             passed: failure_reasons.is_empty(),
             failure_reasons,
@@ -287,5 +498,155 @@ mod tests {
         // Empty epoch should pass all checks
         assert!(result.summary.passed, "Empty epoch scenario should pass");
     }
-}
 
+    #[test]
+    fn test_balanced_flow_runs_until_stable() {
+        let runner = SimRunner::new();
+        let scenario = Scenario::balanced_flow();
+        let max_epochs = 20;
+
+        // Balanced flow settles once the opening JPY rebalancing has played
+        // out, leaving only per-epoch order noise well under 20 units
+        let result = runner.run_until_stable(&scenario, max_epochs, 20.0);
+        assert_eq!(result.stabilized, Some(true));
+        assert!(result.epochs.len() < max_epochs / 2, "took {} epochs", result.epochs.len());
+
+        // Fixed-length runs don't report stability
+        assert!(runner.run_scenario(&scenario).stabilized.is_none());
+    }
+
+    #[test]
+    fn test_buy_wall_utilization_series() {
+        let runner = SimRunner::new();
+        let mut scenario = Scenario::eur_buy_wall();
+        scenario.config.num_epochs = 4;
+        scenario.config.num_orders = 10;
+        scenario.config.flow_pattern = crate::OrderFlowPattern::OneSided {
+            asset: "EUR".to_string(),
+            concentration_pct: 100.0,
+        };
+        let result = runner.run_scenario(&scenario);
+
+        let eur = result.inventory_utilization_series(AssetId::EUR);
+        assert_eq!(eur.len(), 4);
+        for window in eur.windows(2) {
+            assert!(window[1] >= window[0], "EUR utilization should not fall: {:?}", eur);
+        }
+        assert!(eur[3] > eur[0], "EUR utilization should grow: {:?}", eur);
+
+        // Every asset, including AUD, gets a point per epoch
+        let aud = result.inventory_utilization_series(AssetId::AUD);
+        assert_eq!(aud.len(), 4);
+        assert!(aud.iter().all(|u| (0.0..=1.0).contains(u)));
+    }
+
+    #[test]
+    fn test_trace_replay_reproduces_fills() {
+        let mut scenario = Scenario::eur_buy_wall();
+        scenario.config.num_epochs = 3;
+        scenario.config.num_orders = 15;
+        let recorded = SimRunner::new().with_trace_recording().run_scenario(&scenario);
+        let trace = recorded.trace.clone().expect("runner records a trace");
+        assert_eq!(trace.epochs.len(), 3);
+        assert!(trace.epochs.iter().all(|e| e.orders.len() == 15));
+
+        // Replay from JSON, with a different seed to show the generator is unused
+        let trace = ScenarioTrace::from_json(&trace.to_json().unwrap()).unwrap();
+        let replayed = SimRunner::with_seed(7).run_trace(&trace);
+
+        assert_eq!(replayed.seed, recorded.seed);
+        assert_eq!(replayed.epochs.len(), recorded.epochs.len());
+        for (a, b) in recorded.epochs.iter().zip(&replayed.epochs) {
+            assert_eq!(a.fills.len(), b.fills.len());
+            for (fa, fb) in a.fills.iter().zip(&b.fills) {
+                assert_eq!(fa.order_id, fb.order_id);
+                assert_eq!(fa.fill_frac, fb.fill_frac);
+                assert_eq!(fa.pay_units, fb.pay_units);
+                assert_eq!(fa.recv_units, fb.recv_units);
+            }
+            assert_eq!(a.inventory_post, b.inventory_post);
+        }
+
+        // Traces are only attached when requested
+        assert!(SimRunner::new().run_scenario(&scenario).trace.is_none());
+    }
+
+    #[test]
+    fn test_sim_result_csv_roundtrip() {
+        let runner = SimRunner::new();
+        let mut scenario = Scenario::empty_epoch();
+        scenario.config.num_epochs = 3;
+        let result = runner.run_scenario(&scenario);
+
+        let path = std::env::temp_dir().join(format!("convexfx_sim_{}.csv", std::process::id()));
+        result.to_csv(&path).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(contents, result.to_csv_string());
+
+        let mut lines = contents.lines();
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(header.len(), 9);
+        assert_eq!(header[0], "epoch_id");
+        assert_eq!(header[1], "fill_rate");
+
+        let rows: Vec<Vec<&str>> = lines.map(|l| l.split(',').collect()).collect();
+        assert_eq!(rows.len(), result.epochs.len());
+        for (row, epoch) in rows.iter().zip(&result.epochs) {
+            assert_eq!(row.len(), header.len());
+            assert_eq!(row[0].parse::<u64>().unwrap(), epoch.epoch_id);
+            assert_eq!(row[1].parse::<f64>().unwrap(), epoch.kpis.fill_rate);
+            assert_eq!(row[7].parse::<usize>().unwrap(), epoch.kpis.scp_iterations);
+            assert_eq!(row[8].parse::<f64>().unwrap(), epoch.runtime_ms);
+        }
+    }
+
+    #[test]
+    fn test_weighted_avg_iterations_favors_large_batches() {
+        let runner = SimRunner::new();
+        let scenario = Scenario::empty_epoch();
+
+        let epoch = |epoch_id: u64, num_orders: usize, iterations: usize| EpochResult {
+            epoch_id,
+            kpis: EpochKPIs {
+                scp_iterations: iterations,
+                ..EpochKPIs::default()
+            },
+            num_orders,
+            fills: Vec::new(),
+            inventory_post: BTreeMap::new(),
+            runtime_ms: 0.0,
+        };
+
+        // Three tiny batches converge at once, one large batch needs work
+        let epochs = vec![epoch(0, 1, 1), epoch(1, 2, 1), epoch(2, 1, 1), epoch(3, 200, 6)];
+        let summary = runner.calculate_summary(&scenario.config, &epochs);
+
+        assert!((summary.avg_iterations - 2.25).abs() < 1e-12);
+        assert!((summary.weighted_avg_iterations - 1204.0 / 204.0).abs() < 1e-12);
+        assert!(summary.weighted_avg_iterations > summary.avg_iterations);
+    }
+
+    #[test]
+    fn test_same_seed_reproduces_fills() {
+        let mut scenario = Scenario::balanced_flow();
+        scenario.config.num_epochs = 2;
+        scenario.config.num_orders = 8;
+
+        let first = SimRunner::with_seed(7).run_scenario(&scenario);
+        let second = SimRunner::with_seed(7).run_scenario(&scenario);
+        assert_eq!(first.seed, 7);
+
+        let fills = |result: &SimResult| {
+            let fills: Vec<&Fill> = result.epochs.iter().flat_map(|e| &e.fills).collect();
+            serde_json::to_vec(&fills).unwrap()
+        };
+        assert!(!first.epochs.iter().all(|e| e.fills.is_empty()));
+        assert_eq!(fills(&first), fills(&second));
+
+        // Without an override the scenario's own seed is used and recorded
+        let default_run = SimRunner::new().run_scenario(&scenario);
+        assert_eq!(Some(default_run.seed), scenario.config.seed);
+    }
+}
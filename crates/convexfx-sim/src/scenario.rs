@@ -25,6 +25,14 @@ pub enum OrderFlowPattern {
         /// Basket weights per asset
         weights: Vec<(String, f64)>,
     },
+    /// Arbitrage-style flow that trades the pool back toward target:
+    /// traders favour receiving assets the pool is long and paying assets
+    /// it is short
+    MeanReverting {
+        /// How strongly inventory imbalance skews pair selection
+        /// (0 = uniform)
+        strength: f64,
+    },
 }
 
 /// Scenario configuration
@@ -63,6 +71,11 @@ pub struct ScenarioConfig {
     /// Random seed for reproducibility
     pub seed: Option<u64>,
     
+    /// Start each epoch from the previous epoch's post-trade inventory
+    /// (false resets every epoch to the testbed's initial inventory)
+    #[serde(default = "default_carry_inventory")]
+    pub carry_inventory: bool,
+    
     /// Expected outcomes for validation
     pub expected_outcomes: Option<ExpectedOutcomes>,
 }
@@ -104,11 +117,16 @@ impl Default for ScenarioConfig {
             override_tracking_weights: None,
             override_band_bps: None,
             seed: Some(42),
+            carry_inventory: true,
             expected_outcomes: None,
         }
     }
 }
 
+fn default_carry_inventory() -> bool {
+    true
+}
+
 /// Scenario for simulation
 #[derive(Debug, Clone)]
 pub struct Scenario {
@@ -128,6 +146,36 @@ impl Scenario {
         }
     }
     
+    /// Look up a preset scenario by its short name, e.g. `balanced` or
+    /// `eur-wall`; see `PRESET_NAMES`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "empty" => Some(Self::empty_epoch()),
+            "balanced" => Some(Self::balanced_flow()),
+            "eur-wall" => Some(Self::eur_buy_wall()),
+            "gbp-sell" => Some(Self::gbp_sell_limits()),
+            "price-discovery" => Some(Self::price_discovery()),
+            "stress" => Some(Self::high_frequency_stress()),
+            "basket" => Some(Self::basket_trading()),
+            "bilateral" => Some(Self::bilateral_trading()),
+            "moderate-slippage" => Some(Self::moderate_slippage_trading()),
+            _ => None,
+        }
+    }
+
+    /// Short names accepted by `from_name`
+    pub const PRESET_NAMES: &'static [&'static str] = &[
+        "empty",
+        "balanced",
+        "eur-wall",
+        "gbp-sell",
+        "price-discovery",
+        "stress",
+        "basket",
+        "bilateral",
+        "moderate-slippage",
+    ];
+
     /// Scenario A: Empty epoch
     pub fn empty_epoch() -> Self {
         Self::new(
@@ -190,7 +190,7 @@ impl Testbed {
         risk.gamma_diag = d_sigma.iter().map(|s| self.risk_lambda * s * s).collect();
         
         // Rebuild matrices
-        risk.rebuild_matrices();
+        risk.rebuild_matrices().expect("testbed risk sets no correlation to mismatch");
         
         risk
     }
@@ -9,6 +9,131 @@ mod tests {
         let scenario = Scenario::default_scenario();
         assert_eq!(scenario.config.name, "default");
     }
-}
 
+    #[test]
+    fn test_ultra_low_slippage_beats_default_p90() {
+        use convexfx_clearing::{EpochInstance, ScpClearing};
+        use convexfx_oracle::{MockOracle, Oracle};
+        use convexfx_risk::RiskParams;
+        use convexfx_types::{AccountId, Amount, AssetId, PairOrder};
+
+        let testbed = Testbed::standard_5_asset();
+        let ref_prices = MockOracle::with_prices(testbed.oracle_mids.clone())
+            .reference_prices(0)
+            .unwrap();
+
+        // USD buyers of every other asset with limits 0-80 bps through the mid
+        let targets: Vec<AssetId> = AssetId::all().iter().copied().filter(|a| *a != AssetId::USD).collect();
+        let orders: Vec<PairOrder> = (0..60)
+            .map(|i| {
+                let receive = targets[i % targets.len()];
+                let mid_ratio = (ref_prices.get_ref(receive) - ref_prices.get_ref(AssetId::USD)).exp();
+                let through_bps = ((i * 13) % 80) as f64;
+                PairOrder {
+                    id: format!("order_{}", i),
+                    trader: AccountId::new(format!("trader_{}", i)),
+                    pay: AssetId::USD,
+                    receive,
+                    budget: Amount::from_f64(0.5 + (i % 5) as f64 * 0.25).unwrap(),
+                    limit_ratio: Some(mid_ratio * (1.0 - through_bps / 10_000.0)),
+                    min_fill_fraction: None,
+                    max_slippage_bps: None,
+                    submission_mid: None,
+                    metadata: serde_json::json!({}),
+                }
+            })
+            .collect();
+
+        let clearing = ScpClearing::with_clarabel();
+        let p90 = |risk: RiskParams| {
+            let inst = EpochInstance::new(
+                0,
+                testbed.initial_inventory.clone(),
+                orders.clone(),
+                ref_prices.clone(),
+                risk,
+            );
+            let solution = clearing.clear_epoch(&inst).unwrap();
+            KpiCalculator::calculate_epoch_kpis(
+                &orders,
+                &solution,
+                &ref_prices,
+                &testbed.initial_inventory,
+                &testbed.target_inventory,
+                &inst.risk.q_max,
+            )
+            .slippage_bps_p90
+        };
+
+        let default_p90 = p90(RiskParams::default_demo());
+        let ultra_p90 = p90(RiskParams::ultra_low_slippage());
+        println!("slippage p90: default {:.3} bps, ultra {:.3} bps", default_p90, ultra_p90);
+        assert!(ultra_p90 < default_p90);
+    }
+
+    #[test]
+    fn test_inventory_carries_across_epochs() {
+        use convexfx_types::AssetId;
+
+        let mut scenario = Scenario::eur_buy_wall();
+        scenario.config.num_orders = 10;
+        scenario.config.num_epochs = 3;
+        scenario.config.flow_pattern = OrderFlowPattern::OneSided {
+            asset: "EUR".to_string(),
+            concentration_pct: 100.0,
+        };
+        let initial_eur = scenario.testbed.initial_inventory[&AssetId::EUR];
 
+        // Each epoch starts where the last one ended, so EUR keeps draining
+        let carried = SimRunner::new().run_scenario(&scenario);
+        assert_eq!(carried.epochs.len(), 3);
+        let mut last_eur = initial_eur;
+        for epoch in &carried.epochs {
+            let eur = epoch.inventory_post[&AssetId::EUR];
+            assert!(eur < last_eur, "epoch {}: EUR {} not below {}", epoch.epoch_id, eur, last_eur);
+            last_eur = eur;
+        }
+
+        // Without carryover every epoch drains from the initial inventory
+        scenario.config.carry_inventory = false;
+        let reset = SimRunner::new().run_scenario(&scenario);
+        for epoch in &reset.epochs {
+            assert!(epoch.inventory_post[&AssetId::EUR] > last_eur);
+        }
+    }
+
+    #[test]
+    fn test_mean_reverting_flow_reduces_imbalance() {
+        use convexfx_types::AssetId;
+        use std::collections::BTreeMap;
+
+        // The pool starts 10% long EUR and 10% short USD
+        let mut scenario = Scenario::balanced_flow();
+        scenario.config.num_epochs = 6;
+        scenario.config.expected_outcomes = None;
+        let target = scenario.testbed.target_inventory.clone();
+        scenario.testbed.initial_inventory.insert(AssetId::EUR, target[&AssetId::EUR] * 1.1);
+        scenario.testbed.initial_inventory.insert(AssetId::USD, target[&AssetId::USD] * 0.9);
+
+        // Total relative distance from target, and EUR's alone
+        let imbalance = |inventory: &BTreeMap<AssetId, f64>| -> (f64, f64) {
+            let b = |asset: &AssetId| ((inventory[asset] - target[asset]) / target[asset]).abs();
+            (target.keys().map(b).sum(), b(&AssetId::EUR))
+        };
+        let final_imbalance = |pattern: OrderFlowPattern| {
+            let mut scenario = scenario.clone();
+            scenario.config.flow_pattern = pattern;
+            let result = SimRunner::new().run_scenario(&scenario);
+            assert_eq!(result.epochs.len(), 6);
+            imbalance(&result.epochs.last().unwrap().inventory_post)
+        };
+
+        let start = imbalance(&scenario.testbed.initial_inventory);
+        let uniform = final_imbalance(OrderFlowPattern::Uniform);
+        let reverting = final_imbalance(OrderFlowPattern::MeanReverting { strength: 20.0 });
+        println!("imbalance (total, EUR): start {:?}, uniform {:?}, mean-reverting {:?}", start, uniform, reverting);
+        assert!(reverting.0 < uniform.0);
+        assert!(reverting.1 < uniform.1);
+        assert!(reverting.1 < start.1);
+    }
+}
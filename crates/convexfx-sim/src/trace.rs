@@ -0,0 +1,49 @@
+use convexfx_oracle::RefPrices;
+use convexfx_risk::RiskParams;
+use convexfx_types::{AssetId, PairOrder};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::ScenarioConfig;
+
+/// Clearing inputs recorded for one epoch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochTrace {
+    pub epoch_id: u64,
+    /// Orders the generator produced
+    pub orders: Vec<PairOrder>,
+    /// Inventory the epoch started from
+    pub inventory: BTreeMap<AssetId, f64>,
+    pub ref_prices: RefPrices,
+    /// Risk parameters after scenario overrides (matrices are rebuilt on replay)
+    pub risk: RiskParams,
+}
+
+/// Everything needed to replay a scenario run without the order generator
+///
+/// Recorded by a runner built with `SimRunner::with_trace_recording` and
+/// replayed with `SimRunner::run_trace`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioTrace {
+    pub config: ScenarioConfig,
+    /// Order generator seed of the recorded run
+    pub seed: u64,
+    /// Target inventory used for KPIs
+    pub target_inventory: BTreeMap<AssetId, f64>,
+    pub epochs: Vec<EpochTrace>,
+}
+
+impl ScenarioTrace {
+    /// Serialize to pretty-printed JSON
+    ///
+    /// Floats round-trip exactly, so a replay from JSON clears the same
+    /// inputs bit for bit.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Parse a trace written by `to_json`
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
@@ -1,7 +1,7 @@
 use convexfx_types::Result;
 use serde::{Deserialize, Serialize};
 
-use crate::qp_model::QpModel;
+use crate::qp_model::{BindingConstraint, QpModel};
 
 /// QP solver status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -11,6 +11,14 @@ pub enum QpStatus {
     DualInfeasible,
     MaxIterations,
     Unsolved,
+    /// No QP was solved (e.g. an epoch with no orders)
+    Skipped,
+}
+
+impl std::fmt::Display for QpStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
 }
 
 /// Solution from QP solver
@@ -20,13 +28,24 @@ pub struct QpSolution {
     pub status: QpStatus,
     pub objective: f64,
     pub iterations: usize,
+    /// Constraints active at `x`
+    #[serde(default)]
+    pub binding: Vec<BindingConstraint>,
 }
 
+/// Relative slack below which a constraint counts as binding
+pub const BINDING_TOLERANCE: f64 = 1e-6;
+
 /// Trait for QP solver backends
 pub trait SolverBackend: Send + Sync {
     /// Solve a QP problem: minimize 0.5 * x^T P x + q^T x
     /// subject to l <= A x <= u
     fn solve_qp(&self, model: &QpModel) -> Result<QpSolution>;
+
+    /// Short backend name reported by health and status endpoints
+    fn name(&self) -> &'static str {
+        "unknown"
+    }
 }
 
 
@@ -0,0 +1,155 @@
+use convexfx_types::{ConvexFxError, Result};
+
+use crate::backend::{QpSolution, QpStatus, SolverBackend, BINDING_TOLERANCE};
+use crate::qp_model::{ConstraintMeta, QpModel, VarMeta};
+
+/// Slack allowed on a row before the closed-form point counts as infeasible
+const FEASIBILITY_TOLERANCE: f64 = 1e-9;
+
+/// Closed-form solver for single-order clearing QPs (for tests)
+///
+/// Holds every log-price at the centre of its price band, i.e. the oracle
+/// rate, and fills the lone order as far as the remaining constraints allow
+/// at those prices. For a single order this is the QP optimum, so tests get
+/// exact fills without running an iterative solver. Models with more than
+/// one fill variable are rejected.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeterministicSolver;
+
+impl DeterministicSolver {
+    pub fn new() -> Self {
+        DeterministicSolver
+    }
+
+    /// Log-prices pinned at their band centres, numeraire at its fixed value
+    fn oracle_prices(model: &QpModel, x: &mut [f64]) -> Result<()> {
+        for (row, meta) in model.constraint_meta.iter().enumerate() {
+            if let ConstraintMeta::PriceBand(_) = meta {
+                x[Self::single_column(model, row)?] = 0.5 * (model.l[row] + model.u[row]);
+            }
+        }
+        // The numeraire row overrides its band centre
+        for (row, meta) in model.constraint_meta.iter().enumerate() {
+            if *meta == ConstraintMeta::Numeraire {
+                x[Self::single_column(model, row)?] = model.l[row];
+            }
+        }
+        for (col, meta) in model.var_meta.iter().enumerate() {
+            if let VarMeta::CrossRate(i, j) = meta {
+                let y = |asset| {
+                    model
+                        .var_meta
+                        .iter()
+                        .position(|m| matches!(m, VarMeta::LogPrice(a) if *a == asset))
+                        .map(|idx| x[idx])
+                        .unwrap_or(0.0)
+                };
+                let cross_rate = y(*i) - y(*j);
+                x[col] = cross_rate;
+            }
+        }
+        Ok(())
+    }
+
+    /// Column of the single variable a price row constrains
+    fn single_column(model: &QpModel, row: usize) -> Result<usize> {
+        let a_row = model.a.row(row);
+        let mut cols = a_row.iter().enumerate().filter(|(_, v)| v.abs() > 0.0);
+        match (cols.next(), cols.next()) {
+            (Some((col, _)), None) => Ok(col),
+            _ => Err(ConvexFxError::SolverError(format!(
+                "price row {} does not constrain a single variable",
+                row
+            ))),
+        }
+    }
+
+    /// Objective 0.5 * x^T P x + q^T x
+    fn objective(model: &QpModel, x: &[f64]) -> f64 {
+        let x = nalgebra::DVector::from_column_slice(x);
+        0.5 * x.dot(&(&model.p * &x)) + model.q.dot(&x)
+    }
+}
+
+impl SolverBackend for DeterministicSolver {
+    fn solve_qp(&self, model: &QpModel) -> Result<QpSolution> {
+        model.validate()?;
+        if model.constraint_meta.len() != model.num_constraints() {
+            return Err(ConvexFxError::SolverError(
+                "deterministic solver needs labeled constraints".to_string(),
+            ));
+        }
+
+        let fills: Vec<usize> = model
+            .var_meta
+            .iter()
+            .enumerate()
+            .filter(|(_, meta)| matches!(meta, VarMeta::FillFraction(_)))
+            .map(|(col, _)| col)
+            .collect();
+        if fills.len() > 1 {
+            return Err(ConvexFxError::SolverError(format!(
+                "deterministic solver handles at most one order, got {}",
+                fills.len()
+            )));
+        }
+
+        let mut x = vec![0.0; model.num_vars()];
+        Self::oracle_prices(model, &mut x)?;
+
+        // Every row is linear in the fill once prices are fixed, so together
+        // they bound it to an interval
+        let (mut lo, mut hi) = (f64::NEG_INFINITY, f64::INFINITY);
+        let mut feasible = true;
+        for row in 0..model.num_constraints() {
+            let coeff = fills.first().map_or(0.0, |&k| model.a[(row, k)]);
+            let rest: f64 = (0..model.num_vars())
+                .filter(|col| Some(col) != fills.first())
+                .map(|col| model.a[(row, col)] * x[col])
+                .sum();
+            if coeff.abs() < 1e-12 {
+                feasible &= rest >= model.l[row] - FEASIBILITY_TOLERANCE
+                    && rest <= model.u[row] + FEASIBILITY_TOLERANCE;
+                continue;
+            }
+            let (a, b) = ((model.l[row] - rest) / coeff, (model.u[row] - rest) / coeff);
+            lo = lo.max(a.min(b));
+            hi = hi.min(a.max(b));
+        }
+        feasible &= lo <= hi + FEASIBILITY_TOLERANCE;
+
+        if let Some(&k) = fills.first() {
+            // Minimize the objective along the fill with prices held fixed
+            let cross: f64 = (0..model.num_vars())
+                .filter(|&col| col != k)
+                .map(|col| model.p[(k, col)] * x[col])
+                .sum();
+            let slope = model.q[k] + cross;
+            let curvature = model.p[(k, k)];
+            let unconstrained = if curvature > 0.0 {
+                -slope / curvature
+            } else if slope <= 0.0 {
+                f64::INFINITY
+            } else {
+                f64::NEG_INFINITY
+            };
+            x[k] = unconstrained.min(hi).max(lo.min(hi));
+            if !x[k].is_finite() {
+                x[k] = 0.0;
+                feasible = false;
+            }
+        }
+
+        Ok(QpSolution {
+            objective: Self::objective(model, &x),
+            status: if feasible { QpStatus::Optimal } else { QpStatus::PrimalInfeasible },
+            iterations: 1,
+            binding: model.binding_constraints(&x, BINDING_TOLERANCE),
+            x,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "deterministic"
+    }
+}
@@ -1,12 +1,18 @@
 mod qp_model;
 mod backend;
 mod simple_backend;
+mod clarabel_backend;
+mod deterministic_backend;
+#[cfg(feature = "osqp")]
 mod osqp_backend;
 
-pub use qp_model::{QpModel, QpVariable, QpConstraint, VarMeta};
-pub use backend::{SolverBackend, QpSolution, QpStatus};
+pub use qp_model::{QpModel, QpVariable, QpConstraint, VarMeta, ConstraintMeta, BindingConstraint, BindingSide};
+pub use backend::{SolverBackend, QpSolution, QpStatus, BINDING_TOLERANCE};
 pub use simple_backend::SimpleQpSolver;
+pub use clarabel_backend::ClarabelSolver;
+pub use deterministic_backend::DeterministicSolver;
+#[cfg(feature = "osqp")]
 pub use osqp_backend::OsqpSolver;
 
 #[cfg(test)]
-mod tests;
\ No newline at end of file
+mod tests;
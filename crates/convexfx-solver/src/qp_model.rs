@@ -2,13 +2,55 @@ use nalgebra::{DMatrix, DVector};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
-use convexfx_types::{AssetId, OrderId};
+use convexfx_types::{AccountId, AssetId, OrderId};
 
 /// Variable metadata for tracking
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum VarMeta {
     LogPrice(AssetId),
     FillFraction(OrderId),
+    /// Auxiliary cross-rate variable y_ij = y_i - y_j
+    CrossRate(AssetId, AssetId),
+}
+
+/// Constraint metadata: what each row of `A` means
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConstraintMeta {
+    /// Numeraire log-price pinned at zero (USD unless configured otherwise)
+    Numeraire,
+    /// Trust-region band around the reference log-price
+    PriceBand(AssetId),
+    /// 0 <= alpha_k <= 1
+    FillBound(OrderId),
+    /// y_receive - y_pay <= log(limit_ratio)
+    Limit(OrderId),
+    /// Linearized post-trade inventory within [q_min, q_max]
+    InventoryBound(AssetId),
+    /// Linearized net position change of one trader in one asset
+    PositionLimit(AccountId, AssetId),
+    /// y_i - y_j - y_ij = 0, tying a cross-rate variable to the prices
+    CrossRate(AssetId, AssetId),
+    /// |y_ab + y_bc - y_ac| <= tolerance
+    Triangle(AssetId, AssetId, AssetId),
+}
+
+/// Side of a constraint that is active at the solution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BindingSide {
+    Lower,
+    Upper,
+    /// Equality row (l == u), always active
+    Equality,
+}
+
+/// A constraint row that is active at a solution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BindingConstraint {
+    /// Row index into `A`
+    pub row: usize,
+    /// Semantic meaning of the row, if the model was labeled
+    pub meta: Option<ConstraintMeta>,
+    pub side: BindingSide,
 }
 
 /// QP model in standard form:
@@ -28,6 +70,8 @@ pub struct QpModel {
     pub u: DVector<f64>,
     /// Variable metadata
     pub var_meta: Vec<VarMeta>,
+    /// Constraint metadata, one per row of `A` (empty if unlabeled)
+    pub constraint_meta: Vec<ConstraintMeta>,
 }
 
 impl QpModel {
@@ -40,7 +84,100 @@ impl QpModel {
         u: DVector<f64>,
         var_meta: Vec<VarMeta>,
     ) -> Self {
-        QpModel { p, q, a, l, u, var_meta }
+        QpModel { p, q, a, l, u, var_meta, constraint_meta: Vec::new() }
+    }
+
+    /// Label each constraint row with its meaning
+    pub fn with_constraint_meta(mut self, constraint_meta: Vec<ConstraintMeta>) -> Self {
+        self.constraint_meta = constraint_meta;
+        self
+    }
+
+    /// Rows of `A` that are active at `x`
+    ///
+    /// A row is binding when `A x` lies within `tolerance` (relative to the
+    /// bound's magnitude, floored at 1) of its lower or upper bound.
+    pub fn binding_constraints(&self, x: &[f64], tolerance: f64) -> Vec<BindingConstraint> {
+        if x.len() != self.num_vars() {
+            return Vec::new();
+        }
+
+        let ax = &self.a * DVector::from_column_slice(x);
+        let near = |value: f64, bound: f64| {
+            bound.is_finite() && (value - bound).abs() <= tolerance * bound.abs().max(1.0)
+        };
+
+        let mut binding = Vec::new();
+        for row in 0..self.num_constraints() {
+            let (l, u) = (self.l[row], self.u[row]);
+            let side = if l == u {
+                BindingSide::Equality
+            } else if near(ax[row], l) {
+                BindingSide::Lower
+            } else if near(ax[row], u) {
+                BindingSide::Upper
+            } else {
+                continue;
+            };
+            binding.push(BindingConstraint {
+                row,
+                meta: self.constraint_meta.get(row).cloned(),
+                side,
+            });
+        }
+        binding
+    }
+
+    /// Clamp `x` into every single-variable row `l <= c * x_j <= u`
+    ///
+    /// Cleans up the small bound violations iterative solvers leave behind.
+    pub fn clamp_to_box_rows(&self, x: &mut [f64]) {
+        for i in 0..self.num_constraints() {
+            let li = self.l[i];
+            let ui = self.u[i];
+
+            // Check if this is a simple box constraint (A row has single entry)
+            let a_row = self.a.row(i);
+            let nonzero_entries: Vec<(usize, f64)> = a_row.iter()
+                .enumerate()
+                .filter(|(_, &v)| v.abs() > 1e-10)
+                .map(|(idx, &v)| (idx, v))
+                .collect();
+
+            if nonzero_entries.len() == 1 {
+                let (var_idx, coeff) = nonzero_entries[0];
+
+                // Box constraint: l <= coeff * x[var_idx] <= u
+                // => l/coeff <= x[var_idx] <= u/coeff (if coeff > 0)
+                if coeff > 0.0 {
+                    if li.is_finite() {
+                        x[var_idx] = x[var_idx].max(li / coeff);
+                    }
+                    if ui.is_finite() {
+                        x[var_idx] = x[var_idx].min(ui / coeff);
+                    }
+                } else {
+                    // Negative coefficient: inequality flips
+                    if li.is_finite() {
+                        x[var_idx] = x[var_idx].min(li / coeff);
+                    }
+                    if ui.is_finite() {
+                        x[var_idx] = x[var_idx].max(ui / coeff);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Largest amount by which `A x` falls outside `[l, u]`
+    pub fn max_violation(&self, x: &[f64]) -> f64 {
+        if x.len() != self.num_vars() {
+            return f64::INFINITY;
+        }
+        let ax = &self.a * DVector::from_column_slice(x);
+        (0..self.num_constraints())
+            .map(|row| (self.l[row] - ax[row]).max(ax[row] - self.u[row]).max(0.0))
+            .fold(0.0, f64::max)
     }
 
     /// Get number of variables
@@ -76,6 +213,12 @@ impl QpModel {
             ));
         }
 
+        if !self.constraint_meta.is_empty() && self.constraint_meta.len() != m {
+            return Err(convexfx_types::ConvexFxError::SolverError(
+                format!("constraint_meta length {} != num_constraints {}", self.constraint_meta.len(), m)
+            ));
+        }
+
         Ok(())
     }
 }
@@ -110,6 +253,17 @@ impl QpConstraint {
     pub fn ineq(coeffs: BTreeMap<usize, f64>, lower: f64, upper: f64) -> Self {
         QpConstraint { coeffs, lower, upper }
     }
+
+    /// Create a cross-rate coherence constraint: |x[i] - x[j] - x[ij]| <= tolerance
+    ///
+    /// With `tolerance = 0` this is the equality y_i - y_j - y_ij = 0.
+    pub fn cross_rate(i: usize, j: usize, ij: usize, tolerance: f64) -> Self {
+        let mut coeffs = BTreeMap::new();
+        *coeffs.entry(i).or_insert(0.0) += 1.0;
+        *coeffs.entry(j).or_insert(0.0) -= 1.0;
+        *coeffs.entry(ij).or_insert(0.0) -= 1.0;
+        QpConstraint::ineq(coeffs, -tolerance, tolerance)
+    }
 }
 
 
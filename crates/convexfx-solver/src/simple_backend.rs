@@ -1,6 +1,6 @@
 use nalgebra::DVector;
 
-use crate::backend::{QpSolution, QpStatus, SolverBackend};
+use crate::backend::{QpSolution, QpStatus, SolverBackend, BINDING_TOLERANCE};
 use crate::qp_model::QpModel;
 use convexfx_types::Result;
 
@@ -220,6 +220,7 @@ impl SolverBackend for SimpleQpSolver {
                     status,
                     objective: obj_new,
                     iterations,
+                    binding: model.binding_constraints(x.as_slice(), BINDING_TOLERANCE),
                 });
             }
 
@@ -232,8 +233,13 @@ impl SolverBackend for SimpleQpSolver {
             status: QpStatus::MaxIterations,
             objective: prev_obj,
             iterations,
+            binding: model.binding_constraints(x.as_slice(), BINDING_TOLERANCE),
         })
     }
+
+    fn name(&self) -> &'static str {
+        "simple"
+    }
 }
 
 #[cfg(test)]
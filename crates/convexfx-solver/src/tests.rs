@@ -246,4 +246,56 @@ mod tests {
         // Objective should be near zero (optimal point is origin)
         assert!(solution.objective < 0.01);
     }
+
+    #[test]
+    fn test_cross_rate_constraint() {
+        let c = QpConstraint::cross_rate(1, 2, 6, 1e-4);
+
+        assert_eq!(c.coeffs[&1], 1.0);
+        assert_eq!(c.coeffs[&2], -1.0);
+        assert_eq!(c.coeffs[&6], -1.0);
+        assert_eq!(c.lower, -1e-4);
+        assert_eq!(c.upper, 1e-4);
+
+        // Zero tolerance collapses to an equality
+        let eq = QpConstraint::cross_rate(0, 1, 2, 0.0);
+        assert_eq!(eq.lower, eq.upper);
+    }
+
+    #[cfg(feature = "osqp")]
+    #[test]
+    fn test_osqp_matches_clarabel() {
+        // Same problems as the Clarabel backend's test_simple_qp and
+        // test_constrained_qp; both backends should agree within tolerance
+        let models = vec![
+            QpModel::new(
+                DMatrix::from_diagonal(&DVector::from_vec(vec![2.0, 2.0])),
+                DVector::from_vec(vec![1.0, 1.0]),
+                DMatrix::identity(2, 2),
+                DVector::from_element(2, 0.0),
+                DVector::from_element(2, f64::INFINITY),
+                vec![VarMeta::LogPrice(AssetId::USD), VarMeta::LogPrice(AssetId::EUR)],
+            ),
+            QpModel::new(
+                DMatrix::identity(2, 2),
+                DVector::from_vec(vec![-2.0, -1.0]),
+                DMatrix::from_row_slice(3, 2, &[1.0, 0.0, 0.0, 1.0, 1.0, 1.0]),
+                DVector::from_vec(vec![0.0, 0.0, -f64::INFINITY]),
+                DVector::from_vec(vec![f64::INFINITY, f64::INFINITY, 1.0]),
+                vec![VarMeta::LogPrice(AssetId::USD), VarMeta::LogPrice(AssetId::EUR)],
+            ),
+        ];
+
+        for model in &models {
+            let clarabel = ClarabelSolver::new().solve_qp(model).unwrap();
+            let osqp = OsqpSolver::new().solve_qp(model).unwrap();
+
+            assert_eq!(clarabel.status, QpStatus::Optimal);
+            assert_eq!(osqp.status, QpStatus::Optimal);
+            for (a, b) in clarabel.x.iter().zip(&osqp.x) {
+                assert!((a - b).abs() < 1e-3, "clarabel {} vs osqp {}", a, b);
+            }
+            assert!((clarabel.objective - osqp.objective).abs() < 1e-3);
+        }
+    }
 }
@@ -2,10 +2,14 @@
 //!
 //! This program runs in the SP1 zkVM and proves that ConvexFX clearing results
 //! satisfy all optimality conditions (local laws) before being submitted to Delta.
+//!
+//! The predicates are `convexfx_local_laws::check_local_laws`, the same code
+//! `EpochSolution::validate` runs on the host.
 
 #![no_main]
 sp1_zkvm::entrypoints::init_io();
 
+use convexfx_local_laws::{check_local_laws, FillFlows, ObjectiveBreakdown, Tolerances, Witness, NUM_ASSETS};
 use sp1_zkvm::prelude::*;
 
 /// Input data for proving ConvexFX clearing validity
@@ -17,6 +21,7 @@ struct ClearingProofInput {
     fills: Vec<FillData>,           // All fills
     initial_inventory: Vec<(u8, f64)>, // Initial inventory
     final_inventory: Vec<(u8, f64)>,   // Final inventory (q_post)
+    numeraire: u8,                  // Asset ID whose log price is pinned at 0
     
     // Diagnostics from SCP algorithm
     convergence_achieved: bool,
@@ -38,197 +43,93 @@ struct FillData {
     recv_asset: u8,
     pay_units: f64,
     recv_units: f64,
+    fees_paid: Vec<(u8, f64)>,      // Asset ID -> fee credited to the pool
+}
+
+/// Compact witness written by the host, mirroring `CompactClearingProofInput`
+/// in convexfx-delta: per-asset values are dense arrays indexed by asset ID
+#[derive(serde::Deserialize, serde::Serialize)]
+struct CompactClearingProofInput {
+    y_star: [f64; NUM_ASSETS],
+    prices: [f64; NUM_ASSETS],
+    fills: Vec<FillData>,
+    initial_inventory: [f64; NUM_ASSETS],
+    final_inventory: [f64; NUM_ASSETS],
+    numeraire: u8,
+    convergence_achieved: bool,
+    final_step_norm_y: f64,
+    final_step_norm_alpha: f64,
+    inventory_risk: f64,
+    price_tracking: f64,
+    fill_incentive: f64,
+    total_objective: f64,
 }
 
-/// Predicate parameters
-const TOLERANCE_Y: f64 = 1e-5;
-const TOLERANCE_ALPHA: f64 = 1e-6;
-const MAX_PRICE_DEVIATION: f64 = 0.01; // 1%
-const INVENTORY_TOLERANCE: f64 = 1e-6;
-const USD_ASSET_ID: u8 = 0;
+impl CompactClearingProofInput {
+    /// Expand to the `(asset, value)` form the local laws check
+    fn expand(self) -> ClearingProofInput {
+        let sparse = |values: [f64; NUM_ASSETS]| -> Vec<(u8, f64)> {
+            values.iter().enumerate().map(|(i, v)| (i as u8, *v)).collect()
+        };
+        ClearingProofInput {
+            y_star: sparse(self.y_star),
+            prices: sparse(self.prices),
+            fills: self.fills,
+            initial_inventory: sparse(self.initial_inventory),
+            final_inventory: sparse(self.final_inventory),
+            numeraire: self.numeraire,
+            convergence_achieved: self.convergence_achieved,
+            final_step_norm_y: self.final_step_norm_y,
+            final_step_norm_alpha: self.final_step_norm_alpha,
+            inventory_risk: self.inventory_risk,
+            price_tracking: self.price_tracking,
+            fill_incentive: self.fill_incentive,
+            total_objective: self.total_objective,
+        }
+    }
+}
 
 pub fn main() {
     // Read input from the SP1 zkVM
-    let input: ClearingProofInput = sp1_zkvm::io::read();
-    
-    // ===== PREDICATE 1: CONVERGENCE VALIDATION =====
-    // Ensures the SCP algorithm converged to an optimal solution
-    assert!(
-        input.convergence_achieved,
-        "SCP algorithm did not converge"
-    );
-    
-    assert!(
-        input.final_step_norm_y < TOLERANCE_Y,
-        "Price step norm {} exceeds tolerance {}",
-        input.final_step_norm_y,
-        TOLERANCE_Y
-    );
-    
-    assert!(
-        input.final_step_norm_alpha < TOLERANCE_ALPHA,
-        "Fill step norm {} exceeds tolerance {}",
-        input.final_step_norm_alpha,
-        TOLERANCE_ALPHA
-    );
-    
-    // ===== PREDICATE 2: PRICE CONSISTENCY VALIDATION =====
-    // Verifies price = exp(log_price) and USD numeraire constraint
-    for (asset_id, log_price) in &input.y_star {
-        // Find corresponding linear price
-        let linear_price = input.prices
-            .iter()
-            .find(|(id, _)| id == asset_id)
-            .expect("Missing linear price for asset")
-            .1;
-        
-        // Check price consistency: price = exp(log_price)
-        let expected = log_price.exp();
-        let error = (expected - linear_price).abs() / linear_price;
-        
-        assert!(
-            error < MAX_PRICE_DEVIATION,
-            "Price inconsistency for asset {}: expected {}, got {}, error {}",
-            asset_id,
-            expected,
-            linear_price,
-            error
-        );
-        
-        // Check price is positive and finite
-        assert!(
-            linear_price > 0.0 && linear_price.is_finite(),
-            "Invalid price for asset {}: {}",
-            asset_id,
-            linear_price
-        );
-    }
-    
-    // Check USD numeraire constraint: y[USD] = 0
-    let usd_log_price = input.y_star
+    let input = sp1_zkvm::io::read::<CompactClearingProofInput>().expand();
+
+    let fills: Vec<FillFlows<'_>> = input.fills
         .iter()
-        .find(|(id, _)| *id == USD_ASSET_ID)
-        .map(|(_, price)| *price)
-        .unwrap_or(0.0);
-    
-    assert!(
-        usd_log_price.abs() < TOLERANCE_Y,
-        "USD numeraire constraint violated: y_USD = {}",
-        usd_log_price
-    );
-    
-    // ===== PREDICATE 3: FILL FEASIBILITY VALIDATION =====
-    // Ensures all fills are valid and feasible
-    for (i, fill) in input.fills.iter().enumerate() {
-        // Check fill fraction in range [0, 1]
-        assert!(
-            fill.fill_frac >= 0.0 && fill.fill_frac <= 1.0,
-            "Invalid fill fraction {} for fill {}",
-            fill.fill_frac,
-            i
-        );
-        
-        // For non-zero fills, check amounts are positive
-        // Use a small tolerance to handle numerical precision issues
-        const MIN_FILL_AMOUNT: f64 = 1e-8;
-        if fill.fill_frac > MIN_FILL_AMOUNT {
-            assert!(
-                fill.pay_units > MIN_FILL_AMOUNT,
-                "Non-positive pay amount {} for fill {}",
-                fill.pay_units,
-                i
-            );
-            
-            assert!(
-                fill.recv_units > MIN_FILL_AMOUNT,
-                "Non-positive receive amount {} for fill {}",
-                fill.recv_units,
-                i
-            );
-            
-            assert!(
-                fill.pay_units.is_finite(),
-                "Non-finite pay amount for fill {}",
-                i
-            );
-            
-            assert!(
-                fill.recv_units.is_finite(),
-                "Non-finite receive amount for fill {}",
-                i
-            );
-        }
-    }
-    
-    // ===== PREDICATE 4: INVENTORY CONSERVATION VALIDATION =====
-    // Verifies the fundamental law: final_inventory = initial_inventory + net_flow
-    for (asset_id, initial) in &input.initial_inventory {
-        let final_inv = input.final_inventory
-            .iter()
-            .find(|(id, _)| id == asset_id)
-            .expect("Missing final inventory for asset")
-            .1;
-        
-        // Calculate net flow from fills
-        let mut net_flow = 0.0;
-        for fill in &input.fills {
-            if fill.pay_asset == *asset_id {
-                net_flow += fill.pay_units; // Pool receives pay asset
-            }
-            if fill.recv_asset == *asset_id {
-                net_flow -= fill.recv_units; // Pool gives receive asset
-            }
-        }
-        
-        let expected = initial + net_flow;
-        let error = (final_inv - expected).abs();
-        
-        assert!(
-            error < INVENTORY_TOLERANCE,
-            "Inventory conservation violated for asset {}: initial={}, net_flow={}, expected={}, actual={}, error={}",
-            asset_id,
-            initial,
-            net_flow,
-            expected,
-            final_inv,
-            error
-        );
+        .map(|fill| FillFlows {
+            fill_frac: fill.fill_frac,
+            pay_asset: fill.pay_asset,
+            recv_asset: fill.recv_asset,
+            pay_units: fill.pay_units,
+            recv_units: fill.recv_units,
+            fees_paid: &fill.fees_paid,
+        })
+        .collect();
+    let witness = Witness {
+        converged: input.convergence_achieved,
+        final_step_norm_y: input.final_step_norm_y,
+        final_step_norm_alpha: input.final_step_norm_alpha,
+        y_star: &input.y_star,
+        prices: &input.prices,
+        numeraire: input.numeraire,
+        fills: &fills,
+        initial_inventory: &input.initial_inventory,
+        final_inventory: &input.final_inventory,
+        objective: ObjectiveBreakdown {
+            inventory_risk: input.inventory_risk,
+            price_tracking: input.price_tracking,
+            fill_incentive: input.fill_incentive,
+            total: input.total_objective,
+        },
+    };
+
+    // ===== PREDICATES 1-5 =====
+    // Convergence, price consistency, fill feasibility, inventory
+    // conservation (fees included) and the objective breakdown
+    if let Err(violation) = check_local_laws(&witness, &Tolerances::DEFAULT) {
+        panic!("{}", violation);
     }
-    
-    // ===== PREDICATE 5: OBJECTIVE OPTIMALITY VALIDATION =====
-    // Ensures objective function is properly computed
-    assert!(
-        input.inventory_risk >= -INVENTORY_TOLERANCE,
-        "Negative inventory risk: {}",
-        input.inventory_risk
-    );
-    
-    assert!(
-        input.price_tracking >= -INVENTORY_TOLERANCE,
-        "Negative price tracking: {}",
-        input.price_tracking
-    );
-    
-    assert!(
-        input.total_objective.is_finite(),
-        "Non-finite objective value: {}",
-        input.total_objective
-    );
-    
-    // Check that objective components sum correctly
-    let computed_total = input.inventory_risk + input.price_tracking + input.fill_incentive;
-    let total_error = (input.total_objective - computed_total).abs();
-    
-    assert!(
-        total_error < INVENTORY_TOLERANCE,
-        "Objective components don't sum: components={}, total={}, error={}",
-        computed_total,
-        input.total_objective,
-        total_error
-    );
-    
+
     // ===== COMMIT RESULT =====
     // Commit success flag to public output
     sp1_zkvm::io::commit(&true);
 }
-
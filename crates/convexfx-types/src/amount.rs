@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
-use std::ops::{Add, Sub, Neg};
+use std::ops::{Add, Mul, Sub, Neg};
+use crate::asset::AssetId;
 use crate::error::{ConvexFxError, Result};
+use crate::fixed_amount::FixedAmount;
 
 /// Fixed-point amount with 9 decimal places
 /// Internally stored as i128 to prevent overflow
@@ -106,9 +108,10 @@ impl Amount {
     }
 
     /// Multiply by f64 (for solver calculations)
+    /// Rounds to the nearest raw unit, so 1 * 0.7 is exactly 0.7
     pub fn mul_f64(&self, factor: f64) -> Result<Self> {
-        let result = (self.0 as f64) * factor;
-        if !result.is_finite() || result.abs() > (i128::MAX as f64) {
+        let result = ((self.0 as f64) * factor).round();
+        if !result.is_finite() || result.abs() >= (i128::MAX as f64) {
             return Err(ConvexFxError::InvalidAmount(format!(
                 "overflow in f64 multiplication: {} * {}",
                 self.to_f64(),
@@ -118,6 +121,15 @@ impl Amount {
         Ok(Amount(result as i128))
     }
 
+    /// Format at the asset's display precision (0 decimals for JPY, 2 for
+    /// the rest), rounding half away from zero
+    pub fn to_display_string(&self, asset: AssetId) -> String {
+        match FixedAmount::from_amount_lossy(*self, asset.decimals()) {
+            Ok(fixed) => fixed.to_string(),
+            Err(_) => self.to_string(),
+        }
+    }
+
     /// Round toward pool (conservative rounding)
     /// If negative (outflow from pool), round away from zero (larger outflow)
     /// If positive (inflow to pool), round toward zero (smaller inflow)
@@ -142,6 +154,17 @@ impl Sub for Amount {
     }
 }
 
+/// Scale by a fill fraction or rate
+///
+/// Panics on overflow or a non-finite factor, like the integer operators;
+/// use `mul_f64` to handle those as errors.
+impl Mul<f64> for Amount {
+    type Output = Self;
+    fn mul(self, factor: f64) -> Self {
+        self.mul_f64(factor).expect("Amount * f64 overflow")
+    }
+}
+
 impl Neg for Amount {
     type Output = Self;
     fn neg(self) -> Self {
@@ -180,6 +203,40 @@ mod tests {
         assert_eq!((-a).to_f64(), -10.0);
     }
 
+    #[test]
+    fn test_checked_sub_underflow() {
+        let balance = Amount::from_units(5);
+        let short = balance.checked_sub(Amount::from_units(8)).unwrap();
+        assert!(short.is_negative());
+
+        let floor = Amount::from_raw(i128::MIN + 1);
+        assert!(floor.checked_sub(Amount::from_raw(2)).is_err());
+        assert!(floor.checked_sub(Amount::from_raw(1)).is_ok());
+    }
+
+    #[test]
+    fn test_scaling_by_fill_fraction() {
+        // Rounds to the nearest raw unit instead of truncating 0.69999...
+        assert_eq!(Amount::from_units(1) * 0.7, Amount::from_raw(700_000_000));
+        assert_eq!(Amount::from_units(1000) * 0.1, Amount::from_units(100));
+        assert_eq!(Amount::from_units(10) * (1.0 / 3.0), Amount::from_raw(3_333_333_333));
+        assert_eq!(Amount::from_raw(123_456_789_012) * 0.25, Amount::from_raw(30_864_197_253));
+        assert_eq!(Amount::from_units(-4) * 0.5, Amount::from_units(-2));
+
+        assert!(Amount::from_units(1).mul_f64(f64::NAN).is_err());
+        assert!(Amount::from_raw(i128::MAX / 2).mul_f64(4.0).is_err());
+    }
+
+    #[test]
+    fn test_display_string_uses_asset_decimals() {
+        let amount = Amount::from_f64(1500.456).unwrap();
+        assert_eq!(amount.to_display_string(AssetId::JPY), "1500");
+        assert_eq!(amount.to_display_string(AssetId::EUR), "1500.46");
+        assert_eq!(Amount::from_f64(0.5).unwrap().to_display_string(AssetId::JPY), "1");
+        assert_eq!(Amount::from_units(-3).to_display_string(AssetId::USD), "-3.00");
+        assert_eq!(Amount::ZERO.to_display_string(AssetId::GBP), "0.00");
+    }
+
     #[test]
     fn test_amount_checks() {
         assert!(Amount::from_units(10).is_positive());
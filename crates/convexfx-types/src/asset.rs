@@ -23,32 +23,32 @@ impl AssetRegistry {
         // Add default assets
         assets.insert("USD".to_string(), AssetInfo {
             name: "US Dollar".to_string(),
-            decimals: 2,
+            decimals: AssetId::USD.decimals(),
             is_base_currency: true,
         });
         assets.insert("EUR".to_string(), AssetInfo {
             name: "Euro".to_string(),
-            decimals: 2,
+            decimals: AssetId::EUR.decimals(),
             is_base_currency: false,
         });
         assets.insert("JPY".to_string(), AssetInfo {
             name: "Japanese Yen".to_string(),
-            decimals: 0,
+            decimals: AssetId::JPY.decimals(),
             is_base_currency: false,
         });
         assets.insert("GBP".to_string(), AssetInfo {
             name: "British Pound".to_string(),
-            decimals: 2,
+            decimals: AssetId::GBP.decimals(),
             is_base_currency: false,
         });
         assets.insert("CHF".to_string(), AssetInfo {
             name: "Swiss Franc".to_string(),
-            decimals: 2,
+            decimals: AssetId::CHF.decimals(),
             is_base_currency: false,
         });
         assets.insert("AUD".to_string(), AssetInfo {
             name: "Australian Dollar".to_string(),
-            decimals: 2,
+            decimals: AssetId::AUD.decimals(),
             is_base_currency: false,
         });
 
@@ -76,6 +76,12 @@ impl AssetRegistry {
 
         Ok(())
     }
+
+    pub fn remove_asset(&mut self, symbol: &str) -> Result<AssetInfo, String> {
+        self.assets
+            .remove(symbol)
+            .ok_or_else(|| format!("Asset {} not found", symbol))
+    }
 }
 
 impl Default for AssetRegistry {
@@ -120,9 +126,11 @@ impl AssetId {
         }
     }
 
-    /// Parse an asset from a string
+    /// Parse an asset from a symbol, ignoring case and surrounding whitespace
+    ///
+    /// Returns `None` for unknown symbols.
     pub fn from_str(s: &str) -> Option<Self> {
-        match s.to_uppercase().as_str() {
+        match s.trim().to_uppercase().as_str() {
             "USD" => Some(AssetId::USD),
             "EUR" => Some(AssetId::EUR),
             "JPY" => Some(AssetId::JPY),
@@ -133,6 +141,14 @@ impl AssetId {
         }
     }
 
+    /// Number of decimal places in the asset's minor unit
+    pub const fn decimals(&self) -> u32 {
+        match self {
+            AssetId::JPY => 0,
+            _ => 2,
+        }
+    }
+
     /// Returns the index of this asset in the canonical ordering
     pub fn index(&self) -> usize {
         match self {
@@ -165,5 +181,20 @@ impl fmt::Display for AssetId {
     }
 }
 
-
-
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str_normalizes_input() {
+        assert_eq!(AssetId::from_str("USD"), Some(AssetId::USD));
+        assert_eq!(AssetId::from_str("eur"), Some(AssetId::EUR));
+        assert_eq!(AssetId::from_str("Gbp"), Some(AssetId::GBP));
+        assert_eq!(AssetId::from_str(" usd "), Some(AssetId::USD));
+        assert_eq!(AssetId::from_str("\tJPY\n"), Some(AssetId::JPY));
+
+        assert_eq!(AssetId::from_str("XYZ"), None);
+        assert_eq!(AssetId::from_str("US D"), None);
+        assert_eq!(AssetId::from_str("   "), None);
+    }
+}
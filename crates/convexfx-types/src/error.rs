@@ -8,6 +8,9 @@ pub enum ConvexFxError {
     #[error("Invalid amount: {0}")]
     InvalidAmount(String),
 
+    #[error("Invalid price: {0}")]
+    InvalidPrice(String),
+
     #[error("Invalid order: {0}")]
     InvalidOrder(String),
 
@@ -35,6 +38,9 @@ pub enum ConvexFxError {
     #[error("Convergence failed: {0}")]
     ConvergenceFailed(String),
 
+    #[error("Invalid solution: {0}")]
+    InvalidSolution(String),
+
     #[error("Serialization error: {0}")]
     SerializationError(String),
 
@@ -44,6 +50,9 @@ pub enum ConvexFxError {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::amount::Amount;
+use crate::asset::AssetId;
+use crate::error::{ConvexFxError, Result};
+
+/// Decimal places of `Amount`'s internal representation
+const AMOUNT_DECIMALS: u32 = 9;
+
+/// Exact amount in an asset's smallest unit ("planck")
+///
+/// Stored as i128 scaled by the asset's decimals (cents for EUR, whole yen
+/// for JPY), matching how the Delta SDK settles balances. Add and subtract
+/// are exact; conversions from f64 round and are marked `_lossy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FixedAmount {
+    planck: i128,
+    decimals: u32,
+}
+
+impl FixedAmount {
+    /// Create from a raw planck count with the given decimals
+    pub const fn from_planck(planck: i128, decimals: u32) -> Self {
+        FixedAmount { planck, decimals }
+    }
+
+    /// Create from a raw planck count in the asset's decimals
+    pub const fn for_asset(asset: AssetId, planck: i128) -> Self {
+        Self::from_planck(planck, asset.decimals())
+    }
+
+    /// Zero in the given decimals
+    pub const fn zero(decimals: u32) -> Self {
+        Self::from_planck(0, decimals)
+    }
+
+    /// Raw planck count
+    pub const fn planck(&self) -> i128 {
+        self.planck
+    }
+
+    /// Decimal places of one planck
+    pub const fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    /// Check if amount is negative
+    pub const fn is_negative(&self) -> bool {
+        self.planck < 0
+    }
+
+    /// Check if amount is zero
+    pub const fn is_zero(&self) -> bool {
+        self.planck == 0
+    }
+
+    /// Create from f64, rounding to the nearest planck
+    pub fn from_f64_lossy(value: f64, decimals: u32) -> Result<Self> {
+        if !value.is_finite() {
+            return Err(ConvexFxError::InvalidAmount(format!(
+                "non-finite value: {}",
+                value
+            )));
+        }
+        let scaled = (value * 10f64.powi(decimals as i32)).round();
+        if scaled.abs() >= (i128::MAX as f64) {
+            return Err(ConvexFxError::InvalidAmount(format!(
+                "overflow: {}",
+                value
+            )));
+        }
+        Ok(Self::from_planck(scaled as i128, decimals))
+    }
+
+    /// Convert to f64 (nearest representable value)
+    pub fn to_f64_lossy(&self) -> f64 {
+        (self.planck as f64) / 10f64.powi(self.decimals as i32)
+    }
+
+    /// Convert to `Amount`, exactly when decimals fit its 9 places
+    pub fn to_amount(&self) -> Result<Amount> {
+        if self.decimals > AMOUNT_DECIMALS {
+            return Amount::from_f64(self.to_f64_lossy());
+        }
+        self.planck
+            .checked_mul(10i128.pow(AMOUNT_DECIMALS - self.decimals))
+            .map(Amount::from_raw)
+            .ok_or_else(|| ConvexFxError::InvalidAmount("overflow converting to Amount".to_string()))
+    }
+
+    /// Create from `Amount`, rounding half away from zero to the nearest planck
+    pub fn from_amount_lossy(amount: Amount, decimals: u32) -> Result<Self> {
+        if decimals >= AMOUNT_DECIMALS {
+            return Self::from_planck(amount.raw(), AMOUNT_DECIMALS).rescale(decimals);
+        }
+        let divisor = 10i128.pow(AMOUNT_DECIMALS - decimals);
+        let raw = amount.raw();
+        let half = if raw < 0 { -divisor / 2 } else { divisor / 2 };
+        let planck = raw
+            .checked_add(half)
+            .ok_or_else(|| ConvexFxError::InvalidAmount("overflow converting from Amount".to_string()))?
+            / divisor;
+        Ok(Self::from_planck(planck, decimals))
+    }
+
+    /// Express in more decimals (exact); fewer decimals are rejected
+    pub fn rescale(&self, decimals: u32) -> Result<Self> {
+        if decimals < self.decimals {
+            return Err(ConvexFxError::InvalidAmount(format!(
+                "cannot rescale {} decimals down to {} exactly",
+                self.decimals, decimals
+            )));
+        }
+        10i128
+            .checked_pow(decimals - self.decimals)
+            .and_then(|factor| self.planck.checked_mul(factor))
+            .map(|planck| Self::from_planck(planck, decimals))
+            .ok_or_else(|| ConvexFxError::InvalidAmount("overflow in rescale".to_string()))
+    }
+
+    /// Checked addition (both sides must share decimals)
+    pub fn checked_add(&self, other: Self) -> Result<Self> {
+        self.check_decimals(&other)?;
+        self.planck
+            .checked_add(other.planck)
+            .map(|planck| Self::from_planck(planck, self.decimals))
+            .ok_or_else(|| ConvexFxError::InvalidAmount("overflow in addition".to_string()))
+    }
+
+    /// Checked subtraction (both sides must share decimals)
+    pub fn checked_sub(&self, other: Self) -> Result<Self> {
+        self.check_decimals(&other)?;
+        self.planck
+            .checked_sub(other.planck)
+            .map(|planck| Self::from_planck(planck, self.decimals))
+            .ok_or_else(|| ConvexFxError::InvalidAmount("overflow in subtraction".to_string()))
+    }
+
+    fn check_decimals(&self, other: &Self) -> Result<()> {
+        if self.decimals != other.decimals {
+            return Err(ConvexFxError::InvalidAmount(format!(
+                "decimal mismatch: {} vs {}",
+                self.decimals, other.decimals
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FixedAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.planck < 0 { "-" } else { "" };
+        let abs = self.planck.unsigned_abs();
+        if self.decimals == 0 {
+            return write!(f, "{}{}", sign, abs);
+        }
+        let unit = 10u128.pow(self.decimals);
+        write!(
+            f,
+            "{}{}.{:0width$}",
+            sign,
+            abs / unit,
+            abs % unit,
+            width = self.decimals as usize
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_amount_no_drift() {
+        let dime = FixedAmount::from_f64_lossy(0.1, AssetId::EUR.decimals()).unwrap();
+        let mut fixed = FixedAmount::zero(AssetId::EUR.decimals());
+        let mut float = 0.0_f64;
+
+        for _ in 0..1000 {
+            fixed = fixed.checked_add(dime).unwrap();
+            float += 0.1;
+        }
+        assert_eq!(fixed, FixedAmount::for_asset(AssetId::EUR, 10_000));
+        assert_ne!(float, 100.0, "f64 accumulates rounding error");
+
+        for _ in 0..1000 {
+            fixed = fixed.checked_sub(dime).unwrap();
+            float -= 0.1;
+        }
+        assert!(fixed.is_zero());
+        assert_ne!(float, 0.0, "f64 does not return to zero");
+    }
+
+    #[test]
+    fn test_fixed_amount_asset_decimals() {
+        let jpy = FixedAmount::from_f64_lossy(1234.5, AssetId::JPY.decimals()).unwrap();
+        assert_eq!(jpy.planck(), 1235);
+        assert_eq!(jpy.to_string(), "1235");
+
+        let eur = FixedAmount::from_f64_lossy(-12.345, AssetId::EUR.decimals()).unwrap();
+        assert_eq!(eur.to_string(), "-12.35");
+
+        // Mixing decimals is an error rather than a silent rescale
+        assert!(jpy.checked_add(eur).is_err());
+        assert!(FixedAmount::from_f64_lossy(f64::NAN, 2).is_err());
+    }
+
+    #[test]
+    fn test_fixed_amount_amount_round_trip() {
+        let eur = FixedAmount::for_asset(AssetId::EUR, 12_345);
+        let amount = eur.to_amount().unwrap();
+        assert_eq!(amount, Amount::from_raw(123_450_000_000));
+        assert_eq!(FixedAmount::from_amount_lossy(amount, 2).unwrap(), eur);
+
+        let third = Amount::from_raw(-3_335_000_000);
+        assert_eq!(FixedAmount::from_amount_lossy(third, 2).unwrap().planck(), -334);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Amount, AssetId};
+use crate::{Amount, AssetId, ConvexFxError, Fill, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
@@ -47,6 +47,36 @@ impl Inventory {
         self.add(asset, -delta);
     }
 
+    /// Apply fills to the inventory, as in `Fill::inventory_deltas`
+    ///
+    /// All-or-nothing: if any asset a fill touches would end up negative,
+    /// returns `InsufficientBalance` and leaves the inventory unchanged.
+    pub fn apply_fills(&mut self, fills: &[Fill]) -> Result<()> {
+        let mut units = self.units.clone();
+        for fill in fills {
+            for (asset, delta) in fill.inventory_deltas() {
+                let balance = units.entry(asset).or_insert(Amount::ZERO);
+                *balance = balance.checked_add(Amount::from_f64(delta)?)?;
+            }
+        }
+
+        let touched = fills
+            .iter()
+            .flat_map(|fill| fill.inventory_deltas().map(|(asset, _)| asset));
+        for asset in touched {
+            if units[&asset].is_negative() {
+                return Err(ConvexFxError::InsufficientBalance(
+                    "inventory".to_string(),
+                    asset.to_string(),
+                ));
+            }
+        }
+
+        units.retain(|_, amount| !amount.is_zero());
+        self.units = units;
+        Ok(())
+    }
+
     /// Convert to f64 map (for solver interface)
     pub fn to_f64_map(&self) -> BTreeMap<AssetId, f64> {
         self.units
@@ -55,6 +85,15 @@ impl Inventory {
             .collect()
     }
 
+    /// Like `to_f64_map`, but listing every asset, at 0 where none is held,
+    /// as `EpochInstance::validate` expects
+    pub fn to_solver_map(&self) -> BTreeMap<AssetId, f64> {
+        AssetId::all()
+            .iter()
+            .map(|asset| (*asset, self.get(*asset).to_f64()))
+            .collect()
+    }
+
     /// Create from f64 map (for solver interface)
     pub fn from_f64_map(map: &BTreeMap<AssetId, f64>) -> crate::Result<Self> {
         let units = map
@@ -99,6 +138,43 @@ mod tests {
         assert!(!inv.has_sufficient(AssetId::USD, Amount::from_units(100)));
     }
 
+    #[test]
+    fn test_apply_fills_matches_manual_update() {
+        let mut inv = Inventory::new();
+        inv.set(AssetId::USD, Amount::from_units(1_000));
+        inv.set(AssetId::EUR, Amount::from_units(1_000));
+        inv.set(AssetId::GBP, Amount::from_units(500));
+
+        let fill = |pay_asset, recv_asset, pay_units: f64, recv_units: f64, fee: Option<f64>| Fill {
+            order_id: "order".to_string(),
+            fill_frac: 1.0,
+            pay_asset,
+            recv_asset,
+            pay_units,
+            recv_units,
+            fees_paid: fee.map(|f| (pay_asset, f)).into_iter().collect(),
+        };
+        let fills = vec![
+            fill(AssetId::USD, AssetId::EUR, 110.0, 100.0, Some(0.5)),
+            fill(AssetId::EUR, AssetId::GBP, 50.0, 42.25, None),
+            fill(AssetId::GBP, AssetId::USD, 20.0, 25.5, Some(0.1)),
+        ];
+        inv.apply_fills(&fills).unwrap();
+
+        assert_eq!(inv.get(AssetId::USD), Amount::from_f64(1_000.0 + 110.0 + 0.5 - 25.5).unwrap());
+        assert_eq!(inv.get(AssetId::EUR), Amount::from_f64(1_000.0 - 100.0 + 50.0).unwrap());
+        assert_eq!(inv.get(AssetId::GBP), Amount::from_f64(500.0 - 42.25 + 20.0 + 0.1).unwrap());
+
+        // Paying out more than the pool holds fails and changes nothing
+        let before = inv.clone();
+        let overdraw = [fill(AssetId::USD, AssetId::JPY, 10.0, 1_500.0, None)];
+        assert!(matches!(
+            inv.apply_fills(&overdraw),
+            Err(ConvexFxError::InsufficientBalance(_, ref asset)) if asset == "JPY"
+        ));
+        assert_eq!(inv, before);
+    }
+
     #[test]
     fn test_inventory_f64_conversion() {
         let mut inv = Inventory::new();
@@ -1,5 +1,6 @@
 mod asset;
 mod amount;
+mod fixed_amount;
 mod account;
 mod epoch;
 mod inventory;
@@ -9,6 +10,7 @@ mod error;
 
 pub use asset::{AssetId, AssetInfo, AssetRegistry};
 pub use amount::Amount;
+pub use fixed_amount::FixedAmount;
 pub use account::AccountId;
 pub use epoch::EpochId;
 pub use inventory::Inventory;
@@ -15,6 +15,14 @@ pub struct PairOrder {
     pub budget: Amount,          // B_k (units of pay asset)
     pub limit_ratio: Option<f64>, // optional max p_i/p_j
     pub min_fill_fraction: Option<f64>, // optional minimum fill (default 0.0)
+    /// Optional cap (bps) on adverse slippage versus `submission_mid`; an
+    /// order whose fill would exceed it gets none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_slippage_bps: Option<f64>,
+    /// Oracle mid rate (units of `receive` per unit of `pay`) recorded by
+    /// the venue when it accepted the order. Required with `max_slippage_bps`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submission_mid: Option<f64>,
     pub metadata: serde_json::Value, // client-specific fields
 }
 
@@ -48,6 +56,27 @@ pub struct BasketOrder {
     pub metadata: serde_json::Value,
 }
 
+impl BasketOrder {
+    /// Get effective minimum fill fraction
+    pub fn min_fill(&self) -> f64 {
+        self.min_fill_fraction.unwrap_or(0.0).clamp(0.0, 1.0)
+    }
+
+    /// Basket weights rescaled to sum to 1
+    ///
+    /// Returns no legs if the weights do not sum to a positive value.
+    pub fn normalized_weights(&self) -> Vec<(AssetId, f64)> {
+        let total: f64 = self.basket_weights.values().sum();
+        if !total.is_finite() || total <= 0.0 {
+            return Vec::new();
+        }
+        self.basket_weights
+            .iter()
+            .map(|(asset, weight)| (*asset, weight / total))
+            .collect()
+    }
+}
+
 /// General order enum
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -99,6 +128,14 @@ impl Fill {
     pub fn is_empty(&self) -> bool {
         self.fill_frac < 0.0001
     }
+
+    /// Change in the pool's holdings from this fill, per asset: the pool
+    /// receives the pay units and any fees, and pays out the receive units
+    pub fn inventory_deltas(&self) -> impl Iterator<Item = (AssetId, f64)> + '_ {
+        [(self.pay_asset, self.pay_units), (self.recv_asset, -self.recv_units)]
+            .into_iter()
+            .chain(self.fees_paid.iter().map(|(asset, fee)| (*asset, *fee)))
+    }
 }
 
 #[cfg(test)]
@@ -115,6 +152,8 @@ mod tests {
             budget: Amount::from_units(1000),
             limit_ratio: Some(1.15),
             min_fill_fraction: Some(0.1),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         };
 
@@ -92,6 +92,32 @@ impl Prices {
     pub fn cross_rate(&self, asset1: AssetId, asset2: AssetId) -> f64 {
         self.get(asset1) / self.get(asset2)
     }
+
+    /// Rate and "BASE/QUOTE" label for a pair in market quoting convention
+    ///
+    /// The argument order does not matter: the base is whichever asset ranks
+    /// first in EUR, GBP, AUD, USD, CHF, JPY, and the rate is units of quote
+    /// per unit of base (EUR/USD 1.10, USD/JPY 150).
+    pub fn display_rate(&self, base: AssetId, quote: AssetId) -> (f64, String) {
+        let (base, quote) = if quoting_rank(quote) < quoting_rank(base) {
+            (quote, base)
+        } else {
+            (base, quote)
+        };
+        (self.cross_rate(base, quote), format!("{}/{}", base, quote))
+    }
+}
+
+/// Position of an asset in the FX base-currency priority order
+fn quoting_rank(asset: AssetId) -> u8 {
+    match asset {
+        AssetId::EUR => 0,
+        AssetId::GBP => 1,
+        AssetId::AUD => 2,
+        AssetId::USD => 3,
+        AssetId::CHF => 4,
+        AssetId::JPY => 5,
+    }
 }
 
 impl Default for Prices {
@@ -135,6 +161,34 @@ mod tests {
         assert!((eurjpy_from_logs - eurjpy_direct).abs() < 1e-6); // Consistency check
     }
 
+    #[test]
+    fn test_display_rate_quoting_convention() {
+        let mut prices = Prices::new();
+        prices.p.insert(AssetId::EUR, 1.10);
+        prices.p.insert(AssetId::GBP, 1.25);
+        prices.p.insert(AssetId::AUD, 0.65);
+        prices.p.insert(AssetId::CHF, 1.12);
+        prices.p.insert(AssetId::JPY, 0.0067);
+
+        let cases = [
+            (AssetId::USD, AssetId::EUR, "EUR/USD", 1.10),
+            (AssetId::USD, AssetId::GBP, "GBP/USD", 1.25),
+            (AssetId::USD, AssetId::AUD, "AUD/USD", 0.65),
+            (AssetId::CHF, AssetId::USD, "USD/CHF", 1.0 / 1.12),
+            (AssetId::JPY, AssetId::USD, "USD/JPY", 1.0 / 0.0067),
+            (AssetId::GBP, AssetId::EUR, "EUR/GBP", 1.10 / 1.25),
+            (AssetId::JPY, AssetId::EUR, "EUR/JPY", 1.10 / 0.0067),
+        ];
+        for (a, b, label, rate) in cases {
+            // Both argument orders give the same conventional quote
+            for (base, quote) in [(a, b), (b, a)] {
+                let (got_rate, got_label) = prices.display_rate(base, quote);
+                assert_eq!(got_label, label);
+                assert!((got_rate - rate).abs() < 1e-9 * rate, "{}: {} vs {}", label, got_rate, rate);
+            }
+        }
+    }
+
     #[test]
     fn test_usd_numeraire() {
         let mut log_prices = LogPrices::new();
@@ -14,6 +14,8 @@ mod tests {
             budget: Amount::from_units(1000),
             limit_ratio: Some(1.2),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"source": "api"}),
         };
 
@@ -201,6 +203,8 @@ mod tests {
             budget: Amount::from_units(100),
             limit_ratio: Some(1.2),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         };
         
@@ -35,6 +35,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         budget: Amount::from_f64(1000.0)?,
         limit_ratio: Some(1.1), // Max EUR/USD rate of 1.1
         min_fill_fraction: Some(0.5), // Min 50% fill
+        max_slippage_bps: None,
+        submission_mid: None,
         metadata: serde_json::json!({
             "delta_user": alice_pubkey,
             "message_type": "swap",
@@ -50,6 +52,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         budget: Amount::from_f64(800.0)?,
         limit_ratio: Some(140.0), // Max JPY/EUR rate of 140
         min_fill_fraction: Some(0.8), // Min 80% fill
+        max_slippage_bps: None,
+        submission_mid: None,
         metadata: serde_json::json!({
             "delta_user": bob_pubkey,
             "message_type": "swap",
@@ -42,6 +42,8 @@ fn main() {
             budget: Amount::from_f64(0.5).unwrap(), // 0.5M USD each
             limit_ratio: Some(1.15), // Max EURUSD = 1.15
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         });
     }
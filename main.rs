@@ -1,11 +1,121 @@
-use convexfx_exchange::{Exchange, ExchangeConfig};
+use convexfx_exchange::{Exchange, ExchangeConfig, SolverBackend};
+use convexfx_sim::{Scenario, SimRunner};
+
+const USAGE: &str = "Usage: convexfx [--solver simple|clarabel|osqp] [--scenario NAME]";
+
+/// Command line options
+#[derive(Debug)]
+struct CliArgs {
+    /// Backend used by the exchange demo or the scenario run
+    solver: SolverBackend,
+    /// Preset scenario to simulate instead of the exchange demo
+    scenario: Option<Scenario>,
+}
+
+impl CliArgs {
+    /// Parse arguments, excluding the program name
+    fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut solver = ExchangeConfig::default().solver_backend;
+        let mut scenario = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{} needs a value\n{}", flag, USAGE))
+            };
+            match flag.as_str() {
+                "--solver" => {
+                    solver = value()?.parse().map_err(|e| format!("{}", e))?;
+                }
+                "--scenario" => {
+                    let name = value()?;
+                    scenario = Some(Scenario::from_name(&name).ok_or_else(|| {
+                        format!(
+                            "Unknown scenario '{}' (expected one of: {})",
+                            name,
+                            Scenario::PRESET_NAMES.join(", ")
+                        )
+                    })?);
+                }
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                other => return Err(format!("Unknown argument '{}'\n{}", other, USAGE)),
+            }
+        }
+
+        Ok(Self { solver, scenario })
+    }
+
+    /// Exchange configuration using the selected solver
+    fn exchange_config(&self) -> ExchangeConfig {
+        ExchangeConfig {
+            solver_backend: self.solver.clone(),
+            ..ExchangeConfig::default()
+        }
+    }
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = match CliArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+
+    match &args.scenario {
+        Some(scenario) => run_scenario(&args, scenario),
+        None => run_exchange_demo(&args),
+    }
+}
+
+/// Simulate a preset scenario and print its KPIs
+fn run_scenario(args: &CliArgs, scenario: &Scenario) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🧪 ConvexFX Scenario: {} ({:?} solver)", scenario.config.name, args.solver);
+    println!("========================");
+
+    let runner = SimRunner::new().with_clearing(args.solver.clearing_engine()?);
+    let result = runner.run_scenario(scenario);
+
+    println!("\n📊 Epoch KPIs:");
+    for epoch in &result.epochs {
+        println!("   Epoch {}: fill rate {:.2}%, slippage p90 {:.2} bps, coherence {:.6} bps, {} iterations, {:.0} ms",
+                 epoch.epoch_id,
+                 epoch.kpis.fill_rate * 100.0,
+                 epoch.kpis.slippage_bps_p90,
+                 epoch.kpis.coherence_error_max_bps,
+                 epoch.kpis.scp_iterations,
+                 epoch.runtime_ms);
+    }
+
+    let summary = &result.summary;
+    println!("\n📈 Summary:");
+    println!("   Epochs: {}", summary.total_epochs);
+    println!("   Avg fill rate: {:.2}%", summary.avg_fill_rate * 100.0);
+    println!("   Avg slippage p90: {:.2} bps", summary.avg_slippage_p90_bps);
+    println!("   Max coherence error: {:.6} bps", summary.max_coherence_error_bps);
+    println!("   Avg iterations: {:.1}", summary.avg_iterations);
+    println!("   Passed: {}", summary.passed);
+    for reason in &summary.failure_reasons {
+        println!("     - {}", reason);
+    }
+
+    Ok(())
+}
+
+fn run_exchange_demo(args: &CliArgs) -> Result<(), Box<dyn std::error::Error>> {
     println!("🚀 ConvexFX Exchange Demo");
     println!("========================");
 
-    // Create exchange with default configuration
-    let mut exchange = Exchange::new(ExchangeConfig::default())?;
+    // Create exchange with the selected solver
+    let mut exchange = Exchange::new(args.exchange_config())?;
 
     println!("✅ Exchange initialized");
 
@@ -33,8 +143,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Demonstrate submitting orders
     println!("\n📈 Submitting sample orders...");
-    let order1 = exchange.submit_order("trader_1", "USD", "EUR", 1000.0, Some(1.10), None)?;
-    let order2 = exchange.submit_order("trader_2", "EUR", "USD", 800.0, Some(0.95), None)?;
+    let order1 = exchange.submit_order("trader_1", "USD", "EUR", 1000.0, Some(1.10), None, None)?;
+    let order2 = exchange.submit_order("trader_2", "EUR", "USD", 800.0, Some(0.95), None, None)?;
 
     println!("✅ Orders submitted:");
     println!("   Order {}: {} -> {} for ${}", order1.order_id, order1.pay_asset, order1.receive_asset, order1.budget);
@@ -59,3 +169,54 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn test_defaults_to_exchange_demo_on_clarabel() {
+        let args = parse(&[]).unwrap();
+        assert!(matches!(args.solver, SolverBackend::Clarabel));
+        assert!(args.scenario.is_none());
+    }
+
+    #[test]
+    fn test_every_solver_and_scenario_combination() {
+        let solvers = [
+            ("simple", "Simple"),
+            ("clarabel", "Clarabel"),
+            ("osqp", "OSQP"),
+        ];
+        for (flag, backend) in solvers {
+            let args = parse(&["--solver", flag]).unwrap();
+            assert_eq!(format!("{:?}", args.solver), backend);
+            assert_eq!(format!("{:?}", args.exchange_config().solver_backend), backend);
+            assert!(args.scenario.is_none());
+
+            for name in Scenario::PRESET_NAMES {
+                let expected = Scenario::from_name(name).unwrap().config.name;
+                for argv in [
+                    vec!["--solver".to_string(), flag.to_string(), "--scenario".to_string(), name.to_string()],
+                    vec![format!("--scenario={}", name), format!("--solver={}", flag)],
+                ] {
+                    let args = CliArgs::parse(argv).unwrap();
+                    assert_eq!(format!("{:?}", args.solver), backend);
+                    assert_eq!(args.scenario.unwrap().config.name, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_arguments() {
+        assert!(parse(&["--solver", "gurobi"]).unwrap_err().contains("gurobi"));
+        assert!(parse(&["--scenario", "nope"]).unwrap_err().contains("eur-wall"));
+        assert!(parse(&["--solver"]).unwrap_err().contains("needs a value"));
+        assert!(parse(&["--verbose"]).is_err());
+    }
+}
@@ -18,6 +18,8 @@ fn main() {
             budget: Amount::from_units(1000),
             limit_ratio: Some(1.1),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({}),
         },
     ];
@@ -79,11 +81,11 @@ fn main() {
         }
     }
     
-    println!("\n\nTesting with OSQP solver:");
-    let clearing_osqp = ScpClearing::with_osqp_solver();
+    println!("\n\nTesting with Clarabel solver:");
+    let clearing_osqp = ScpClearing::with_clarabel();
     match clearing_osqp.clear_epoch(&instance) {
         Ok(solution) => {
-            println!("✅ OSQP solver succeeded!");
+            println!("✅ Clarabel solver succeeded!");
             println!("   Iterations: {}", solution.diagnostics.iterations);
             println!("   Converged: {}", solution.diagnostics.convergence_achieved);
             println!("   Final step norm y: {}", solution.diagnostics.final_step_norm_y);
@@ -91,7 +93,7 @@ fn main() {
             println!("   Fills: {}", solution.fills.len());
         }
         Err(e) => {
-            println!("❌ OSQP solver failed: {:?}", e);
+            println!("❌ Clarabel solver failed: {:?}", e);
         }
     }
 }
@@ -44,6 +44,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(1.0).unwrap(),
             limit_ratio: Some(1.15),
             min_fill_fraction: Some(0.5),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "market_making"}),
         },
         PairOrder {
@@ -54,6 +56,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(0.75).unwrap(),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "retail"}),
         },
         // GBP/USD orders
@@ -65,6 +69,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(0.5).unwrap(),
             limit_ratio: Some(0.85), // Max USDGBP = 0.85 (min GBPUSD = 1/0.85 = 1.176)
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "hedge"}),
         },
         // JPY/USD orders  
@@ -76,6 +82,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(1.2).unwrap(),
             limit_ratio: Some(105.0), // Max JPYUSD
             min_fill_fraction: Some(0.3),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "institutional"}),
         },
         // Cross-pair: EUR/GBP
@@ -87,6 +95,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(0.6).unwrap(),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "arb"}),
         },
         // CHF orders
@@ -98,6 +108,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(0.8).unwrap(),
             limit_ratio: Some(1.12),
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "flight_to_quality"}),
         },
         // AUD orders (new 6th asset)
@@ -109,6 +121,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(0.9).unwrap(),
             limit_ratio: Some(1.35), // Max AUDUSD
             min_fill_fraction: Some(0.2),
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "commodity_proxy"}),
         },
         PairOrder {
@@ -119,6 +133,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(1.5).unwrap(),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "risk_off"}),
         },
         // Complex cross: JPY/EUR
@@ -130,6 +146,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(10.0).unwrap(), // 10M JPY
             limit_ratio: Some(125.0),
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "carry_trade"}),
         },
         // AUD/JPY cross
@@ -141,6 +159,8 @@ fn test_complex_6_asset_clearing() {
             budget: Amount::from_f64(0.4).unwrap(),
             limit_ratio: None,
             min_fill_fraction: None,
+            max_slippage_bps: None,
+            submission_mid: None,
             metadata: serde_json::json!({"type": "risk_reversal"}),
         },
     ];
@@ -168,7 +188,7 @@ fn test_complex_6_asset_clearing() {
     }
     risk.gamma_diag = vec![1.0; 6];
     risk.w_diag = vec![100.0; 6];
-    risk.rebuild_matrices();
+    risk.rebuild_matrices().unwrap();
 
     // Create epoch instance
     let instance = EpochInstance::new(1, inventory.clone(), orders.clone(), ref_prices, risk);
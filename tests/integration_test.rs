@@ -25,6 +25,8 @@ fn test_full_clearing_pipeline() {
         budget: Amount::from_f64(1.0).unwrap(),
         limit_ratio: None,
         min_fill_fraction: None,
+        max_slippage_bps: None,
+        submission_mid: None,
         metadata: serde_json::json!({}),
     };
 